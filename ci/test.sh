@@ -18,7 +18,7 @@ up(){
 }
 
 run_tests(){
-  cargo test  --manifest-path libindy_vdr/Cargo.toml --features local_nodes_pool
+  cargo test  --manifest-path libindy_vdr/Cargo.toml --features local_nodes_pool,simulated_pool
 }
 
 down(){
@@ -1,9 +1,8 @@
 extern crate percent_encoding;
 
-use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
-use std::time::UNIX_EPOCH;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use hyper::{Body, Method, Request, Response, StatusCode};
 use percent_encoding::percent_decode_str;
@@ -15,6 +14,7 @@ use indy_vdr::ledger::identifiers::{CredentialDefinitionId, RevocationRegistryId
 use indy_vdr::pool::helpers::{perform_get_txn, perform_ledger_request};
 use indy_vdr::pool::{
     LedgerType, Pool, PreparedRequest, RequestResult, RequestResultMeta, TimingResult,
+    OPENMETRICS_CONTENT_TYPE,
 };
 use indy_vdr::resolver::did::DidUrl;
 use indy_vdr::resolver::PoolResolver as Resolver;
@@ -34,6 +34,7 @@ enum ResponseType {
     RequestFailed(VdrError, Option<TimingResult>),
     Status(StatusCode, String),
     Resolver(String),
+    Metrics(String),
 }
 
 impl<T> From<(RequestResult<T>, RequestResultMeta)> for ResponseType
@@ -228,6 +229,11 @@ fn format_result(
             let reply = format_json_reply(reply, pretty);
             format_text(reply, format, StatusCode::OK, None)
         }
+        ResponseType::Metrics(metrics) => Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", OPENMETRICS_CONTENT_TYPE)
+            .body(metrics.into())
+            .unwrap(),
     };
     Ok(response)
 }
@@ -250,43 +256,43 @@ async fn get_pool_genesis<T: Pool>(pool: &T) -> VdrResult<ResponseType> {
     Ok(ResponseType::Genesis(txns.join("\n")))
 }
 
-fn get_ledgers(state: Rc<RefCell<AppState>>) -> VdrResult<ResponseType> {
+fn get_ledgers(state: &AppState) -> VdrResult<ResponseType> {
     Ok(ResponseType::Json(
         state
-            .borrow()
-            .pool_states
-            .keys()
-            .cloned()
+            .registry
+            .list()?
+            .into_iter()
+            .map(|pool| pool.namespace)
             .collect::<Vec<String>>()
             .join("\n"),
     ))
 }
 
-fn get_pool_status(state: Rc<RefCell<AppState>>, namespace: &str) -> VdrResult<ResponseType> {
-    let pool_states = &state.borrow().pool_states;
-    let opt_pool = &pool_states
-        .get(namespace)
+fn get_pool_status(state: &AppState, namespace: &str) -> VdrResult<ResponseType> {
+    let registered = state
+        .registry
+        .list()?
+        .into_iter()
+        .find(|pool| pool.namespace == namespace)
         .ok_or_else(|| {
             err_msg(
                 VdrErrorKind::Input,
                 format!("Unknown ledger: {}", namespace),
             )
-        })?
-        .pool;
-    let (status, mt_root, mt_size, nodes) = if let Some(pool) = opt_pool {
+        })?;
+    let (status, mt_root, mt_size, nodes) = if registered.connected {
+        let pool = state.registry.get_pool(namespace)?;
         let (mt_root, mt_size) = pool.get_merkle_tree_info();
         let nodes = pool.get_node_aliases();
         ("active", Some(mt_root), Some(mt_size), Some(nodes))
     } else {
         ("init", None, None, None)
     };
-    let last_refresh = state
-        .borrow()
-        .pool_states
-        .get(namespace)
-        .unwrap()
-        .last_refresh;
-    let last_refresh = last_refresh.map(|tm| tm.elapsed().map(|d| d.as_secs()).ok());
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let last_refresh = registered.last_refresh.map(|secs| now.saturating_sub(secs));
 
     let result = json!({"status": status, "pool_mt_root": mt_root, "pool_mt_size": mt_size, "pool_nodes": nodes, "last_refresh": last_refresh});
     let result = serde_json::to_string(&result)
@@ -430,7 +436,7 @@ async fn submit_request<T: Pool>(pool: &T, message: Vec<u8>) -> VdrResult<Respon
 
 pub async fn handle_request(
     req: Request<Body>,
-    state: Rc<RefCell<AppState>>,
+    state: Rc<AppState>,
 ) -> Result<Response<Body>, hyper::Error> {
     let mut parts = req
         .uri()
@@ -476,12 +482,15 @@ pub async fn handle_request(
         ResponseFormat::Raw
     };
 
-    let mut namespace = if state.borrow().is_multiple {
+    if (req.method(), req.uri().path()) == (&Method::GET, "/metrics") {
+        let metrics = state.metrics.encode();
+        return format_result(Ok(ResponseType::Metrics(metrics)), format);
+    }
+
+    let mut namespace = if state.is_multiple {
         parts.next().unwrap_or_else(|| "".to_owned())
     } else {
-        let pool_states = &state.borrow().pool_states;
-        let (ns, _) = pool_states.iter().next().unwrap();
-        ns.to_owned()
+        state.namespace.clone()
     };
     let fst = parts.next().unwrap_or_else(|| "".to_owned());
 
@@ -502,29 +511,24 @@ pub async fn handle_request(
         };
     } else if (req_method, fst.is_empty()) == (&Method::GET, true) {
         if namespace.is_empty() {
-            return format_result(get_ledgers(state.clone()), format);
+            return format_result(get_ledgers(&state), format);
         } else {
-            return format_result(get_pool_status(state.clone(), &namespace), format);
+            return format_result(get_pool_status(&state, &namespace), format);
         }
     }
 
-    let pool_states = &state.borrow().pool_states;
-    let pool_state = match pool_states.get(&namespace) {
-        Some(ps) => ps,
-        None => {
+    let pool = match state.registry.get_pool(&namespace) {
+        Ok(pool) => pool,
+        Err(err) if matches!(err.kind(), VdrErrorKind::Resolver) => {
             return format_result(
                 http_status_msg(StatusCode::BAD_REQUEST, "Unknown ledger"),
                 format,
             );
         }
-    };
-
-    let opt_pool = pool_state.pool.clone();
-    let pool = match opt_pool {
-        None => {
+        Err(err) => {
+            log::error!("Error connecting to pool {}: {}", namespace, err);
             return format_result(http_status(StatusCode::SERVICE_UNAVAILABLE), format);
         }
-        Some(pool) => pool,
     };
 
     let result = if did.is_some() {
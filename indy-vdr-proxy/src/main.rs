@@ -1,5 +1,3 @@
-#![allow(clippy::await_holding_refcell_ref)] // using a single-threaded executor
-
 #[macro_use]
 extern crate serde_json;
 
@@ -7,7 +5,6 @@ mod app;
 mod handlers;
 mod utils;
 
-use std::cell::RefCell;
 use std::collections::HashMap;
 #[cfg(unix)]
 use std::fs;
@@ -15,9 +12,10 @@ use std::net::IpAddr;
 use std::path::PathBuf;
 use std::process::exit;
 use std::rc::Rc;
-use std::time::{Duration, SystemTime};
+use std::sync::Arc;
+use std::time::Duration;
 #[cfg(feature = "tls")]
-use std::{fs::File, io::BufReader, sync::Arc};
+use std::{fs::File, io::BufReader};
 
 use futures_util::future::FutureExt;
 #[cfg(feature = "tls")]
@@ -51,11 +49,9 @@ use tokio_rustls::{
 
 use indy_vdr::common::error::prelude::*;
 use indy_vdr::config::PoolConfig;
-use indy_vdr::pool::{helpers::perform_refresh, LocalPool, PoolBuilder, PoolTransactions};
+use indy_vdr::pool::{PoolMetrics, PoolRegistry, PoolTransactions};
 
-use crate::utils::{
-    init_pool_state_from_folder_structure, AppState, PoolState, INDY_NETWORKS_GITHUB,
-};
+use crate::utils::{init_pool_state_from_folder_structure, AppState, INDY_NETWORKS_GITHUB};
 
 fn main() {
     let config = app::load_config().unwrap_or_else(|err| {
@@ -118,28 +114,17 @@ async fn init_app_state(
     namespace: String,
     is_multiple: bool,
 ) -> VdrResult<AppState> {
-    let mut pool_states: HashMap<String, PoolState> = HashMap::new();
-
-    let state = if !is_multiple {
+    let networks = if !is_multiple {
         let genesis = genesis.unwrap_or_else(|| String::from("genesis.txn"));
         let transactions = if genesis.starts_with("http:") || genesis.starts_with("https:") {
             fetch_transactions(genesis).await?
         } else {
             PoolTransactions::from_json_file(genesis.as_str())?
         };
-        let pool_state = PoolState {
-            pool: None,
-            last_refresh: None,
-            transactions,
-        };
-        pool_states.insert(namespace, pool_state);
-        AppState {
-            is_multiple,
-            pool_states,
-        }
+        HashMap::from([(namespace.clone(), transactions)])
     } else {
         let genesis = genesis.unwrap_or_else(|| String::from(INDY_NETWORKS_GITHUB));
-        let pool_states = if genesis.starts_with("https:") {
+        if genesis.starts_with("https:") {
             let repo_url = genesis;
             let mut just_cloned = false;
             let repo =
@@ -172,57 +157,46 @@ async fn init_app_state(
             init_pool_state_from_folder_structure(path)?
         } else {
             init_pool_state_from_folder_structure(PathBuf::from(genesis))?
-        };
-        AppState {
-            is_multiple,
-            pool_states,
         }
     };
-    Ok(state)
-}
 
-async fn run_pools(state: Rc<RefCell<AppState>>, init_refresh: bool, interval_refresh: u32) {
-    let mut pool_states = HashMap::new();
-
-    for (namespace, pool_state) in &state.clone().borrow().pool_states {
-        let pool_state = match create_pool(state.clone(), namespace.as_str(), init_refresh).await {
-            Ok(pool) => {
-                let pool = Some(pool.clone());
-                PoolState {
-                    pool: pool.clone(),
-                    last_refresh: pool_state.last_refresh,
-                    transactions: pool_state.transactions.clone(),
-                }
-            }
-            Err(err) => {
-                eprintln!("Error initializing pool {} with error : {}", namespace, err);
-                PoolState {
-                    pool: None,
-                    last_refresh: pool_state.last_refresh,
-                    transactions: pool_state.transactions.clone(),
-                }
-            }
-        };
-
-        pool_states.insert(namespace.to_owned(), pool_state);
+    let metrics = Arc::new(PoolMetrics::new());
+    let listeners = metrics.clone();
+    let registry =
+        PoolRegistry::with_connector(PoolConfig::default(), move |namespace, builder| {
+            let pool = builder.into_local()?;
+            // the listener is registered again each time a pool is reconnected
+            pool.get_events().subscribe(listeners.listener(namespace));
+            Ok(pool)
+        });
+    for (namespace, transactions) in networks {
+        registry.register(&namespace, transactions, None)?;
     }
 
-    state.borrow_mut().pool_states = pool_states;
+    Ok(AppState {
+        is_multiple,
+        namespace,
+        registry,
+        metrics,
+    })
+}
+
+async fn run_pools(state: Rc<AppState>, init_refresh: bool, interval_refresh: u32) {
+    for pool in state.registry.list().unwrap_or_default() {
+        if let Err(err) = create_pool(&state, &pool.namespace, init_refresh).await {
+            eprintln!(
+                "Error initializing pool {} with error : {}",
+                pool.namespace, err
+            );
+        }
+    }
 
     let shutdown = shutdown_signal().fuse().shared();
     if interval_refresh > 0 {
         loop {
             select! {
-                refresh_result = refresh_pools(state.clone(), interval_refresh) => {
-                    match refresh_result {
-                        Ok(upd_pool_states) => {
-                            state.borrow_mut().pool_states = upd_pool_states;
-                            log::info!("Refreshed validator pools");
-                        },
-                        Err(err) => {
-                            log::error!("Error refreshing validator pool: {}", err);
-                        }
-                    }
+                _ = refresh_pools(&state, interval_refresh) => {
+                    log::info!("Refreshed validator pools");
                 }
                 _ = shutdown.clone() => {
                     println!("Shutting down");
@@ -254,78 +228,34 @@ async fn shutdown_signal() {
         .expect("failed to install Ctrl-C handler")
 }
 
-async fn create_pool(
-    state: Rc<RefCell<AppState>>,
-    namespace: &str,
-    refresh: bool,
-) -> VdrResult<LocalPool> {
-    let pool_states = &state.borrow().pool_states;
-    let pool_state = pool_states.get(namespace).unwrap();
-    let pool =
-        PoolBuilder::new(PoolConfig::default(), pool_state.transactions.clone()).into_local()?;
-    let refresh_pool = if refresh {
-        refresh_pool(state.clone(), &pool, 0).await?
-    } else {
-        None
-    };
-    Ok(refresh_pool.unwrap_or(pool))
-}
-
-async fn refresh_pools(
-    state: Rc<RefCell<AppState>>,
-    // pool_states: HashMap<String, PoolState>,
-    delay_mins: u32,
-) -> VdrResult<HashMap<String, PoolState>> {
-    let mut upd_pool_states = HashMap::new();
-    let pool_states = &state.borrow().pool_states;
-    for (namespace, pool_state) in pool_states {
-        if let Some(pool) = &pool_state.pool {
-            let upd_pool = match refresh_pool(state.clone(), pool, delay_mins).await {
-                Ok(p) => p,
-                Err(err) => {
-                    eprintln!(
-                        "Error refreshing validator pool {} with error {}",
-                        namespace, err
-                    );
-                    None
-                }
-            };
-            let upd_pool_state = PoolState {
-                pool: upd_pool.or_else(|| Some(pool.clone())),
-                last_refresh: Some(SystemTime::now()),
-                transactions: pool_state.transactions.clone(),
-            };
-
-            upd_pool_states.insert(namespace.to_owned(), upd_pool_state);
-        }
+async fn create_pool(state: &AppState, namespace: &str, refresh: bool) -> VdrResult<()> {
+    state.registry.get_pool(namespace)?;
+    if refresh {
+        state.registry.refresh(namespace).await?;
     }
-
-    Ok(upd_pool_states)
+    Ok(())
 }
 
-async fn refresh_pool(
-    state: Rc<RefCell<AppState>>,
-    pool: &LocalPool,
-    delay_mins: u32,
-) -> VdrResult<Option<LocalPool>> {
-    let n_pools = state.borrow().pool_states.len() as u32;
-    if delay_mins > 0 {
-        tokio::time::sleep(Duration::from_secs((delay_mins * 60 / n_pools) as u64)).await
-    }
-
-    let (txns, _meta) = perform_refresh(pool).await?;
-    if let Some(txns) = txns {
-        let pool = PoolBuilder::new(PoolConfig::default(), txns)
-            .refreshed(true)
-            .into_local()?;
-        Ok(Some(pool))
-    } else {
-        Ok(None)
+async fn refresh_pools(state: &AppState, delay_mins: u32) {
+    let pools = state.registry.list().unwrap_or_default();
+    let n_pools = pools.len().max(1) as u32;
+    for pool in pools {
+        tokio::time::sleep(Duration::from_secs((delay_mins * 60 / n_pools) as u64)).await;
+        // pools which failed to connect are retried on the next request
+        if !pool.connected {
+            continue;
+        }
+        if let Err(err) = state.registry.refresh(&pool.namespace).await {
+            eprintln!(
+                "Error refreshing validator pool {} with error {}",
+                pool.namespace, err
+            );
+        }
     }
 }
 
 async fn init_server(config: app::Config) -> Result<(), String> {
-    let state = Rc::new(RefCell::new(
+    let state = Rc::new(
         init_app_state(
             config.genesis.clone(),
             config.namespace.clone(),
@@ -333,7 +263,7 @@ async fn init_server(config: app::Config) -> Result<(), String> {
         )
         .await
         .map_err(|err| format!("Error loading config: {}", err))?,
-    ));
+    );
 
     #[cfg(unix)]
     if let Some(socket) = &config.socket {
@@ -417,7 +347,7 @@ fn build_tls_config(cert_path: &str, key_path: &str) -> Result<ServerConfig, Str
 
 async fn run_server<I>(
     builder: hyper::server::Builder<I>,
-    state: Rc<RefCell<AppState>>,
+    state: Rc<AppState>,
     address: String,
     config: app::Config,
 ) -> Result<(), String>
@@ -1,28 +1,25 @@
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::time::SystemTime;
+use std::sync::Arc;
 
 use indy_vdr::common::error::prelude::*;
-use indy_vdr::pool::{LocalPool, PoolTransactions};
+use indy_vdr::pool::{LocalPool, PoolMetrics, PoolRegistry, PoolTransactions};
 
 pub const INDY_NETWORKS_GITHUB: &str = "https://github.com/IDunion/indy-did-networks";
 pub const GENESIS_FILENAME: &str = "pool_transactions_genesis.json";
 
-pub struct PoolState {
-    pub pool: Option<LocalPool>,
-    pub last_refresh: Option<SystemTime>,
-    pub transactions: PoolTransactions,
-}
-
 pub struct AppState {
     pub is_multiple: bool,
-    pub pool_states: HashMap<String, PoolState>,
+    /// The namespace served when not running with multiple ledgers
+    pub namespace: String,
+    pub registry: PoolRegistry<LocalPool>,
+    pub metrics: Arc<PoolMetrics>,
 }
 
 pub fn init_pool_state_from_folder_structure(
     path: PathBuf,
-) -> VdrResult<HashMap<String, PoolState>> {
+) -> VdrResult<HashMap<String, PoolTransactions>> {
     let mut networks = HashMap::new();
 
     let entries = fs::read_dir(path).map_err(|err| {
@@ -62,12 +59,7 @@ pub fn init_pool_state_from_folder_structure(
                         PoolTransactions::from_json_file(entry.path().join(GENESIS_FILENAME))?,
                     ),
                 };
-                let pool_state = PoolState {
-                    pool: None,
-                    last_refresh: None,
-                    transactions: genesis_txns,
-                };
-                networks.insert(ledger_prefix, pool_state);
+                networks.insert(ledger_prefix, genesis_txns);
             }
         }
     }
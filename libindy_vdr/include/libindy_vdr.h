@@ -63,6 +63,15 @@ enum ErrorCode
   PoolRequestFailed = 31,
   PoolTimeout = 32,
   Resolver = 9,
+  Wallet = 40,
+  WalletNotFound = 41,
+  WalletDuplicate = 42,
+  WalletAccessDenied = 43,
+  WalletInvalidState = 44,
+  WalletItemNotFound = 45,
+  DidNotFound = 50,
+  DidDuplicate = 51,
+  KeyNotFound = 52,
 };
 #ifndef __cplusplus
 typedef int64_t ErrorCode;
@@ -134,6 +143,8 @@ typedef int64_t RequestHandle;
 
 typedef int64_t PoolHandle;
 
+typedef int64_t WalletHandle;
+
 typedef int64_t CallbackId;
 
 /**
@@ -228,17 +239,7 @@ typedef struct ByteBuffer {
 extern "C" {
 #endif // __cplusplus
 
-ErrorCode did_create();
-
-ErrorCode wallet_create(FfiStr key, FfiStr walletname);
-
-ErrorCode wallet_open(FfiStr key, FfiStr walletname);
-
-ErrorCode did_create(FfiStr key, FfiStr walletname, FfiStr seed, FfiStr method, FfiStr metadata);
-
-ErrorCode did_list(FfiStr key, FfiStr walletname);
-
-ErrorCode did_use();
+void indy_vdr_buffer_free(struct ByteBuffer buffer);
 
 ErrorCode indy_vdr_build_acceptance_mechanisms_request(FfiStr submitter_did,
                                                        FfiStr aml,
@@ -258,6 +259,26 @@ ErrorCode indy_vdr_build_handle_request(FfiStr submitter_did,
                                         FfiStr handle,
                                         RequestHandle *handle_p);
 
+ErrorCode indy_vdr_build_handle_transfer_request(FfiStr submitter_did,
+                                                 FfiStr target_did,
+                                                 FfiStr handle,
+                                                 RequestHandle *handle_p);
+
+ErrorCode indy_vdr_build_auction_bid_request(FfiStr submitter_did,
+                                            FfiStr auction_id,
+                                            int64_t amount,
+                                            RequestHandle *handle_p);
+
+ErrorCode indy_vdr_build_auction_close_request(FfiStr submitter_did,
+                                              FfiStr auction_id,
+                                              RequestHandle *handle_p);
+
+ErrorCode indy_vdr_build_auction_start_request(FfiStr submitter_did,
+                                              FfiStr target_did,
+                                              FfiStr auction_id,
+                                              FfiStr handle,
+                                              RequestHandle *handle_p);
+
 ErrorCode indy_vdr_build_auth_rule_request(FfiStr submitter_did,
                                            FfiStr txn_type,
                                            FfiStr action,
@@ -294,6 +315,12 @@ ErrorCode indy_vdr_build_get_attrib_request(FfiStr submitter_did,
                                             int64_t timestamp,
                                             RequestHandle *handle_p);
 
+ErrorCode indy_vdr_build_get_auction_request(FfiStr submitter_did,
+                                             FfiStr auction_id,
+                                             int32_t seq_no,
+                                             int64_t timestamp,
+                                             RequestHandle *handle_p);
+
 ErrorCode indy_vdr_build_get_auth_rule_request(FfiStr submitter_did,
                                                FfiStr auth_type,
                                                FfiStr auth_action,
@@ -308,6 +335,12 @@ ErrorCode indy_vdr_build_get_cred_def_request(FfiStr submitter_did,
 
 ErrorCode indy_vdr_build_get_frozen_ledgers_request(FfiStr identifier, RequestHandle *handle_p);
 
+ErrorCode indy_vdr_build_get_handle_request(FfiStr submitter_did,
+                                            FfiStr handle,
+                                            int32_t seq_no,
+                                            int64_t timestamp,
+                                            RequestHandle *handle_p);
+
 ErrorCode indy_vdr_build_get_nym_request(FfiStr submitter_did,
                                          FfiStr dest,
                                          int32_t seq_no,
@@ -406,11 +439,138 @@ ErrorCode indy_vdr_build_txn_author_agreement_request(FfiStr submitter_did,
                                                       int64_t retirement_ts,
                                                       RequestHandle *handle_p);
 
+/**
+ * Create a portable receipt for a ledger reply containing a state proof
+ *
+ * @param params pool transactions as for `indy_vdr_pool_create`, of the pool which signed the reply
+ * @param request_handle handle for the Request instance the reply corresponds to
+ * @param reply the reply JSON
+ * @param receipt_p assigned a pointer to the receipt JSON on success
+ */
+ErrorCode indy_vdr_create_ledger_receipt(FfiStr params,
+                                         RequestHandle request_handle,
+                                         FfiStr reply,
+                                         const char **receipt_p);
+
 ErrorCode indy_vdr_dereference(PoolHandle pool_handle,
                                FfiStr did_url,
                                void (*cb)(int64_t cb_id, ErrorCode err, const char *response),
                                int64_t cb_id);
 
+/**
+ * Create a new DID in a wallet.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param seed optional seed for the key: 32 bytes, or base64 or hex encoded
+ * @param metadata optional metadata to store with the DID
+ * @param method optional DID method used to qualify the new DID
+ * @param cb callback receiving a JSON object `{"did": ..., "verkey": ...}`
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_did_create(WalletHandle wallet_handle,
+                              FfiStr seed,
+                              FfiStr metadata,
+                              FfiStr method,
+                              void (*cb)(int64_t cb_id, ErrorCode err, const char *result),
+                              int64_t cb_id);
+
+/**
+ * Fetch the stored information for a DID as a JSON object.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the DID to look up
+ * @param cb callback receiving the JSON string
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_did_get(WalletHandle wallet_handle,
+                           FfiStr did,
+                           void (*cb)(int64_t cb_id, ErrorCode err, const char *result),
+                           int64_t cb_id);
+
+/**
+ * List the DIDs stored in a wallet as a JSON array.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param cb callback receiving the JSON string
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_did_list(WalletHandle wallet_handle,
+                            void (*cb)(int64_t cb_id, ErrorCode err, const char *result),
+                            int64_t cb_id);
+
+/**
+ * Replace an unqualified DID in a wallet with its fully-qualified form.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the DID to qualify
+ * @param method the DID method
+ * @param cb callback receiving the qualified DID
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_did_qualify(WalletHandle wallet_handle,
+                               FfiStr did,
+                               FfiStr method,
+                               void (*cb)(int64_t cb_id, ErrorCode err, const char *did),
+                               int64_t cb_id);
+
+/**
+ * Replace the current key of a DID with the pending key.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the DID to rotate keys for
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_did_replace_keys_apply(WalletHandle wallet_handle,
+                                          FfiStr did,
+                                          void (*cb)(int64_t cb_id, ErrorCode err),
+                                          int64_t cb_id);
+
+/**
+ * Generate a new key for a DID, to be applied with `indy_vdr_did_replace_keys_apply`.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the DID to rotate keys for
+ * @param seed optional seed for the new key
+ * @param cb callback receiving the new verkey
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_did_replace_keys_start(WalletHandle wallet_handle,
+                                          FfiStr did,
+                                          FfiStr seed,
+                                          void (*cb)(int64_t cb_id, ErrorCode err, const char *verkey),
+                                          int64_t cb_id);
+
+/**
+ * Update the metadata stored with a DID.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the DID to update
+ * @param metadata the new metadata
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_did_set_metadata(WalletHandle wallet_handle,
+                                    FfiStr did,
+                                    FfiStr metadata,
+                                    void (*cb)(int64_t cb_id, ErrorCode err),
+                                    int64_t cb_id);
+
+/**
+ * Sign a message with the current key of a DID.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the signing DID
+ * @param message the message bytes
+ * @param cb callback receiving the signature, to be freed with `indy_vdr_buffer_free`
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_did_sign(WalletHandle wallet_handle,
+                            FfiStr did,
+                            struct ByteBuffer message,
+                            void (*cb)(int64_t cb_id, ErrorCode err, struct ByteBuffer signature),
+                            int64_t cb_id);
+
 ErrorCode indy_vdr_get_current_error(const char **error_json_p);
 
 ErrorCode indy_vdr_pool_close(PoolHandle pool_handle);
@@ -453,6 +613,27 @@ ErrorCode indy_vdr_pool_submit_request(PoolHandle pool_handle,
                                                   const char *response),
                                        CallbackId cb_id);
 
+/**
+ * Register a callback receiving the events reported by a pool.
+ *
+ * Each event is passed to the callback as a JSON object with an `event` property
+ * indicating the event type.
+ *
+ * The callback is run synchronously on the thread handling the pool's network events,
+ * so it should return promptly and must not close the pool.
+ *
+ * @param subscription_p pointer receiving the subscription handle
+ */
+ErrorCode indy_vdr_pool_subscribe_events(PoolHandle pool_handle,
+                                         void (*cb)(CallbackId cb_id, const char *event),
+                                         CallbackId cb_id,
+                                         int64_t *subscription_p);
+
+/**
+ * Remove a callback registered with `indy_vdr_pool_subscribe_events`.
+ */
+ErrorCode indy_vdr_pool_unsubscribe_events(PoolHandle pool_handle, int64_t subscription);
+
 /**
  *
  */
@@ -463,6 +644,64 @@ ErrorCode indy_vdr_prepare_txn_author_agreement_acceptance(FfiStr text,
                                                            uint64_t time,
                                                            const char **output_p);
 
+/**
+ * Dereference a `did:indy` DID URL using the pool registered for its namespace.
+ */
+ErrorCode indy_vdr_registry_dereference(FfiStr did_url,
+                                        void (*cb)(CallbackId cb_id,
+                                                   ErrorCode err,
+                                                   const char *response),
+                                        CallbackId cb_id);
+
+/**
+ * Remove the pool registered for a `did:indy` namespace.
+ */
+ErrorCode indy_vdr_registry_deregister(FfiStr namespace_);
+
+/**
+ * Disconnect the registered pools which have not been used recently.
+ *
+ * The pools stay registered and are reconnected when next used.
+ *
+ * @param max_idle the maximum idle time in seconds
+ * @param result_p pointer receiving a JSON array of the evicted namespaces
+ */
+ErrorCode indy_vdr_registry_evict_idle(int64_t max_idle, const char **result_p);
+
+/**
+ * List the registered pools.
+ *
+ * @param result_p pointer receiving a JSON array of registered pools
+ */
+ErrorCode indy_vdr_registry_list(const char **result_p);
+
+/**
+ * Fetch the latest transactions for the pool registered for a namespace.
+ *
+ * The pool is reconnected when new transactions are found, and these are stored in
+ * the pool cache if one is configured.
+ */
+ErrorCode indy_vdr_registry_refresh(FfiStr namespace_,
+                                    void (*cb)(CallbackId cb_id, ErrorCode err),
+                                    CallbackId cb_id);
+
+/**
+ * Register the pool used to resolve DIDs in a `did:indy` namespace.
+ *
+ * @param namespace the namespace, such as `sovrin` or `sovrin:staging`
+ * @param params JSON pool parameters, as accepted by `indy_vdr_pool_create`
+ */
+ErrorCode indy_vdr_registry_register(FfiStr namespace_, FfiStr params);
+
+/**
+ * Resolve a `did:indy` DID using the pool registered for its namespace.
+ */
+ErrorCode indy_vdr_registry_resolve(FfiStr did,
+                                    void (*cb)(CallbackId cb_id,
+                                               ErrorCode err,
+                                               const char *response),
+                                    CallbackId cb_id);
+
 /**
  * Deallocate a Request instance.
  *
@@ -480,6 +719,34 @@ ErrorCode indy_vdr_request_get_body(RequestHandle request_handle, const char **b
 
 ErrorCode indy_vdr_request_get_signature_input(RequestHandle request_handle, const char **input_p);
 
+/**
+ * Add a signature to a request using the key of a DID held in a wallet.
+ *
+ * The DID must match the request identifier or endorser.
+ *
+ * @param request_handle handle for the Request instance
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the signing DID
+ */
+ErrorCode indy_vdr_request_multi_sign_with_wallet(RequestHandle request_handle,
+                                                  WalletHandle wallet_handle,
+                                                  FfiStr did);
+
+/**
+ * Add a signature to a request asynchronously using the key of a DID held in a wallet.
+ *
+ * @param request_handle handle for the Request instance
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the signing DID
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_request_multi_sign_with_wallet_async(RequestHandle request_handle,
+                                                        WalletHandle wallet_handle,
+                                                        FfiStr did,
+                                                        void (*cb)(int64_t cb_id, ErrorCode err),
+                                                        int64_t cb_id);
+
 ErrorCode indy_vdr_request_set_endorser(RequestHandle request_handle, FfiStr endorser);
 
 ErrorCode indy_vdr_request_set_multi_signature(RequestHandle request_handle,
@@ -491,6 +758,34 @@ ErrorCode indy_vdr_request_set_signature(RequestHandle request_handle, struct By
 ErrorCode indy_vdr_request_set_txn_author_agreement_acceptance(RequestHandle request_handle,
                                                                FfiStr acceptance);
 
+/**
+ * Sign a request using the key of a DID held in a wallet.
+ *
+ * The DID must match the request identifier.
+ *
+ * @param request_handle handle for the Request instance
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the signing DID
+ */
+ErrorCode indy_vdr_request_sign_with_wallet(RequestHandle request_handle,
+                                            WalletHandle wallet_handle,
+                                            FfiStr did);
+
+/**
+ * Sign a request asynchronously using the key of a DID held in a wallet.
+ *
+ * @param request_handle handle for the Request instance
+ * @param wallet_handle handle for the Wallet instance
+ * @param did the signing DID
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_request_sign_with_wallet_async(RequestHandle request_handle,
+                                                  WalletHandle wallet_handle,
+                                                  FfiStr did,
+                                                  void (*cb)(int64_t cb_id, ErrorCode err),
+                                                  int64_t cb_id);
+
 ErrorCode indy_vdr_resolve(PoolHandle pool_handle,
                            FfiStr did,
                            void (*cb)(int64_t cb_id, ErrorCode err, const char *response),
@@ -519,8 +814,163 @@ ErrorCode indy_vdr_set_socks_proxy(FfiStr socks_proxy);
  */
 void indy_vdr_string_free(char *s);
 
+/**
+ * Verify a ledger receipt without network access
+ *
+ * @param params pool transactions as for `indy_vdr_pool_create`, of the pool which signed the reply
+ * @param receipt the receipt JSON
+ * @param timestamp time to check freshness against, -1 for the current time
+ * @param result_p assigned a pointer to the verification result JSON on success
+ */
+ErrorCode indy_vdr_verify_ledger_receipt(FfiStr params,
+                                         FfiStr receipt,
+                                         int64_t timestamp,
+                                         const char **result_p);
+
+/**
+ * Verify the state proof of a stored ledger reply without network access
+ *
+ * @param params pool transactions as for `indy_vdr_pool_create`
+ * @param request_handle handle for the Request instance the reply corresponds to
+ * @param reply the stored reply JSON
+ * @param timestamp time to check freshness against, -1 for the current time
+ * @param result_p assigned a pointer to the verification result JSON on success
+ */
+ErrorCode indy_vdr_verify_state_proof(FfiStr params,
+                                      RequestHandle request_handle,
+                                      FfiStr reply,
+                                      int64_t timestamp,
+                                      const char **result_p);
+
 char *indy_vdr_version(void);
 
+/**
+ * Close an open wallet.
+ *
+ * The wallet handle is invalidated when the close begins, and the wallet is
+ * closed once other operations on it have finished.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_wallet_close(WalletHandle wallet_handle,
+                                void (*cb)(int64_t cb_id, ErrorCode err),
+                                int64_t cb_id);
+
+/**
+ * Create a new wallet.
+ *
+ * @param config wallet configuration JSON: `{"id": ..., "storage_type": ..., "storage_config": ...}`
+ * @param credentials wallet credentials JSON: `{"key": ..., "key_derivation_method": ...}`
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_wallet_create(FfiStr config,
+                                 FfiStr credentials,
+                                 void (*cb)(int64_t cb_id, ErrorCode err),
+                                 int64_t cb_id);
+
+/**
+ * Delete a wallet and its storage.
+ *
+ * @param config wallet configuration JSON
+ * @param credentials wallet credentials JSON
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_wallet_delete(FfiStr config,
+                                 FfiStr credentials,
+                                 void (*cb)(int64_t cb_id, ErrorCode err),
+                                 int64_t cb_id);
+
+/**
+ * Export the contents of an open wallet to an encrypted archive file.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param export_config export configuration JSON: `{"path": ..., "key": ..., "key_derivation_method": ..., "categories": [...]}`
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_wallet_export(WalletHandle wallet_handle,
+                                 FfiStr export_config,
+                                 void (*cb)(int64_t cb_id, ErrorCode err),
+                                 int64_t cb_id);
+
+/**
+ * Create a new wallet from a backup.
+ *
+ * @param config configuration JSON for the new wallet
+ * @param credentials credentials JSON for the new wallet
+ * @param import_config import configuration JSON: `{"path": ..., "key": ..., "key_derivation_method": ...}`
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_wallet_import(FfiStr config,
+                                 FfiStr credentials,
+                                 FfiStr import_config,
+                                 void (*cb)(int64_t cb_id, ErrorCode err),
+                                 int64_t cb_id);
+
+/**
+ * List the configurations of all known wallets as a JSON array.
+ *
+ * @param wallets_p pointer receiving the JSON string, to be freed with `indy_vdr_string_free`
+ */
+ErrorCode indy_vdr_wallet_list(const char **wallets_p);
+
+/**
+ * Copy the contents of a wallet into a new wallet, which may use a different
+ * storage backend. The record counts are verified against the source wallet
+ * after the copy.
+ *
+ * @param from_config configuration JSON for the source wallet
+ * @param from_credentials credentials JSON for the source wallet
+ * @param to_config configuration JSON for the new wallet
+ * @param to_credentials credentials JSON for the new wallet
+ * @param categories optional JSON array of the record categories to copy
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_wallet_migrate(FfiStr from_config,
+                                  FfiStr from_credentials,
+                                  FfiStr to_config,
+                                  FfiStr to_credentials,
+                                  FfiStr categories,
+                                  void (*cb)(int64_t cb_id, ErrorCode err),
+                                  int64_t cb_id);
+
+/**
+ * Open an existing wallet.
+ *
+ * @param config wallet configuration JSON
+ * @param credentials wallet credentials JSON
+ * @param cb callback receiving the result and the handle of the opened wallet
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_wallet_open(FfiStr config,
+                               FfiStr credentials,
+                               void (*cb)(int64_t cb_id, ErrorCode err, WalletHandle handle),
+                               int64_t cb_id);
+
+/**
+ * Re-encrypt an open wallet under a new key.
+ *
+ * The rekey waits for other operations on the wallet to finish, and the
+ * wallet handle remains valid whether or not the rekey succeeds.
+ *
+ * @param wallet_handle handle for the Wallet instance
+ * @param key the new wallet key
+ * @param key_derivation_method optional derivation method for the new key: `argon2m`, `argon2i` or `raw`
+ * @param cb callback receiving the result
+ * @param cb_id callback identifier passed back to `cb`
+ */
+ErrorCode indy_vdr_wallet_rekey(WalletHandle wallet_handle,
+                                FfiStr key,
+                                FfiStr key_derivation_method,
+                                void (*cb)(int64_t cb_id, ErrorCode err),
+                                int64_t cb_id);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif // __cplusplus
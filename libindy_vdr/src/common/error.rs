@@ -48,6 +48,25 @@ pub enum VdrErrorKind {
     PoolTimeout,
     #[error("Resolver error")]
     Resolver,
+    // Wallet errors
+    #[error("Wallet error")]
+    Wallet,
+    #[error("Wallet not found")]
+    WalletNotFound,
+    #[error("Wallet already exists")]
+    WalletDuplicate,
+    #[error("Wallet access denied")]
+    WalletAccessDenied,
+    #[error("Invalid wallet state")]
+    WalletInvalidState,
+    #[error("Wallet item not found")]
+    WalletItemNotFound,
+    #[error("DID not found")]
+    DidNotFound,
+    #[error("DID already exists")]
+    DidDuplicate,
+    #[error("Key not found")]
+    KeyNotFound,
 }
 
 impl VdrError {
@@ -124,6 +143,23 @@ impl From<std::io::Error> for VdrError {
     }
 }
 
+impl From<aries_askar::Error> for VdrError {
+    fn from(err: aries_askar::Error) -> VdrError {
+        use aries_askar::ErrorKind as AskarErrorKind;
+        let kind = match err.kind() {
+            AskarErrorKind::Busy => VdrErrorKind::Resource,
+            AskarErrorKind::Duplicate => VdrErrorKind::WalletDuplicate,
+            AskarErrorKind::Encryption => VdrErrorKind::WalletAccessDenied,
+            AskarErrorKind::Input => VdrErrorKind::Input,
+            AskarErrorKind::NotFound => VdrErrorKind::WalletItemNotFound,
+            AskarErrorKind::Unsupported => VdrErrorKind::Incompatible,
+            _ => VdrErrorKind::Wallet,
+        };
+        VdrError::new(kind, None, Some(Box::new(err)))
+    }
+}
+
+#[cfg(feature = "zmq_networker")]
 impl From<zmq::Error> for VdrError {
     fn from(err: zmq::Error) -> VdrError {
         VdrError::new(VdrErrorKind::Connection, None, Some(Box::new(err)))
@@ -9,6 +9,15 @@ pub const DEFAULT_CONN_ACTIVE_TIMEOUT: i64 = 5;
 pub const DEFAULT_CONN_REQUEST_LIMIT: usize = 10;
 pub const DEFAULT_REQUEST_READ_NODES: usize = 2;
 pub const DEFAULT_FRESHNESS_TIMEOUT: u64 = 300;
+pub const DEFAULT_NODE_QUARANTINE_TIMEOUT: i64 = 60;
+pub const DEFAULT_WARM_START_MAX_AGE: i64 = 300;
+pub const DEFAULT_HEDGE_PERCENTILE: f32 = 0.95;
+pub const DEFAULT_HEDGE_MIN_DELAY_MS: u64 = 20;
+pub const DEFAULT_HEDGE_MAX_DELAY_MS: u64 = 1000;
+pub const DEFAULT_HEDGE_MAX_REQUESTS: usize = 1;
+pub const DEFAULT_REFRESH_INTERVAL: u64 = 7200;
+pub const DEFAULT_REFRESH_JITTER: f32 = 0.1;
+pub const DEFAULT_REFRESH_FAILURE_THRESHOLD: u64 = 10;
 pub const DEFAULT_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::Node1_4;
 
 pub static DEFAULT_GENERATOR: Lazy<Generator> = Lazy::new(|| {
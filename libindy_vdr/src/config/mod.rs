@@ -1,7 +1,7 @@
 pub(crate) mod constants;
 pub(crate) mod types;
 
-pub use types::PoolConfig;
+pub use types::{HedgePolicy, PoolConfig, RefreshPolicy, StateProofMode, StateProofPolicy};
 
 /// Library package version
 pub static LIB_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::pool::ProtocolVersion;
 use crate::utils::{Validatable, ValidationError};
 
@@ -30,6 +32,19 @@ pub struct PoolConfig {
     /// The socks proxy host name and port for ZMQ (example: proxy1.intranet.company.com:1080)
     #[serde(default = "PoolConfig::default_socks_proxy")]
     pub socks_proxy: Option<String>,
+    /// The time in seconds to avoid a node which keeps failing, or 0 to disable quarantine
+    #[serde(default = "PoolConfig::default_node_quarantine_timeout")]
+    pub node_quarantine_timeout: i64,
+    /// The policy for sending speculative duplicates of reads with state proofs
+    #[serde(default = "PoolConfig::default_read_hedging")]
+    pub read_hedging: Option<HedgePolicy>,
+    /// The maximum age in seconds of persisted pool state which allows skipping the
+    /// initial status check, or 0 to always check
+    #[serde(default = "PoolConfig::default_warm_start_max_age")]
+    pub warm_start_max_age: i64,
+    /// The policy for refreshing the pool transactions in the background
+    #[serde(default = "PoolConfig::default_refresh_policy")]
+    pub refresh_policy: Option<RefreshPolicy>,
 }
 
 impl Validatable for PoolConfig {
@@ -52,6 +67,18 @@ impl Validatable for PoolConfig {
         if self.request_read_nodes == 0 {
             return Err(invalid!("`request_read_nodes` must be greater than 0"));
         }
+        if self.node_quarantine_timeout < 0 {
+            return Err(invalid!("`node_quarantine_timeout` must not be negative"));
+        }
+        if let Some(hedging) = self.read_hedging.as_ref() {
+            hedging.validate()?;
+        }
+        if self.warm_start_max_age < 0 {
+            return Err(invalid!("`warm_start_max_age` must not be negative"));
+        }
+        if let Some(refresh) = self.refresh_policy.as_ref() {
+            refresh.validate()?;
+        }
         Ok(())
     }
 }
@@ -96,6 +123,26 @@ impl PoolConfig {
     pub fn default_socks_proxy() -> Option<String> {
         None
     }
+
+    /// The default time to avoid a failing node
+    pub fn default_node_quarantine_timeout() -> i64 {
+        constants::DEFAULT_NODE_QUARANTINE_TIMEOUT
+    }
+
+    /// Read hedging is disabled by default
+    pub fn default_read_hedging() -> Option<HedgePolicy> {
+        None
+    }
+
+    /// The default maximum age of persisted pool state for a warm start
+    pub fn default_warm_start_max_age() -> i64 {
+        constants::DEFAULT_WARM_START_MAX_AGE
+    }
+
+    /// Background refresh is disabled by default
+    pub fn default_refresh_policy() -> Option<RefreshPolicy> {
+        None
+    }
 }
 
 impl Default for PoolConfig {
@@ -109,6 +156,220 @@ impl Default for PoolConfig {
             conn_active_timeout: Self::default_conn_active_timeout(),
             request_read_nodes: Self::default_request_read_nodes(),
             socks_proxy: Self::default_socks_proxy(),
+            node_quarantine_timeout: Self::default_node_quarantine_timeout(),
+            read_hedging: Self::default_read_hedging(),
+            warm_start_max_age: Self::default_warm_start_max_age(),
+            refresh_policy: Self::default_refresh_policy(),
+        }
+    }
+}
+
+/// Settings for hedged reads, where a read with a state proof is sent to an additional
+/// node when no verified reply has been received within the expected latency
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HedgePolicy {
+    /// The percentile of recent node reply latencies used as the hedging delay
+    #[serde(default = "HedgePolicy::default_percentile")]
+    pub percentile: f32,
+    /// The minimum hedging delay in milliseconds
+    #[serde(default = "HedgePolicy::default_min_delay_ms")]
+    pub min_delay_ms: u64,
+    /// The maximum hedging delay in milliseconds, also used before latencies are known
+    #[serde(default = "HedgePolicy::default_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// The maximum number of speculative requests per read, or 0 to disable hedging
+    #[serde(default = "HedgePolicy::default_max_hedges")]
+    pub max_hedges: usize,
+}
+
+impl Validatable for HedgePolicy {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if !(self.percentile > 0.0 && self.percentile <= 1.0) {
+            return Err(invalid!("`percentile` must be between 0 and 1"));
         }
+        if self.min_delay_ms > self.max_delay_ms {
+            return Err(invalid!("`min_delay_ms` must not exceed `max_delay_ms`"));
+        }
+        Ok(())
+    }
+}
+
+impl HedgePolicy {
+    /// The default latency percentile used as the hedging delay
+    pub fn default_percentile() -> f32 {
+        constants::DEFAULT_HEDGE_PERCENTILE
+    }
+
+    /// The default minimum hedging delay
+    pub fn default_min_delay_ms() -> u64 {
+        constants::DEFAULT_HEDGE_MIN_DELAY_MS
+    }
+
+    /// The default maximum hedging delay
+    pub fn default_max_delay_ms() -> u64 {
+        constants::DEFAULT_HEDGE_MAX_DELAY_MS
+    }
+
+    /// The default maximum number of speculative requests
+    pub fn default_max_hedges() -> usize {
+        constants::DEFAULT_HEDGE_MAX_REQUESTS
+    }
+
+    /// Determine the hedging delay given the latency at the configured percentile
+    pub fn delay(&self, latency: Option<f32>) -> Duration {
+        let min = Duration::from_millis(self.min_delay_ms);
+        let max = Duration::from_millis(self.max_delay_ms);
+        latency
+            .filter(|secs| secs.is_finite() && *secs >= 0.0)
+            .map(|secs| Duration::from_secs_f32(secs.min(max.as_secs_f32())))
+            .unwrap_or(max)
+            .clamp(min, max)
+    }
+}
+
+impl Default for HedgePolicy {
+    fn default() -> Self {
+        Self {
+            percentile: Self::default_percentile(),
+            min_delay_ms: Self::default_min_delay_ms(),
+            max_delay_ms: Self::default_max_delay_ms(),
+            max_hedges: Self::default_max_hedges(),
+        }
+    }
+}
+
+/// Settings for refreshing the pool transactions in the background
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RefreshPolicy {
+    /// The interval between refreshes in seconds, or 0 to only refresh when triggered
+    #[serde(default = "RefreshPolicy::default_interval")]
+    pub interval: u64,
+    /// The maximum random delay added to each interval, as a fraction of the interval
+    #[serde(default = "RefreshPolicy::default_jitter")]
+    pub jitter: f32,
+    /// The number of node timeouts and invalid state proofs since the last refresh
+    /// which trigger a refresh, or 0 to disable
+    #[serde(default = "RefreshPolicy::default_failure_threshold")]
+    pub failure_threshold: u64,
+    /// Refresh when a verified state proof reports a different pool ledger root
+    #[serde(default = "RefreshPolicy::default_on_status_mismatch")]
+    pub on_status_mismatch: bool,
+}
+
+impl Validatable for RefreshPolicy {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if !(0.0..=1.0).contains(&self.jitter) {
+            return Err(invalid!("`jitter` must be between 0 and 1"));
+        }
+        Ok(())
+    }
+}
+
+impl RefreshPolicy {
+    /// The default interval between refreshes
+    pub fn default_interval() -> u64 {
+        constants::DEFAULT_REFRESH_INTERVAL
+    }
+
+    /// The default jitter applied to the refresh interval
+    pub fn default_jitter() -> f32 {
+        constants::DEFAULT_REFRESH_JITTER
+    }
+
+    /// The default number of node failures which trigger a refresh
+    pub fn default_failure_threshold() -> u64 {
+        constants::DEFAULT_REFRESH_FAILURE_THRESHOLD
+    }
+
+    /// Refresh on a pool ledger status mismatch by default
+    pub fn default_on_status_mismatch() -> bool {
+        true
+    }
+
+    /// Determine the delay before the next scheduled refresh, if any
+    pub fn next_delay(&self) -> Option<Duration> {
+        if self.interval == 0 {
+            return None;
+        }
+        let interval = Duration::from_secs(self.interval);
+        Some(interval + interval.mul_f32(self.jitter * rand::random::<f32>()))
+    }
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        Self {
+            interval: Self::default_interval(),
+            jitter: Self::default_jitter(),
+            failure_threshold: Self::default_failure_threshold(),
+            on_status_mismatch: Self::default_on_status_mismatch(),
+        }
+    }
+}
+
+/// Determines whether replies to reads must carry a verified state proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateProofMode {
+    /// Fail the request unless a reply with a verified state proof is received
+    Required,
+    /// Accept `f + 1` matching replies when no state proof can be verified
+    Preferred,
+    /// Skip state proof verification and rely on `f + 1` matching replies
+    Disabled,
+}
+
+/// Settings for the verification of state proofs in the replies to a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateProofPolicy {
+    /// The handling of replies without a verified state proof
+    #[serde(default = "StateProofPolicy::default_mode")]
+    pub mode: StateProofMode,
+    /// The maximum age in seconds of a fresh state proof, overriding the pool configuration
+    #[serde(default)]
+    pub freshness_threshold: Option<u64>,
+    /// The minimum number of nodes participating in the multi-signature
+    #[serde(default)]
+    pub min_signers: Option<usize>,
+}
+
+impl Validatable for StateProofPolicy {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.freshness_threshold == Some(0) {
+            return Err(invalid!("`freshness_threshold` must be greater than 0"));
+        }
+        Ok(())
+    }
+}
+
+impl StateProofPolicy {
+    /// Create a new policy with the given mode
+    pub fn new(mode: StateProofMode) -> Self {
+        Self {
+            mode,
+            freshness_threshold: None,
+            min_signers: None,
+        }
+    }
+
+    /// State proofs are preferred by default
+    pub fn default_mode() -> StateProofMode {
+        StateProofMode::Preferred
+    }
+
+    /// Determine whether a verified state proof is required
+    pub fn is_required(&self) -> bool {
+        self.mode == StateProofMode::Required
+    }
+
+    /// Determine whether state proofs are verified
+    pub fn is_enabled(&self) -> bool {
+        self.mode != StateProofMode::Disabled
+    }
+}
+
+impl Default for StateProofPolicy {
+    fn default() -> Self {
+        Self::new(Self::default_mode())
     }
 }
@@ -1,43 +1,263 @@
-use std::ffi::c_char;
-//use crate::tools::wallet::Wallet;
-
-//use libc::c_int;
-use std::ffi::CStr;
-//use futures_util::future::ok;
-
-use crate::tools::did::Did;
-use crate::ffi::wallet;
-use crate::tools::did::DidInfo;
-
-#[no_mangle]
-pub extern "C" fn did_create(key_ptr: *const c_char, wallet_name_ptr: *const c_char, seed_ptr: *const c_char, method_ptr: *const c_char, metadata_ptr: *const c_char) -> Result<(), ()> {
-    println!("Starting DID creation...");
-    let seed = unsafe { CStr::from_ptr(seed_ptr).to_str().expect("Invalid UTF-8 sequence") };
-    let method = unsafe { CStr::from_ptr(method_ptr).to_str().expect("Invalid UTF-8 sequence") };
-    let metadata = unsafe { CStr::from_ptr(metadata_ptr).to_str().expect("Invalid UTF-8 sequence") };
-    let openwallet = wallet::wallet_open(key_ptr, wallet_name_ptr);
-    let (did, vk) = Did::create(&openwallet,Some(seed),Some(metadata), Some(method))
-    .map_err(|err| println_err!("{}", err.message(None)))?;
-    let abbreviate_verkey = Did::abbreviate_verkey(&did, &vk);
-    let vk = abbreviate_verkey.unwrap_or(vk);
-    println_succ!("Did \"{}\" has been created with \"{}\" verkey", did, vk);
-    Ok(())
-}
-
-#[no_mangle]
-pub extern "C" fn did_list(key_ptr: *const c_char, wallet_name_ptr: *const c_char) -> Result<Vec<DidInfo>, ()> {
-    println!("Listing DIDs...");
-    let openwallet = wallet::wallet_open(key_ptr, wallet_name_ptr);
-    let mut dids: Vec<crate::tools::did::DidInfo> = Did::list(&openwallet).map_err(|err| println_err!("{}", err.message(None)))?;
-        for did_info in dids.iter_mut() {
-            did_info.verkey = Did::abbreviate_verkey(&did_info.did, &did_info.verkey)
-                .unwrap_or_else(|_| did_info.verkey.clone());
-        }
-    let vec = dids;
-    Ok(vec)
-}
-
-#[no_mangle]
-pub extern "C" fn did_use() {
-    
-}
\ No newline at end of file
+use std::os::raw::c_char;
+
+use ffi_support::{rust_string_to_c, ByteBuffer, FfiStr};
+
+use crate::common::error::prelude::*;
+use crate::tools::did::Did;
+use crate::utils::did::DidValue;
+use crate::utils::Qualifiable;
+
+use super::error::{set_last_error, ErrorCode};
+use super::wallet::{get_wallet, spawn_wallet_task, WalletHandle};
+use super::CallbackId;
+
+fn to_json<T: serde::Serialize>(result: &T) -> VdrResult<String> {
+    serde_json::to_string(result).with_err_msg(VdrErrorKind::Unexpected, "Error serializing result")
+}
+
+/// Create a new DID in a wallet.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param seed optional seed for the key: 32 bytes, or base64 or hex encoded
+/// @param metadata optional metadata to store with the DID
+/// @param method optional DID method used to qualify the new DID
+/// @param cb callback receiving a JSON object `{"did": ..., "verkey": ...}`
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_did_create(
+    wallet_handle: WalletHandle,
+    seed: FfiStr,
+    metadata: FfiStr,
+    method: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode, result: *const c_char)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Create DID: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let seed = seed.into_opt_string();
+        let metadata = metadata.into_opt_string();
+        let method = method.into_opt_string();
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move {
+                let (did, verkey) = Did::create_async(
+                    &*wallet.read().await?,
+                    seed.as_deref(),
+                    metadata.as_deref(),
+                    method.as_deref(),
+                )
+                .await?;
+                to_json(&json!({ "did": did, "verkey": verkey }))
+            },
+            move |errcode, result| {
+                cb(cb_id, errcode, rust_string_to_c(result.unwrap_or_default()))
+            },
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// List the DIDs stored in a wallet as a JSON array.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param cb callback receiving the JSON string
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_did_list(
+    wallet_handle: WalletHandle,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode, result: *const c_char)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("List DIDs: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move { to_json(&Did::list_async(&*wallet.read().await?).await?) },
+            move |errcode, result| {
+                cb(cb_id, errcode, rust_string_to_c(result.unwrap_or_default()))
+            },
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Fetch the stored information for a DID as a JSON object.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the DID to look up
+/// @param cb callback receiving the JSON string
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_did_get(
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode, result: *const c_char)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Get DID: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let did = DidValue::from_str(did.as_str())?;
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move { to_json(&Did::get_async(&*wallet.read().await?, &did).await?) },
+            move |errcode, result| {
+                cb(cb_id, errcode, rust_string_to_c(result.unwrap_or_default()))
+            },
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Generate a new key for a DID, to be applied with `indy_vdr_did_replace_keys_apply`.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the DID to rotate keys for
+/// @param seed optional seed for the new key
+/// @param cb callback receiving the new verkey
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_did_replace_keys_start(
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+    seed: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode, verkey: *const c_char)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Replace DID keys start: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let did = did.into_string();
+        let seed = seed.into_opt_string();
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move {
+                Did::replace_keys_start_async(&*wallet.read().await?, &did, seed.as_deref()).await
+            },
+            move |errcode, verkey| {
+                cb(cb_id, errcode, rust_string_to_c(verkey.unwrap_or_default()))
+            },
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Replace the current key of a DID with the pending key.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the DID to rotate keys for
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_did_replace_keys_apply(
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Replace DID keys apply: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let did = did.into_string();
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move { Did::replace_keys_apply_async(&*wallet.read().await?, &did).await },
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Update the metadata stored with a DID.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the DID to update
+/// @param metadata the new metadata
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_did_set_metadata(
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+    metadata: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Set DID metadata: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let did = did.into_string();
+        let metadata = metadata.into_string();
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move { Did::set_metadata_async(&*wallet.read().await?, &did, &metadata).await },
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Replace an unqualified DID in a wallet with its fully-qualified form.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the DID to qualify
+/// @param method the DID method
+/// @param cb callback receiving the qualified DID
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_did_qualify(
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+    method: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode, did: *const c_char)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Qualify DID: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let did = DidValue::from_str(did.as_str())?;
+        let method = method.into_string();
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move { Did::qualify_async(&*wallet.read().await?, &did, &method).await },
+            move |errcode, qualified| {
+                let qualified = qualified.map(|did| did.to_string()).unwrap_or_default();
+                cb(cb_id, errcode, rust_string_to_c(qualified))
+            },
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Sign a message with the current key of a DID.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the signing DID
+/// @param message the message bytes
+/// @param cb callback receiving the signature, to be freed with `indy_vdr_buffer_free`
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_did_sign(
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+    message: ByteBuffer,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode, signature: ByteBuffer)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Sign with DID: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let did = did.into_string();
+        let message = message.as_slice().to_vec();
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move { Did::sign_async(&*wallet.read().await?, &did, &message).await },
+            move |errcode, signature| {
+                cb(cb_id, errcode, ByteBuffer::from_vec(signature.unwrap_or_default()))
+            },
+        );
+        Ok(ErrorCode::Success)
+    }
+}
@@ -24,6 +24,15 @@ pub enum ErrorCode {
     PoolRequestFailed = 31,
     PoolTimeout = 32,
     Resolver = 9,
+    Wallet = 40,
+    WalletNotFound = 41,
+    WalletDuplicate = 42,
+    WalletAccessDenied = 43,
+    WalletInvalidState = 44,
+    WalletItemNotFound = 45,
+    DidNotFound = 50,
+    DidDuplicate = 51,
+    KeyNotFound = 52,
 }
 
 impl From<&VdrErrorKind> for ErrorCode {
@@ -41,6 +50,15 @@ impl From<&VdrErrorKind> for ErrorCode {
             VdrErrorKind::PoolRequestFailed(_) => ErrorCode::PoolRequestFailed,
             VdrErrorKind::PoolTimeout => ErrorCode::PoolTimeout,
             VdrErrorKind::Resolver => ErrorCode::Resolver,
+            VdrErrorKind::Wallet => ErrorCode::Wallet,
+            VdrErrorKind::WalletNotFound => ErrorCode::WalletNotFound,
+            VdrErrorKind::WalletDuplicate => ErrorCode::WalletDuplicate,
+            VdrErrorKind::WalletAccessDenied => ErrorCode::WalletAccessDenied,
+            VdrErrorKind::WalletInvalidState => ErrorCode::WalletInvalidState,
+            VdrErrorKind::WalletItemNotFound => ErrorCode::WalletItemNotFound,
+            VdrErrorKind::DidNotFound => ErrorCode::DidNotFound,
+            VdrErrorKind::DidDuplicate => ErrorCode::DidDuplicate,
+            VdrErrorKind::KeyNotFound => ErrorCode::KeyNotFound,
         }
     }
 }
@@ -111,9 +111,9 @@ pub extern "C" fn indy_vdr_build_attrib_request(
 
 #[no_mangle]
 pub extern "C" fn indy_vdr_build_handle_request(
-    submitter_did: FfiStr, // optional
+    submitter_did: FfiStr,
     target_did: FfiStr,
-    handle: FfiStr,  // optional
+    handle: FfiStr,
     handle_p: *mut RequestHandle,
 ) -> ErrorCode {
     catch_err! {
@@ -132,20 +132,96 @@ pub extern "C" fn indy_vdr_build_handle_request(
     }
 }
 
-pub extern "C" fn indy_vdr_build_auction_request(
-    submitter_did: FfiStr, // optional
+#[no_mangle]
+pub extern "C" fn indy_vdr_build_auction_start_request(
+    submitter_did: FfiStr,
     target_did: FfiStr,
-    handle: FfiStr,  // optional
+    auction_id: FfiStr,
+    handle: FfiStr,
     handle_p: *mut RequestHandle,
 ) -> ErrorCode {
     catch_err! {
-        trace!("Build auction request");
+        trace!("Build AUCTION_START request");
         check_useful_c_ptr!(handle_p);
         let builder = get_request_builder()?;
         let identifier = DidValue::from_str(submitter_did.as_str())?;
         let dest = DidValue::from_str(target_did.as_str())?;
-        let handle = handle.as_str();
-        let req = builder.build_auction_request(&identifier, &dest, handle.to_string())?;
+        let auction_id = auction_id.into_string();
+        let handle = handle.into_string();
+        let req = builder.build_auction_start_request(&identifier, &dest, auction_id, handle)?;
+        let reqhandle = add_request(req)?;
+        unsafe {
+            *handle_p = reqhandle;
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn indy_vdr_build_auction_bid_request(
+    submitter_did: FfiStr,
+    auction_id: FfiStr,
+    amount: i64,
+    handle_p: *mut RequestHandle,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Build AUCTION_BID request");
+        check_useful_c_ptr!(handle_p);
+        let builder = get_request_builder()?;
+        let identifier = DidValue::from_str(submitter_did.as_str())?;
+        let auction_id = auction_id.into_string();
+        let amount = u64::try_from(amount).map_err(|_| input_err("Invalid bid amount"))?;
+        let req = builder.build_auction_bid_request(&identifier, auction_id, amount)?;
+        let reqhandle = add_request(req)?;
+        unsafe {
+            *handle_p = reqhandle;
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn indy_vdr_build_auction_close_request(
+    submitter_did: FfiStr,
+    auction_id: FfiStr,
+    handle_p: *mut RequestHandle,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Build AUCTION_CLOSE request");
+        check_useful_c_ptr!(handle_p);
+        let builder = get_request_builder()?;
+        let identifier = DidValue::from_str(submitter_did.as_str())?;
+        let auction_id = auction_id.into_string();
+        let req = builder.build_auction_close_request(&identifier, auction_id)?;
+        let reqhandle = add_request(req)?;
+        unsafe {
+            *handle_p = reqhandle;
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn indy_vdr_build_get_auction_request(
+    submitter_did: FfiStr, // optional
+    auction_id: FfiStr,
+    seq_no: i32,    // optional, -1 for None
+    timestamp: i64, // optional, -1 for None
+    handle_p: *mut RequestHandle,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Build GET_AUCTION request");
+        check_useful_c_ptr!(handle_p);
+        let builder = get_request_builder()?;
+        let identifier = submitter_did
+            .as_opt_str()
+            .map(DidValue::from_str)
+            .transpose()?;
+        let auction_id = auction_id.into_string();
+        let seq_no = if seq_no == -1 { None } else { Some(seq_no) };
+        let timestamp = if timestamp == -1 { None } else { Some(timestamp as u64) };
+        let req =
+            builder.build_get_auction_request(identifier.as_ref(), auction_id, seq_no, timestamp)?;
         let reqhandle = add_request(req)?;
         unsafe {
             *handle_p = reqhandle;
@@ -154,27 +230,28 @@ pub extern "C" fn indy_vdr_build_auction_request(
     }
 }
 
-// pub extern "C" fn indy_vdr_build_add_handle_request(
-//     submitter_did: FfiStr, // optional
-//     target_did: FfiStr,
-//     handle: FfiStr,  // optional
-//     handle_p: *mut RequestHandle,
-// ) -> ErrorCode {
-//     catch_err! {
-//         trace!("Build add handle request");
-//         check_useful_c_ptr!(handle_p);
-//         let builder = get_request_builder()?;
-//         let identifier = DidValue::from_str(submitter_did.as_str())?;
-//         let dest = DidValue::from_str(target_did.as_str())?;
-//         let handle = handle.as_str();
-//         let req = builder.build_handle_request(&identifier, &dest, handle.to_string())?;
-//         let reqhandle = add_request(req)?;
-//         unsafe {
-//             *handle_p = reqhandle;
-//         }
-//         Ok(ErrorCode::Success)
-//     }
-// }
+#[no_mangle]
+pub extern "C" fn indy_vdr_build_handle_transfer_request(
+    submitter_did: FfiStr,
+    target_did: FfiStr,
+    handle: FfiStr,
+    handle_p: *mut RequestHandle,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Build HANDLE_TRANSACTION request");
+        check_useful_c_ptr!(handle_p);
+        let builder = get_request_builder()?;
+        let identifier = DidValue::from_str(submitter_did.as_str())?;
+        let dest = DidValue::from_str(target_did.as_str())?;
+        let handle = handle.into_string();
+        let req = builder.build_handle_transfer_request(&identifier, &dest, handle)?;
+        let reqhandle = add_request(req)?;
+        unsafe {
+            *handle_p = reqhandle;
+        }
+        Ok(ErrorCode::Success)
+    }
+}
 
 #[no_mangle]
 pub extern "C" fn indy_vdr_build_get_attrib_request(
@@ -210,29 +287,33 @@ pub extern "C" fn indy_vdr_build_get_attrib_request(
     }
 }
 
-// pub extern "C" fn indy_vdr_build_get_handle_request(
-//     submitter_did: FfiStr, // optional
-//     dest: FfiStr,
-//     seq_no: i32,    // optional, -1 for None
-//     timestamp: i64, // optional, -1 for None
-//     handle_p: *mut RequestHandle,
-// ) -> ErrorCode {
-//     catch_err! {
-//         trace!("Build GET_HANDLE request");
-//         check_useful_c_ptr!(handle_p);
-//         let builder = get_request_builder()?;
-//         let identifier = submitter_did.as_opt_str().map(DidValue::from_str).transpose()?;
-//         let dest = DidValue::from_str(dest.as_str())?;
-//         let seq_no = if seq_no == -1 { None } else { Some(seq_no) };
-//         let timestamp = if timestamp == -1 { None } else { Some(timestamp as u64) };
-//         let req = builder.build_get_nym_request(identifier.as_ref(), &dest, seq_no, timestamp)?;
-//         let handle = add_request(req)?;
-//         unsafe {
-//             *handle_p = handle;
-//         }
-//         Ok(ErrorCode::Success)
-//     }
-// }
+#[no_mangle]
+pub extern "C" fn indy_vdr_build_get_handle_request(
+    submitter_did: FfiStr, // optional
+    handle: FfiStr,
+    seq_no: i32,    // optional, -1 for None
+    timestamp: i64, // optional, -1 for None
+    handle_p: *mut RequestHandle,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Build GET_HANDLE request");
+        check_useful_c_ptr!(handle_p);
+        let builder = get_request_builder()?;
+        let identifier = submitter_did
+            .as_opt_str()
+            .map(DidValue::from_str)
+            .transpose()?;
+        let handle = handle.into_string();
+        let seq_no = if seq_no == -1 { None } else { Some(seq_no) };
+        let timestamp = if timestamp == -1 { None } else { Some(timestamp as u64) };
+        let req = builder.build_get_handle_request(identifier.as_ref(), handle, seq_no, timestamp)?;
+        let reqhandle = add_request(req)?;
+        unsafe {
+            *handle_p = reqhandle;
+        }
+        Ok(ErrorCode::Success)
+    }
+}
 
 #[no_mangle]
 pub extern "C" fn indy_vdr_build_cred_def_request(
@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 use std::os::raw::c_char;
 use std::sync::Arc;
 
-use ffi_support::{define_string_destructor, rust_string_to_c, FfiStr};
+use ffi_support::{define_bytebuffer_destructor, define_string_destructor, rust_string_to_c, FfiStr};
 
 #[macro_use]
 mod macros;
@@ -10,8 +10,10 @@ mod macros;
 mod error;
 mod ledger;
 mod pool;
+mod registry;
 mod requests;
 mod resolver;
+mod state_proof;
 mod wallet;
 mod did;
 
@@ -25,6 +27,7 @@ use self::pool::{POOL_CACHE, POOL_CONFIG};
 
 pub type CallbackId = i64;
 
+define_bytebuffer_destructor!(indy_vdr_buffer_free);
 define_string_destructor!(indy_vdr_string_free);
 
 #[no_mangle]
@@ -10,8 +10,8 @@ use crate::common::error::prelude::*;
 use crate::common::handle::ResourceHandle;
 use crate::config::PoolConfig;
 use crate::pool::{
-    InMemoryCache, PoolBuilder, PoolRunner, PoolTransactions, PoolTransactionsCache, RequestMethod,
-    RequestResult, RequestResultMeta,
+    EventSubscription, InMemoryCache, PoolBuilder, PoolEvents, PoolRunner, PoolTransactions,
+    PoolTransactionsCache, RequestMethod, RequestResult, RequestResultMeta,
 };
 
 use super::error::{set_last_error, ErrorCode};
@@ -26,8 +26,11 @@ impl_sequence_handle!(PoolHandle, FFI_PH_COUNTER);
 
 pub struct PoolInstance {
     pub runner: PoolRunner,
+    /// The transactions the pool was created from, used as the pool cache key
     pub init_txns: PoolTransactions,
     pub node_weights: Option<NodeWeights>,
+    /// Event listeners registered through FFI, retained across refreshes
+    pub events: Arc<PoolEvents>,
 }
 
 pub type NodeWeights = HashMap<String, f32>;
@@ -41,7 +44,7 @@ pub static POOL_CACHE: Lazy<RwLock<Option<Arc<dyn PoolTransactionsCache>>>> =
     Lazy::new(|| RwLock::new(Some(Arc::new(InMemoryCache::new()))));
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
-struct PoolCreateParams {
+pub(super) struct PoolCreateParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transactions: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -50,6 +53,20 @@ struct PoolCreateParams {
     pub node_weights: Option<NodeWeights>,
 }
 
+impl PoolCreateParams {
+    pub(super) fn load_transactions(&self) -> VdrResult<PoolTransactions> {
+        if let Some(txns) = self.transactions.as_ref() {
+            PoolTransactions::from_json(txns.as_str())
+        } else if let Some(path) = self.transactions_path.as_ref() {
+            PoolTransactions::from_json_file(path.as_str())
+        } else {
+            Err(input_err(
+                "Invalid pool create parameters: must provide transactions or transactions_path",
+            ))
+        }
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn indy_vdr_pool_create(params: FfiStr, handle_p: *mut PoolHandle) -> ErrorCode {
     catch_err! {
@@ -57,29 +74,13 @@ pub extern "C" fn indy_vdr_pool_create(params: FfiStr, handle_p: *mut PoolHandle
         check_useful_c_ptr!(handle_p);
         let params = serde_json::from_str::<PoolCreateParams>(params.as_str())
             .with_input_err("Error deserializing pool create parameters")?;
-        let mut txns = if let Some(txns) = params.transactions {
-            PoolTransactions::from_json(txns.as_str())?
-        }
-        else if let Some(path) = params.transactions_path {
-            PoolTransactions::from_json_file(path.as_str())?
-        }
-        else {
-            return Err(input_err(
-                "Invalid pool create parameters: must provide transactions or transactions_path"
-            ));
-        };
-        let mut cached = false;
-        if let Some(cache) = read_lock!(POOL_CACHE)?.as_ref() {
-            if let Some(newer_txns) = cache.resolve_latest(&txns)? {
-                txns = newer_txns;
-                cached = true;
-            }
-        }
-        let config = read_lock!(POOL_CONFIG)?.clone();
-        let runner = PoolBuilder::new(config, txns.clone()).node_weights(params.node_weights.clone()).refreshed(cached).into_runner()?;
+        let init_txns = params.load_transactions()?;
+        let runner = build_runner(&init_txns, None, params.node_weights.clone())?;
+        let events = Arc::new(PoolEvents::default());
+        forward_events(&runner, &events)?;
         let handle = PoolHandle::next();
         let mut pools = write_lock!(POOLS)?;
-        pools.insert(handle, PoolInstance { runner, init_txns: txns, node_weights: params.node_weights });
+        pools.insert(handle, PoolInstance { runner, init_txns, node_weights: params.node_weights, events });
         unsafe {
             *handle_p = handle;
         }
@@ -87,6 +88,44 @@ pub extern "C" fn indy_vdr_pool_create(params: FfiStr, handle_p: *mut PoolHandle
     }
 }
 
+/// Build a pool runner, resolving the latest transactions from the pool cache when
+/// `latest_txns` is not provided.
+///
+/// The cached transactions and snapshots are keyed by the `init_txns` used to create the pool.
+fn build_runner(
+    init_txns: &PoolTransactions,
+    latest_txns: Option<PoolTransactions>,
+    node_weights: Option<NodeWeights>,
+) -> VdrResult<PoolRunner> {
+    let cache = read_lock!(POOL_CACHE)?.clone();
+    let mut refreshed = latest_txns.is_some();
+    let txns = match (latest_txns, cache.as_ref()) {
+        (Some(txns), _) => txns,
+        (None, Some(cache)) => match cache.resolve_latest(init_txns)? {
+            Some(txns) => {
+                refreshed = true;
+                txns
+            }
+            None => init_txns.clone(),
+        },
+        (None, None) => init_txns.clone(),
+    };
+    let config = read_lock!(POOL_CONFIG)?.clone();
+    let mut builder = PoolBuilder::new(config, txns)
+        .node_weights(node_weights)
+        .refreshed(refreshed);
+    if let Some(cache) = cache {
+        builder = builder.snapshot_cache(cache, init_txns.clone());
+    }
+    builder.into_runner()
+}
+
+fn forward_events(runner: &PoolRunner, events: &Arc<PoolEvents>) -> VdrResult<()> {
+    let events = events.clone();
+    runner.subscribe(Arc::new(move |event| events.emit(event.clone())))?;
+    Ok(())
+}
+
 fn handle_pool_refresh(
     pool_handle: PoolHandle,
     init_txns: PoolTransactions,
@@ -94,17 +133,16 @@ fn handle_pool_refresh(
     node_weights: Option<NodeWeights>,
 ) -> ErrorCode {
     catch_err! {
-        let latest_txns = new_txns.as_ref().unwrap_or(&init_txns);
-        let count = latest_txns.len();
-        debug!("Updating pool transactions, length: {count}");
-        let config = read_lock!(POOL_CONFIG)?.clone();
-        if let Some(cache) = read_lock!(POOL_CACHE)?.as_ref() {
-            cache.update(&init_txns, latest_txns)?;
-        }
         if let Some(new_txns) = new_txns {
-            let runner = PoolBuilder::new(config, new_txns).node_weights(node_weights).refreshed(true).into_runner()?;
+            let count = new_txns.len();
+            debug!("Updating pool transactions, length: {count}");
+            if let Some(cache) = read_lock!(POOL_CACHE)?.as_ref() {
+                cache.update(&init_txns, &new_txns)?;
+            }
+            let runner = build_runner(&init_txns, Some(new_txns), node_weights)?;
             let mut pools = write_lock!(POOLS)?;
             if let Entry::Occupied(mut entry) = pools.entry(pool_handle) {
+                forward_events(&runner, &entry.get().events)?;
                 entry.get_mut().runner = runner;
             } else {
                 return Err(err_msg(VdrErrorKind::Unexpected, "Pool was freed before refresh completed"))
@@ -124,7 +162,7 @@ pub extern "C" fn indy_vdr_pool_refresh(
         trace!("Refresh pool");
         let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
         let pools = read_lock!(POOLS)?;
-        let PoolInstance { runner, init_txns, node_weights } = pools.get(&pool_handle)
+        let PoolInstance { runner, init_txns, node_weights, .. } = pools.get(&pool_handle)
             .ok_or_else(|| input_err("Unknown pool handle"))?;
         let init_txns = init_txns.clone();
         let node_weights = node_weights.clone();
@@ -153,6 +191,60 @@ pub extern "C" fn indy_vdr_pool_refresh(
     }
 }
 
+/// Register a callback receiving the events reported by a pool.
+///
+/// Each event is passed to the callback as a JSON object with an `event` property
+/// indicating the event type.
+///
+/// The callback is run synchronously on the thread handling the pool's network events,
+/// so it should return promptly and must not close the pool.
+///
+/// @param subscription_p pointer receiving the subscription handle
+#[no_mangle]
+pub extern "C" fn indy_vdr_pool_subscribe_events(
+    pool_handle: PoolHandle,
+    cb: Option<extern "C" fn(cb_id: CallbackId, event: *const c_char)>,
+    cb_id: CallbackId,
+    subscription_p: *mut i64,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Subscribe to pool events: {}", pool_handle);
+        check_useful_c_ptr!(subscription_p);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let pools = read_lock!(POOLS)?;
+        let PoolInstance { events, .. } = pools.get(&pool_handle)
+            .ok_or_else(|| input_err("Unknown pool handle"))?;
+        let subscription = events.subscribe(Arc::new(move |event| {
+            match serde_json::to_string(event) {
+                Ok(event) => cb(cb_id, rust_string_to_c(event)),
+                Err(err) => warn!("Error serializing pool event: {}", err),
+            }
+        }));
+        unsafe {
+            *subscription_p = *subscription;
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Remove a callback registered with `indy_vdr_pool_subscribe_events`.
+#[no_mangle]
+pub extern "C" fn indy_vdr_pool_unsubscribe_events(
+    pool_handle: PoolHandle,
+    subscription: i64,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Unsubscribe from pool events: {}", pool_handle);
+        let pools = read_lock!(POOLS)?;
+        let PoolInstance { events, .. } = pools.get(&pool_handle)
+            .ok_or_else(|| input_err("Unknown pool handle"))?;
+        if !events.unsubscribe(EventSubscription(subscription)) {
+            return Err(input_err("Unknown event subscription"));
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn indy_vdr_pool_get_status(
     pool_handle: PoolHandle,
@@ -334,6 +426,7 @@ pub extern "C" fn indy_vdr_pool_submit_request(
 pub extern "C" fn indy_vdr_pool_close(pool_handle: PoolHandle) -> ErrorCode {
     catch_err! {
         let mut pools = write_lock!(POOLS)?;
+        // the pool snapshot is stored by the pool thread before it exits
         pools.remove(&pool_handle)
             .ok_or_else(|| input_err("Unknown pool handle"))?;
         Ok(ErrorCode::Success)
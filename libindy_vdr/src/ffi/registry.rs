@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::os::raw::c_char;
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+use ffi_support::{rust_string_to_c, FfiStr};
+use futures_channel::mpsc::{unbounded, UnboundedSender};
+use futures_executor::LocalPool;
+use futures_util::future::{self, FutureExt, LocalBoxFuture};
+use futures_util::stream::StreamExt;
+use futures_util::task::LocalSpawnExt;
+use once_cell::sync::Lazy;
+
+use crate::common::error::prelude::*;
+use crate::pool::{PoolBuilder, PoolRegistry, PoolTransactions, PoolTransactionsCache, SharedPool};
+use crate::resolver::PoolResolver;
+
+use super::error::{set_last_error, ErrorCode};
+use super::pool::{PoolCreateParams, POOL_CACHE, POOL_CONFIG};
+use super::CallbackId;
+
+static REGISTRY: Lazy<Arc<PoolRegistry>> = Lazy::new(|| {
+    Arc::new(PoolRegistry::with_connector(
+        Default::default(),
+        connect_pool,
+    ))
+});
+
+/// The transactions each namespace was registered with, used as the pool cache key
+static REGISTERED_TXNS: Lazy<RwLock<HashMap<String, PoolTransactions>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Connect a registered pool, persisting its snapshots to the pool cache if one is configured
+fn connect_pool(namespace: &str, mut builder: PoolBuilder) -> VdrResult<SharedPool> {
+    let init_txns = read_lock!(REGISTERED_TXNS)?.get(namespace).cloned();
+    if let (Some(cache), Some(init_txns)) = (read_lock!(POOL_CACHE)?.clone(), init_txns) {
+        builder = builder.snapshot_cache(cache, init_txns);
+    }
+    builder.into_shared()
+}
+
+type RegistryTask = Box<dyn FnOnce() -> LocalBoxFuture<'static, ()> + Send>;
+
+/// Registry requests are run on a single worker thread, as pool requests are not `Send`
+static WORKER: Lazy<UnboundedSender<RegistryTask>> = Lazy::new(|| {
+    let (sender, receiver) = unbounded::<RegistryTask>();
+    thread::spawn(move || {
+        let mut pool = LocalPool::new();
+        let spawner = pool.spawner();
+        pool.run_until(receiver.for_each(|task| {
+            spawner.spawn_local(task()).ok();
+            future::ready(())
+        }));
+    });
+    sender
+});
+
+type ResolveCallback = extern "C" fn(cb_id: CallbackId, err: ErrorCode, response: *const c_char);
+type RefreshCallback = extern "C" fn(cb_id: CallbackId, err: ErrorCode);
+
+fn spawn_task<F, R>(task: F) -> VdrResult<()>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Future<Output = ()> + 'static,
+{
+    WORKER
+        .unbounded_send(Box::new(move || task().boxed_local()))
+        .with_err_msg(VdrErrorKind::Unexpected, "Registry worker has exited")
+}
+
+/// Register the pool used to resolve DIDs in a `did:indy` namespace.
+///
+/// @param namespace the namespace, such as `sovrin` or `sovrin:staging`
+/// @param params JSON pool parameters, as accepted by `indy_vdr_pool_create`
+#[no_mangle]
+pub extern "C" fn indy_vdr_registry_register(namespace: FfiStr, params: FfiStr) -> ErrorCode {
+    catch_err! {
+        trace!("Register pool: {:?}", namespace);
+        let namespace = namespace.as_opt_str().ok_or_else(|| input_err("No namespace provided"))?;
+        let params = serde_json::from_str::<PoolCreateParams>(params.as_str())
+            .with_input_err("Error deserializing pool create parameters")?;
+        let init_txns = params.load_transactions()?;
+        let mut txns = init_txns.clone();
+        if let Some(cache) = read_lock!(POOL_CACHE)?.as_ref() {
+            if let Some(newer_txns) = cache.resolve_latest(&txns)? {
+                txns = newer_txns;
+            }
+        }
+        REGISTRY.set_config(read_lock!(POOL_CONFIG)?.clone())?;
+        // the initial transactions must be known before the pool is first connected
+        write_lock!(REGISTERED_TXNS)?.insert(namespace.to_string(), init_txns);
+        REGISTRY.register(namespace, txns, params.node_weights)?;
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Remove the pool registered for a `did:indy` namespace.
+#[no_mangle]
+pub extern "C" fn indy_vdr_registry_deregister(namespace: FfiStr) -> ErrorCode {
+    catch_err! {
+        trace!("Deregister pool: {:?}", namespace);
+        if !REGISTRY.deregister(namespace.as_str())? {
+            return Err(input_err("Unknown pool namespace"));
+        }
+        write_lock!(REGISTERED_TXNS)?.remove(namespace.as_str());
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// List the registered pools.
+///
+/// @param result_p pointer receiving a JSON array of registered pools
+#[no_mangle]
+pub extern "C" fn indy_vdr_registry_list(result_p: *mut *const c_char) -> ErrorCode {
+    catch_err! {
+        trace!("List registered pools");
+        check_useful_c_ptr!(result_p);
+        let pools = serde_json::to_string(&REGISTRY.list()?)
+            .with_err_msg(VdrErrorKind::Unexpected, "Error serializing result")?;
+        unsafe {
+            *result_p = rust_string_to_c(pools);
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Fetch the latest transactions for the pool registered for a namespace.
+///
+/// The pool is reconnected when new transactions are found, and these are stored in
+/// the pool cache if one is configured.
+#[no_mangle]
+pub extern "C" fn indy_vdr_registry_refresh(
+    namespace: FfiStr,
+    cb: Option<RefreshCallback>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Refresh registered pool: {:?}", namespace);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let namespace = namespace.as_str().to_owned();
+        spawn_task(move || async move {
+            let errcode = match refresh_pool(&namespace).await {
+                Ok(()) => ErrorCode::Success,
+                Err(err) => {
+                    let code = ErrorCode::from(err.kind());
+                    set_last_error(Some(err));
+                    code
+                }
+            };
+            cb(cb_id, errcode)
+        })?;
+        Ok(ErrorCode::Success)
+    }
+}
+
+async fn refresh_pool(namespace: &str) -> VdrResult<()> {
+    if let Some(new_txns) = REGISTRY.refresh(namespace).await? {
+        let init_txns = read_lock!(REGISTERED_TXNS)?.get(namespace).cloned();
+        if let (Some(cache), Some(init_txns)) = (read_lock!(POOL_CACHE)?.as_ref(), init_txns) {
+            cache.update(&init_txns, &new_txns)?;
+        }
+    }
+    Ok(())
+}
+
+/// Disconnect the registered pools which have not been used recently.
+///
+/// The pools stay registered and are reconnected when next used.
+///
+/// @param max_idle the maximum idle time in seconds
+/// @param result_p pointer receiving a JSON array of the evicted namespaces
+#[no_mangle]
+pub extern "C" fn indy_vdr_registry_evict_idle(
+    max_idle: i64,
+    result_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Evict idle pools: {}", max_idle);
+        check_useful_c_ptr!(result_p);
+        let max_idle = u64::try_from(max_idle).with_input_err("Invalid idle time")?;
+        let evicted = REGISTRY.evict_idle(Duration::from_secs(max_idle))?;
+        let evicted = serde_json::to_string(&evicted)
+            .with_err_msg(VdrErrorKind::Unexpected, "Error serializing result")?;
+        unsafe {
+            *result_p = rust_string_to_c(evicted);
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+fn spawn_resolve(
+    did: String,
+    dereference: bool,
+    cb: ResolveCallback,
+    cb_id: CallbackId,
+) -> VdrResult<()> {
+    spawn_task(move || async move {
+        let resolver = PoolResolver::with_registry(REGISTRY.clone());
+        let result = if dereference {
+            resolver.dereference(&did).await
+        } else {
+            resolver.resolve(&did).await
+        };
+        let (errcode, reply) = match result {
+            Ok(result) => (ErrorCode::Success, result),
+            Err(err) => {
+                let code = ErrorCode::from(err.kind());
+                set_last_error(Some(err));
+                (code, String::new())
+            }
+        };
+        cb(cb_id, errcode, rust_string_to_c(reply))
+    })
+}
+
+/// Resolve a `did:indy` DID using the pool registered for its namespace.
+#[no_mangle]
+pub extern "C" fn indy_vdr_registry_resolve(
+    did: FfiStr,
+    cb: Option<ResolveCallback>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Resolve DID: {:?}", did);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        spawn_resolve(did.as_str().to_owned(), false, cb, cb_id)?;
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Dereference a `did:indy` DID URL using the pool registered for its namespace.
+#[no_mangle]
+pub extern "C" fn indy_vdr_registry_dereference(
+    did_url: FfiStr,
+    cb: Option<ResolveCallback>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Dereference DID Url: {:?}", did_url);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        spawn_resolve(did_url.as_str().to_owned(), true, cb, cb_id)?;
+        Ok(ErrorCode::Success)
+    }
+}
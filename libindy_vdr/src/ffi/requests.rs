@@ -10,10 +10,12 @@ use crate::common::handle::ResourceHandle;
 use crate::ledger::{RequestBuilder, TxnAuthrAgrmtAcceptanceData};
 use crate::pool::PreparedRequest;
 use crate::utils::did::DidValue;
+use crate::utils::futures::block_on;
 use crate::utils::Qualifiable;
 
 use super::error::{set_last_error, ErrorCode};
-use super::POOL_CONFIG;
+use super::wallet::{get_wallet, spawn_wallet_task, WalletHandle};
+use super::{CallbackId, POOL_CONFIG};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[repr(transparent)]
@@ -199,3 +201,201 @@ pub extern "C" fn indy_vdr_request_set_txn_author_agreement_acceptance(
         Ok(ErrorCode::Success)
     }
 }
+
+async fn sign_request_with_wallet(
+    request_handle: RequestHandle,
+    wallet_handle: WalletHandle,
+    did: DidValue,
+    multi: bool,
+) -> VdrResult<()> {
+    let wallet = get_wallet(wallet_handle)?;
+    // sign a copy of the request to avoid holding the request lock while signing
+    let (mut signed, sig_input) = {
+        let reqs = read_lock!(REQUESTS)?;
+        let req = reqs
+            .get(&request_handle)
+            .ok_or_else(|| input_err("Unknown request handle"))?;
+        let copy = PreparedRequest::new(
+            req.protocol_version,
+            req.txn_type.clone(),
+            req.req_id.clone(),
+            req.req_json.clone(),
+            None,
+        );
+        (copy, req.get_signature_input()?)
+    };
+    {
+        let wallet = wallet.read().await?;
+        if multi {
+            signed.multi_sign_with_wallet_async(&wallet, &did).await?;
+        } else {
+            signed.sign_with_wallet_async(&wallet, &did).await?;
+        }
+    }
+    let mut reqs = write_lock!(REQUESTS)?;
+    let req = reqs
+        .get_mut(&request_handle)
+        .ok_or_else(|| input_err("Unknown request handle"))?;
+    if req.get_signature_input()? != sig_input {
+        return Err(input_err("Request was modified while it was being signed"));
+    }
+    req.req_json = signed.req_json;
+    Ok(())
+}
+
+/// Sign a request using the key of a DID held in a wallet.
+///
+/// The DID must match the request identifier.
+///
+/// @param request_handle handle for the Request instance
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the signing DID
+#[no_mangle]
+pub extern "C" fn indy_vdr_request_sign_with_wallet(
+    request_handle: RequestHandle,
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Sign request with wallet: {}", request_handle);
+        let did = DidValue::from_str(did.as_str())?;
+        block_on(sign_request_with_wallet(request_handle, wallet_handle, did, false))?;
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Sign a request asynchronously using the key of a DID held in a wallet.
+///
+/// @param request_handle handle for the Request instance
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the signing DID
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_request_sign_with_wallet_async(
+    request_handle: RequestHandle,
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Sign request with wallet: {}", request_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let did = DidValue::from_str(did.as_str())?;
+        spawn_wallet_task(
+            sign_request_with_wallet(request_handle, wallet_handle, did, false),
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Add a signature to a request using the key of a DID held in a wallet.
+///
+/// The DID must match the request identifier or endorser.
+///
+/// @param request_handle handle for the Request instance
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the signing DID
+#[no_mangle]
+pub extern "C" fn indy_vdr_request_multi_sign_with_wallet(
+    request_handle: RequestHandle,
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Multi-sign request with wallet: {}", request_handle);
+        let did = DidValue::from_str(did.as_str())?;
+        block_on(sign_request_with_wallet(request_handle, wallet_handle, did, true))?;
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Add a signature to a request asynchronously using the key of a DID held in a wallet.
+///
+/// @param request_handle handle for the Request instance
+/// @param wallet_handle handle for the Wallet instance
+/// @param did the signing DID
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_request_multi_sign_with_wallet_async(
+    request_handle: RequestHandle,
+    wallet_handle: WalletHandle,
+    did: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Multi-sign request with wallet: {}", request_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let did = DidValue::from_str(did.as_str())?;
+        spawn_wallet_task(
+            sign_request_with_wallet(request_handle, wallet_handle, did, true),
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::wallet::{OpenWallet, WALLETS};
+    use crate::pool::ProtocolVersion;
+    use crate::tools::did::Did;
+    use crate::tools::wallet::{test_utils::wallet_config, Wallet};
+
+    #[test]
+    fn test_sign_request_with_wallet() {
+        let (config, credentials) = wallet_config("sign_request");
+        Wallet::create(&config, &credentials).unwrap();
+        let wallet = Wallet::open(&config, &credentials).unwrap();
+        let (did, _verkey) = Did::create(&wallet, None, None, None).unwrap();
+        let did = DidValue(did);
+        let wallet_handle = WalletHandle::next();
+        write_lock!(WALLETS)
+            .unwrap()
+            .insert(wallet_handle, OpenWallet::new(wallet));
+
+        let request = RequestBuilder::new(ProtocolVersion::default())
+            .build_nym_request(&did, &did, None, None, None, None, None)
+            .unwrap();
+        let sig_input = request.get_signature_input().unwrap();
+        let request_handle = add_request(request).unwrap();
+
+        block_on(sign_request_with_wallet(
+            request_handle,
+            wallet_handle,
+            did.clone(),
+            false,
+        ))
+        .unwrap();
+        let other = DidValue("V4SGRU86Z58d6TV7PBUe6f".to_string());
+        block_on(sign_request_with_wallet(
+            request_handle,
+            wallet_handle,
+            other,
+            false,
+        ))
+        .unwrap_err();
+
+        let wallet = write_lock!(WALLETS)
+            .unwrap()
+            .remove(&wallet_handle)
+            .unwrap();
+        let wallet = block_on(wallet.take()).unwrap();
+        let expected = Did::sign(&wallet, &did.0, sig_input.as_bytes()).unwrap();
+        let signature = write_lock!(REQUESTS)
+            .unwrap()
+            .remove(&request_handle)
+            .unwrap()
+            .req_json["signature"]
+            .clone();
+        assert_eq!(signature, json!(crate::utils::base58::encode(expected)));
+
+        wallet.close().unwrap();
+        Wallet::delete(&config, &credentials).unwrap();
+    }
+}
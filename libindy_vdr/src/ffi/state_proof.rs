@@ -0,0 +1,140 @@
+use std::os::raw::c_char;
+
+use ffi_support::{rust_string_to_c, FfiStr};
+
+use crate::common::error::prelude::*;
+use crate::pool::{StateProofOutcome, StateProofResult};
+use crate::state_proof::{LedgerReceipt, StateProofVerifier};
+
+use super::error::{set_last_error, ErrorCode};
+use super::pool::{PoolCreateParams, POOL_CONFIG};
+use super::requests::{RequestHandle, REQUESTS};
+
+/// Verify the state proof of a stored ledger reply without network access
+///
+/// @param params pool transactions as for `indy_vdr_pool_create`
+/// @param request_handle handle for the Request instance the reply corresponds to
+/// @param reply the stored reply JSON
+/// @param timestamp time to check freshness against, -1 for the current time
+/// @param result_p assigned a pointer to the verification result JSON on success
+#[no_mangle]
+pub extern "C" fn indy_vdr_verify_state_proof(
+    params: FfiStr,
+    request_handle: RequestHandle,
+    reply: FfiStr,
+    timestamp: i64, // optional, -1 for None
+    result_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Verify state proof: {}", request_handle);
+        check_useful_c_ptr!(result_p);
+        let verifier = load_verifier(params)?;
+        let result = {
+            let reqs = read_lock!(REQUESTS)?;
+            let req = reqs.get(&request_handle)
+                .ok_or_else(|| input_err("Unknown request handle"))?;
+            if timestamp == -1 {
+                verifier.verify(req, reply.as_str())?
+            } else {
+                verifier.verify_at(req, reply.as_str(), timestamp as u64)?
+            }
+        };
+        let result = rust_string_to_c(format_state_proof_result(&result).to_string());
+        unsafe {
+            *result_p = result;
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Create a portable receipt for a ledger reply containing a state proof
+///
+/// @param params pool transactions as for `indy_vdr_pool_create`, of the pool which signed the reply
+/// @param request_handle handle for the Request instance the reply corresponds to
+/// @param reply the reply JSON
+/// @param receipt_p assigned a pointer to the receipt JSON on success
+#[no_mangle]
+pub extern "C" fn indy_vdr_create_ledger_receipt(
+    params: FfiStr,
+    request_handle: RequestHandle,
+    reply: FfiStr,
+    receipt_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Create ledger receipt: {}", request_handle);
+        check_useful_c_ptr!(receipt_p);
+        let params = serde_json::from_str::<PoolCreateParams>(params.as_str())
+            .with_input_err("Error deserializing pool create parameters")?;
+        let txns = params.load_transactions()?;
+        let receipt = {
+            let reqs = read_lock!(REQUESTS)?;
+            let req = reqs.get(&request_handle)
+                .ok_or_else(|| input_err("Unknown request handle"))?;
+            LedgerReceipt::new(req, reply.as_str(), txns.root_hash_base58()?, txns.len())?
+        };
+        let receipt = serde_json::to_string(&receipt)
+            .with_err_msg(VdrErrorKind::Unexpected, "Error serializing ledger receipt")?;
+        unsafe {
+            *receipt_p = rust_string_to_c(receipt);
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Verify a ledger receipt without network access
+///
+/// @param params pool transactions as for `indy_vdr_pool_create`, of the pool which signed the reply
+/// @param receipt the receipt JSON
+/// @param timestamp time to check freshness against, -1 for the current time
+/// @param result_p assigned a pointer to the verification result JSON on success
+#[no_mangle]
+pub extern "C" fn indy_vdr_verify_ledger_receipt(
+    params: FfiStr,
+    receipt: FfiStr,
+    timestamp: i64, // optional, -1 for None
+    result_p: *mut *const c_char,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Verify ledger receipt");
+        check_useful_c_ptr!(result_p);
+        let verifier = load_verifier(params)?;
+        let receipt = serde_json::from_str::<LedgerReceipt>(receipt.as_str())
+            .with_input_err("Error deserializing ledger receipt")?;
+        let result = if timestamp == -1 {
+            verifier.verify_receipt(&receipt)?
+        } else {
+            verifier.verify_receipt_at(&receipt, timestamp as u64)?
+        };
+        let result = rust_string_to_c(format_state_proof_result(&result).to_string());
+        unsafe {
+            *result_p = result;
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+fn load_verifier(params: FfiStr) -> VdrResult<StateProofVerifier> {
+    let params = serde_json::from_str::<PoolCreateParams>(params.as_str())
+        .with_input_err("Error deserializing pool create parameters")?;
+    let txns = params.load_transactions()?;
+    let config = read_lock!(POOL_CONFIG)?.clone();
+    Ok(
+        StateProofVerifier::from_transactions(&txns, config.protocol_version)?
+            .freshness_threshold(config.freshness_threshold),
+    )
+}
+
+fn format_state_proof_result(result: &StateProofResult) -> serde_json::Value {
+    let (error, asserts) = match result {
+        StateProofResult::Missing => (None, None),
+        StateProofResult::Invalid(err, asserts) => (Some(err), asserts.as_ref()),
+        StateProofResult::Expired(asserts) | StateProofResult::Verified(asserts) => {
+            (None, Some(asserts))
+        }
+    };
+    json!({
+        "result": StateProofOutcome::from(result).as_str(),
+        "error": error,
+        "assertions": asserts,
+    })
+}
@@ -1,141 +1,439 @@
-
-use std::ffi::c_char;
-//use crate::tools::wallet::Wallet;
-
-use libc::c_int;
-use std::ffi::CStr;
-//use tokio::runtime::Runtime;
-//use tools::wallet::wallet_config::WalletConfig;
-use crate::tools::wallet::{wallet_binding::WalletBinding, Wallet};
-// use core::ffi::c_char;
-// use std::os::raw::c_char;
-// use std::ffi::CString;
-// use std::ffi::c_char;
-// //use crate::tools::wallet::Wallet;
-
-// use libc::c_int;
-// use std::ffi::CStr;
-// //use tokio::runtime::Runtime;
-// //use tools::wallet::wallet_config::WalletConfig;
-// use crate::tools::wallet::wallet_binding::WalletBinding;
-//use core::ffi::c_char;
-//use std::os::raw::c_char;
-//use std::ffi::CString;
-
-
-#[no_mangle]
-pub extern "C" fn wallet_create(key_ptr: *const c_char, wallet_name_ptr: *const c_char) -> c_int {
-    println!("Wallet function execution success...");
-   // Calculate length of key string
-    let mut key_len = 0;
-    unsafe {
-        while *key_ptr.offset(key_len) != 0 {
-            key_len += 1;
-        }
-    }
-    // Calculate length of wallet_name string
-    let mut wallet_name_len = 0;
-    unsafe {
-        while *wallet_name_ptr.offset(wallet_name_len) != 0 {
-            wallet_name_len += 1;
-        }
-    }
-    // Convert raw pointers to CStr and then to &str
-    let key = unsafe { CStr::from_ptr(key_ptr).to_str().expect("Invalid UTF-8 sequence") };
-    let wallet_name = unsafe { CStr::from_ptr(wallet_name_ptr).to_str().expect("Invalid UTF-8 sequence") };
-    println!("Rust function executing...");
-    // Define result variable in a scope that encompasses its usage
-    let result = match WalletBinding::parse_config_creds(key, wallet_name) {
-        Ok(wallet_binding) => {
-            // Access fields of WalletBinding
-            let config = wallet_binding.config;
-            let credential = wallet_binding.credential;
-            // Now you can use config and credential as needed
-            println!("Config: {:?}", config);
-            println!("Credential: {:?}", credential);
-            // Create a Tokio  
-        //    let rt = Runtime::new().unwrap();
-            // Call the async function and await its result
-            WalletBinding::wallet_create_async_func(config, credential)
-            
-        }
-        Err(cli_error) => {
-            // Handle error
-            println!("Error: {:?}", cli_error);
-            // Return an error value if needed
-            return -1; // For example, returning -1 to indicate an error
-        }
-    };
-    // Create an async runtime (Tokio) to execute the async function
-    println!("Rust function executed..");
-    // Use the result
-    match result {
-        Ok(_) => println!("Wallet creation succeeded"),
-        Err(e) => println!("Wallet creation failed: {:?}", e),
-    }
-    // Return value indicating success
-    0
-}
-
-
-
-#[no_mangle]
-pub extern "C" fn wallet_open(key_ptr: *const c_char, wallet_name_ptr: *const c_char) -> Wallet {
-    println!("Wallet open function execution in progress...");
-   // Calculate length of key string
-    let mut key_len = 0;
-    unsafe {
-        while *key_ptr.offset(key_len) != 0 {
-            key_len += 1;
-        }
-    }
-    // Calculate length of wallet_name string
-    let mut wallet_name_len = 0;
-    unsafe {
-        while *wallet_name_ptr.offset(wallet_name_len) != 0 {
-            wallet_name_len += 1;
-        }
-    }
-    // Convert raw pointers to CStr and then to &str
-    let key = unsafe { CStr::from_ptr(key_ptr).to_str().expect("Invalid UTF-8 sequence") };
-    let wallet_name = unsafe { CStr::from_ptr(wallet_name_ptr).to_str().expect("Invalid UTF-8 sequence") };
-    println!("Open Rust function executing...");
-    // Define result variable in a scope that encompasses its usage
-    let result = match WalletBinding::parse_config_creds(key, wallet_name) {
-        Ok(wallet_binding) => {
-            let config = wallet_binding.config;
-            let credential = wallet_binding.credential;
-            
-            // Open the wallet asynchronously
-            let wallet_result = WalletBinding::wallet_open_async_func(config, credential);
-            match wallet_result {
-                Ok(wallet) => Some(wallet), // Return the wallet
-                Err(error) => {
-                    // Handle the error if needed
-                    println!("Error opening wallet: {:?}", error);
-                    None // Return None if an error occurs
-                }
-            }
-        }
-        Err(cli_error) => {
-            // Handle the error if needed
-            println!("Error parsing configuration: {:?}", cli_error);
-            None // Return None if an error occurs
-        },
-    };
-    
-    // Return the wallet or handle the error
-    match result {
-        Some(wallet) => wallet, // Return the wallet if it exists
-        None => {
-            // You may return a default wallet or panic here, depending on your requirements
-            // For example:
-            panic!("Failed to open wallet");
-        }
-    }    // Use the result
-    // match result {
-    //     Ok(_) => println!("Wallet creation succeeded"),
-    //     Err(e) => println!("Wallet creation failed: {:?}", e),
-    // }
-    // Return value indicating success
-}
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::os::raw::c_char;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, RwLock};
+
+use ffi_support::{rust_string_to_c, FfiStr};
+use futures_util::FutureExt;
+use once_cell::sync::Lazy;
+use tokio::sync::{
+    RwLock as AsyncRwLock, RwLockMappedWriteGuard, RwLockReadGuard, RwLockWriteGuard,
+};
+
+use crate::common::error::prelude::*;
+use crate::common::handle::ResourceHandle;
+use crate::tools::wallet::{
+    wallet_config::WalletConfig, Credentials, ExportConfig, ImportConfig, Wallet,
+};
+use crate::utils::futures::spawn_ok;
+
+use super::error::{set_last_error, ErrorCode};
+use super::CallbackId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct WalletHandle(pub i64);
+
+impl_sequence_handle!(WalletHandle, FFI_WH_COUNTER);
+
+/// An open wallet registered under a handle.
+///
+/// Operations lock the wallet rather than removing it from the handle map,
+/// so closing and rekeying wait for pending operations to finish.
+#[derive(Clone)]
+pub struct OpenWallet(Arc<AsyncRwLock<Option<Wallet>>>);
+
+impl OpenWallet {
+    pub(crate) fn new(wallet: Wallet) -> Self {
+        Self(Arc::new(AsyncRwLock::new(Some(wallet))))
+    }
+
+    /// Lock the wallet for a shared operation.
+    pub async fn read(&self) -> VdrResult<RwLockReadGuard<'_, Wallet>> {
+        RwLockReadGuard::try_map(self.0.read().await, Option::as_ref).map_err(|_| wallet_closed())
+    }
+
+    /// Lock the wallet for a shared operation, blocking the current thread.
+    ///
+    /// Must not be called from within the async runtime.
+    pub fn blocking_read(&self) -> VdrResult<RwLockReadGuard<'_, Wallet>> {
+        RwLockReadGuard::try_map(self.0.blocking_read(), Option::as_ref)
+            .map_err(|_| wallet_closed())
+    }
+
+    /// Lock the wallet for an exclusive operation.
+    async fn write(&self) -> VdrResult<RwLockMappedWriteGuard<'_, Wallet>> {
+        RwLockWriteGuard::try_map(self.0.write().await, Option::as_mut).map_err(|_| wallet_closed())
+    }
+
+    /// Wait for pending operations and take ownership of the wallet.
+    pub(crate) async fn take(&self) -> VdrResult<Wallet> {
+        self.0.write().await.take().ok_or_else(wallet_closed)
+    }
+}
+
+fn wallet_closed() -> VdrError {
+    input_err("Wallet is closed")
+}
+
+pub static WALLETS: Lazy<RwLock<BTreeMap<WalletHandle, OpenWallet>>> =
+    Lazy::new(|| RwLock::new(BTreeMap::new()));
+
+/// Look up an open wallet by handle.
+pub fn get_wallet(handle: WalletHandle) -> VdrResult<OpenWallet> {
+    read_lock!(WALLETS)?
+        .get(&handle)
+        .cloned()
+        .ok_or_else(|| input_err("Unknown wallet handle"))
+}
+
+/// Run a wallet operation on the async runtime and report the result to `complete`.
+pub(crate) fn spawn_wallet_task<T, F, C>(task: F, complete: C)
+where
+    T: Send + 'static,
+    F: Future<Output = VdrResult<T>> + Send + 'static,
+    C: FnOnce(ErrorCode, Option<T>) + Send + 'static,
+{
+    spawn_ok(async move {
+        let result = AssertUnwindSafe(task)
+            .catch_unwind()
+            .await
+            .unwrap_or_else(|_| Err(err_msg(VdrErrorKind::Unexpected, "Panic during execution")));
+        match result {
+            Ok(value) => complete(ErrorCode::Success, Some(value)),
+            Err(err) => {
+                let code = ErrorCode::from(err.kind());
+                set_last_error(Some(err));
+                complete(code, None)
+            }
+        }
+    });
+}
+
+fn parse_config(config: FfiStr) -> VdrResult<WalletConfig> {
+    serde_json::from_str(config.as_str()).with_input_err("Error deserializing wallet config")
+}
+
+fn parse_credentials(credentials: FfiStr) -> VdrResult<Credentials> {
+    serde_json::from_str(credentials.as_str())
+        .with_input_err("Error deserializing wallet credentials")
+}
+
+/// Create a new wallet.
+///
+/// @param config wallet configuration JSON: `{"id": ..., "storage_type": ..., "storage_config": ...}`
+/// @param credentials wallet credentials JSON: `{"key": ..., "key_derivation_method": ...}`
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_wallet_create(
+    config: FfiStr,
+    credentials: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Create wallet");
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let config = parse_config(config)?;
+        let credentials = parse_credentials(credentials)?;
+        spawn_wallet_task(
+            async move { Wallet::create_async(&config, &credentials).await },
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Open an existing wallet.
+///
+/// @param config wallet configuration JSON
+/// @param credentials wallet credentials JSON
+/// @param cb callback receiving the result and the handle of the opened wallet
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_wallet_open(
+    config: FfiStr,
+    credentials: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode, handle: WalletHandle)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Open wallet");
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let config = parse_config(config)?;
+        let credentials = parse_credentials(credentials)?;
+        spawn_wallet_task(
+            async move {
+                let wallet = Wallet::open_async(&config, &credentials).await?;
+                let handle = WalletHandle::next();
+                write_lock!(WALLETS)?.insert(handle, OpenWallet::new(wallet));
+                Ok(handle)
+            },
+            move |errcode, handle| cb(cb_id, errcode, handle.unwrap_or_else(WalletHandle::invalid)),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Close an open wallet.
+///
+/// The wallet handle is invalidated when the close begins, and the wallet is
+/// closed once other operations on it have finished.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_wallet_close(
+    wallet_handle: WalletHandle,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Close wallet: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let wallet = write_lock!(WALLETS)?
+            .remove(&wallet_handle)
+            .ok_or_else(|| input_err("Unknown wallet handle"))?;
+        spawn_wallet_task(
+            async move { wallet.take().await?.close_async().await },
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Re-encrypt an open wallet under a new key.
+///
+/// The rekey waits for other operations on the wallet to finish, and the
+/// wallet handle remains valid whether or not the rekey succeeds.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param key the new wallet key
+/// @param key_derivation_method optional derivation method for the new key: `argon2m`, `argon2i` or `raw`
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_wallet_rekey(
+    wallet_handle: WalletHandle,
+    key: FfiStr,
+    key_derivation_method: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Rekey wallet: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let key = key.into_string();
+        let key_derivation_method = key_derivation_method.into_opt_string();
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move {
+                let mut wallet = wallet.write().await?;
+                wallet.rekey_async(&key, key_derivation_method.as_deref()).await
+            },
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Delete a wallet and its storage.
+///
+/// @param config wallet configuration JSON
+/// @param credentials wallet credentials JSON
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_wallet_delete(
+    config: FfiStr,
+    credentials: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Delete wallet");
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let config = parse_config(config)?;
+        let credentials = parse_credentials(credentials)?;
+        spawn_wallet_task(
+            async move { Wallet::delete_async(&config, &credentials).await },
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// List the configurations of all known wallets as a JSON array.
+///
+/// @param wallets_p pointer receiving the JSON string, to be freed with `indy_vdr_string_free`
+#[no_mangle]
+pub extern "C" fn indy_vdr_wallet_list(wallets_p: *mut *const c_char) -> ErrorCode {
+    catch_err! {
+        trace!("List wallets");
+        check_useful_c_ptr!(wallets_p);
+        let wallets = serde_json::to_string(&Wallet::list())
+            .with_err_msg(VdrErrorKind::Unexpected, "Error serializing wallet list")?;
+        unsafe {
+            *wallets_p = rust_string_to_c(wallets);
+        }
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Export the contents of an open wallet to an encrypted archive file.
+///
+/// @param wallet_handle handle for the Wallet instance
+/// @param export_config export configuration JSON: `{"path": ..., "key": ..., "key_derivation_method": ..., "categories": [...]}`
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_wallet_export(
+    wallet_handle: WalletHandle,
+    export_config: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Export wallet: {}", wallet_handle);
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let export_config: ExportConfig = serde_json::from_str(export_config.as_str())
+            .with_input_err("Error deserializing export config")?;
+        let wallet = get_wallet(wallet_handle)?;
+        spawn_wallet_task(
+            async move { wallet.read().await?.export_async(&export_config).await },
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Create a new wallet from a backup.
+///
+/// @param config configuration JSON for the new wallet
+/// @param credentials credentials JSON for the new wallet
+/// @param import_config import configuration JSON: `{"path": ..., "key": ..., "key_derivation_method": ...}`
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_wallet_import(
+    config: FfiStr,
+    credentials: FfiStr,
+    import_config: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Import wallet");
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let config = parse_config(config)?;
+        let credentials = parse_credentials(credentials)?;
+        let import_config: ImportConfig = serde_json::from_str(import_config.as_str())
+            .with_input_err("Error deserializing import config")?;
+        spawn_wallet_task(
+            async move { Wallet::import_async(&config, &credentials, &import_config).await },
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+/// Copy the contents of a wallet into a new wallet, which may use a different
+/// storage backend. The record counts are verified against the source wallet
+/// after the copy.
+///
+/// @param from_config configuration JSON for the source wallet
+/// @param from_credentials credentials JSON for the source wallet
+/// @param to_config configuration JSON for the new wallet
+/// @param to_credentials credentials JSON for the new wallet
+/// @param categories optional JSON array of the record categories to copy
+/// @param cb callback receiving the result
+/// @param cb_id callback identifier passed back to `cb`
+#[no_mangle]
+pub extern "C" fn indy_vdr_wallet_migrate(
+    from_config: FfiStr,
+    from_credentials: FfiStr,
+    to_config: FfiStr,
+    to_credentials: FfiStr,
+    categories: FfiStr,
+    cb: Option<extern "C" fn(cb_id: CallbackId, err: ErrorCode)>,
+    cb_id: CallbackId,
+) -> ErrorCode {
+    catch_err! {
+        trace!("Migrate wallet");
+        let cb = cb.ok_or_else(|| input_err("No callback provided"))?;
+        let from_config = parse_config(from_config)?;
+        let from_credentials = parse_credentials(from_credentials)?;
+        let to_config = parse_config(to_config)?;
+        let to_credentials = parse_credentials(to_credentials)?;
+        let categories: Option<Vec<String>> = categories
+            .as_opt_str()
+            .map(serde_json::from_str)
+            .transpose()
+            .with_input_err("Error deserializing record categories")?;
+        spawn_wallet_task(
+            async move {
+                Wallet::migrate_async(
+                    &from_config,
+                    &from_credentials,
+                    &to_config,
+                    &to_credentials,
+                    categories.as_deref(),
+                )
+                .await
+            },
+            move |errcode, _| cb(cb_id, errcode),
+        );
+        Ok(ErrorCode::Success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::wallet::test_utils::wallet_config;
+    use std::ffi::CString;
+    use std::sync::{mpsc, Mutex};
+
+    static RESULTS: Lazy<Mutex<Option<mpsc::Sender<ErrorCode>>>> = Lazy::new(|| Mutex::new(None));
+
+    extern "C" fn callback(_cb_id: CallbackId, err: ErrorCode) {
+        if let Some(sender) = RESULTS.lock().unwrap().as_ref() {
+            sender.send(err).ok();
+        }
+    }
+
+    fn rekey(handle: WalletHandle, method: &str) -> ErrorCode {
+        let key = CString::new("new_wallet_key").unwrap();
+        let method = CString::new(method).unwrap();
+        indy_vdr_wallet_rekey(
+            handle,
+            FfiStr::from_cstr(&key),
+            FfiStr::from_cstr(&method),
+            Some(callback),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_rekey_keeps_wallet_handle() {
+        let (sender, results) = mpsc::channel();
+        *RESULTS.lock().unwrap() = Some(sender);
+        let (config, credentials) = wallet_config("rekey_handle");
+        Wallet::create(&config, &credentials).unwrap();
+        let handle = WalletHandle::next();
+        let wallet = Wallet::open(&config, &credentials).unwrap();
+        write_lock!(WALLETS)
+            .unwrap()
+            .insert(handle, OpenWallet::new(wallet));
+
+        assert_eq!(rekey(handle, "unknown"), ErrorCode::Success);
+        assert_eq!(results.recv().unwrap(), ErrorCode::Input);
+        get_wallet(handle).unwrap().blocking_read().unwrap();
+
+        assert_eq!(rekey(handle, "argon2i"), ErrorCode::Success);
+        assert_eq!(results.recv().unwrap(), ErrorCode::Success);
+        get_wallet(handle).unwrap().blocking_read().unwrap();
+
+        assert_eq!(
+            indy_vdr_wallet_close(handle, Some(callback), 0),
+            ErrorCode::Success
+        );
+        assert_eq!(results.recv().unwrap(), ErrorCode::Success);
+        assert!(get_wallet(handle).is_err());
+
+        Wallet::delete(&config, &credentials).unwrap();
+    }
+}
@@ -37,6 +37,9 @@ pub const AUTH_RULES: &str = "122";
 pub const FLAG: &str = "130";
 pub const GET_FLAG: &str = "131";
 pub const AUCTION_START: &str = "99990";
+pub const AUCTION_BID: &str = "99991";
+pub const AUCTION_CLOSE: &str = "99992";
+pub const GET_AUCTION: &str = "99993";
 pub const HANDLE_TRANSACTION: &str = "99994";
 
 // RichSchema objects
@@ -50,13 +53,18 @@ pub const RICH_SCHEMA_PRES_DEF: &str = "205";
 pub const GET_RICH_SCHEMA_BY_ID: &str = "300";
 pub const GET_RICH_SCHEMA_BY_METADATA: &str = "301";
 
-pub const REQUESTS: [&str; 37] = [
+pub const REQUESTS: [&str; 42] = [
     NODE,
     NYM,
     GET_TXN,
     ATTRIB,
     HANDLE,
     GET_HANDLE,
+    AUCTION_START,
+    AUCTION_BID,
+    AUCTION_CLOSE,
+    GET_AUCTION,
+    HANDLE_TRANSACTION,
     SCHEMA,
     CRED_DEF,
     GET_ATTR,
@@ -91,12 +99,13 @@ pub const REQUESTS: [&str; 37] = [
 ];
 
 // likely matches REQUESTS_FOR_STATE_PROOFS
-pub const READ_REQUESTS: [&str; 15] = [
+pub const READ_REQUESTS: [&str; 16] = [
     GET_NYM,
     GET_TXN_AUTHR_AGRMT,
     GET_TXN_AUTHR_AGRMT_AML,
     GET_SCHEMA,
     GET_HANDLE,
+    GET_AUCTION,
     GET_CRED_DEF,
     GET_ATTR,
     GET_REVOC_REG,
@@ -343,6 +352,13 @@ pub fn txn_name_to_code(txn: &str) -> Option<&str> {
         "GET_FROZEN_LEDGERS" => Some(GET_FROZEN_LEDGERS),
         "FLAG" => Some(FLAG),
         "GET_FLAG" => Some(GET_FLAG),
+        "HANDLE" => Some(HANDLE),
+        "GET_HANDLE" => Some(GET_HANDLE),
+        "AUCTION_START" => Some(AUCTION_START),
+        "AUCTION_BID" => Some(AUCTION_BID),
+        "AUCTION_CLOSE" => Some(AUCTION_CLOSE),
+        "GET_AUCTION" => Some(GET_AUCTION),
+        "HANDLE_TRANSACTION" => Some(HANDLE_TRANSACTION),
         val => Some(val),
     }
 }
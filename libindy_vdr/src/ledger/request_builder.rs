@@ -4,15 +4,19 @@ use sha2::{Digest, Sha256};
 
 use crate::common::error::prelude::*;
 use crate::pool::{new_request_id, PreparedRequest, ProtocolVersion, RequestMethod};
+use crate::state_proof::get_state_proof_spec;
 use crate::utils::did::{DidValue, DEFAULT_LIBINDY_DID};
-use crate::utils::Qualifiable;
+use crate::utils::{Qualifiable, Validatable};
 
 
 //use super::identifiers::RichSchemaId;
 use super::identifiers::{CredentialDefinitionId, RevocationRegistryId, SchemaId,RichSchemaId};
 use super::requests::attrib::{AttribOperation, GetAttribOperation};
-use super::requests::handle::HandleOperation;
-use super::requests::auction::AuctionOperation;
+use super::requests::auction::{
+    AuctionBidOperation, AuctionCloseOperation, AuctionStartOperation, GetAuctionOperation,
+    HandleTransferOperation,
+};
+use super::requests::handle::{GetHandleOperation, HandleOperation};
 use super::requests::auth_rule::{
     AuthAction, AuthRuleOperation, AuthRules, AuthRulesOperation, Constraint, GetAuthRuleOperation,
 };
@@ -101,14 +105,27 @@ impl RequestBuilder {
         let identifier = identifier.or(Some(&DEFAULT_LIBINDY_DID));
         let txn_type = T::get_txn_type().to_string();
         let sp_key = operation.get_sp_key(self.protocol_version)?;
-        let method = if let Some(sp_key) = sp_key {
-            Some(RequestMethod::BuiltinStateProof {
-                sp_key,
-                sp_timestamps: operation.get_sp_timestamps()?,
-            })
-        } else {
-            None
+        let sp_timestamps = operation.get_sp_timestamps()?;
+        let (sp_key, sp_timestamps) = match get_state_proof_spec(&txn_type) {
+            // registered types may derive the key and timestamps from the operation JSON
+            Some(spec) => {
+                let op_json = serde_json::to_value(&operation)
+                    .with_input_err("Error serializing request operation")?;
+                let sp_key = sp_key.or_else(|| spec.derive_key(&op_json, self.protocol_version));
+                let sp_timestamps = if sp_timestamps == (None, None) {
+                    spec.timestamps(&op_json)
+                } else {
+                    sp_timestamps
+                };
+                (sp_key, sp_timestamps)
+            }
+            None => (sp_key, sp_timestamps),
         };
+        let method = sp_key.map(|sp_key| RequestMethod::BuiltinStateProof {
+            sp_key,
+            sp_timestamps,
+            hedge_policy: None,
+        });
         let body = Request::build_request(
             req_id,
             operation,
@@ -202,29 +219,93 @@ impl RequestBuilder {
         self.build(operation, Some(identifier))
     }
 
-    /// Build an `HANDLE` transaction request
+    /// Build a `HANDLE` transaction request
     pub fn build_handle_request(
-            &self,
-            identifier: &DidValue,
-            dest: &DidValue,
-            handle: String,
-        ) -> VdrResult<PreparedRequest> {
-            let operation =
-                HandleOperation::new(dest.to_short(), handle);
-            self.build(operation, Some(identifier))
-        }
-    
-    pub fn build_auction_request(
-            &self,
-            identifier: &DidValue,
-            dest: &DidValue,
-            handle:String,
-        ) -> VdrResult<PreparedRequest> {
-            let val:String = "abc".to_string(); 
-            let operation =
-                AuctionOperation::new(dest.to_short(), val, handle);
-            self.build(operation, Some(identifier))
-        }
+        &self,
+        identifier: &DidValue,
+        dest: &DidValue,
+        handle: String,
+    ) -> VdrResult<PreparedRequest> {
+        let operation = HandleOperation::new(dest.to_short(), handle);
+        operation.validate()?;
+        self.build(operation, Some(identifier))
+    }
+
+    /// Build a `GET_HANDLE` transaction request
+    /// Use only one of seq_no and timestamp
+    pub fn build_get_handle_request(
+        &self,
+        identifier: Option<&DidValue>,
+        handle: String,
+        seq_no: Option<i32>,
+        timestamp: Option<u64>,
+    ) -> VdrResult<PreparedRequest> {
+        let operation = GetHandleOperation::new(handle, seq_no, timestamp);
+        operation.validate()?;
+        self.build(operation, identifier)
+    }
+
+    /// Build an `AUCTION_START` transaction request
+    pub fn build_auction_start_request(
+        &self,
+        identifier: &DidValue,
+        dest: &DidValue,
+        auction_id: String,
+        handle: String,
+    ) -> VdrResult<PreparedRequest> {
+        let operation = AuctionStartOperation::new(dest.to_short(), auction_id, handle);
+        operation.validate()?;
+        self.build(operation, Some(identifier))
+    }
+
+    /// Build an `AUCTION_BID` transaction request
+    pub fn build_auction_bid_request(
+        &self,
+        identifier: &DidValue,
+        auction_id: String,
+        amount: u64,
+    ) -> VdrResult<PreparedRequest> {
+        let operation = AuctionBidOperation::new(auction_id, amount);
+        operation.validate()?;
+        self.build(operation, Some(identifier))
+    }
+
+    /// Build an `AUCTION_CLOSE` transaction request
+    pub fn build_auction_close_request(
+        &self,
+        identifier: &DidValue,
+        auction_id: String,
+    ) -> VdrResult<PreparedRequest> {
+        let operation = AuctionCloseOperation::new(auction_id);
+        operation.validate()?;
+        self.build(operation, Some(identifier))
+    }
+
+    /// Build a `GET_AUCTION` transaction request
+    /// Use only one of seq_no and timestamp
+    pub fn build_get_auction_request(
+        &self,
+        identifier: Option<&DidValue>,
+        auction_id: String,
+        seq_no: Option<i32>,
+        timestamp: Option<u64>,
+    ) -> VdrResult<PreparedRequest> {
+        let operation = GetAuctionOperation::new(auction_id, seq_no, timestamp);
+        operation.validate()?;
+        self.build(operation, identifier)
+    }
+
+    /// Build a `HANDLE_TRANSACTION` request transferring a handle to a new owner
+    pub fn build_handle_transfer_request(
+        &self,
+        identifier: &DidValue,
+        dest: &DidValue,
+        handle: String,
+    ) -> VdrResult<PreparedRequest> {
+        let operation = HandleTransferOperation::new(dest.to_short(), handle);
+        operation.validate()?;
+        self.build(operation, Some(identifier))
+    }
 
     /// Build a `GET_ATTRIB` transaction request
     /// seq_no and timestamp are only supported for did:indy compliant ledgers
@@ -806,11 +887,42 @@ mod tests {
                         227, 51, 254, 11, 192, 83, 219, 131, 59, 204, 0, 126, 41, 96, 118, 238,
                         152, 250, 160, 191, 198, 247, 4, 130, 44, 199, 140, 143, 18, 182, 93, 229
                     ],
-                    sp_timestamps: (None, None)
+                    sp_timestamps: (None, None),
+                    hedge_policy: None,
                 }
             );
         }
 
+        #[rstest]
+        fn test_prepared_request_from_request_json_for_get_handle_request(
+            request_builder: RequestBuilder,
+        ) {
+            let request = request_builder
+                .build_get_handle_request(None, "alice".to_string(), None, None)
+                .unwrap();
+            let parsed = PreparedRequest::from_request_json(request.req_json.to_string()).unwrap();
+
+            assert_eq!(parsed.txn_type, constants::GET_HANDLE);
+            assert_eq!(parsed.method, request.method);
+        }
+
+        #[rstest]
+        fn test_build_get_handle_request_with_timestamp(request_builder: RequestBuilder) {
+            let timestamp = 123456789;
+            let request = request_builder
+                .build_get_handle_request(None, "alice".to_string(), None, Some(timestamp))
+                .unwrap();
+            let parsed = PreparedRequest::from_request_json(request.req_json.to_string()).unwrap();
+
+            let expected = RequestMethod::BuiltinStateProof {
+                sp_key: Sha256::digest(b"alice").to_vec(),
+                sp_timestamps: (None, Some(timestamp)),
+                hedge_policy: None,
+            };
+            assert_eq!(request.method, expected);
+            assert_eq!(parsed.method, expected);
+        }
+
         #[rstest]
         fn test_prepared_request_from_request_json_for_get_request_with_single_timestamp(
             request_builder: RequestBuilder,
@@ -832,7 +944,8 @@ mod tests {
                 request.method,
                 RequestMethod::BuiltinStateProof {
                     sp_key: vec![50, 58, 108, 97, 116, 101, 115, 116],
-                    sp_timestamps: (None, Some(123456789))
+                    sp_timestamps: (None, Some(123456789)),
+                    hedge_policy: None,
                 }
             );
         }
@@ -862,12 +975,58 @@ mod tests {
                         46, 48, 58, 116, 97, 103, 58, 67, 76, 95, 65, 67, 67, 85, 77, 58, 84, 65,
                         71, 95, 49
                     ],
-                    sp_timestamps: (Some(from as u64), Some(to as u64))
+                    sp_timestamps: (Some(from as u64), Some(to as u64)),
+                    hedge_policy: None,
                 }
             );
         }
     }
 
+    #[derive(Serialize)]
+    struct GetCustomOperation {
+        #[serde(rename = "type")]
+        _type: String,
+        name: String,
+        timestamp: u64,
+    }
+
+    impl crate::ledger::requests::RequestType for GetCustomOperation {
+        fn get_txn_type<'a>() -> &'a str {
+            "99982"
+        }
+    }
+
+    #[rstest]
+    fn test_build_registered_state_proof_request(request_builder: RequestBuilder) {
+        use crate::state_proof::{
+            register_state_proof_spec, unregister_state_proof_spec, StateProofSpec,
+        };
+
+        register_state_proof_spec(
+            "99982",
+            StateProofSpec::new()
+                .key(|op, _| op["name"].as_str().map(|name| name.as_bytes().to_vec()))
+                .timestamp_field("timestamp"),
+        )
+        .unwrap();
+        let operation = GetCustomOperation {
+            _type: "99982".to_string(),
+            name: "custom".to_string(),
+            timestamp: 100,
+        };
+        let request = request_builder.build(operation, None).unwrap();
+        let parsed = PreparedRequest::from_request_json(request.req_json.to_string()).unwrap();
+        unregister_state_proof_spec("99982");
+
+        let expected = RequestMethod::BuiltinStateProof {
+            sp_key: b"custom".to_vec(),
+            sp_timestamps: (None, Some(100)),
+            hedge_policy: None,
+        };
+        assert_eq!(request.method, expected);
+        assert_eq!(parsed.method, expected);
+    }
+
     #[rstest]
     fn test_prepared_request_get_signature_input(prepared_request: PreparedRequest) {
         let expected = String::from("identifier:V4SGRU86Z58d6TV7PBUe6f|operation:dest:VsKV7grR1BUE29mG2Fm2kX|type:1|protocolVersion:2|reqId:1585221529670242337");
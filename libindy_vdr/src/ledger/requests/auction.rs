@@ -1,101 +1,243 @@
-use sha2::{Digest, Sha256};
-
-use super::constants::AUCTION_START;
-use super::did::ShortDidValue;
-use super::{get_sp_key_marker, ProtocolVersion, RequestType};
-use crate::common::error::VdrResult;
-use crate::ledger::constants::{HANDLE_TRANSACTION, GET_HANDLE};
-
-#[derive(Serialize, PartialEq, Debug)]
-pub struct AuctionOperation {
-    #[serde(rename = "type")]
-    pub _type: String,
-    pub dest: ShortDidValue,
-    pub auctionid: String,
-    pub handle: String,
-}
-
-impl AuctionOperation {
-    pub fn new(
-        dest: ShortDidValue,
-        auctionid: String,
-        handle: String,
-    ) -> AuctionOperation {
-        AuctionOperation {
-            _type: Self::get_txn_type().to_string(),
-            dest,
-            auctionid,
-            handle,
-        }
-    }
-}
-
-impl RequestType for AuctionOperation {
-    fn get_txn_type<'a>() -> &'a str {
-        AUCTION_START;
-        HANDLE_TRANSACTION;
-        GET_HANDLE
-    }
-}
-
-// #[derive(Serialize, PartialEq, Debug)]
-// pub struct GetAttribOperation {
-//     #[serde(rename = "type")]
-//     pub _type: String,
-//     pub dest: ShortDidValue,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub raw: Option<String>,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub hash: Option<String>,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub enc: Option<String>,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub seq_no: Option<i32>,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub timestamp: Option<u64>,
-// }
-
-// impl GetAttribOperation {
-//     pub fn new(
-//         dest: ShortDidValue,
-//         raw: Option<String>,
-//         hash: Option<String>,
-//         enc: Option<String>,
-//         seq_no: Option<i32>,
-//         timestamp: Option<u64>,
-//     ) -> GetAttribOperation {
-//         GetAttribOperation {
-//             _type: Self::get_txn_type().to_string(),
-//             dest,
-//             raw,
-//             hash,
-//             enc,
-//             seq_no,
-//             timestamp,
-//         }
-//     }
-// }
-
-// impl RequestType for GetAttribOperation {
-//     fn get_txn_type<'a>() -> &'a str {
-//         GET_ATTR
-//     }
-
-//     fn get_sp_key(&self, protocol_version: ProtocolVersion) -> VdrResult<Option<Vec<u8>>> {
-//         if let Some(attr_name) = self
-//             .raw
-//             .as_ref()
-//             .or(self.enc.as_ref())
-//             .or(self.hash.as_ref())
-//         {
-//             let marker = get_sp_key_marker(1, protocol_version);
-//             let hash = Sha256::digest(attr_name.as_bytes());
-//             return Ok(Some(
-//                 format!("{}:{}:{}", &*self.dest, marker, hex::encode(hash))
-//                     .as_bytes()
-//                     .to_vec(),
-//             ));
-//         }
-//         Ok(None)
-//     }
-// }
+use super::constants::{
+    AUCTION_BID, AUCTION_CLOSE, AUCTION_START, GET_AUCTION, HANDLE_TRANSACTION,
+};
+use super::did::ShortDidValue;
+use super::handle::validate_handle;
+use super::{ProtocolVersion, RequestType};
+use crate::common::error::VdrResult;
+use crate::state_proof::StateProofSpec;
+use crate::utils::{Validatable, ValidationError};
+
+/// Maximum length of an auction identifier
+pub const MAX_AUCTION_ID_LENGTH: usize = 64;
+
+/// Check that an auction identifier is well-formed
+///
+/// Auction identifiers are ASCII letters, digits, `-` and `_`.
+pub fn validate_auction_id(auction_id: &str) -> Result<(), ValidationError> {
+    if auction_id.is_empty() || auction_id.len() > MAX_AUCTION_ID_LENGTH {
+        return Err(invalid!(
+            "Invalid auction ID: must be between 1 and {} characters",
+            MAX_AUCTION_ID_LENGTH
+        ));
+    }
+    if !auction_id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(invalid!(
+            "Invalid auction ID: unsupported characters in {:?}",
+            auction_id
+        ));
+    }
+    Ok(())
+}
+
+/// Derive the state proof key for an auction
+pub fn auction_sp_key(auction_id: &str) -> Vec<u8> {
+    format!("auction:{}", auction_id).into_bytes()
+}
+
+/// The state proof specification of `GET_AUCTION` replies, registered by default
+pub fn get_auction_state_proof_spec() -> StateProofSpec {
+    StateProofSpec::new()
+        .key(|operation, _| operation["auctionid"].as_str().map(auction_sp_key))
+        .timestamp_field("timestamp")
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct AuctionStartOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: ShortDidValue,
+    pub auctionid: String,
+    pub handle: String,
+}
+
+impl AuctionStartOperation {
+    pub fn new(dest: ShortDidValue, auctionid: String, handle: String) -> AuctionStartOperation {
+        AuctionStartOperation {
+            _type: Self::get_txn_type().to_string(),
+            dest,
+            auctionid,
+            handle,
+        }
+    }
+}
+
+impl Validatable for AuctionStartOperation {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_auction_id(&self.auctionid)?;
+        validate_handle(&self.handle)
+    }
+}
+
+impl RequestType for AuctionStartOperation {
+    fn get_txn_type<'a>() -> &'a str {
+        AUCTION_START
+    }
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct AuctionBidOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub auctionid: String,
+    pub amount: u64,
+}
+
+impl AuctionBidOperation {
+    pub fn new(auctionid: String, amount: u64) -> AuctionBidOperation {
+        AuctionBidOperation {
+            _type: Self::get_txn_type().to_string(),
+            auctionid,
+            amount,
+        }
+    }
+}
+
+impl Validatable for AuctionBidOperation {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_auction_id(&self.auctionid)?;
+        if self.amount == 0 {
+            return Err(invalid!("Invalid bid: amount must be greater than 0"));
+        }
+        Ok(())
+    }
+}
+
+impl RequestType for AuctionBidOperation {
+    fn get_txn_type<'a>() -> &'a str {
+        AUCTION_BID
+    }
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct AuctionCloseOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub auctionid: String,
+}
+
+impl AuctionCloseOperation {
+    pub fn new(auctionid: String) -> AuctionCloseOperation {
+        AuctionCloseOperation {
+            _type: Self::get_txn_type().to_string(),
+            auctionid,
+        }
+    }
+}
+
+impl Validatable for AuctionCloseOperation {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_auction_id(&self.auctionid)
+    }
+}
+
+impl RequestType for AuctionCloseOperation {
+    fn get_txn_type<'a>() -> &'a str {
+        AUCTION_CLOSE
+    }
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAuctionOperation {
+    pub _type: String,
+    pub auctionid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq_no: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+}
+
+impl GetAuctionOperation {
+    pub fn new(
+        auctionid: String,
+        seq_no: Option<i32>,
+        timestamp: Option<u64>,
+    ) -> GetAuctionOperation {
+        GetAuctionOperation {
+            _type: Self::get_txn_type().to_string(),
+            auctionid,
+            seq_no,
+            timestamp,
+        }
+    }
+}
+
+impl Validatable for GetAuctionOperation {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_auction_id(&self.auctionid)?;
+        if self.seq_no.is_some() && self.timestamp.is_some() {
+            return Err(invalid!("Only one of seq_no and timestamp may be set"));
+        }
+        Ok(())
+    }
+}
+
+impl RequestType for GetAuctionOperation {
+    fn get_txn_type<'a>() -> &'a str {
+        GET_AUCTION
+    }
+
+    fn get_sp_key(&self, _protocol_version: ProtocolVersion) -> VdrResult<Option<Vec<u8>>> {
+        Ok(Some(auction_sp_key(&self.auctionid)))
+    }
+
+    fn get_sp_timestamps(&self) -> VdrResult<(Option<u64>, Option<u64>)> {
+        Ok((None, self.timestamp))
+    }
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct HandleTransferOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: ShortDidValue,
+    pub handle: String,
+}
+
+impl HandleTransferOperation {
+    pub fn new(dest: ShortDidValue, handle: String) -> HandleTransferOperation {
+        HandleTransferOperation {
+            _type: Self::get_txn_type().to_string(),
+            dest,
+            handle,
+        }
+    }
+}
+
+impl Validatable for HandleTransferOperation {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_handle(&self.handle)
+    }
+}
+
+impl RequestType for HandleTransferOperation {
+    fn get_txn_type<'a>() -> &'a str {
+        HANDLE_TRANSACTION
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rstest(auction_id, case("abc"), case("A-1_b"), case("0123456789abcdef"))]
+    fn test_validate_auction_id_works(auction_id: &str) {
+        validate_auction_id(auction_id).unwrap();
+    }
+
+    #[rstest(auction_id, case(""), case("abc:def"), case("abc def"), case("ab/c"))]
+    fn test_validate_auction_id_fails(auction_id: &str) {
+        validate_auction_id(auction_id).unwrap_err();
+    }
+
+    #[test]
+    fn test_validate_bid_fails_for_zero_amount() {
+        AuctionBidOperation::new("abc".to_string(), 0)
+            .validate()
+            .unwrap_err();
+    }
+}
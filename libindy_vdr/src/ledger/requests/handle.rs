@@ -1,95 +1,162 @@
-use sha2::{Digest, Sha256};
-
-use super::constants::HANDLE;
-use super::did::ShortDidValue;
-use super::{get_sp_key_marker, ProtocolVersion, RequestType};
-use crate::common::error::VdrResult;
-
-#[derive(Serialize, PartialEq, Debug)]
-pub struct HandleOperation {
-    #[serde(rename = "type")]
-    pub _type: String,
-    pub dest: ShortDidValue,
-    pub handle: String,
-}
-
-impl HandleOperation {
-    pub fn new(
-        dest: ShortDidValue,
-        handle: String,
-    ) -> HandleOperation {
-        HandleOperation {
-            _type: Self::get_txn_type().to_string(),
-            dest,
-            handle,
-        }
-    }
-}
-
-impl RequestType for HandleOperation {
-    fn get_txn_type<'a>() -> &'a str {
-        HANDLE
-    }
-}
-
-// #[derive(Serialize, PartialEq, Debug)]
-// pub struct GetAttribOperation {
-//     #[serde(rename = "type")]
-//     pub _type: String,
-//     pub dest: ShortDidValue,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub raw: Option<String>,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub hash: Option<String>,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub enc: Option<String>,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub seq_no: Option<i32>,
-//     #[serde(skip_serializing_if = "Option::is_none")]
-//     pub timestamp: Option<u64>,
-// }
-
-// impl GetAttribOperation {
-//     pub fn new(
-//         dest: ShortDidValue,
-//         raw: Option<String>,
-//         hash: Option<String>,
-//         enc: Option<String>,
-//         seq_no: Option<i32>,
-//         timestamp: Option<u64>,
-//     ) -> GetAttribOperation {
-//         GetAttribOperation {
-//             _type: Self::get_txn_type().to_string(),
-//             dest,
-//             raw,
-//             hash,
-//             enc,
-//             seq_no,
-//             timestamp,
-//         }
-//     }
-// }
-
-// impl RequestType for GetAttribOperation {
-//     fn get_txn_type<'a>() -> &'a str {
-//         GET_ATTR
-//     }
-
-//     fn get_sp_key(&self, protocol_version: ProtocolVersion) -> VdrResult<Option<Vec<u8>>> {
-//         if let Some(attr_name) = self
-//             .raw
-//             .as_ref()
-//             .or(self.enc.as_ref())
-//             .or(self.hash.as_ref())
-//         {
-//             let marker = get_sp_key_marker(1, protocol_version);
-//             let hash = Sha256::digest(attr_name.as_bytes());
-//             return Ok(Some(
-//                 format!("{}:{}:{}", &*self.dest, marker, hex::encode(hash))
-//                     .as_bytes()
-//                     .to_vec(),
-//             ));
-//         }
-//         Ok(None)
-//     }
-// }
+use sha2::{Digest, Sha256};
+
+use super::constants::{GET_HANDLE, HANDLE};
+use super::did::ShortDidValue;
+use super::{ProtocolVersion, RequestType};
+use crate::common::error::VdrResult;
+use crate::state_proof::StateProofSpec;
+use crate::utils::{Validatable, ValidationError};
+
+/// Maximum length of a handle name
+pub const MAX_HANDLE_LENGTH: usize = 64;
+
+/// Derive the domain state key of a handle record
+pub fn handle_sp_key(handle: &str) -> Vec<u8> {
+    Sha256::digest(handle.as_bytes()).to_vec()
+}
+
+/// The state proof specification of `GET_HANDLE` replies, registered by default
+pub fn get_handle_state_proof_spec() -> StateProofSpec {
+    StateProofSpec::new()
+        .key(|operation, _| operation["handle"].as_str().map(handle_sp_key))
+        .timestamp_field("timestamp")
+}
+
+/// Check that a handle name is well-formed
+///
+/// Handles are lowercase ASCII letters, digits and the separators `-`, `_` and `.`,
+/// and must start and end with a letter or digit.
+pub fn validate_handle(handle: &str) -> Result<(), ValidationError> {
+    if handle.is_empty() || handle.len() > MAX_HANDLE_LENGTH {
+        return Err(invalid!(
+            "Invalid handle: must be between 1 and {} characters",
+            MAX_HANDLE_LENGTH
+        ));
+    }
+    if !handle
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(invalid!(
+            "Invalid handle: unsupported characters in {:?}",
+            handle
+        ));
+    }
+    let is_alnum = |c: Option<char>| c.map_or(false, |c| c.is_ascii_alphanumeric());
+    if !is_alnum(handle.chars().next()) || !is_alnum(handle.chars().last()) {
+        return Err(invalid!(
+            "Invalid handle: must start and end with a letter or digit"
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+pub struct HandleOperation {
+    #[serde(rename = "type")]
+    pub _type: String,
+    pub dest: ShortDidValue,
+    pub handle: String,
+}
+
+impl HandleOperation {
+    pub fn new(dest: ShortDidValue, handle: String) -> HandleOperation {
+        HandleOperation {
+            _type: Self::get_txn_type().to_string(),
+            dest,
+            handle,
+        }
+    }
+}
+
+impl Validatable for HandleOperation {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_handle(&self.handle)
+    }
+}
+
+impl RequestType for HandleOperation {
+    fn get_txn_type<'a>() -> &'a str {
+        HANDLE
+    }
+}
+
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetHandleOperation {
+    pub _type: String,
+    pub handle: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq_no: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+}
+
+impl GetHandleOperation {
+    pub fn new(handle: String, seq_no: Option<i32>, timestamp: Option<u64>) -> GetHandleOperation {
+        GetHandleOperation {
+            _type: Self::get_txn_type().to_string(),
+            handle,
+            seq_no,
+            timestamp,
+        }
+    }
+}
+
+impl Validatable for GetHandleOperation {
+    fn validate(&self) -> Result<(), ValidationError> {
+        validate_handle(&self.handle)?;
+        if self.seq_no.is_some() && self.timestamp.is_some() {
+            return Err(invalid!("Only one of seq_no and timestamp may be set"));
+        }
+        Ok(())
+    }
+}
+
+impl RequestType for GetHandleOperation {
+    fn get_txn_type<'a>() -> &'a str {
+        GET_HANDLE
+    }
+
+    fn get_sp_key(&self, _protocol_version: ProtocolVersion) -> VdrResult<Option<Vec<u8>>> {
+        Ok(Some(handle_sp_key(&self.handle)))
+    }
+
+    fn get_sp_timestamps(&self) -> VdrResult<(Option<u64>, Option<u64>)> {
+        Ok((None, self.timestamp))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rstest(
+        handle,
+        case("alice"),
+        case("a"),
+        case("alice.smith-01"),
+        case("bob_2")
+    )]
+    fn test_validate_handle_works(handle: &str) {
+        validate_handle(handle).unwrap();
+    }
+
+    #[rstest(
+        handle,
+        case(""),
+        case("Alice"),
+        case("-alice"),
+        case("alice."),
+        case("al ice"),
+        case("alicé")
+    )]
+    fn test_validate_handle_fails(handle: &str) {
+        validate_handle(handle).unwrap_err();
+    }
+
+    #[test]
+    fn test_validate_handle_fails_for_too_long() {
+        validate_handle(&"a".repeat(MAX_HANDLE_LENGTH + 1)).unwrap_err();
+    }
+}
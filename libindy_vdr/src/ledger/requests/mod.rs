@@ -1,5 +1,7 @@
 /// ATTRIB, GET_ATTRIB transaction operations
 pub mod attrib;
+/// Handle auction transaction operations
+pub mod auction;
 /// AUTH_RULE and related transaction operations
 pub mod auth_rule;
 /// Transaction author agreement data types and operations
@@ -8,6 +10,8 @@ pub mod author_agreement;
 pub mod cred_def;
 /// FLAG transactions operations
 pub mod flag;
+/// HANDLE, GET_HANDLE transaction operations
+pub mod handle;
 /// Frozen Ledger operations
 pub mod ledgers_freeze;
 /// NODE transactions operations
@@ -30,10 +34,6 @@ pub mod txn;
 /// GET_VALIDATOR_INFO operation
 pub mod validator_info;
 
-pub mod handle;
-
-pub mod auction;
-
 use std::collections::HashMap;
 
 use serde;
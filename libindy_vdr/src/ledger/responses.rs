@@ -1,11 +1,165 @@
+use crate::common::error::prelude::*;
+use crate::ledger::constants;
+use crate::ledger::identifiers::{CredentialDefinitionId, RevocationRegistryId, RichSchemaId};
+use crate::ledger::requests::auth_rule::AuthRule;
 use crate::utils::did::DidValue;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use serde_json::Value as SJsonValue;
 use std::collections::HashMap;
 
 pub enum ResponseTypes {
     GetNymResult(GetNymResult),
     GetSchemaResult(GetSchemaResult),
     GetClaimDefResult(GetClaimDefResult),
+    GetHandleResult(GetHandleResult),
+    GetAttribResult(GetAttribResult),
+    GetRevocRegDefResult(GetRevocRegDefResult),
+    GetRevocRegResult(GetRevocRegResult),
+    GetRevocRegDeltaResult(GetRevocRegDeltaResult),
+    GetTxnAuthorAgreementResult(GetTxnAuthorAgreementResult),
+    GetAcceptanceMechanismsResult(GetAcceptanceMechanismsResult),
+    GetAuthRuleResult(GetAuthRuleResult),
+    GetTxnResult(GetTxnResult),
+    GetFrozenLedgersResult(GetFrozenLedgersResult),
+    GetFlagResult(GetFlagResult),
+    GetRichSchemaResult(GetRichSchemaResult),
+    GetValidatorInfoResult(GetValidatorInfoResult),
+}
+
+/// Parse a ledger reply into a typed response.
+///
+/// Both the V0 (`result`) and V1 (`data.result`) reply layouts are accepted.
+/// The transaction type of the reply must match the response type.
+pub fn parse_response<T: ResponseData>(reply: &str) -> VdrResult<LedgerResponse<T>> {
+    let reply: SJsonValue =
+        serde_json::from_str(reply).with_input_err("Error deserializing ledger reply")?;
+    if let Some(op) = reply["op"].as_str() {
+        if op != "REPLY" {
+            let reason = reply["reason"].as_str().unwrap_or(op);
+            return Err(input_err(format!("Ledger request failed: {}", reason)));
+        }
+    }
+    let result = reply
+        .get("result") // V0
+        .or_else(|| reply["data"]["result"][0].get("result")) // V1
+        .ok_or_else(|| input_err("Ledger reply has no result"))?;
+
+    if let Some(txn_type) = result["type"].as_str() {
+        if !T::TXN_TYPES.contains(&txn_type) {
+            return Err(input_err(format!(
+                "Unexpected transaction type in ledger reply: {}",
+                txn_type
+            )));
+        }
+    }
+
+    let metadata: ResponseMetadata = serde_json::from_value(result.clone())
+        .with_input_err("Error deserializing ledger reply metadata")?;
+    let data = T::from_result(result)?;
+    Ok(LedgerResponse { data, metadata })
+}
+
+/// A typed ledger reply along with its metadata
+#[derive(Debug)]
+pub struct LedgerResponse<T> {
+    /// The response data, or `None` if the requested object was not found
+    pub data: Option<T>,
+    pub metadata: ResponseMetadata,
+}
+
+impl<T> LedgerResponse<T> {
+    /// The sequence number of the transaction
+    pub fn seq_no(&self) -> Option<u64> {
+        self.metadata.seq_no
+    }
+
+    /// The ledger time of the transaction
+    pub fn txn_time(&self) -> Option<u64> {
+        self.metadata.txn_time
+    }
+
+    /// The state proof attached to the reply
+    pub fn state_proof(&self) -> Option<&StateProofMetadata> {
+        self.metadata.state_proof.as_ref()
+    }
+}
+
+/// Fields common to the results of ledger read requests
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseMetadata {
+    #[serde(rename = "type")]
+    pub txn_type: Option<String>,
+    pub identifier: Option<String>,
+    pub req_id: Option<u64>,
+    pub seq_no: Option<u64>,
+    pub txn_time: Option<u64>,
+    #[serde(rename = "state_proof")]
+    pub state_proof: Option<StateProofMetadata>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct StateProofMetadata {
+    pub root_hash: String,
+    pub proof_nodes: String,
+    pub multi_signature: Option<MultiSignatureMetadata>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MultiSignatureMetadata {
+    pub signature: String,
+    pub participants: Vec<String>,
+    pub value: MultiSignatureValue,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct MultiSignatureValue {
+    pub ledger_id: u64,
+    pub pool_state_root_hash: String,
+    pub state_root_hash: String,
+    pub txn_root_hash: String,
+    pub timestamp: u64,
+}
+
+/// Typed data of the reply to a ledger read request
+pub trait ResponseData: DeserializeOwned {
+    /// The transaction types of the requests answered by this response
+    const TXN_TYPES: &'static [&'static str];
+
+    /// Extract the response data from the `result` of a ledger reply
+    fn from_result(result: &SJsonValue) -> VdrResult<Option<Self>> {
+        parse_data(&result["data"])
+    }
+}
+
+/// Deserialize reply data, which may be JSON or a string containing JSON
+fn parse_data<T: DeserializeOwned>(data: &SJsonValue) -> VdrResult<Option<T>> {
+    match data {
+        SJsonValue::Null => Ok(None),
+        SJsonValue::String(data) => serde_json::from_str(data)
+            .map(Some)
+            .with_input_err("Error deserializing ledger reply data"),
+        data => serde_json::from_value(data.clone())
+            .map(Some)
+            .with_input_err("Error deserializing ledger reply data"),
+    }
+}
+
+impl ResponseData for GetNymResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_NYM];
+}
+
+impl ResponseData for GetSchemaResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_SCHEMA];
+}
+
+impl ResponseData for GetClaimDefResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_CRED_DEF];
+}
+
+impl ResponseData for GetHandleResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_HANDLE];
 }
 
 #[derive(Deserialize, Debug)]
@@ -55,7 +209,447 @@ pub struct GetClaimDefResult {
     pub ref_schema_version: String,
 }
 
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetHandleResult {
+    pub identifier: Option<DidValue>,
+    pub dest: DidValue,
+    pub handle: String,
+}
+
 #[derive(Clone, Serialize, Deserialize, Eq, PartialEq, Debug)]
 pub struct Endpoint {
     pub endpoint: HashMap<String, String>,
 }
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+pub struct GetAttribResult {
+    pub dest: DidValue,
+    /// The attribute name, for raw attributes
+    pub raw: Option<String>,
+    pub hash: Option<String>,
+    pub enc: Option<String>,
+    /// The raw JSON, hash or encrypted value of the attribute
+    pub data: String,
+}
+
+impl ResponseData for GetAttribResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_ATTR];
+
+    fn from_result(result: &SJsonValue) -> VdrResult<Option<Self>> {
+        if result["data"].is_null() {
+            return Ok(None);
+        }
+        serde_json::from_value(result.clone())
+            .map(Some)
+            .with_input_err("Error deserializing ATTRIB reply")
+    }
+}
+
+impl GetAttribResult {
+    /// Parse the value of a raw attribute
+    pub fn raw_value(&self) -> VdrResult<SJsonValue> {
+        if self.raw.is_none() {
+            return Err(input_err("Attribute value is not raw"));
+        }
+        serde_json::from_str(&self.data).with_input_err("Error deserializing raw attribute")
+    }
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRevocRegDefResult {
+    pub id: RevocationRegistryId,
+    pub revoc_def_type: String,
+    pub tag: String,
+    pub cred_def_id: CredentialDefinitionId,
+    pub value: RevocRegDefValue,
+    pub ver: Option<String>,
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocRegDefValue {
+    pub issuance_type: String,
+    pub max_cred_num: u32,
+    pub public_keys: SJsonValue,
+    pub tails_hash: String,
+    pub tails_location: String,
+}
+
+impl ResponseData for GetRevocRegDefResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_REVOC_REG_DEF];
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRevocRegResult {
+    pub revoc_def_type: Option<String>,
+    pub revoc_reg_def_id: RevocationRegistryId,
+    pub seq_no: Option<u64>,
+    pub txn_time: Option<u64>,
+    pub value: RevocRegValue,
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+pub struct RevocRegValue {
+    pub accum: String,
+}
+
+impl ResponseData for GetRevocRegResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_REVOC_REG];
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRevocRegDeltaResult {
+    pub revoc_def_type: Option<String>,
+    pub revoc_reg_def_id: RevocationRegistryId,
+    pub value: RevocRegDeltaValue,
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+pub struct RevocRegDeltaValue {
+    pub accum_from: Option<GetRevocRegResult>,
+    pub accum_to: GetRevocRegResult,
+    #[serde(default)]
+    pub issued: Vec<u32>,
+    #[serde(default)]
+    pub revoked: Vec<u32>,
+}
+
+impl ResponseData for GetRevocRegDeltaResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_REVOC_REG_DELTA];
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+pub struct GetTxnAuthorAgreementResult {
+    pub text: String,
+    pub version: String,
+    pub digest: Option<String>,
+    pub ratification_ts: Option<u64>,
+    pub retirement_ts: Option<u64>,
+}
+
+impl ResponseData for GetTxnAuthorAgreementResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_TXN_AUTHR_AGRMT];
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetAcceptanceMechanismsResult {
+    pub aml: HashMap<String, String>,
+    pub version: String,
+    pub aml_context: Option<String>,
+}
+
+impl ResponseData for GetAcceptanceMechanismsResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_TXN_AUTHR_AGRMT_AML];
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(transparent)]
+pub struct GetAuthRuleResult {
+    pub rules: Vec<AuthRule>,
+}
+
+impl ResponseData for GetAuthRuleResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_AUTH_RULE];
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTxnResult {
+    pub txn: SJsonValue,
+    pub txn_metadata: TxnMetadata,
+    #[serde(default)]
+    pub req_signature: SJsonValue,
+    pub ver: Option<String>,
+    pub audit_path: Option<Vec<String>>,
+    pub ledger_size: Option<u64>,
+    pub root_hash: Option<String>,
+}
+
+impl GetTxnResult {
+    /// The transaction type of the returned transaction
+    pub fn txn_type(&self) -> Option<&str> {
+        self.txn["type"].as_str()
+    }
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TxnMetadata {
+    pub seq_no: Option<u64>,
+    pub txn_time: Option<u64>,
+    pub txn_id: Option<String>,
+}
+
+impl ResponseData for GetTxnResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_TXN];
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[serde(transparent)]
+pub struct GetFrozenLedgersResult {
+    /// Frozen ledgers by ledger ID
+    pub ledgers: HashMap<String, FrozenLedger>,
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+pub struct FrozenLedger {
+    pub ledger: String,
+    pub state: String,
+    pub seq_no: u64,
+}
+
+impl ResponseData for GetFrozenLedgersResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_FROZEN_LEDGERS];
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+pub struct GetFlagResult {
+    pub value: String,
+}
+
+impl ResponseData for GetFlagResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_FLAG];
+
+    fn from_result(result: &SJsonValue) -> VdrResult<Option<Self>> {
+        // the flag value is returned as-is rather than as encoded JSON
+        match &result["data"] {
+            SJsonValue::Null => Ok(None),
+            SJsonValue::String(value) => Ok(Some(GetFlagResult {
+                value: value.to_string(),
+            })),
+            data => parse_data(data),
+        }
+    }
+}
+
+#[derive(Deserialize, Eq, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRichSchemaResult {
+    pub id: RichSchemaId,
+    pub rs_type: String,
+    pub rs_name: String,
+    pub rs_version: String,
+    pub content: String,
+    pub from: Option<DidValue>,
+    pub endorser: Option<DidValue>,
+    pub ver: Option<String>,
+}
+
+impl ResponseData for GetRichSchemaResult {
+    const TXN_TYPES: &'static [&'static str] = &[
+        constants::GET_RICH_SCHEMA_BY_ID,
+        constants::GET_RICH_SCHEMA_BY_METADATA,
+    ];
+}
+
+/// The reply of a single node to a GET_VALIDATOR_INFO request
+#[derive(Deserialize, PartialEq, Debug)]
+pub struct GetValidatorInfoResult {
+    pub alias: Option<String>,
+    pub did: Option<String>,
+    pub verkey: Option<String>,
+    pub timestamp: Option<u64>,
+    #[serde(rename = "Node_info")]
+    pub node_info: Option<SJsonValue>,
+    #[serde(rename = "Pool_info")]
+    pub pool_info: Option<SJsonValue>,
+    #[serde(rename = "Software")]
+    pub software: Option<SJsonValue>,
+    /// Any remaining sections of the validator info
+    #[serde(flatten)]
+    pub extra: HashMap<String, SJsonValue>,
+}
+
+impl ResponseData for GetValidatorInfoResult {
+    const TXN_TYPES: &'static [&'static str] = &[constants::GET_VALIDATOR_INFO];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(result: SJsonValue) -> String {
+        json!({ "op": "REPLY", "result": result }).to_string()
+    }
+
+    fn state_proof() -> SJsonValue {
+        json!({
+            "root_hash": "7Wdj3rrMCZ1R1M78H4xK5jxikmdUUGW2kbfJQ1HoEpK",
+            "proof_nodes": "+QHl+FGAgICg0he/hjc9t/tPFzmCrb2T+nHnN0cRwqPKqZEc3pw2iCaAoAsA80p3oFwfl4dDaKkNI8z8weRsSaS9Y8n3HoardRzxgICAgICAgICAgID4naAgwxDOAEoIq+wUHr5h9jjSAIPDjS7SEG1NvWJbToxVQbh6+Hi4dnsiaWRlbnRpZmllciI6Ikw1QUQ1ZzY1VERRcjFQUEhIUm9pRnkiLCJyb2xlIjpudWxsLCJzZXFObyI6MTAsInR4blRpbWUiOjE1NTY4Nzg2OTQsInZlcmtleSI6In42M1k0MUpHQjNGTUJDOVlodEF4a1JjIn3gICAgICAgICAgICAgICAgICA",
+            "multi_signature": {
+                "value": {
+                    "timestamp": 1556878694,
+                    "ledger_id": 1,
+                    "txn_root_hash": "AKboMiJZJm247Sa7GsKQo5Ba8ukgxTQ3DsLc2pyVuDkU",
+                    "pool_state_root_hash": "7o9YBbFMs6hr8RxrwhWEG5SuAG1yoCcM9GtJbvQPKzy4",
+                    "state_root_hash": "7Wdj3rrMCZ1R1M78H4xK5jxikmdUUGW2kbfJQ1HoEpK"
+                },
+                "signature": "QwkoPr9pwXyBdtMMUtJ841QjX3pTEQP6bumBpHCWiBCn4AduEW55SQXHjfQZd7EXEjArMfjNyDjgC3Qsvh7RfGv",
+                "participants": ["Node2", "Node1", "Node3"]
+            }
+        })
+    }
+
+    #[test]
+    fn parse_get_nym_response() {
+        let reply = reply(json!({
+            "type": constants::GET_NYM,
+            "identifier": "L5AD5g65TDQr1PPHHRoiFy",
+            "reqId": 1556878694,
+            "seqNo": 10,
+            "txnTime": 1556878694,
+            "dest": "V4SGRU86Z58d6TV7PBUe6f",
+            "data": "{\"dest\":\"V4SGRU86Z58d6TV7PBUe6f\",\"identifier\":\"L5AD5g65TDQr1PPHHRoiFy\",\"role\":null,\"seqNo\":10,\"txnTime\":1556878694,\"verkey\":\"~63Y41JGB3FMBC9YhtAxkRc\"}",
+            "state_proof": state_proof(),
+        }));
+        let response = parse_response::<GetNymResult>(&reply).unwrap();
+        assert_eq!(response.seq_no(), Some(10));
+        assert_eq!(response.txn_time(), Some(1556878694));
+        let state_proof = response.state_proof().unwrap();
+        assert_eq!(
+            state_proof
+                .multi_signature
+                .as_ref()
+                .unwrap()
+                .value
+                .ledger_id,
+            1
+        );
+        let verkey = match response.data.unwrap() {
+            GetNymResult::GetNymResultV0(nym) => nym.verkey,
+            GetNymResult::GetNymResultV1(nym) => nym.verkey,
+        };
+        assert_eq!(verkey, "~63Y41JGB3FMBC9YhtAxkRc");
+    }
+
+    #[test]
+    fn parse_not_found_response() {
+        let reply = reply(json!({
+            "type": constants::GET_SCHEMA,
+            "seqNo": null,
+            "txnTime": null,
+            "data": null,
+        }));
+        let response = parse_response::<GetSchemaResult>(&reply).unwrap();
+        assert!(response.data.is_none());
+        assert_eq!(response.seq_no(), None);
+    }
+
+    #[test]
+    fn parse_response_rejects_mismatched_type() {
+        let reply = reply(json!({
+            "type": constants::GET_NYM,
+            "data": null,
+        }));
+        let err = parse_response::<GetSchemaResult>(&reply).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::Input));
+    }
+
+    #[test]
+    fn parse_response_rejects_nack() {
+        let reply = json!({"op": "REQNACK", "reason": "client request invalid"}).to_string();
+        let err = parse_response::<GetSchemaResult>(&reply).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::Input));
+    }
+
+    #[test]
+    fn parse_get_cred_def_response() {
+        let reply = reply(json!({
+            "type": constants::GET_CRED_DEF,
+            "seqNo": 15,
+            "txnTime": 1556878700,
+            "data": {
+                "refSchemaAttributes": ["name", "age"],
+                "refSchemaFrom": "L5AD5g65TDQr1PPHHRoiFy",
+                "refSchemaId": "L5AD5g65TDQr1PPHHRoiFy:2:gvt:1.0",
+                "refSchemaName": "gvt",
+                "refSchemaTxnSeqNo": 14,
+                "refSchemaTxnTime": "2019-05-03T10:18:14Z",
+                "refSchemaVersion": "1.0"
+            },
+        }));
+        let response = parse_response::<GetClaimDefResult>(&reply).unwrap();
+        assert_eq!(response.seq_no(), Some(15));
+        let cred_def = response.data.unwrap();
+        assert_eq!(cred_def.ref_schema_txn_seq_no, 14);
+        assert_eq!(cred_def.ref_schema_attributes, vec!["name", "age"]);
+    }
+
+    #[test]
+    fn parse_get_attrib_response() {
+        let reply = reply(json!({
+            "type": constants::GET_ATTR,
+            "dest": "V4SGRU86Z58d6TV7PBUe6f",
+            "raw": "endpoint",
+            "seqNo": 12,
+            "txnTime": 1556878700,
+            "data": "{\"endpoint\":{\"endpoint\":\"http://127.0.0.1:5555\"}}",
+        }));
+        let response = parse_response::<GetAttribResult>(&reply).unwrap();
+        assert_eq!(response.seq_no(), Some(12));
+        let attrib = response.data.unwrap();
+        assert_eq!(attrib.raw.as_deref(), Some("endpoint"));
+        assert_eq!(
+            attrib.raw_value().unwrap()["endpoint"]["endpoint"],
+            "http://127.0.0.1:5555"
+        );
+    }
+
+    #[test]
+    fn parse_get_txn_author_agreement_response() {
+        let reply = reply(json!({
+            "type": constants::GET_TXN_AUTHR_AGRMT,
+            "seqNo": 3,
+            "txnTime": 1556878700,
+            "data": {
+                "text": "some agreement text",
+                "version": "1.0.0",
+                "digest": "83d907821df1c87db829e96569a11f6fc2e7880acba5e43d07ab786959e13bd3",
+                "ratification_ts": 1556878600
+            },
+        }));
+        let response = parse_response::<GetTxnAuthorAgreementResult>(&reply).unwrap();
+        let taa = response.data.unwrap();
+        assert_eq!(taa.version, "1.0.0");
+        assert_eq!(taa.ratification_ts, Some(1556878600));
+        assert_eq!(taa.retirement_ts, None);
+    }
+
+    #[test]
+    fn parse_get_frozen_ledgers_response() {
+        let reply = reply(json!({
+            "type": constants::GET_FROZEN_LEDGERS,
+            "data": {
+                "909": { "ledger": "4Vbx4XxqYPLrJKE7HaQRbMDbdb2nNqgJSgbURnFL5QYG", "state": "7bRWNMKqDYmaYJjQWybHo2VUrDxTpVFvQF4VAZJymNw4", "seq_no": 10 }
+            },
+        }));
+        let response = parse_response::<GetFrozenLedgersResult>(&reply).unwrap();
+        assert_eq!(response.data.unwrap().ledgers["909"].seq_no, 10);
+    }
+
+    #[test]
+    fn parse_v1_reply() {
+        let reply = json!({
+            "op": "REPLY",
+            "data": {
+                "result": [{
+                    "result": {
+                        "type": constants::GET_FLAG,
+                        "seqNo": 4,
+                        "txnTime": 1556878700,
+                        "data": "true"
+                    }
+                }]
+            }
+        })
+        .to_string();
+        let response = parse_response::<GetFlagResult>(&reply).unwrap();
+        assert_eq!(response.txn_time(), Some(1556878700));
+        assert_eq!(response.data.unwrap().value, "true");
+    }
+}
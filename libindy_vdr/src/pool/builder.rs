@@ -1,12 +1,18 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use crate::common::error::prelude::*;
 use crate::config::PoolConfig;
 
-use super::genesis::PoolTransactions;
-use super::manager::{LocalPool, SharedPool};
-use super::networker::{MakeLocal, MakeShared, ZMQNetworkerFactory};
+use super::genesis::{PoolTransactions, PoolTransactionsCache};
+use super::manager::{LocalPool, PoolImpl, SharedPool};
+use super::networker::Networker;
+use super::networker::{DefaultNetworkerFactory, MakeLocal, MakeShared};
+use super::refresh::{PoolRefreshEvent, RefreshListener, SharedPoolRefresher};
 use super::runner::PoolRunner;
+use super::snapshot::{PoolSnapshot, SnapshotStore};
+use super::types::PoolSetup;
+use super::Pool;
 
 /// A utility class for building a new pool instance or runner.
 #[derive(Clone)]
@@ -15,6 +21,9 @@ pub struct PoolBuilder {
     transactions: PoolTransactions,
     node_weights: Option<HashMap<String, f32>>,
     refreshed: bool,
+    snapshot: Option<PoolSnapshot>,
+    snapshot_store: Option<Arc<SnapshotStore>>,
+    refresh_listener: Option<RefreshListener>,
 }
 
 impl PoolBuilder {
@@ -25,6 +34,9 @@ impl PoolBuilder {
             transactions,
             node_weights: None,
             refreshed: false,
+            snapshot: None,
+            snapshot_store: None,
+            refresh_listener: None,
         }
     }
 
@@ -40,41 +52,136 @@ impl PoolBuilder {
         self
     }
 
+    /// Set a persisted snapshot to restore the pool state from.
+    pub fn snapshot(mut self, snapshot: Option<PoolSnapshot>) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Persist snapshots of the pool state to a cache while the pool is in use and when it
+    /// is dropped. The transactions found by background refreshes are also stored.
+    ///
+    /// Snapshots are keyed by the `base` transactions the pool was originally created from,
+    /// before resolving any cached updates. The stored snapshot is restored when the pool is
+    /// built, unless one has been set explicitly.
+    pub fn snapshot_cache(
+        mut self,
+        cache: Arc<dyn PoolTransactionsCache>,
+        base: PoolTransactions,
+    ) -> Self {
+        self.snapshot_store = Some(Arc::new(SnapshotStore::new(cache, base)));
+        self
+    }
+
+    /// Set the callback receiving the changes applied by background refreshes of a `PoolRunner`.
+    pub fn refresh_listener(mut self, listener: Option<RefreshListener>) -> Self {
+        self.refresh_listener = listener;
+        self
+    }
+
     /// Create a `LocalPool` instance from the builder, for use in a single thread.
     pub fn into_local(self) -> VdrResult<LocalPool> {
         let merkle_tree = self.transactions.merkle_tree()?;
-        LocalPool::build(
+        let pool = LocalPool::build(
             self.config,
             merkle_tree,
-            MakeLocal(ZMQNetworkerFactory {}),
+            MakeLocal(DefaultNetworkerFactory::default()),
             self.node_weights,
             self.refreshed,
-        )
+        )?;
+        restore_and_persist(&pool, self.snapshot, self.snapshot_store);
+        Ok(pool)
     }
 
     /// Create a `SharedPool` instance from the builder, for use across multiple threads.
     pub fn into_shared(self) -> VdrResult<SharedPool> {
         let merkle_tree = self.transactions.merkle_tree()?;
 
-        SharedPool::build(
+        let pool = SharedPool::build(
             self.config,
             merkle_tree,
-            MakeShared(ZMQNetworkerFactory {}),
+            MakeShared(DefaultNetworkerFactory::default()),
             self.node_weights,
             self.refreshed,
-        )
+        )?;
+        restore_and_persist(&pool, self.snapshot, self.snapshot_store);
+        Ok(pool)
     }
 
     /// Create a `PoolRunner` instance from the builder, to handle pool interaction
     /// in a dedicated thread.
     pub fn into_runner(self) -> VdrResult<PoolRunner> {
         let merkle_tree = self.transactions.merkle_tree()?;
-        Ok(PoolRunner::new(
+        let runner = PoolRunner::new(
             self.config,
             merkle_tree,
-            MakeLocal(ZMQNetworkerFactory {}),
+            MakeLocal(DefaultNetworkerFactory::default()),
             self.node_weights,
             self.refreshed,
-        ))
+        );
+        let snapshot = self
+            .snapshot
+            .or_else(|| self.snapshot_store.as_ref().and_then(|store| store.load()));
+        if let Some(snapshot) = snapshot {
+            runner.restore_snapshot(snapshot)?;
+        }
+        if let Some(store) = self.snapshot_store {
+            runner.persist_snapshots(store)?;
+        }
+        if self.refresh_listener.is_some() {
+            runner.set_refresh_listener(self.refresh_listener)?;
+        }
+        Ok(runner)
+    }
+
+    /// Create a `SharedPool` instance from the builder, along with a `SharedPoolRefresher`
+    /// applying the configured `refresh_policy`. Each refreshed pool instance is passed
+    /// to `on_update`.
+    pub fn into_shared_refresher<U>(
+        self,
+        on_update: U,
+    ) -> VdrResult<(SharedPool, SharedPoolRefresher)>
+    where
+        U: Fn(SharedPool, PoolRefreshEvent) + Send + 'static,
+    {
+        let policy = self
+            .config
+            .refresh_policy
+            .ok_or_else(|| input_err("No refresh policy configured"))?;
+        let config = self.config.clone();
+        let node_weights = self.node_weights.clone();
+        let snapshot_store = self.snapshot_store.clone();
+        let pool = self.into_shared()?;
+        let connect = move |txns| {
+            let pool = PoolBuilder::new(config.clone(), txns)
+                .node_weights(node_weights.clone())
+                .refreshed(true)
+                .into_shared()?;
+            // snapshots of the refreshed pool replace those of the previous instance
+            if let Some(store) = snapshot_store.as_ref() {
+                store.store_transactions(&pool.get_transactions());
+                pool.persist_snapshots(store.clone());
+            }
+            Ok(pool)
+        };
+        let refresher = SharedPoolRefresher::start(pool.clone(), policy, connect, on_update);
+        Ok((pool, refresher))
+    }
+}
+
+fn restore_and_persist<S, T>(
+    pool: &PoolImpl<S, T>,
+    snapshot: Option<PoolSnapshot>,
+    store: Option<Arc<SnapshotStore>>,
+) where
+    S: AsRef<PoolSetup> + Clone,
+    T: Networker + Clone,
+{
+    let snapshot = snapshot.or_else(|| store.as_ref().and_then(|store| store.load()));
+    if let Some(snapshot) = snapshot.as_ref() {
+        pool.restore_snapshot(snapshot);
+    }
+    if let Some(store) = store {
+        pool.persist_snapshots(store);
     }
 }
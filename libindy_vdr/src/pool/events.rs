@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::common::handle::ResourceHandle;
+
+use super::types::{StateProofResult, TimingResult};
+
+/// A callback receiving pool events
+pub type PoolEventListener = Arc<dyn Fn(&PoolEvent) + Send + Sync>;
+
+/// An event reported by a verifier pool
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PoolEvent {
+    /// A connection to a node was opened
+    NodeConnected { node: String },
+    /// A connection to a node was closed
+    NodeDisconnected { node: String },
+    /// The networker opened a new set of node connections
+    ConnectionOpened {
+        /// The number of open connection sets
+        connections: usize,
+        /// Set when the previous connection reached `conn_request_limit`
+        rollover: bool,
+    },
+    /// The networker closed an idle set of node connections
+    ConnectionClosed {
+        /// The number of open connection sets
+        connections: usize,
+    },
+    /// A node did not reply to a request in time
+    NodeTimeout { node: String },
+    /// A node returned a reply with an invalid state proof
+    StateProofFailed { node: String, reason: String },
+    /// A refresh of the pool transactions completed
+    RefreshCompleted {
+        previous_root: String,
+        previous_size: usize,
+        mt_root: String,
+        mt_size: usize,
+    },
+    /// A ledger request completed
+    RequestCompleted {
+        req_id: Option<String>,
+        /// The numeric transaction type
+        txn_type: String,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        timing: Option<TimingResult>,
+        /// The state proof verification outcome for each node
+        #[serde(skip_serializing_if = "HashMap::is_empty")]
+        state_proof: HashMap<String, StateProofOutcome>,
+    },
+    /// The nodes did not reach consensus on a reply
+    ConsensusFailed {
+        /// The node which sent the sample reply
+        node: Option<String>,
+        /// A sample of the most common reply received
+        reply: Option<String>,
+    },
+}
+
+/// The outcome of a state proof verification
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateProofOutcome {
+    Missing,
+    Invalid,
+    Expired,
+    Verified,
+}
+
+impl StateProofOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::Invalid => "invalid",
+            Self::Expired => "expired",
+            Self::Verified => "verified",
+        }
+    }
+}
+
+impl From<&StateProofResult> for StateProofOutcome {
+    fn from(result: &StateProofResult) -> Self {
+        match result {
+            StateProofResult::Missing => Self::Missing,
+            StateProofResult::Invalid(..) => Self::Invalid,
+            StateProofResult::Expired(_) => Self::Expired,
+            StateProofResult::Verified(_) => Self::Verified,
+        }
+    }
+}
+
+/// A handle for a registered pool event listener
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct EventSubscription(pub i64);
+
+impl_sequence_handle!(EventSubscription, EVENT_SUB_COUNTER);
+
+/// The registered listeners for the events of a pool
+#[derive(Default)]
+pub struct PoolEvents {
+    listeners: Mutex<Vec<(EventSubscription, PoolEventListener)>>,
+}
+
+impl PoolEvents {
+    /// Register a listener, returning a handle used to unsubscribe
+    pub fn subscribe(&self, listener: PoolEventListener) -> EventSubscription {
+        let sub = EventSubscription::next();
+        self.subscribe_with(sub, listener);
+        sub
+    }
+
+    /// Register a listener with an existing subscription handle
+    pub fn subscribe_with(&self, sub: EventSubscription, listener: PoolEventListener) {
+        if let Ok(mut listeners) = self.listeners.lock() {
+            listeners.push((sub, listener));
+        }
+    }
+
+    /// Remove a listener, returning `false` if it was not registered
+    pub fn unsubscribe(&self, sub: EventSubscription) -> bool {
+        if let Ok(mut listeners) = self.listeners.lock() {
+            let count = listeners.len();
+            listeners.retain(|(id, _)| *id != sub);
+            listeners.len() != count
+        } else {
+            false
+        }
+    }
+
+    /// Check whether any listeners are registered
+    pub fn is_empty(&self) -> bool {
+        self.listeners
+            .lock()
+            .map(|listeners| listeners.is_empty())
+            .unwrap_or(true)
+    }
+
+    /// Deliver an event to all registered listeners
+    pub fn emit(&self, event: PoolEvent) {
+        let listeners = match self.listeners.lock() {
+            Ok(listeners) if !listeners.is_empty() => listeners.clone(),
+            _ => return,
+        };
+        // listeners are invoked without holding the lock, so they may unsubscribe
+        for (_, listener) in listeners {
+            listener(&event);
+        }
+    }
+
+    /// Copy the listeners registered with another instance, such as a replaced pool
+    pub fn inherit(&self, other: &PoolEvents) {
+        let inherited = match other.listeners.lock() {
+            Ok(listeners) => listeners.clone(),
+            Err(_) => return,
+        };
+        if let Ok(mut listeners) = self.listeners.lock() {
+            for (sub, listener) in inherited {
+                if !listeners.iter().any(|(id, _)| *id == sub) {
+                    listeners.push((sub, listener));
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for PoolEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self
+            .listeners
+            .lock()
+            .map(|listeners| listeners.len())
+            .unwrap_or(0);
+        f.debug_struct("PoolEvents")
+            .field("listeners", &count)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_subscribe_emit() {
+        let events = PoolEvents::default();
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        let sub = events.subscribe(Arc::new(move |event| {
+            if let PoolEvent::NodeTimeout { node } = event {
+                assert_eq!(node, "Node1");
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        }));
+        events.emit(PoolEvent::NodeTimeout {
+            node: "Node1".to_string(),
+        });
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        let replaced = PoolEvents::default();
+        replaced.inherit(&events);
+        assert!(events.unsubscribe(sub));
+        assert!(!events.unsubscribe(sub));
+        events.emit(PoolEvent::NodeTimeout {
+            node: "Node1".to_string(),
+        });
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        replaced.emit(PoolEvent::NodeTimeout {
+            node: "Node1".to_string(),
+        });
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_serialize_event() {
+        let event = PoolEvent::StateProofFailed {
+            node: "Node1".to_string(),
+            reason: "Invalid signature".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            json!({"event": "state_proof_failed", "node": "Node1", "reason": "Invalid signature"})
+        );
+    }
+}
+
+#[cfg(all(test, feature = "simulated_pool"))]
+mod simulated_tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::PoolEvent;
+    use crate::config::PoolConfig;
+    use crate::pool::networker::fixtures::{read_nym, simulated_pool, write_nym};
+    use crate::pool::networker::NodeFault;
+    use crate::pool::Pool;
+
+    #[test]
+    fn pool_events_reported() {
+        let sim = simulated_pool();
+        // read from every node so that the faulty node is queried
+        let pool = sim
+            .local_pool(PoolConfig {
+                request_read_nodes: 4,
+                ..PoolConfig::default()
+            })
+            .unwrap();
+        let builder = pool.get_request_builder();
+        write_nym(&pool, &builder);
+
+        let events = Arc::new(Mutex::new(vec![]));
+        let received = events.clone();
+        let sub = pool
+            .get_events()
+            .subscribe(Arc::new(move |event: &PoolEvent| {
+                received.lock().unwrap().push(event.clone())
+            }));
+        // the faulty node replies before the others
+        sim.set_fault("Node1", Some(NodeFault::Lying)).unwrap();
+        for alias in ["Node2", "Node3", "Node4"] {
+            let delay = Duration::from_millis(50);
+            sim.set_fault(alias, Some(NodeFault::Slow(delay))).unwrap();
+        }
+        read_nym(&pool, &builder);
+        assert!(pool.get_events().unsubscribe(sub));
+
+        let events = events.lock().unwrap();
+        assert!(events.iter().any(|event| matches!(
+            event,
+            PoolEvent::StateProofFailed { node, .. } if node == "Node1"
+        )));
+        assert!(matches!(
+            events.last(),
+            Some(PoolEvent::RequestCompleted { success: true, .. })
+        ));
+    }
+}
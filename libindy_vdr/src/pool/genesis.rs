@@ -9,6 +9,7 @@ use std::sync::Mutex;
 use rand::random;
 use serde_json::{self, Deserializer, Value as SJsonValue};
 
+use super::snapshot::{PoolSnapshot, POOL_SNAPSHOT_VERSION};
 use super::types::{
     NodeTransaction, NodeTransactionV0, NodeTransactionV1, ProtocolVersion, VerifierInfo,
     VerifierKey, Verifiers,
@@ -242,11 +243,22 @@ pub trait PoolTransactionsCache: Send + Sync {
     fn resolve_latest(&self, txns: &PoolTransactions) -> VdrResult<Option<PoolTransactions>>;
 
     fn update(&self, base: &PoolTransactions, latest: &PoolTransactions) -> VdrResult<()>;
+
+    /// Load the persisted state of the pool created from the `base` transactions.
+    fn load_snapshot(&self, _base: &PoolTransactions) -> VdrResult<Option<PoolSnapshot>> {
+        Ok(None)
+    }
+
+    /// Persist the state of the pool created from the `base` transactions.
+    fn store_snapshot(&self, _base: &PoolTransactions, _snapshot: &PoolSnapshot) -> VdrResult<()> {
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
 struct _MemoryCacheEntry {
-    txns: PoolTransactions,
+    txns: Option<PoolTransactions>,
+    snapshot: Option<PoolSnapshot>,
 }
 
 #[derive(Debug, Default)]
@@ -264,11 +276,7 @@ impl PoolTransactionsCache for InMemoryCache {
     fn resolve_latest(&self, txns: &PoolTransactions) -> VdrResult<Option<PoolTransactions>> {
         let hash = txns.root_hash_base58()?;
         let cache = self.cache.lock().unwrap();
-        if let Some(entry) = cache.get(&hash) {
-            Ok(Some(entry.txns.clone()))
-        } else {
-            Ok(None)
-        }
+        Ok(cache.get(&hash).and_then(|entry| entry.txns.clone()))
     }
 
     fn update(&self, base: &PoolTransactions, latest: &PoolTransactions) -> VdrResult<()> {
@@ -276,9 +284,29 @@ impl PoolTransactionsCache for InMemoryCache {
         let mut cache = self.cache.lock().unwrap();
         cache
             .entry(from_hash)
-            .and_modify(|e| e.txns = latest.clone())
+            .and_modify(|e| e.txns = Some(latest.clone()))
+            .or_insert_with(|| _MemoryCacheEntry {
+                txns: Some(latest.clone()),
+                snapshot: None,
+            });
+        Ok(())
+    }
+
+    fn load_snapshot(&self, base: &PoolTransactions) -> VdrResult<Option<PoolSnapshot>> {
+        let hash = base.root_hash_base58()?;
+        let cache = self.cache.lock().unwrap();
+        Ok(cache.get(&hash).and_then(|entry| entry.snapshot.clone()))
+    }
+
+    fn store_snapshot(&self, base: &PoolTransactions, snapshot: &PoolSnapshot) -> VdrResult<()> {
+        let hash = base.root_hash_base58()?;
+        let mut cache = self.cache.lock().unwrap();
+        cache
+            .entry(hash)
+            .and_modify(|e| e.snapshot = Some(snapshot.clone()))
             .or_insert_with(|| _MemoryCacheEntry {
-                txns: latest.clone(),
+                txns: None,
+                snapshot: Some(snapshot.clone()),
             });
         Ok(())
     }
@@ -300,6 +328,14 @@ impl FilesystemCache {
         Ok(fs::create_dir_all(self.cache_dir.join(ident))?)
     }
 
+    fn remove_cache_file(&self, ident: &str, name: &str) {
+        let mut path = self.cache_dir.clone();
+        path.extend(&[ident, name]);
+        fs::remove_file(path)
+            .map_err(|e| warn!("Error removing from pool genesis cache: {e}"))
+            .ok();
+    }
+
     fn read_cache_file(&self, ident: &str, name: &str) -> Option<String> {
         let mut path = self.cache_dir.clone();
         path.extend(&[ident, name]);
@@ -344,6 +380,32 @@ impl PoolTransactionsCache for FilesystemCache {
         self.write_cache_file(&ident, "txns", &latest.encode_json_string()?)?;
         Ok(())
     }
+
+    fn load_snapshot(&self, base: &PoolTransactions) -> VdrResult<Option<PoolSnapshot>> {
+        let ident = base.root_hash_base58()?;
+        let snapshot = match self.read_cache_file(&ident, "snapshot") {
+            Some(snapshot) => snapshot,
+            None => return Ok(None),
+        };
+        let version = serde_json::from_str::<SJsonValue>(&snapshot)
+            .ok()
+            .and_then(|value| value["version"].as_u64());
+        if version != Some(POOL_SNAPSHOT_VERSION as u64) {
+            debug!("Discarding pool snapshot with unsupported version: {version:?}");
+            self.remove_cache_file(&ident, "snapshot");
+            return Ok(None);
+        }
+        Ok(serde_json::from_str(&snapshot)
+            .map_err(|e| warn!("Error reading pool snapshot from cache: {e}"))
+            .ok())
+    }
+
+    fn store_snapshot(&self, base: &PoolTransactions, snapshot: &PoolSnapshot) -> VdrResult<()> {
+        let ident = base.root_hash_base58()?;
+        let snapshot = serde_json::to_string(snapshot)
+            .with_err_msg(VdrErrorKind::Unexpected, "Error serializing pool snapshot")?;
+        self.write_cache_file(&ident, "snapshot", &snapshot)
+    }
 }
 
 pub fn build_node_transaction_map<T>(
@@ -631,4 +693,36 @@ mod tests {
         );
         fs::remove_dir_all(&temp_dir).unwrap();
     }
+
+    #[test]
+    fn test_fs_cache_snapshot() {
+        let temp_name = format!("vdr-test-{:020}", random::<u64>());
+        let temp_dir = temp_dir().join(temp_name);
+        let txns = _transactions();
+        let cache = FilesystemCache::new(&temp_dir);
+        assert_eq!(cache.load_snapshot(&txns).unwrap(), None);
+
+        let mut snapshot = PoolSnapshot {
+            version: POOL_SNAPSHOT_VERSION,
+            mt_root: txns.root_hash_base58().unwrap(),
+            mt_size: txns.len(),
+            ledgers: HashMap::new(),
+            node_health: HashMap::new(),
+            bls_keys: HashMap::new(),
+            saved_at: 0,
+        };
+        cache.store_snapshot(&txns, &snapshot).unwrap();
+        assert_eq!(
+            cache.load_snapshot(&txns).unwrap().as_ref(),
+            Some(&snapshot)
+        );
+
+        snapshot.version = POOL_SNAPSHOT_VERSION + 1;
+        cache.store_snapshot(&txns, &snapshot).unwrap();
+        assert_eq!(cache.load_snapshot(&txns).unwrap(), None);
+        assert!(cache
+            .read_cache_file(&snapshot.mt_root, "snapshot")
+            .is_none());
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
 }
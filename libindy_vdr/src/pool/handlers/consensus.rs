@@ -1,18 +1,23 @@
 use std::hash::{Hash, Hasher};
 
+use futures_util::future::{select, Either};
 use futures_util::stream::StreamExt;
 
 use serde_json::Value as SJsonValue;
 
 use crate::common::error::prelude::*;
 use crate::config::constants::DEFAULT_GENERATOR;
+use crate::config::{HedgePolicy, StateProofPolicy};
+use crate::pool::events::PoolEvent;
 use crate::state_proof::{check_state_proof, result_without_state_proof, BoxedSPParser};
 use crate::utils::base64;
+use crate::utils::futures::start_timer;
+use crate::utils::Validatable;
 
 use super::types::Message;
 use super::{
-    min_consensus, ConsensusState, HashableValue, PoolRequest, ReplyState, RequestEvent,
-    RequestResult, RequestResultMeta,
+    min_consensus, ConsensusState, HashableValue, PoolRequest, ReplyState, ReplyVerification,
+    RequestEvent, RequestResult, RequestResultMeta,
 };
 
 pub async fn handle_consensus_request<R: PoolRequest>(
@@ -21,8 +26,11 @@ pub async fn handle_consensus_request<R: PoolRequest>(
     state_proof_timestamps: (Option<u64>, Option<u64>),
     as_read_request: bool,
     custom_state_proof_parser: Option<&BoxedSPParser>,
+    hedge_policy: Option<HedgePolicy>,
+    state_proof_policy: StateProofPolicy,
 ) -> VdrResult<(RequestResult<String>, RequestResultMeta)> {
     trace!("consensus request");
+    state_proof_policy.validate()?;
     let config = request.pool_config();
     let node_keys = request.node_keys();
     let total_nodes_count = request.node_count();
@@ -31,7 +39,28 @@ pub async fn handle_consensus_request<R: PoolRequest>(
     let mut consensus = ConsensusState::new();
     let mut fail_consensus = ConsensusState::new();
 
-    let request_with_state_proof = state_proof_key.is_some() || custom_state_proof_parser.is_some();
+    let request_with_state_proof = state_proof_policy.is_enabled()
+        && (state_proof_key.is_some() || custom_state_proof_parser.is_some());
+    let state_proof_required = state_proof_policy.is_required();
+    if state_proof_required && !request_with_state_proof {
+        return Err(input_err(
+            "State proof verification is required but not supported for this request",
+        ));
+    }
+    let freshness_threshold = state_proof_policy
+        .freshness_threshold
+        .unwrap_or(config.freshness_threshold);
+    // requiring more signers is equivalent to tolerating fewer faulty nodes
+    let state_proof_f = match state_proof_policy.min_signers {
+        Some(min_signers) if min_signers > node_keys.len() => {
+            return Err(input_err(format!(
+                "Minimum number of state proof signers exceeds the number of nodes: {}",
+                min_signers
+            )));
+        }
+        Some(min_signers) => f.min(node_keys.len() - min_signers),
+        None => f,
+    };
 
     let init_send = if request_with_state_proof {
         config.request_read_nodes
@@ -41,8 +70,43 @@ pub async fn handle_consensus_request<R: PoolRequest>(
         total_nodes_count
     };
     request.send_to_any(init_send, config.ack_timeout)?;
+
+    // speculatively send to additional nodes when no verified reply arrives within the delay
+    let hedge = hedge_policy
+        .filter(|policy| request_with_state_proof && policy.max_hedges > 0)
+        .map(|policy| {
+            (
+                policy,
+                policy.delay(request.latency_percentile(policy.percentile)),
+            )
+        });
+    let mut hedges_sent = 0;
+    let mut hedge_timer = hedge.map(|(_, delay)| start_timer(delay));
+
     loop {
-        let resend = match request.next().await {
+        let event = match hedge_timer.as_mut() {
+            Some(timer) => match select(request.next(), timer).await {
+                Either::Left((event, _)) => Some(event),
+                Either::Right(_) => None,
+            },
+            None => Some(request.next().await),
+        };
+        let event = match (event, hedge) {
+            (Some(event), _) => event,
+            (None, Some((policy, delay))) => {
+                let sent = request.send_to_any(1, config.ack_timeout)?;
+                debug!("Sent hedged read to {:?} after {:?}", sent, delay);
+                hedges_sent += 1;
+                hedge_timer = if sent.is_empty() || hedges_sent >= policy.max_hedges {
+                    None
+                } else {
+                    Some(start_timer(delay))
+                };
+                continue;
+            }
+            (None, None) => unreachable!(),
+        };
+        let resend = match event {
             Some(RequestEvent::Received(node_alias, raw_msg, parsed)) => match parsed {
                 Message::Reply(reply) => {
                     trace!("reply on consensus request");
@@ -75,14 +139,14 @@ pub async fn handle_consensus_request<R: PoolRequest>(
                         if request_with_state_proof {
                             let sp_result = check_state_proof(
                                 result,
-                                f,
+                                state_proof_f,
                                 &DEFAULT_GENERATOR,
                                 &node_keys,
                                 &raw_msg,
                                 state_proof_key.as_deref(),
                                 state_proof_timestamps,
                                 last_write_time,
-                                config.freshness_threshold,
+                                freshness_threshold,
                                 custom_state_proof_parser,
                             );
                             let verified = sp_result.is_verified();
@@ -100,17 +164,25 @@ pub async fn handle_consensus_request<R: PoolRequest>(
                                     base64::encode(state_proof_key.as_ref().unwrap()),
                                 );
                             }
-                            if verified || cnt > f {
+                            if verified {
+                                return Ok((
+                                    RequestResult::Reply(raw_msg),
+                                    get_meta(request, ReplyVerification::StateProof),
+                                ));
+                            } else if cnt > f && !state_proof_required {
                                 return Ok((
-                                    RequestResult::Reply(if verified { raw_msg } else { soonest }),
-                                    request.get_meta(),
+                                    RequestResult::Reply(soonest),
+                                    get_meta(request, ReplyVerification::ConsensusFallback),
                                 ));
                             } else {
                                 request.clean_timeout(node_alias)?;
                                 true
                             }
                         } else if cnt > f {
-                            return Ok((RequestResult::Reply(soonest), request.get_meta()));
+                            return Ok((
+                                RequestResult::Reply(soonest),
+                                get_meta(request, ReplyVerification::Consensus),
+                            ));
                         } else {
                             false
                         }
@@ -167,7 +239,23 @@ pub async fn handle_consensus_request<R: PoolRequest>(
         };
         let total_replies = replies.len();
         if total_replies >= total_nodes_count {
-            let err = replies.get_error();
+            let err = if state_proof_required && consensus.max_entry().is_some() {
+                err_msg(
+                    VdrErrorKind::PoolNoConsensus,
+                    "No reply with a verified state proof was received",
+                )
+            } else {
+                replies.get_error()
+            };
+            if matches!(err.kind(), VdrErrorKind::PoolNoConsensus) {
+                let sample = consensus
+                    .max_entry()
+                    .and_then(|(_, responses)| responses.iter().next());
+                request.emit_event(PoolEvent::ConsensusFailed {
+                    node: sample.map(|resp| resp.node_alias.clone()),
+                    reply: sample.map(|resp| resp.raw_msg.clone()),
+                });
+            }
             return Ok((RequestResult::Failed(err), request.get_meta()));
         }
         if resend {
@@ -176,6 +264,12 @@ pub async fn handle_consensus_request<R: PoolRequest>(
     }
 }
 
+fn get_meta<R: PoolRequest>(request: &R, verification: ReplyVerification) -> RequestResultMeta {
+    let mut meta = request.get_meta();
+    meta.verification = Some(verification);
+    meta
+}
+
 #[derive(Debug)]
 struct NodeResponse {
     raw_msg: String,
@@ -248,3 +342,306 @@ pub struct ResponseMetadata {
     pub last_txn_time: Option<u64>,
     pub last_seq_no: Option<u64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, VecDeque};
+    use std::pin::Pin;
+
+    use futures_util::stream::{FusedStream, Stream};
+    use futures_util::task::{Context, Poll};
+
+    use super::*;
+    use crate::config::{PoolConfig, StateProofMode};
+    use crate::pool::{StateProofResult, TimingResult, VerifierKeys};
+    use crate::utils::futures::block_on;
+
+    const NODES: [&str; 4] = ["Node1", "Node2", "Node3", "Node4"];
+    const REPLY: &str = r#"{"op":"REPLY","result":{"type":"105","identifier":"V4SGRU86Z58d6TV7PBUe6f","reqId":1,"seqNo":null,"data":null,"dest":"V4SGRU86Z58d6TV7PBUe6f"}}"#;
+
+    /// A request answered by every node with the same reply, without a state proof
+    #[derive(Debug, Default)]
+    struct MockRequest {
+        pending: VecDeque<RequestEvent>,
+        sent: usize,
+        state_proof: HashMap<String, StateProofResult>,
+    }
+
+    impl Stream for MockRequest {
+        type Item = RequestEvent;
+
+        fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Option<Self::Item>> {
+            Poll::Ready(self.pending.pop_front())
+        }
+    }
+
+    impl FusedStream for MockRequest {
+        fn is_terminated(&self) -> bool {
+            self.pending.is_empty()
+        }
+    }
+
+    impl PoolRequest for MockRequest {
+        fn clean_timeout(&self, _node_alias: String) -> VdrResult<()> {
+            Ok(())
+        }
+
+        fn extend_timeout(&self, _node_alias: String, _timeout: i64) -> VdrResult<()> {
+            Ok(())
+        }
+
+        fn get_meta(&self) -> RequestResultMeta {
+            RequestResultMeta {
+                state_proof: self.state_proof.clone(),
+                timing: None,
+                receipt: None,
+                verification: None,
+            }
+        }
+
+        fn get_timing(&self) -> Option<TimingResult> {
+            None
+        }
+
+        fn emit_event(&self, _event: PoolEvent) {}
+
+        fn is_active(&self) -> bool {
+            true
+        }
+
+        fn latency_percentile(&self, _percentile: f32) -> Option<f32> {
+            None
+        }
+
+        fn node_count(&self) -> usize {
+            NODES.len()
+        }
+
+        fn node_keys(&self) -> VerifierKeys {
+            VerifierKeys::new()
+        }
+
+        fn node_order(&self) -> Vec<String> {
+            NODES.iter().map(|node| node.to_string()).collect()
+        }
+
+        fn pool_config(&self) -> PoolConfig {
+            PoolConfig::default()
+        }
+
+        fn send_to_all(&mut self, timeout: i64) -> VdrResult<()> {
+            self.send_to_any(NODES.len(), timeout)?;
+            Ok(())
+        }
+
+        fn send_to_any(&mut self, count: usize, _timeout: i64) -> VdrResult<Vec<String>> {
+            let nodes: Vec<String> = NODES
+                .iter()
+                .skip(self.sent)
+                .take(count)
+                .map(|node| node.to_string())
+                .collect();
+            for node in nodes.iter() {
+                let parsed = Message::from_raw_str(REPLY)?;
+                self.pending.push_back(RequestEvent::Received(
+                    node.clone(),
+                    REPLY.to_string(),
+                    parsed,
+                ));
+            }
+            self.sent += nodes.len();
+            Ok(nodes)
+        }
+
+        fn send_to(&mut self, node_aliases: Vec<String>, timeout: i64) -> VdrResult<Vec<String>> {
+            self.send_to_any(node_aliases.len(), timeout)
+        }
+
+        fn set_preferred_nodes(&mut self, _nodes: &[String]) {}
+
+        fn set_state_proof_result(&mut self, node_alias: String, res: StateProofResult) {
+            self.state_proof.insert(node_alias, res);
+        }
+    }
+
+    fn perform(
+        sp_key: Option<Vec<u8>>,
+        as_read_request: bool,
+        policy: StateProofPolicy,
+    ) -> VdrResult<(RequestResult<String>, RequestResultMeta)> {
+        let mut request = MockRequest::default();
+        block_on(handle_consensus_request(
+            &mut request,
+            sp_key,
+            (None, None),
+            as_read_request,
+            None,
+            None,
+            policy,
+        ))
+    }
+
+    fn sp_key() -> Option<Vec<u8>> {
+        Some(b"V4SGRU86Z58d6TV7PBUe6f".to_vec())
+    }
+
+    #[test]
+    fn required_rejects_unverified_replies() {
+        let (result, meta) = perform(
+            sp_key(),
+            true,
+            StateProofPolicy::new(StateProofMode::Required),
+        )
+        .unwrap();
+        match result {
+            RequestResult::Failed(err) => {
+                assert!(matches!(err.kind(), VdrErrorKind::PoolNoConsensus))
+            }
+            RequestResult::Reply(reply) => panic!("Unexpected reply: {}", reply),
+        }
+        assert_eq!(meta.state_proof.len(), NODES.len());
+        assert!(meta.verification.is_none());
+    }
+
+    #[test]
+    fn required_rejects_requests_without_state_proof() {
+        let policy = StateProofPolicy::new(StateProofMode::Required);
+        // read consensus
+        let err = perform(None, true, policy).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::Input));
+        // write consensus
+        let err = perform(None, false, policy).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::Input));
+    }
+
+    #[test]
+    fn preferred_falls_back_to_consensus() {
+        let (result, meta) = perform(
+            sp_key(),
+            true,
+            StateProofPolicy::new(StateProofMode::Preferred),
+        )
+        .unwrap();
+        assert!(matches!(result, RequestResult::Reply(_)));
+        assert_eq!(
+            meta.verification,
+            Some(ReplyVerification::ConsensusFallback)
+        );
+        assert!(!meta.state_proof.is_empty());
+    }
+
+    #[test]
+    fn disabled_skips_state_proof() {
+        let (result, meta) = perform(
+            sp_key(),
+            true,
+            StateProofPolicy::new(StateProofMode::Disabled),
+        )
+        .unwrap();
+        assert!(matches!(result, RequestResult::Reply(_)));
+        assert_eq!(meta.verification, Some(ReplyVerification::Consensus));
+        assert!(meta.state_proof.is_empty());
+    }
+
+    #[test]
+    fn min_signers_exceeding_nodes_is_rejected() {
+        let mut policy = StateProofPolicy::new(StateProofMode::Preferred);
+        policy.min_signers = Some(NODES.len() + 1);
+        let err = perform(sp_key(), true, policy).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::Input));
+    }
+}
+
+#[cfg(all(test, feature = "simulated_pool"))]
+mod simulated_tests {
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
+
+    use crate::config::{HedgePolicy, PoolConfig, StateProofMode, StateProofPolicy};
+    use crate::pool::helpers::perform_ledger_request;
+    use crate::pool::networker::fixtures::{
+        get_nym_request, pool_with_nym, simulated_pool, write_nym, VERKEY,
+    };
+    use crate::pool::networker::{MakeLocal, NodeFault};
+    use crate::pool::{LocalPool, Pool, ReplyVerification, RequestResult};
+    use crate::utils::futures::block_on;
+
+    #[test]
+    fn hedged_read_with_slow_nodes() {
+        let sim = simulated_pool();
+        // the only fast node is practically always contacted last
+        let weights = HashMap::from([
+            ("Node1".to_string(), 1000.0),
+            ("Node2".to_string(), 1000.0),
+            ("Node3".to_string(), 1000.0),
+            ("Node4".to_string(), 0.001),
+        ]);
+        let pool = LocalPool::build(
+            PoolConfig::default(),
+            sim.genesis().unwrap().merkle_tree().unwrap(),
+            MakeLocal(sim.clone()),
+            Some(weights),
+            false,
+        )
+        .unwrap();
+        let builder = pool.get_request_builder();
+        write_nym(&pool, &builder);
+        for alias in ["Node1", "Node2", "Node3"] {
+            let delay = Duration::from_secs(5);
+            sim.set_fault(alias, Some(NodeFault::Slow(delay))).unwrap();
+        }
+
+        let mut req = get_nym_request(&builder);
+        req.method
+            .set_hedge_policy(Some(HedgePolicy {
+                min_delay_ms: 50,
+                max_delay_ms: 50,
+                max_hedges: 2,
+                ..HedgePolicy::default()
+            }))
+            .unwrap();
+        let start = Instant::now();
+        let (result, meta) = block_on(perform_ledger_request(&pool, &req)).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(3));
+        assert!(matches!(result, RequestResult::Reply(reply) if reply.contains(VERKEY)));
+        assert_eq!(meta.verification, Some(ReplyVerification::StateProof));
+        assert!(meta.state_proof["Node4"].is_verified());
+    }
+
+    #[test]
+    fn state_proof_policy() {
+        let sim = simulated_pool();
+        let (pool, builder) = pool_with_nym(&sim);
+        // the faulty node does not sign state proofs, leaving three signers
+        sim.set_fault("Node1", Some(NodeFault::Lying)).unwrap();
+
+        let read = |mode: StateProofMode, min_signers: Option<usize>| {
+            let mut req = get_nym_request(&builder);
+            req.state_proof_policy = StateProofPolicy {
+                min_signers,
+                ..StateProofPolicy::new(mode)
+            };
+            block_on(perform_ledger_request(&pool, &req)).unwrap()
+        };
+
+        let (result, meta) = read(StateProofMode::Required, None);
+        assert!(matches!(result, RequestResult::Reply(_)));
+        assert_eq!(meta.verification, Some(ReplyVerification::StateProof));
+
+        let (result, meta) = read(StateProofMode::Preferred, Some(4));
+        assert!(matches!(result, RequestResult::Reply(reply) if reply.contains(VERKEY)));
+        assert_eq!(
+            meta.verification,
+            Some(ReplyVerification::ConsensusFallback)
+        );
+
+        let (result, meta) = read(StateProofMode::Required, Some(4));
+        assert!(matches!(result, RequestResult::Failed(_)));
+        assert_eq!(meta.verification, None);
+
+        let (result, meta) = read(StateProofMode::Disabled, None);
+        assert!(matches!(result, RequestResult::Reply(_)));
+        assert!(meta.state_proof.is_empty());
+        assert_eq!(meta.verification, Some(ReplyVerification::Consensus));
+    }
+}
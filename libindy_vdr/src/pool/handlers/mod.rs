@@ -10,7 +10,7 @@ use crate::utils::{base58, ValidationError};
 use super::requests::{PoolRequest, RequestEvent};
 use super::types::{
     self, CatchupReq, LedgerStatus, LedgerType, Message, NodeReplies, ProtocolVersion,
-    RequestResult, RequestResultMeta, SingleReply,
+    ReplyVerification, RequestResult, RequestResultMeta, SingleReply,
 };
 
 mod catchup;
@@ -19,9 +19,10 @@ mod full;
 mod status;
 
 pub use catchup::handle_catchup_request;
+pub(crate) use consensus::get_last_signed_time;
 pub use consensus::handle_consensus_request;
 pub use full::handle_full_request;
-pub use status::{handle_status_request, CatchupTarget};
+pub use status::{handle_ledger_status_request, handle_status_request, CatchupTarget, LedgerHead};
 
 #[derive(Debug)]
 struct ReplyState<T> {
@@ -155,7 +156,7 @@ impl PartialEq for HashableValue {
 
 impl Eq for HashableValue {}
 
-fn min_consensus(cnt: usize) -> usize {
+pub(crate) fn min_consensus(cnt: usize) -> usize {
     if cnt < 4 {
         return 0;
     }
@@ -191,10 +192,26 @@ pub(crate) fn build_pool_status_request(
     merkle_tree_size: usize,
     protocol_version: ProtocolVersion,
 ) -> VdrResult<Message> {
+    build_ledger_status_request(
+        LedgerType::POOL.to_id(),
+        merkle_root,
+        merkle_tree_size,
+        protocol_version,
+    )
+}
+
+pub(crate) fn build_ledger_status_request(
+    ledger_id: i32,
+    merkle_root: String,
+    merkle_tree_size: usize,
+    protocol_version: ProtocolVersion,
+) -> VdrResult<Message> {
+    let ledger_id = u8::try_from(ledger_id)
+        .map_err(|_| input_err(format!("Invalid ledger ID: {}", ledger_id)))?;
     let lr = LedgerStatus {
         txnSeqNo: merkle_tree_size,
         merkleRoot: merkle_root,
-        ledgerId: LedgerType::POOL as u8,
+        ledgerId: ledger_id,
         ppSeqNo: None,
         viewNo: None,
         protocolVersion: Some(protocol_version as usize),
@@ -15,6 +15,10 @@ use super::{
 
 pub type CatchupTarget = (Vec<u8>, usize, Vec<String>);
 
+/// The merkle root and size of a ledger agreed by the nodes, with the consistency
+/// proof from the requested ledger status when the ledger has grown
+pub type LedgerHead = (String, usize, Vec<String>);
+
 pub async fn handle_status_request<R: PoolRequest>(
     request: &mut R,
     merkle_tree: &MerkleTree,
@@ -92,6 +96,73 @@ pub async fn handle_status_request<R: PoolRequest>(
     }
 }
 
+pub async fn handle_ledger_status_request<R: PoolRequest>(
+    request: &mut R,
+) -> VdrResult<(RequestResult<LedgerHead>, RequestResultMeta)> {
+    trace!("ledger status request");
+    let config = request.pool_config();
+    let total_node_count = request.node_count();
+    let mut replies = ReplyState::new();
+    let mut consensus = ConsensusState::new();
+    let f = min_consensus(total_node_count);
+    request.send_to_all(config.reply_timeout)?;
+    loop {
+        match request.next().await {
+            Some(RequestEvent::Received(node_alias, raw_msg, parsed)) => {
+                match parsed {
+                    Message::LedgerStatus(ls) => {
+                        trace!("Received ledger status from {}", &node_alias);
+                        replies.add_reply(node_alias.clone(), true);
+                        let key = (ls.merkleRoot, ls.txnSeqNo, vec![]);
+                        consensus.insert(key, node_alias.clone());
+                    }
+                    Message::ConsistencyProof(cp) => {
+                        trace!("Received consistency proof from {}", &node_alias);
+                        replies.add_reply(node_alias.clone(), true);
+                        let key = (cp.newMerkleRoot, cp.seqNoEnd, cp.hashes);
+                        consensus.insert(key, node_alias.clone());
+                    }
+                    Message::ReqACK(_) => continue,
+                    Message::ReqNACK(_) | Message::Reject(_) => {
+                        debug!("Ledger status request failed for {}", &node_alias);
+                        replies.add_failed(node_alias.clone(), raw_msg);
+                    }
+                    _ => {
+                        debug!("Unexpected reply from {}", &node_alias);
+                        replies.add_failed(node_alias.clone(), raw_msg);
+                    }
+                };
+                request.clean_timeout(node_alias)?;
+            }
+            Some(RequestEvent::Timeout(node_alias)) => {
+                replies.add_timeout(node_alias);
+            }
+            None => {
+                return Ok((
+                    RequestResult::Failed(err_msg(
+                        VdrErrorKind::PoolTimeout,
+                        "Request was interrupted",
+                    )),
+                    request.get_meta(),
+                ))
+            }
+        };
+        let max_consensus = match consensus.max_entry() {
+            Some((head, votes)) if votes.len() > f => {
+                return Ok((RequestResult::Reply(head.clone()), request.get_meta()));
+            }
+            Some((_, votes)) => votes.len(),
+            None => 0,
+        };
+        if max_consensus + total_node_count - replies.len() <= f {
+            return Ok((
+                RequestResult::Failed(replies.get_error()),
+                request.get_meta(),
+            ));
+        }
+    }
+}
+
 enum CatchupProgress {
     ShouldBeStarted(CatchupTarget),
     NoConsensus,
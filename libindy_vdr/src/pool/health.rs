@@ -0,0 +1,289 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Weight given to the latest observation in the rolling averages
+const SMOOTHING: f32 = 0.2;
+/// Consecutive timeouts or invalid state proofs before a node is quarantined
+const QUARANTINE_FAILURES: u32 = 3;
+/// Reply latency (in seconds) at which the score of a node is halved
+const LATENCY_SCALE: f32 = 1.0;
+/// Lowest score of an active node, so that slow nodes are still occasionally selected
+const MIN_SCORE: f32 = 0.01;
+/// Number of recent reply latencies kept across all nodes
+const LATENCY_SAMPLES: usize = 200;
+
+#[derive(Debug, Default)]
+struct NodeStats {
+    latency: Option<f32>,
+    error_rate: f32,
+    replies: u64,
+    timeouts: u64,
+    invalid_proofs: u64,
+    consecutive_timeouts: u32,
+    consecutive_invalid: u32,
+    quarantined_until: Option<Instant>,
+}
+
+impl NodeStats {
+    fn observe_error(&mut self, error: bool) {
+        let sample = if error { 1.0 } else { 0.0 };
+        self.error_rate += SMOOTHING * (sample - self.error_rate);
+    }
+
+    fn is_quarantined(&self, now: Instant) -> bool {
+        self.quarantined_until.map(|t| t > now).unwrap_or(false)
+    }
+
+    fn score(&self, now: Instant) -> f32 {
+        if self.is_quarantined(now) {
+            return 0.0;
+        }
+        let latency = self.latency.unwrap_or(0.0);
+        ((1.0 - self.error_rate) / (1.0 + latency / LATENCY_SCALE)).max(MIN_SCORE)
+    }
+}
+
+/// A snapshot of the health of a single validator node
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct NodeHealthStatus {
+    /// The rolling average reply latency in seconds
+    pub latency: Option<f32>,
+    /// The rolling fraction of requests which timed out or failed state proof verification
+    pub error_rate: f32,
+    pub replies: u64,
+    pub timeouts: u64,
+    pub invalid_proofs: u64,
+    /// The selection score between 0 and 1
+    pub score: f32,
+    pub quarantined: bool,
+}
+
+/// The persisted statistics of a single validator node
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeHealthRecord {
+    pub latency: Option<f32>,
+    pub error_rate: f32,
+    pub replies: u64,
+    pub timeouts: u64,
+    pub invalid_proofs: u64,
+}
+
+/// Rolling latency and error scores for the nodes of a validator pool
+#[derive(Debug)]
+pub struct NodeHealth {
+    quarantine: Duration,
+    nodes: Mutex<HashMap<String, NodeStats>>,
+    latencies: Mutex<VecDeque<f32>>,
+}
+
+impl NodeHealth {
+    /// Create a new instance, quarantining failing nodes for the given duration
+    pub fn new(quarantine: Duration) -> Self {
+        Self {
+            quarantine,
+            nodes: Mutex::new(HashMap::new()),
+            latencies: Mutex::new(VecDeque::with_capacity(LATENCY_SAMPLES)),
+        }
+    }
+
+    fn update<R>(&self, node_alias: &str, f: impl FnOnce(&mut NodeStats) -> R) -> Option<R> {
+        // health tracking is advisory, so a poisoned lock is ignored
+        let mut nodes = self.nodes.lock().ok()?;
+        Some(f(nodes.entry(node_alias.to_string()).or_default()))
+    }
+
+    fn check_quarantine(&self, stats: &mut NodeStats) {
+        if self.quarantine.is_zero()
+            || (stats.consecutive_timeouts < QUARANTINE_FAILURES
+                && stats.consecutive_invalid < QUARANTINE_FAILURES)
+        {
+            return;
+        }
+        stats.consecutive_timeouts = 0;
+        stats.consecutive_invalid = 0;
+        stats.quarantined_until = Some(Instant::now() + self.quarantine);
+    }
+
+    /// Record a reply from a node, received `latency` seconds after the request was sent
+    pub fn record_reply(&self, node_alias: &str, latency: f32) {
+        self.update(node_alias, |stats| {
+            stats.latency = Some(match stats.latency {
+                Some(avg) => avg + SMOOTHING * (latency - avg),
+                None => latency,
+            });
+            stats.replies += 1;
+            stats.consecutive_timeouts = 0;
+            stats.observe_error(false);
+        });
+        if let Ok(mut latencies) = self.latencies.lock() {
+            if latencies.len() == LATENCY_SAMPLES {
+                latencies.pop_front();
+            }
+            latencies.push_back(latency);
+        }
+    }
+
+    /// Record a request to a node which timed out
+    pub fn record_timeout(&self, node_alias: &str) {
+        self.update(node_alias, |stats| {
+            stats.timeouts += 1;
+            stats.consecutive_timeouts += 1;
+            stats.observe_error(true);
+            self.check_quarantine(stats);
+        });
+    }
+
+    /// Record the outcome of verifying a state proof returned by a node
+    pub fn record_state_proof(&self, node_alias: &str, valid: bool) {
+        self.update(node_alias, |stats| {
+            if valid {
+                stats.consecutive_invalid = 0;
+            } else {
+                stats.invalid_proofs += 1;
+                stats.consecutive_invalid += 1;
+                stats.observe_error(true);
+                self.check_quarantine(stats);
+            }
+        });
+    }
+
+    /// Get the selection score of a node, from 0 when quarantined up to 1
+    pub fn score(&self, node_alias: &str) -> f32 {
+        self.nodes
+            .lock()
+            .ok()
+            .and_then(|nodes| nodes.get(node_alias).map(|s| s.score(Instant::now())))
+            .unwrap_or(1.0)
+    }
+
+    /// Get the reply latency in seconds at a percentile (between 0 and 1) of recent replies
+    pub fn latency_percentile(&self, percentile: f32) -> Option<f32> {
+        let mut sorted = self
+            .latencies
+            .lock()
+            .ok()?
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        if sorted.is_empty() {
+            return None;
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let rank = (percentile.clamp(0.0, 1.0) * sorted.len() as f32).ceil() as usize;
+        Some(sorted[rank.clamp(1, sorted.len()) - 1])
+    }
+
+    /// Export the rolling statistics of all observed nodes
+    pub fn records(&self) -> HashMap<String, NodeHealthRecord> {
+        let nodes = match self.nodes.lock() {
+            Ok(nodes) => nodes,
+            Err(_) => return HashMap::new(),
+        };
+        nodes
+            .iter()
+            .map(|(alias, stats)| {
+                let record = NodeHealthRecord {
+                    latency: stats.latency,
+                    error_rate: stats.error_rate,
+                    replies: stats.replies,
+                    timeouts: stats.timeouts,
+                    invalid_proofs: stats.invalid_proofs,
+                };
+                (alias.clone(), record)
+            })
+            .collect()
+    }
+
+    /// Restore previously exported statistics. Quarantines are not restored.
+    pub fn restore(&self, records: &HashMap<String, NodeHealthRecord>) {
+        for (alias, record) in records {
+            self.update(alias, |stats| {
+                stats.latency = record.latency;
+                stats.error_rate = record.error_rate;
+                stats.replies = record.replies;
+                stats.timeouts = record.timeouts;
+                stats.invalid_proofs = record.invalid_proofs;
+            });
+        }
+    }
+
+    /// Get a snapshot of the health of a set of nodes
+    pub fn status<'a>(
+        &self,
+        node_aliases: impl IntoIterator<Item = &'a String>,
+    ) -> HashMap<String, NodeHealthStatus> {
+        let now = Instant::now();
+        let empty = NodeStats::default();
+        let nodes = self.nodes.lock().ok();
+        node_aliases
+            .into_iter()
+            .map(|alias| {
+                let stats = nodes
+                    .as_ref()
+                    .and_then(|nodes| nodes.get(alias))
+                    .unwrap_or(&empty);
+                let status = NodeHealthStatus {
+                    latency: stats.latency,
+                    error_rate: stats.error_rate,
+                    replies: stats.replies,
+                    timeouts: stats.timeouts,
+                    invalid_proofs: stats.invalid_proofs,
+                    score: stats.score(now),
+                    quarantined: stats.is_quarantined(now),
+                };
+                (alias.clone(), status)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_scores() {
+        let health = NodeHealth::new(Duration::from_secs(60));
+        health.record_reply("fast", 0.1);
+        health.record_reply("slow", 4.0);
+        assert!(health.score("fast") > health.score("slow"));
+        assert_eq!(health.latency_percentile(0.5), Some(0.1));
+        assert_eq!(health.latency_percentile(0.95), Some(4.0));
+        assert_eq!(health.score("unknown"), 1.0);
+
+        for _ in 0..QUARANTINE_FAILURES {
+            health.record_timeout("slow");
+        }
+        for _ in 0..QUARANTINE_FAILURES - 1 {
+            health.record_state_proof("fast", false);
+        }
+        let status = health.status(&["fast".to_string(), "slow".to_string()]);
+        assert!(!status["fast"].quarantined);
+        assert_eq!(status["fast"].invalid_proofs, 2);
+        assert!(status["slow"].quarantined);
+        assert_eq!(status["slow"].score, 0.0);
+    }
+
+    #[test]
+    fn test_restore_records() {
+        let health = NodeHealth::new(Duration::from_secs(60));
+        health.record_reply("node", 0.5);
+        health.record_timeout("node");
+        let records = health.records();
+
+        let restored = NodeHealth::new(Duration::from_secs(60));
+        restored.restore(&records);
+        assert_eq!(restored.records(), records);
+        assert_eq!(restored.score("node"), health.score("node"));
+    }
+
+    #[test]
+    fn test_quarantine_disabled() {
+        let health = NodeHealth::new(Duration::ZERO);
+        for _ in 0..QUARANTINE_FAILURES {
+            health.record_state_proof("node", false);
+        }
+        assert!(health.score("node") >= MIN_SCORE);
+    }
+}
@@ -1,19 +1,23 @@
+use std::collections::HashMap;
 use std::iter::FromIterator;
 use std::string::ToString;
 
 use serde_json;
 
+use super::events::{PoolEvent, StateProofOutcome};
 use super::genesis::PoolTransactions;
 use super::handlers::{
-    build_pool_catchup_request, build_pool_status_request, handle_catchup_request,
-    handle_consensus_request, handle_full_request, handle_status_request, CatchupTarget,
+    build_ledger_status_request, build_pool_catchup_request, build_pool_status_request,
+    handle_catchup_request, handle_consensus_request, handle_full_request,
+    handle_ledger_status_request, handle_status_request, CatchupTarget, LedgerHead,
 };
 use super::manager::Pool;
 use super::requests::{PoolRequest, PreparedRequest, RequestMethod};
-use super::types::{NodeReplies, RequestResult, RequestResultMeta};
+use super::types::{NodeReplies, ReplyVerification, RequestResult, RequestResultMeta};
 
 use crate::common::error::prelude::*;
 use crate::pool::LedgerType;
+use crate::state_proof::LedgerReceipt;
 use crate::utils::base58;
 
 /// Perform a pool ledger status request to see if catchup is required
@@ -22,6 +26,17 @@ pub async fn perform_pool_status_request<T: Pool>(
 ) -> VdrResult<(RequestResult<Option<CatchupTarget>>, RequestResultMeta)> {
     let (mt_root, mt_size) = pool.get_merkle_tree_info();
 
+    if pool.take_warm_start() {
+        debug!("Pool was restored from a recent snapshot, skipping status check");
+        let meta = RequestResultMeta {
+            state_proof: HashMap::new(),
+            timing: None,
+            receipt: None,
+            verification: None,
+        };
+        return Ok((RequestResult::Reply(None), meta));
+    }
+
     if pool.get_refreshed() {
         trace!("Performing fast status check");
         match perform_get_txn(pool, LedgerType::POOL.to_id(), 1).await {
@@ -66,6 +81,25 @@ pub async fn perform_pool_status_request<T: Pool>(
     handle_status_request(&mut request, pool.get_merkle_tree()).await
 }
 
+/// Perform a ledger status request, returning the current ledger root and size agreed by
+/// the nodes along with the consistency proof from the provided ledger root and size
+pub async fn perform_ledger_status_request<T: Pool>(
+    pool: &T,
+    ledger_type: i32,
+    merkle_root: String,
+    merkle_tree_size: usize,
+) -> VdrResult<(RequestResult<LedgerHead>, RequestResultMeta)> {
+    let message = build_ledger_status_request(
+        ledger_type,
+        merkle_root,
+        merkle_tree_size,
+        pool.get_config().protocol_version,
+    )?;
+    let req_json = message.serialize()?.to_string();
+    let mut request = pool.create_request("".to_string(), req_json).await?;
+    handle_ledger_status_request(&mut request).await
+}
+
 /// Perform a pool ledger catchup request to fetch the latest verifier pool transactions
 pub async fn perform_pool_catchup_request<T: Pool>(
     pool: &T,
@@ -92,9 +126,10 @@ pub async fn perform_pool_catchup_request<T: Pool>(
 pub async fn perform_refresh<T: Pool>(
     pool: &T,
 ) -> VdrResult<(Option<PoolTransactions>, RequestResultMeta)> {
+    let (previous_root, previous_size) = pool.get_merkle_tree_info();
     let (result, meta) = perform_pool_status_request(pool).await?;
     trace!("Got status result: {:?}", &result);
-    match result {
+    let refreshed = match result {
         RequestResult::Reply(target) => match target {
             Some((target_mt_root, target_mt_size, nodes)) => {
                 debug!(
@@ -116,7 +151,18 @@ pub async fn perform_refresh<T: Pool>(
             warn!("Catchup target not found {:?}", meta);
             Err(err)
         }
-    }
+    }?;
+    let (mt_root, mt_size) = match &refreshed.0 {
+        Some(txns) => (txns.root_hash_base58()?, txns.len()),
+        None => (previous_root.clone(), previous_size),
+    };
+    pool.get_events().emit(PoolEvent::RefreshCompleted {
+        previous_root,
+        previous_size,
+        mt_root,
+        mt_size,
+    });
+    Ok(refreshed)
 }
 
 pub(crate) async fn perform_catchup<T: Pool>(
@@ -189,6 +235,45 @@ pub async fn perform_ledger_request<T: Pool>(
     pool: &T,
     prepared: &PreparedRequest,
 ) -> VdrResult<(RequestResult<String>, RequestResultMeta)> {
+    let result = _perform_ledger_request(pool, prepared).await;
+    let events = pool.get_events();
+    if !events.is_empty() {
+        let (success, error, meta) = match &result {
+            Ok((RequestResult::Reply(_), meta)) => (true, None, Some(meta)),
+            Ok((RequestResult::Failed(err), meta)) => (false, Some(err.to_string()), Some(meta)),
+            Err(err) => (false, Some(err.to_string()), None),
+        };
+        let state_proof = meta
+            .map(|meta| {
+                meta.state_proof
+                    .iter()
+                    .map(|(node, result)| (node.clone(), StateProofOutcome::from(result)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        events.emit(PoolEvent::RequestCompleted {
+            req_id: Some(prepared.req_id.clone()),
+            txn_type: prepared.txn_type.clone(),
+            success,
+            error,
+            timing: meta.and_then(|meta| meta.timing.clone()),
+            state_proof,
+        });
+    }
+    result
+}
+
+async fn _perform_ledger_request<T: Pool>(
+    pool: &T,
+    prepared: &PreparedRequest,
+) -> VdrResult<(RequestResult<String>, RequestResultMeta)> {
+    if prepared.state_proof_policy.is_required()
+        && matches!(prepared.method, RequestMethod::Full { .. })
+    {
+        return Err(input_err(
+            "State proof verification is required but not supported for this request",
+        ));
+    }
     let mut request = pool
         .create_request(prepared.req_id.clone(), prepared.req_json.to_string())
         .await?;
@@ -205,16 +290,48 @@ pub async fn perform_ledger_request<T: Pool>(
         RequestMethod::BuiltinStateProof {
             sp_key,
             sp_timestamps,
+            ..
         } => (Some(sp_key.clone()), *sp_timestamps, true, None),
         RequestMethod::CustomStateProof {
             sp_parser,
             sp_timestamps,
+            ..
         } => (None, *sp_timestamps, true, Some(sp_parser)),
         RequestMethod::ReadConsensus => (None, (None, None), true, None),
         RequestMethod::Consensus => (None, (None, None), false, None),
     };
 
-    handle_consensus_request(&mut request, sp_key, sp_timestamps, is_read_req, sp_parser).await
+    let hedge_policy = if prepared.method.supports_hedging() {
+        prepared
+            .method
+            .hedge_policy()
+            .or_else(|| pool.get_config().read_hedging)
+    } else {
+        None
+    };
+
+    let (result, mut meta) = handle_consensus_request(
+        &mut request,
+        sp_key,
+        sp_timestamps,
+        is_read_req,
+        sp_parser,
+        hedge_policy,
+        prepared.state_proof_policy,
+    )
+    .await?;
+
+    if prepared.receipt && meta.verification == Some(ReplyVerification::StateProof) {
+        if let RequestResult::Reply(reply) = &result {
+            let (mt_root, mt_size) = pool.get_merkle_tree_info();
+            match LedgerReceipt::new(prepared, reply, mt_root, mt_size) {
+                Ok(receipt) => meta.receipt = Some(receipt),
+                Err(err) => debug!("Error creating ledger receipt: {}", err),
+            }
+        }
+    }
+
+    Ok((result, meta))
 }
 
 /// Format a collection of node replies in the expected response format
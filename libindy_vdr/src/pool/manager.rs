@@ -9,12 +9,15 @@ use futures_channel::mpsc::unbounded;
 use futures_util::future::{lazy, FutureExt, LocalBoxFuture};
 use rand::seq::SliceRandom;
 
+use super::events::PoolEvents;
 use super::genesis::{build_node_transaction_map, build_verifiers, PoolTransactions};
+use super::health::{NodeHealth, NodeHealthStatus};
 use super::networker::{
     LocalNetworker, Networker, NetworkerEvent, NetworkerFactory, SharedNetworker,
 };
 use super::requests::{PoolRequest, PoolRequestImpl};
-use super::types::{PoolSetup, RequestHandle, Verifiers};
+use super::snapshot::{PoolSnapshot, SnapshotStore, VerifiedLedgerStatus};
+use super::types::{LedgerType, PoolSetup, RequestHandle, Verifiers};
 
 use crate::common::error::prelude::*;
 use crate::common::handle::ResourceHandle;
@@ -64,6 +67,25 @@ pub trait Pool: Clone {
 
     /// Get the summarized verifier details.
     fn get_verifier_info(&self) -> VdrResult<Verifiers>;
+
+    /// Get the rolling health scores of the verifier nodes.
+    fn get_node_health(&self) -> HashMap<String, NodeHealthStatus>;
+
+    /// Get the last verified status of a ledger.
+    fn get_ledger_status(&self, ledger_type: LedgerType) -> Option<VerifiedLedgerStatus>;
+
+    /// Capture the persistent state of the pool.
+    fn get_snapshot(&self) -> PoolSnapshot;
+
+    /// Restore the state of the pool from a snapshot, returning `false` if it does not apply.
+    fn restore_snapshot(&self, snapshot: &PoolSnapshot) -> bool;
+
+    /// Determine whether the initial status check may be skipped after a warm start.
+    /// Only the first call may return `true`.
+    fn take_warm_start(&self) -> bool;
+
+    /// Get the listeners for events reported by the pool.
+    fn get_events(&self) -> &PoolEvents;
 }
 
 /// The default `Pool` implementation
@@ -103,10 +125,23 @@ where
         let verifiers = build_verifiers(txn_map)?;
         let networker = networker_factory.make_networker(config.clone(), &verifiers)?;
         let setup = PoolSetup::new(config, merkle_tree, node_weights, verifiers, refreshed);
+        networker.set_events(setup.events.clone());
         Ok(Self::new(S::from(Box::new(setup)), networker))
     }
 }
 
+impl<S, T> PoolImpl<S, T>
+where
+    S: AsRef<PoolSetup> + Clone,
+    T: Networker + Clone,
+{
+    /// Persist snapshots of the pool state to a store while in use and when dropped,
+    /// returning `false` if a store was already set.
+    pub fn persist_snapshots(&self, store: Arc<SnapshotStore>) -> bool {
+        self.setup.as_ref().persist_snapshots(store)
+    }
+}
+
 impl<S, T> Pool for PoolImpl<S, T>
 where
     S: AsRef<PoolSetup> + Clone,
@@ -125,7 +160,11 @@ where
             let (tx, rx) = unbounded();
             let handle = RequestHandle::next();
             let setup_ref = setup.as_ref();
-            let node_order = choose_nodes(&setup_ref.verifiers, setup_ref.node_weights.as_ref());
+            let node_order = choose_nodes(
+                &setup_ref.verifiers,
+                setup_ref.node_weights.as_ref(),
+                Some(&setup_ref.health),
+            );
             debug!(
                 "New {}: reqId({}), node order: {:?}",
                 handle, req_id, node_order
@@ -157,12 +196,41 @@ where
     fn get_verifier_info(&self) -> VdrResult<Verifiers> {
         Ok(self.setup.as_ref().verifiers.clone())
     }
+
+    fn get_node_health(&self) -> HashMap<String, NodeHealthStatus> {
+        let setup = self.setup.as_ref();
+        setup.health.status(setup.verifiers.keys())
+    }
+
+    fn get_ledger_status(&self, ledger_type: LedgerType) -> Option<VerifiedLedgerStatus> {
+        self.setup.as_ref().ledgers.get(ledger_type.to_id())
+    }
+
+    fn get_snapshot(&self) -> PoolSnapshot {
+        self.setup.as_ref().snapshot()
+    }
+
+    fn restore_snapshot(&self, snapshot: &PoolSnapshot) -> bool {
+        self.setup.as_ref().restore(snapshot)
+    }
+
+    fn take_warm_start(&self) -> bool {
+        self.setup.as_ref().take_warm_start()
+    }
+
+    fn get_events(&self) -> &PoolEvents {
+        &self.setup.as_ref().events
+    }
 }
 
+/// Order the verifier nodes randomly, favoring nodes with higher weights and health scores.
+/// Quarantined nodes are placed last.
 pub(crate) fn choose_nodes(
     verifiers: &Verifiers,
     weights: Option<&HashMap<String, f32>>,
+    health: Option<&NodeHealth>,
 ) -> Vec<String> {
+    let mut quarantined = vec![];
     let mut weighted = verifiers
         .keys()
         .filter_map(|name| {
@@ -172,9 +240,14 @@ pub(crate) fn choose_nodes(
                 .copied()
                 .unwrap_or(1.0);
             if weight <= 0.0 {
+                return None;
+            }
+            let score = health.map(|h| h.score(name)).unwrap_or(1.0);
+            if score <= 0.0 {
+                quarantined.push(name.to_string());
                 None
             } else {
-                Some((weight, name.as_str()))
+                Some((weight * score, name.as_str()))
             }
         })
         .collect::<Vec<(f32, &str)>>();
@@ -187,6 +260,8 @@ pub(crate) fn choose_nodes(
         found.0 = 0.0;
         result.push(found.1.to_string());
     }
+    quarantined.shuffle(&mut rng);
+    result.extend(quarantined);
     result
 }
 
@@ -215,7 +290,28 @@ mod tests {
         let mut weights = HashMap::new();
         weights.insert("a".into(), 0.0);
         weights.insert("b".into(), 0.000001);
-        let found = choose_nodes(&verifiers, Some(&weights));
+        let found = choose_nodes(&verifiers, Some(&weights), None);
         assert_eq!(found, ["c", "b"]);
     }
+
+    #[test]
+    fn test_choose_nodes_quarantined() {
+        let test_verif = VerifierInfo {
+            client_addr: "127.0.0.1".into(),
+            node_addr: "127.0.0.1".into(),
+            public_key: "pk".into(),
+            enc_key: "ek".into(),
+            bls_key: None,
+        };
+        let mut verifiers = Verifiers::new();
+        verifiers.insert("a".into(), test_verif.clone());
+        verifiers.insert("b".into(), test_verif);
+
+        let health = NodeHealth::new(std::time::Duration::from_secs(60));
+        for _ in 0..3 {
+            health.record_timeout("a");
+        }
+        let found = choose_nodes(&verifiers, None, Some(&health));
+        assert_eq!(found, ["b", "a"]);
+    }
 }
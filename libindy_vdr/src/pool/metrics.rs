@@ -0,0 +1,330 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+use super::events::{PoolEvent, PoolEventListener};
+
+/// The content type of the metrics produced by `PoolMetrics::encode`
+pub const OPENMETRICS_CONTENT_TYPE: &str =
+    "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// The upper bounds of the node latency histogram buckets, in seconds
+const LATENCY_BUCKETS: [f64; 10] = [0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Collects metrics for one or more verifier pools from their events.
+///
+/// Each pool is identified by a label, so a single instance may be shared by
+/// several pools and exported together in the OpenMetrics text format.
+#[derive(Debug, Default)]
+pub struct PoolMetrics {
+    pools: Mutex<BTreeMap<String, MetricsState>>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    node_latency: BTreeMap<String, Histogram>,
+    requests: BTreeMap<(String, &'static str), u64>,
+    state_proofs: BTreeMap<&'static str, u64>,
+    consensus_failures: u64,
+    connections: usize,
+    connection_rollovers: u64,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if let Some(idx) = LATENCY_BUCKETS.iter().position(|bound| value <= *bound) {
+            self.buckets[idx] += 1;
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+impl PoolMetrics {
+    /// Create a new, empty metrics collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a listener recording the events of a pool under the given label.
+    ///
+    /// The listener should be registered with `PoolEvents::subscribe`.
+    pub fn listener(self: &Arc<Self>, pool: &str) -> PoolEventListener {
+        let metrics = self.clone();
+        let pool = pool.to_string();
+        Arc::new(move |event| metrics.record(&pool, event))
+    }
+
+    /// Update the metrics for a pool event
+    pub fn record(&self, pool: &str, event: &PoolEvent) {
+        let mut pools = match self.pools.lock() {
+            Ok(pools) => pools,
+            Err(_) => return,
+        };
+        let state = pools.entry(pool.to_string()).or_default();
+        match event {
+            PoolEvent::RequestCompleted {
+                txn_type,
+                success,
+                timing,
+                state_proof,
+                ..
+            } => {
+                let outcome = if *success { "success" } else { "failed" };
+                *state
+                    .requests
+                    .entry((txn_type.clone(), outcome))
+                    .or_default() += 1;
+                for (node, latency) in timing.iter().flatten() {
+                    // nodes which did not reply are reported with a negative latency
+                    if *latency >= 0.0 {
+                        state
+                            .node_latency
+                            .entry(node.clone())
+                            .or_default()
+                            .observe(*latency as f64);
+                    }
+                }
+                for outcome in state_proof.values() {
+                    *state.state_proofs.entry(outcome.as_str()).or_default() += 1;
+                }
+            }
+            PoolEvent::ConsensusFailed { .. } => {
+                state.consensus_failures += 1;
+            }
+            PoolEvent::ConnectionOpened {
+                connections,
+                rollover,
+            } => {
+                state.connections = *connections;
+                if *rollover {
+                    state.connection_rollovers += 1;
+                }
+            }
+            PoolEvent::ConnectionClosed { connections } => {
+                state.connections = *connections;
+            }
+            _ => (),
+        }
+    }
+
+    /// Remove the metrics recorded for a pool
+    pub fn remove(&self, pool: &str) {
+        if let Ok(mut pools) = self.pools.lock() {
+            pools.remove(pool);
+        }
+    }
+
+    /// Export the metrics in the OpenMetrics text format
+    pub fn encode(&self) -> String {
+        let pools = match self.pools.lock() {
+            Ok(pools) => pools,
+            Err(_) => return "# EOF\n".to_string(),
+        };
+        let mut out = String::new();
+
+        family(
+            &mut out,
+            "indy_vdr_node_latency_seconds",
+            "histogram",
+            "Latency of node replies",
+        );
+        for (pool, state) in pools.iter() {
+            for (node, hist) in state.node_latency.iter() {
+                let labels = format_labels(&[("pool", pool.as_str()), ("node", node.as_str())]);
+                let mut cumulative = 0;
+                for (bound, count) in LATENCY_BUCKETS.iter().zip(hist.buckets.iter()) {
+                    cumulative += count;
+                    let le = labels_with(&labels, "le", &format!("{:?}", bound));
+                    sample(
+                        &mut out,
+                        "indy_vdr_node_latency_seconds_bucket",
+                        &le,
+                        cumulative,
+                    );
+                }
+                let le = labels_with(&labels, "le", "+Inf");
+                sample(
+                    &mut out,
+                    "indy_vdr_node_latency_seconds_bucket",
+                    &le,
+                    hist.count,
+                );
+                sample(
+                    &mut out,
+                    "indy_vdr_node_latency_seconds_count",
+                    &labels,
+                    hist.count,
+                );
+                sample(
+                    &mut out,
+                    "indy_vdr_node_latency_seconds_sum",
+                    &labels,
+                    hist.sum,
+                );
+            }
+        }
+
+        family(
+            &mut out,
+            "indy_vdr_requests",
+            "counter",
+            "Ledger requests by transaction type and outcome",
+        );
+        for (pool, state) in pools.iter() {
+            for ((txn_type, outcome), count) in state.requests.iter() {
+                let labels = format_labels(&[
+                    ("pool", pool.as_str()),
+                    ("txn_type", txn_type.as_str()),
+                    ("outcome", *outcome),
+                ]);
+                sample(&mut out, "indy_vdr_requests_total", &labels, count);
+            }
+        }
+
+        family(
+            &mut out,
+            "indy_vdr_state_proofs",
+            "counter",
+            "State proof verifications by outcome",
+        );
+        for (pool, state) in pools.iter() {
+            for (outcome, count) in state.state_proofs.iter() {
+                let labels = format_labels(&[("pool", pool.as_str()), ("outcome", *outcome)]);
+                sample(&mut out, "indy_vdr_state_proofs_total", &labels, count);
+            }
+        }
+
+        family(
+            &mut out,
+            "indy_vdr_consensus_failures",
+            "counter",
+            "Requests for which the nodes did not reach consensus",
+        );
+        for (pool, state) in pools.iter() {
+            let labels = format_labels(&[("pool", pool.as_str())]);
+            sample(
+                &mut out,
+                "indy_vdr_consensus_failures_total",
+                &labels,
+                state.consensus_failures,
+            );
+        }
+
+        family(
+            &mut out,
+            "indy_vdr_connections",
+            "gauge",
+            "Open sets of node connections",
+        );
+        for (pool, state) in pools.iter() {
+            let labels = format_labels(&[("pool", pool.as_str())]);
+            sample(&mut out, "indy_vdr_connections", &labels, state.connections);
+        }
+
+        family(
+            &mut out,
+            "indy_vdr_connection_rollovers",
+            "counter",
+            "New node connections opened after reaching the connection request limit",
+        );
+        for (pool, state) in pools.iter() {
+            let labels = format_labels(&[("pool", pool.as_str())]);
+            sample(
+                &mut out,
+                "indy_vdr_connection_rollovers_total",
+                &labels,
+                state.connection_rollovers,
+            );
+        }
+
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# TYPE {} {}", name, kind).ok();
+    writeln!(out, "# HELP {} {}", name, help).ok();
+}
+
+fn sample<V: std::fmt::Display>(out: &mut String, name: &str, labels: &str, value: V) {
+    writeln!(out, "{}{{{}}} {}", name, labels, value).ok();
+}
+
+fn format_labels(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn labels_with(labels: &str, name: &str, value: &str) -> String {
+    format!("{},{}=\"{}\"", labels, name, escape_label(value))
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool::StateProofOutcome;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_encode_metrics() {
+        let metrics = Arc::new(PoolMetrics::new());
+        let listener = metrics.listener("sovrin");
+        listener(&PoolEvent::RequestCompleted {
+            req_id: None,
+            txn_type: "105".to_string(),
+            success: true,
+            error: None,
+            timing: Some(HashMap::from([
+                ("Node1".to_string(), 0.2),
+                ("Node2".to_string(), -1.0),
+            ])),
+            state_proof: HashMap::from([("Node1".to_string(), StateProofOutcome::Verified)]),
+        });
+        listener(&PoolEvent::ConsensusFailed {
+            node: None,
+            reply: None,
+        });
+        listener(&PoolEvent::ConnectionOpened {
+            connections: 2,
+            rollover: true,
+        });
+
+        let text = metrics.encode();
+        assert!(text.contains(
+            "indy_vdr_node_latency_seconds_bucket{pool=\"sovrin\",node=\"Node1\",le=\"0.1\"} 0\n"
+        ));
+        assert!(text.contains(
+            "indy_vdr_node_latency_seconds_bucket{pool=\"sovrin\",node=\"Node1\",le=\"0.25\"} 1\n"
+        ));
+        assert!(!text.contains("node=\"Node2\""));
+        assert!(text.contains(
+            "indy_vdr_requests_total{pool=\"sovrin\",txn_type=\"105\",outcome=\"success\"} 1\n"
+        ));
+        assert!(
+            text.contains("indy_vdr_state_proofs_total{pool=\"sovrin\",outcome=\"verified\"} 1\n")
+        );
+        assert!(text.contains("indy_vdr_consensus_failures_total{pool=\"sovrin\"} 1\n"));
+        assert!(text.contains("indy_vdr_connections{pool=\"sovrin\"} 2\n"));
+        assert!(text.contains("indy_vdr_connection_rollovers_total{pool=\"sovrin\"} 1\n"));
+        assert!(text.ends_with("# EOF\n"));
+    }
+}
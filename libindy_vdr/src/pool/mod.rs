@@ -1,30 +1,58 @@
 mod builder;
+/// Event listeners for monitoring verifier pools
+mod events;
 mod genesis;
+/// Rolling health scores for verifier nodes
+mod health;
 /// Transaction request handlers
 pub(crate) mod handlers;
 /// Methods for performing requests against the verifier pool
 pub mod helpers;
 /// General verifier pool management
 mod manager;
+/// Metrics collection for verifier pools
+mod metrics;
+/// Consistency monitoring of the ledger transaction logs
+mod monitor;
+/// Background refresh of the verifier pool transactions
+mod refresh;
+/// Named verifier pools for multiple ledgers
+mod registry;
 /// Pool networker traits and implementations
 pub mod networker;
 /// Data types and traits for handling pending verifier pool requests
 mod requests;
 /// A pool executor that processes events in its own thread
 mod runner;
+/// Persistent pool state for warm restarts
+mod snapshot;
 mod types;
 
 pub use {
     self::builder::PoolBuilder,
+    self::events::{
+        EventSubscription, PoolEvent, PoolEventListener, PoolEvents, StateProofOutcome,
+    },
     self::genesis::{FilesystemCache, InMemoryCache, PoolTransactions, PoolTransactionsCache},
+    self::health::{NodeHealth, NodeHealthRecord, NodeHealthStatus},
     self::manager::{LocalPool, Pool, PoolImpl, SharedPool},
+    self::metrics::{PoolMetrics, OPENMETRICS_CONTENT_TYPE},
+    self::monitor::{LedgerAlert, LedgerAlertKind, LedgerCheckpoint, LedgerMonitor},
+    self::refresh::{PoolRefreshEvent, RefreshListener, RefreshTrigger, SharedPoolRefresher},
+    self::registry::{PoolRegistry, RegisteredPool},
     self::requests::{
         new_request_id, PoolRequest, PoolRequestImpl, PreparedRequest, RequestMethod,
     },
     self::runner::{PoolRunner, PoolRunnerStatus},
+    self::snapshot::{
+        LedgerStatuses, PoolSnapshot, SnapshotStore, VerifiedLedgerStatus, POOL_SNAPSHOT_VERSION,
+        SNAPSHOT_SAVE_INTERVAL,
+    },
     self::types::{
-        LedgerType, NodeReplies, PoolSetup, ProtocolVersion, RequestHandle, RequestResult,
-        RequestResultMeta, SingleReply, StateProofAssertions, StateProofResult, TimingResult,
-        VerifierInfo, VerifierKey, VerifierKeys, Verifiers,
+        LedgerType, NodeReplies, PoolSetup, ProtocolVersion, ReplyVerification, RequestHandle,
+        RequestResult, RequestResultMeta, SingleReply, StateProofAssertions, StateProofResult,
+        TimingResult, VerifierInfo, VerifierKey, VerifierKeys, Verifiers,
     },
 };
+
+pub(crate) use self::genesis::{build_node_transaction_map, build_verifiers};
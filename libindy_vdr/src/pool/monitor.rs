@@ -0,0 +1,835 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde_json::Value as SJsonValue;
+use sha2::{Digest, Sha256};
+
+use crate::common::error::prelude::*;
+use crate::config::{StateProofMode, StateProofPolicy};
+use crate::utils::base58;
+
+use super::handlers::LedgerHead;
+use super::helpers::{perform_ledger_request, perform_ledger_status_request};
+use super::manager::Pool;
+use super::types::{LedgerType, RequestResult};
+
+/// The number of attempts to fetch matching ledger roots while the ledger is growing
+const HEAD_ATTEMPTS: usize = 3;
+
+/// The last consistent observation of a ledger
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerCheckpoint {
+    /// The base58-encoded merkle root of the ledger transactions
+    pub root_hash: String,
+    /// The number of ledger transactions
+    pub size: usize,
+    /// The sequence number of the audit transaction consulted for the observation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_seq_no: Option<usize>,
+}
+
+/// The kind of inconsistency found by a `LedgerMonitor`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerAlertKind {
+    /// The ledger contains fewer transactions than previously observed
+    Rollback,
+    /// The ledger history does not extend the previously observed history
+    Fork,
+}
+
+/// An inconsistency between observations of a ledger
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerAlert {
+    pub ledger_id: i32,
+    pub kind: LedgerAlertKind,
+    /// The last consistent observation of the ledger, if any
+    pub previous: Option<LedgerCheckpoint>,
+    /// The conflicting observation of the ledger
+    pub current: LedgerCheckpoint,
+    /// A description of the inconsistency
+    pub reason: String,
+}
+
+/// Monitors the growth of the ledgers, checking that each observation of a ledger
+/// extends the last consistent one.
+///
+/// Each poll verifies consistency proofs from the previous ledger roots and from the roots
+/// recorded by the latest audit transaction, which is fetched with a state proof.
+/// The checkpoints may be persisted and restored using `with_checkpoints`.
+#[derive(Clone, Debug)]
+pub struct LedgerMonitor {
+    ledgers: Vec<LedgerType>,
+    checkpoints: HashMap<i32, LedgerCheckpoint>,
+}
+
+impl LedgerMonitor {
+    /// Create a new monitor for the domain and config ledgers
+    pub fn new() -> Self {
+        Self::with_checkpoints(HashMap::new())
+    }
+
+    /// Create a new monitor resuming from previous checkpoints, indexed by ledger ID
+    pub fn with_checkpoints(checkpoints: HashMap<i32, LedgerCheckpoint>) -> Self {
+        Self {
+            ledgers: vec![LedgerType::DOMAIN, LedgerType::CONFIG],
+            checkpoints,
+        }
+    }
+
+    /// Set the monitored ledgers. The audit ledger is always monitored.
+    pub fn ledgers(mut self, ledgers: Vec<LedgerType>) -> Self {
+        self.ledgers = ledgers;
+        self
+    }
+
+    /// Get the last consistent observation of a ledger
+    pub fn checkpoint(&self, ledger_type: LedgerType) -> Option<&LedgerCheckpoint> {
+        self.checkpoints.get(&ledger_type.to_id())
+    }
+
+    /// Get the last consistent observation of each ledger, indexed by ledger ID
+    pub fn checkpoints(&self) -> &HashMap<i32, LedgerCheckpoint> {
+        &self.checkpoints
+    }
+
+    /// Poll the monitored ledgers, returning any inconsistencies found.
+    ///
+    /// The checkpoint for a ledger is not advanced when an alert is raised for it.
+    pub async fn poll<T: Pool>(&mut self, pool: &T) -> VdrResult<Vec<LedgerAlert>> {
+        let mut alerts = vec![];
+        let audit_id = LedgerType::AUDIT.to_id();
+        let audit_head = self.fetch_head(pool, audit_id).await?;
+        let audit = if audit_head.1 > 0 {
+            Some(fetch_audit_txn(pool, audit_head.1).await?)
+        } else {
+            None
+        };
+        let audit_seq_no = audit.as_ref().map(|audit| audit.seq_no);
+        let anchor = audit
+            .as_ref()
+            .map(|audit| (audit.root_hash.clone(), audit.ledger_size));
+        // the audit transaction was fetched at the current audit ledger size
+        self.check(
+            audit_id,
+            audit_head,
+            anchor,
+            None,
+            audit_seq_no,
+            &mut alerts,
+        );
+
+        for ledger_type in self.ledgers.clone() {
+            let ledger_id = ledger_type.to_id();
+            if ledger_id == audit_id {
+                continue;
+            }
+            let anchor = match audit.as_ref() {
+                Some(audit) => audit_ledger_root(pool, audit, ledger_id).await?,
+                None => None,
+            };
+            let (head, anchor_head) = self.fetch_heads(pool, ledger_id, anchor.as_ref()).await?;
+            self.check(
+                ledger_id,
+                head,
+                anchor,
+                anchor_head,
+                audit_seq_no,
+                &mut alerts,
+            );
+        }
+        Ok(alerts)
+    }
+
+    /// Fetch the current ledger root and the consistency proof from the last checkpoint
+    async fn fetch_head<T: Pool>(&self, pool: &T, ledger_id: i32) -> VdrResult<LedgerHead> {
+        let (root_hash, size) = match self.checkpoints.get(&ledger_id) {
+            Some(checkpoint) => (checkpoint.root_hash.clone(), checkpoint.size),
+            None => (base58::encode(Sha256::digest(b"")), 0),
+        };
+        fetch_ledger_head(pool, ledger_id, root_hash, size).await
+    }
+
+    /// Fetch the current ledger root, along with the consistency proof from the root
+    /// recorded by the audit ledger when it is older than the current root
+    async fn fetch_heads<T: Pool>(
+        &self,
+        pool: &T,
+        ledger_id: i32,
+        anchor: Option<&(String, usize)>,
+    ) -> VdrResult<(LedgerHead, Option<LedgerHead>)> {
+        let mut head = self.fetch_head(pool, ledger_id).await?;
+        let (anchor_root, anchor_size) = match anchor {
+            Some((root_hash, size)) if *size > 0 && *size < head.1 => (root_hash, *size),
+            _ => return Ok((head, None)),
+        };
+        for _ in 0..HEAD_ATTEMPTS {
+            let anchor_head =
+                fetch_ledger_head(pool, ledger_id, anchor_root.clone(), anchor_size).await?;
+            if anchor_head.1 <= head.1 {
+                return Ok((head, Some(anchor_head)));
+            }
+            // the ledger grew between the requests
+            head = self.fetch_head(pool, ledger_id).await?;
+        }
+        Err(err_msg(
+            VdrErrorKind::Unavailable,
+            format!(
+                "Ledger {} changed while fetching the ledger root",
+                ledger_id
+            ),
+        ))
+    }
+
+    fn check(
+        &mut self,
+        ledger_id: i32,
+        head: LedgerHead,
+        anchor: Option<(String, usize)>,
+        anchor_head: Option<LedgerHead>,
+        audit_seq_no: Option<usize>,
+        alerts: &mut Vec<LedgerAlert>,
+    ) {
+        let (root_hash, size, proof) = head;
+        let current = LedgerCheckpoint {
+            root_hash,
+            size,
+            audit_seq_no,
+        };
+        let previous = self.checkpoints.get(&ledger_id);
+        match check_consistency(
+            previous,
+            &current,
+            &proof,
+            anchor.as_ref(),
+            anchor_head.as_ref(),
+        ) {
+            Ok(()) => {
+                self.checkpoints.insert(ledger_id, current);
+            }
+            Err((kind, reason)) => {
+                warn!("Ledger {} inconsistency detected: {}", ledger_id, reason);
+                alerts.push(LedgerAlert {
+                    ledger_id,
+                    kind,
+                    previous: previous.cloned(),
+                    current,
+                    reason,
+                });
+            }
+        }
+    }
+}
+
+impl Default for LedgerMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An audit ledger transaction verified against the multi-signature of the pool
+struct AuditTxn {
+    seq_no: usize,
+    /// The signed root of the audit ledger
+    root_hash: String,
+    /// The signed size of the audit ledger
+    ledger_size: usize,
+    data: SJsonValue,
+}
+
+async fn fetch_audit_txn<T: Pool>(pool: &T, seq_no: usize) -> VdrResult<AuditTxn> {
+    let seq_no_req = i32::try_from(seq_no).with_input_err("Invalid audit sequence number")?;
+    let mut request = pool.get_request_builder().build_get_txn_request(
+        None,
+        LedgerType::AUDIT.to_id(),
+        seq_no_req,
+    )?;
+    request.state_proof_policy = StateProofPolicy::new(StateProofMode::Required);
+    let reply = match perform_ledger_request(pool, &request).await?.0 {
+        RequestResult::Reply(reply) => reply,
+        RequestResult::Failed(err) => return Err(err),
+    };
+    let reply: SJsonValue =
+        serde_json::from_str(&reply).with_input_err("Invalid audit transaction reply")?;
+    let data = &reply["result"]["data"];
+    if data.is_null() {
+        return Err(input_err(format!(
+            "Audit transaction not found: {}",
+            seq_no
+        )));
+    }
+    let (root_hash, ledger_size) = match (data["rootHash"].as_str(), data["ledgerSize"].as_u64()) {
+        (Some(root_hash), Some(ledger_size)) => (root_hash.to_string(), ledger_size as usize),
+        _ => {
+            return Err(input_err(
+                "Audit transaction reply is missing the ledger root",
+            ))
+        }
+    };
+    Ok(AuditTxn {
+        seq_no,
+        root_hash,
+        ledger_size,
+        data: data["txn"]["data"].clone(),
+    })
+}
+
+async fn fetch_ledger_head<T: Pool>(
+    pool: &T,
+    ledger_id: i32,
+    root_hash: String,
+    size: usize,
+) -> VdrResult<LedgerHead> {
+    match perform_ledger_status_request(pool, ledger_id, root_hash, size)
+        .await?
+        .0
+    {
+        RequestResult::Reply(head) => Ok(head),
+        RequestResult::Failed(err) => Err(err),
+    }
+}
+
+/// Look up the root and size of a ledger recorded by an audit transaction
+async fn audit_ledger_root<T: Pool>(
+    pool: &T,
+    audit: &AuditTxn,
+    ledger_id: i32,
+) -> VdrResult<Option<(String, usize)>> {
+    let key = ledger_id.to_string();
+    let size = match audit.data["ledgerSize"][key.as_str()].as_u64() {
+        Some(size) => size as usize,
+        None => return Ok(None),
+    };
+    let root_hash = match &audit.data["ledgerRoot"][key.as_str()] {
+        SJsonValue::String(root_hash) => root_hash.clone(),
+        // an unchanged ledger root refers back to an earlier audit transaction
+        SJsonValue::Number(delta) => {
+            let seq_no = delta
+                .as_u64()
+                .and_then(|delta| audit.seq_no.checked_sub(delta as usize))
+                .filter(|seq_no| *seq_no > 0)
+                .ok_or_else(|| input_err("Invalid audit ledger root reference"))?;
+            let earlier = fetch_audit_txn(pool, seq_no).await?;
+            earlier.data["ledgerRoot"][key.as_str()]
+                .as_str()
+                .ok_or_else(|| input_err("Invalid audit ledger root reference"))?
+                .to_string()
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some((root_hash, size)))
+}
+
+fn check_consistency(
+    previous: Option<&LedgerCheckpoint>,
+    current: &LedgerCheckpoint,
+    proof: &[String],
+    anchor: Option<&(String, usize)>,
+    anchor_head: Option<&LedgerHead>,
+) -> Result<(), (LedgerAlertKind, String)> {
+    if let Some((anchor_root, anchor_size)) = anchor {
+        if previous.map(|prev| *anchor_size < prev.size) == Some(true) {
+            return Err((
+                LedgerAlertKind::Rollback,
+                "The audit ledger records fewer transactions than previously observed".into(),
+            ));
+        }
+        match anchor_size.cmp(&current.size) {
+            Ordering::Greater => {
+                return Err((
+                    LedgerAlertKind::Rollback,
+                    "The ledger contains fewer transactions than recorded by the audit ledger"
+                        .into(),
+                ))
+            }
+            Ordering::Equal if *anchor_root != current.root_hash => {
+                return Err((
+                    LedgerAlertKind::Fork,
+                    "The ledger root differs from the root recorded by the audit ledger".into(),
+                ))
+            }
+            Ordering::Less if *anchor_size > 0 => {
+                let verified = match anchor_head {
+                    Some((root_hash, size, anchor_proof)) => {
+                        *root_hash == current.root_hash
+                            && *size == current.size
+                            && verify_encoded_consistency_proof(
+                                *anchor_size,
+                                anchor_root,
+                                current,
+                                anchor_proof,
+                            )
+                    }
+                    None => false,
+                };
+                if !verified {
+                    return Err((
+                        LedgerAlertKind::Fork,
+                        "Consistency proof verification failed for the audit ledger root".into(),
+                    ));
+                }
+            }
+            _ => (),
+        }
+    }
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return Ok(()),
+    };
+    match current.size.cmp(&previous.size) {
+        Ordering::Less => Err((
+            LedgerAlertKind::Rollback,
+            "The ledger contains fewer transactions than previously observed".into(),
+        )),
+        Ordering::Equal if current.root_hash != previous.root_hash => Err((
+            LedgerAlertKind::Fork,
+            "The ledger root differs from the previously observed root".into(),
+        )),
+        Ordering::Equal => Ok(()),
+        Ordering::Greater => {
+            if verify_encoded_consistency_proof(previous.size, &previous.root_hash, current, proof)
+            {
+                Ok(())
+            } else {
+                Err((
+                    LedgerAlertKind::Fork,
+                    "Consistency proof verification failed".into(),
+                ))
+            }
+        }
+    }
+}
+
+/// Verify a base58-encoded consistency proof from an earlier ledger root
+fn verify_encoded_consistency_proof(
+    old_size: usize,
+    old_root: &str,
+    current: &LedgerCheckpoint,
+    proof: &[String],
+) -> bool {
+    match (
+        base58::decode(old_root),
+        base58::decode(&current.root_hash),
+        proof
+            .iter()
+            .map(base58::decode)
+            .collect::<Result<Vec<_>, _>>(),
+    ) {
+        (Ok(old_root), Ok(new_root), Ok(proof)) => {
+            verify_consistency_proof(old_size, &old_root, current.size, &new_root, &proof)
+        }
+        _ => false,
+    }
+}
+
+fn hash_nodes(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
+}
+
+/// Verify a merkle tree consistency proof between two tree sizes, as defined by RFC 9162
+fn verify_consistency_proof(
+    old_size: usize,
+    old_root: &[u8],
+    new_size: usize,
+    new_root: &[u8],
+    proof: &[Vec<u8>],
+) -> bool {
+    if old_size == 0 {
+        return proof.is_empty();
+    }
+    if old_size >= new_size {
+        return old_size == new_size && proof.is_empty() && old_root == new_root;
+    }
+    let mut path: Vec<&[u8]> = proof.iter().map(Vec::as_slice).collect();
+    if old_size.is_power_of_two() {
+        path.insert(0, old_root);
+    }
+    let (first, rest) = match path.split_first() {
+        Some(split) => split,
+        None => return false,
+    };
+    let mut fnode = old_size - 1;
+    let mut snode = new_size - 1;
+    while fnode & 1 == 1 {
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    let mut fr = first.to_vec();
+    let mut sr = first.to_vec();
+    for hash in rest {
+        if snode == 0 {
+            return false;
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            fr = hash_nodes(hash, &fr);
+            sr = hash_nodes(hash, &sr);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            sr = hash_nodes(&sr, hash);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    snode == 0 && fr == old_root && sr == new_root
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::SystemTime;
+
+    use futures_channel::mpsc::UnboundedSender;
+    use futures_executor::block_on;
+
+    use super::super::genesis::PoolTransactions;
+    use super::super::manager::LocalPool;
+    use super::super::networker::{MakeLocal, Networker, NetworkerEvent, NetworkerFactory};
+    use super::super::requests::RequestExtEvent;
+    use super::super::types::{ConsistencyProof, LedgerStatus, Message, RequestHandle, Verifiers};
+    use super::*;
+    use crate::config::PoolConfig;
+
+    const NODE1: &str = r#"{"reqSignature":{},"txn":{"data":{"data":{"alias":"Node1","blskey":"4N8aUNHSgjQVgkpm8nhNEfDf6txHznoYREg9kirmJrkivgL4oSEimFF6nsQ6M41QvhM2Z33nves5vfSn9n1UwNFJBYtWVnHYMATn76vLuL3zU88KyeAYcHfsih3He6UHcXDxcaecHVz6jhCYz1P2UZn2bDVruL5wXpehgBfBaLKm3Ba","blskey_pop":"RahHYiCvoNCtPTrVtP7nMC5eTYrsUA8WjXbdhNc8debh1agE9bGiJxWBXYNFbnJXoXhWFMvyqhqhRoq737YQemH5ik9oL7R4NTTCz2LEZhkgLJzB3QRQqJyBNyv7acbdHrAT8nQ9UkLbaVL9NBpnWXBTw4LEMePaSHEw66RzPNdAX1","client_ip":"127.0.0.1","client_port":9702,"node_ip":"127.0.0.1","node_port":9701,"services":["VALIDATOR"]},"dest":"Gw6pDLhcBcoQesN72qfotTgFa7cbuqZpkX3Xo6pLhPhv"},"metadata":{"from":"Th7MpTaRZVRYnPiabds81Y"},"type":"0"},"txnMetadata":{"seqNo":1,"txnId":"fea82e10e894419fe2bea7d96296a6d46f50f93f9eeda954ec461b2ed2950b62"},"ver":"1"}"#;
+
+    type Ledgers = Rc<RefCell<HashMap<u8, Vec<Vec<u8>>>>>;
+
+    fn leaves(count: usize) -> Vec<Vec<u8>> {
+        (0..count)
+            .map(|idx| Sha256::digest(idx.to_string().as_bytes()).to_vec())
+            .collect()
+    }
+
+    fn split_point(n: usize) -> usize {
+        n.next_power_of_two() / 2
+    }
+
+    fn merkle_root(hashes: &[Vec<u8>]) -> Vec<u8> {
+        match hashes.len() {
+            1 => hashes[0].clone(),
+            n => {
+                let k = split_point(n);
+                hash_nodes(&merkle_root(&hashes[..k]), &merkle_root(&hashes[k..]))
+            }
+        }
+    }
+
+    fn subproof(m: usize, hashes: &[Vec<u8>], complete: bool, proof: &mut Vec<Vec<u8>>) {
+        let n = hashes.len();
+        if m == n {
+            if !complete {
+                proof.push(merkle_root(hashes));
+            }
+            return;
+        }
+        let k = split_point(n);
+        if m <= k {
+            subproof(m, &hashes[..k], complete, proof);
+            proof.push(merkle_root(&hashes[k..]));
+        } else {
+            subproof(m - k, &hashes[k..], false, proof);
+            proof.push(merkle_root(&hashes[..k]));
+        }
+    }
+
+    #[test]
+    fn test_verify_consistency_proof() {
+        let hashes = leaves(12);
+        for new_size in 1..=hashes.len() {
+            let new_root = merkle_root(&hashes[..new_size]);
+            for old_size in 1..new_size {
+                let old_root = merkle_root(&hashes[..old_size]);
+                let mut proof = vec![];
+                subproof(old_size, &hashes[..new_size], true, &mut proof);
+                assert!(verify_consistency_proof(
+                    old_size, &old_root, new_size, &new_root, &proof
+                ));
+                assert!(!verify_consistency_proof(
+                    old_size, &new_root, new_size, &new_root, &proof
+                ));
+                if let Some(last) = proof.last_mut() {
+                    last[0] ^= 1;
+                    assert!(!verify_consistency_proof(
+                        old_size, &old_root, new_size, &new_root, &proof
+                    ));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_consistency() {
+        let hashes = leaves(3);
+        let checkpoint = |size: usize| LedgerCheckpoint {
+            root_hash: base58::encode(merkle_root(&hashes[..size])),
+            size,
+            audit_seq_no: None,
+        };
+        let mut proof = vec![];
+        subproof(2, &hashes, true, &mut proof);
+        let proof = proof.iter().map(base58::encode).collect::<Vec<_>>();
+
+        assert!(
+            check_consistency(Some(&checkpoint(2)), &checkpoint(3), &proof, None, None).is_ok()
+        );
+        assert_eq!(
+            check_consistency(Some(&checkpoint(3)), &checkpoint(2), &[], None, None)
+                .unwrap_err()
+                .0,
+            LedgerAlertKind::Rollback
+        );
+        let mut forked = checkpoint(2);
+        forked.root_hash = checkpoint(1).root_hash;
+        assert_eq!(
+            check_consistency(Some(&forked), &checkpoint(3), &proof, None, None)
+                .unwrap_err()
+                .0,
+            LedgerAlertKind::Fork
+        );
+        let anchor = (checkpoint(1).root_hash, 3);
+        assert_eq!(
+            check_consistency(None, &checkpoint(3), &[], Some(&anchor), None)
+                .unwrap_err()
+                .0,
+            LedgerAlertKind::Fork
+        );
+    }
+
+    #[test]
+    fn test_check_consistency_from_anchor() {
+        let hashes = leaves(3);
+        let checkpoint = |size: usize| LedgerCheckpoint {
+            root_hash: base58::encode(merkle_root(&hashes[..size])),
+            size,
+            audit_seq_no: None,
+        };
+        let mut proof = vec![];
+        subproof(2, &hashes, true, &mut proof);
+        let proof = proof.iter().map(base58::encode).collect::<Vec<_>>();
+        let current = checkpoint(3);
+        let anchor_head = (current.root_hash.clone(), current.size, proof);
+
+        let anchor = (checkpoint(2).root_hash, 2);
+        assert!(check_consistency(None, &current, &[], Some(&anchor), Some(&anchor_head)).is_ok());
+        assert_eq!(
+            check_consistency(None, &current, &[], Some(&anchor), None)
+                .unwrap_err()
+                .0,
+            LedgerAlertKind::Fork
+        );
+        let forked = (checkpoint(1).root_hash, 2);
+        assert_eq!(
+            check_consistency(None, &current, &[], Some(&forked), Some(&anchor_head))
+                .unwrap_err()
+                .0,
+            LedgerAlertKind::Fork
+        );
+        let ahead = (current.root_hash.clone(), 4);
+        assert_eq!(
+            check_consistency(None, &current, &[], Some(&ahead), None)
+                .unwrap_err()
+                .0,
+            LedgerAlertKind::Rollback
+        );
+    }
+
+    /// Answers ledger status requests from the shared ledger contents
+    struct LedgerNetworker {
+        ledgers: Ledgers,
+        requests: RefCell<HashMap<RequestHandle, (String, UnboundedSender<RequestExtEvent>)>>,
+    }
+
+    impl LedgerNetworker {
+        fn reply(&self, body: &str) -> String {
+            let status = match Message::from_raw_str(body).unwrap() {
+                Message::LedgerStatus(status) => status,
+                message => panic!("Unexpected request: {:?}", message),
+            };
+            let ledgers = self.ledgers.borrow();
+            let hashes = ledgers
+                .get(&status.ledgerId)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let message = if status.txnSeqNo == 0 || status.txnSeqNo >= hashes.len() {
+                let root = if hashes.is_empty() {
+                    Sha256::digest(b"").to_vec()
+                } else {
+                    merkle_root(hashes)
+                };
+                Message::LedgerStatus(LedgerStatus {
+                    txnSeqNo: hashes.len(),
+                    merkleRoot: base58::encode(root),
+                    ledgerId: status.ledgerId,
+                    ppSeqNo: None,
+                    viewNo: None,
+                    protocolVersion: None,
+                })
+            } else {
+                let mut proof = vec![];
+                subproof(status.txnSeqNo, hashes, true, &mut proof);
+                Message::ConsistencyProof(ConsistencyProof {
+                    seqNoEnd: hashes.len(),
+                    seqNoStart: status.txnSeqNo,
+                    ledgerId: status.ledgerId as usize,
+                    hashes: proof.iter().map(base58::encode).collect(),
+                    oldMerkleRoot: status.merkleRoot,
+                    newMerkleRoot: base58::encode(merkle_root(hashes)),
+                })
+            };
+            message.serialize().unwrap().to_string()
+        }
+    }
+
+    impl Networker for LedgerNetworker {
+        fn send(&self, event: NetworkerEvent) -> VdrResult<()> {
+            match event {
+                NetworkerEvent::NewRequest(handle, _, body, sender) => {
+                    sender.unbounded_send(RequestExtEvent::Init).unwrap();
+                    self.requests.borrow_mut().insert(handle, (body, sender));
+                }
+                NetworkerEvent::Dispatch(handle, nodes, _) => {
+                    let requests = self.requests.borrow();
+                    let (body, sender) = &requests[&handle];
+                    let reply = self.reply(body);
+                    for node in nodes {
+                        let parsed = Message::from_raw_str(&reply).unwrap();
+                        let now = SystemTime::now();
+                        sender
+                            .unbounded_send(RequestExtEvent::Sent(node.clone(), now))
+                            .unwrap();
+                        sender
+                            .unbounded_send(RequestExtEvent::Received(
+                                node,
+                                reply.clone(),
+                                parsed,
+                                now,
+                            ))
+                            .unwrap();
+                    }
+                }
+                NetworkerEvent::FinishRequest(handle) => {
+                    self.requests.borrow_mut().remove(&handle);
+                }
+                _ => (),
+            }
+            Ok(())
+        }
+    }
+
+    struct LedgerFactory(Ledgers);
+
+    impl NetworkerFactory for LedgerFactory {
+        type Output = LedgerNetworker;
+        fn make_networker(&self, _: PoolConfig, _: &Verifiers) -> VdrResult<LedgerNetworker> {
+            Ok(LedgerNetworker {
+                ledgers: self.0.clone(),
+                requests: RefCell::new(HashMap::new()),
+            })
+        }
+    }
+
+    #[test]
+    fn test_poll() {
+        let domain = LedgerType::DOMAIN.to_id() as u8;
+        let ledgers = Ledgers::default();
+        let merkle_tree = PoolTransactions::from_json_transactions(&[NODE1])
+            .unwrap()
+            .merkle_tree()
+            .unwrap();
+        let pool = LocalPool::build(
+            PoolConfig::default(),
+            merkle_tree,
+            MakeLocal(LedgerFactory(ledgers.clone())),
+            None,
+            true,
+        )
+        .unwrap();
+        let mut monitor = LedgerMonitor::new().ledgers(vec![LedgerType::DOMAIN]);
+
+        ledgers.borrow_mut().insert(domain, leaves(2));
+        assert!(block_on(monitor.poll(&pool)).unwrap().is_empty());
+        assert_eq!(monitor.checkpoint(LedgerType::DOMAIN).unwrap().size, 2);
+
+        ledgers.borrow_mut().insert(domain, leaves(5));
+        assert!(block_on(monitor.poll(&pool)).unwrap().is_empty());
+        let checkpoint = monitor.checkpoint(LedgerType::DOMAIN).cloned().unwrap();
+        assert_eq!(checkpoint.size, 5);
+
+        // rewrite the ledger history
+        let mut forked = leaves(6);
+        forked[0] = Sha256::digest(b"forked").to_vec();
+        ledgers.borrow_mut().insert(domain, forked);
+        let alerts = block_on(monitor.poll(&pool)).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, LedgerAlertKind::Fork);
+        assert_eq!(alerts[0].previous.as_ref(), Some(&checkpoint));
+        assert_eq!(alerts[0].current.size, 6);
+        assert_eq!(monitor.checkpoint(LedgerType::DOMAIN), Some(&checkpoint));
+
+        ledgers.borrow_mut().insert(domain, leaves(3));
+        let alerts = block_on(monitor.poll(&pool)).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, LedgerAlertKind::Rollback);
+        assert_eq!(monitor.checkpoint(LedgerType::DOMAIN), Some(&checkpoint));
+    }
+}
+
+#[cfg(all(test, feature = "simulated_pool"))]
+mod simulated_tests {
+    use futures_executor::block_on;
+
+    use super::{LedgerAlertKind, LedgerMonitor};
+    use crate::config::PoolConfig;
+    use crate::pool::networker::fixtures::{simulated_pool, write_nym};
+    use crate::pool::{LedgerType, Pool};
+
+    #[test]
+    fn ledger_monitor() {
+        let sim = simulated_pool();
+        let pool = sim.local_pool(PoolConfig::default()).unwrap();
+        let builder = pool.get_request_builder();
+        let mut monitor = LedgerMonitor::new();
+        assert_eq!(block_on(monitor.poll(&pool)).unwrap(), vec![]);
+
+        write_nym(&pool, &builder);
+        write_nym(&pool, &builder);
+        assert_eq!(block_on(monitor.poll(&pool)).unwrap(), vec![]);
+        let domain = monitor.checkpoint(LedgerType::DOMAIN).unwrap();
+        assert_eq!((domain.size, domain.audit_seq_no), (2, Some(2)));
+
+        write_nym(&pool, &builder);
+        assert_eq!(block_on(monitor.poll(&pool)).unwrap(), vec![]);
+        assert_eq!(monitor.checkpoint(LedgerType::AUDIT).unwrap().size, 3);
+
+        // a checkpoint which the ledger does not extend raises an alert
+        let mut checkpoints = monitor.checkpoints().clone();
+        let config_root = checkpoints[&LedgerType::CONFIG.to_id()].root_hash.clone();
+        let domain = checkpoints.get_mut(&LedgerType::DOMAIN.to_id()).unwrap();
+        domain.root_hash = config_root;
+        let forged = domain.clone();
+        let mut forked = LedgerMonitor::with_checkpoints(checkpoints.clone());
+        write_nym(&pool, &builder);
+        let alerts = block_on(forked.poll(&pool)).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].ledger_id, LedgerType::DOMAIN.to_id());
+        assert_eq!(alerts[0].kind, LedgerAlertKind::Fork);
+        assert_eq!(forked.checkpoint(LedgerType::DOMAIN), Some(&forged));
+
+        checkpoints
+            .get_mut(&LedgerType::DOMAIN.to_id())
+            .unwrap()
+            .size = 10;
+        let mut rolled_back = LedgerMonitor::with_checkpoints(checkpoints);
+        let alerts = block_on(rolled_back.poll(&pool)).unwrap();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].kind, LedgerAlertKind::Rollback);
+        assert_eq!(block_on(monitor.poll(&pool)).unwrap(), vec![]);
+    }
+}
@@ -6,12 +6,44 @@ use futures_channel::mpsc::UnboundedSender;
 use crate::common::error::prelude::*;
 use crate::config::types::PoolConfig;
 
+use super::events::{PoolEvent, PoolEvents};
 use super::requests::RequestExtEvent;
 use super::types::{self, RequestHandle, Verifiers};
 
+#[cfg(not(any(feature = "zmq_networker", feature = "zmtp_networker")))]
+compile_error!("Either the `zmq_networker` or `zmtp_networker` feature must be enabled");
+
+#[cfg(feature = "zmq_networker")]
 mod zmq;
+#[cfg(feature = "zmq_networker")]
 pub use self::zmq::{ZMQNetworker, ZMQNetworkerFactory};
 
+#[cfg(feature = "zmtp_networker")]
+mod zmtp;
+#[cfg(feature = "zmtp_networker")]
+pub use self::zmtp::{ZmtpNetworker, ZmtpNetworkerFactory};
+
+mod recording;
+pub use self::recording::{
+    RecordedEntry, RecordedEvent, Recording, RecordingNetworker, RecordingNetworkerFactory,
+    ReplayNetworker, ReplayNetworkerFactory,
+};
+
+#[cfg(feature = "simulated_pool")]
+mod simulated;
+#[cfg(feature = "simulated_pool")]
+pub use self::simulated::{NodeFault, SimulatedNetworker, SimulatedPool};
+#[cfg(all(test, feature = "simulated_pool"))]
+pub(crate) use self::simulated::fixtures;
+
+/// The `NetworkerFactory` used by `PoolBuilder`
+#[cfg(feature = "zmq_networker")]
+pub type DefaultNetworkerFactory = ZMQNetworkerFactory;
+
+/// The `NetworkerFactory` used by `PoolBuilder`
+#[cfg(all(feature = "zmtp_networker", not(feature = "zmq_networker")))]
+pub type DefaultNetworkerFactory = ZmtpNetworkerFactory;
+
 /// Events used to drive a `Networker` instance
 #[derive(Debug)]
 pub enum NetworkerEvent {
@@ -38,9 +70,32 @@ pub enum NetworkerEvent {
     ),
 }
 
+/// The pool event listeners for a networker, attached after it is created
+#[derive(Clone, Default)]
+pub(crate) struct EventSink(Arc<Mutex<Option<Arc<PoolEvents>>>>);
+
+impl EventSink {
+    pub fn set(&self, events: Arc<PoolEvents>) {
+        if let Ok(mut sink) = self.0.lock() {
+            sink.replace(events);
+        }
+    }
+
+    pub fn emit(&self, event: PoolEvent) {
+        let events = self.0.lock().ok().and_then(|sink| sink.clone());
+        if let Some(events) = events {
+            events.emit(event)
+        }
+    }
+}
+
 /// A simple trait implemented by all networker types
 pub trait Networker {
     fn send(&self, event: NetworkerEvent) -> VdrResult<()>;
+
+    /// Attach the listeners for node connection events.
+    /// Networkers which do not track connections may ignore this.
+    fn set_events(&self, _events: Arc<PoolEvents>) {}
 }
 
 /// A factory for `Networker` instances
@@ -49,6 +104,13 @@ pub trait NetworkerFactory {
     fn make_networker(&self, config: PoolConfig, verifiers: &Verifiers) -> VdrResult<Self::Output>;
 }
 
+impl<T: NetworkerFactory + ?Sized> NetworkerFactory for &T {
+    type Output = T::Output;
+    fn make_networker(&self, config: PoolConfig, verifiers: &Verifiers) -> VdrResult<Self::Output> {
+        (**self).make_networker(config, verifiers)
+    }
+}
+
 /// A `Networker` instance which can be cloned and used within one thread
 pub type LocalNetworker = Rc<dyn Networker + 'static>;
 
@@ -73,6 +135,10 @@ where
     fn send(&self, event: NetworkerEvent) -> VdrResult<()> {
         self.as_ref().send(event)
     }
+
+    fn set_events(&self, events: Arc<PoolEvents>) {
+        self.as_ref().set_events(events)
+    }
 }
 
 /// A `Networker` instance which can be cloned and used across multiple threads
@@ -105,6 +171,12 @@ impl Networker for Arc<Mutex<dyn Networker + Send>> {
             })?
             .send(event)
     }
+
+    fn set_events(&self, events: Arc<PoolEvents>) {
+        if let Ok(networker) = self.lock() {
+            networker.set_events(events)
+        }
+    }
 }
 
 /*
@@ -0,0 +1,725 @@
+//! Networker wrappers for recording pool traffic and replaying it deterministically
+
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures_executor::LocalPool;
+use futures_util::future;
+use futures_util::stream::StreamExt;
+use futures_util::task::LocalSpawnExt;
+
+use crate::common::error::prelude::*;
+use crate::config::PoolConfig;
+use crate::pool::events::PoolEvents;
+
+use super::types::{Message, Verifiers};
+use super::{Networker, NetworkerEvent, NetworkerFactory, RequestExtEvent, RequestHandle};
+
+/// An event sent to or produced by a networker
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RecordedEvent {
+    NewRequest { sub_id: String, body: String },
+    Dispatch { nodes: Vec<String>, timeout: i64 },
+    CleanTimeout { node: String },
+    ExtendTimeout { node: String, timeout: i64 },
+    FinishRequest,
+    Init,
+    Sent { node: String },
+    Received { node: String, message: String },
+    Timeout { node: String },
+}
+
+impl RecordedEvent {
+    /// Check whether the event was sent to the networker by a request
+    pub fn is_outgoing(&self) -> bool {
+        matches!(
+            self,
+            Self::NewRequest { .. }
+                | Self::Dispatch { .. }
+                | Self::CleanTimeout { .. }
+                | Self::ExtendTimeout { .. }
+                | Self::FinishRequest
+        )
+    }
+
+    fn from_networker_event(event: &NetworkerEvent) -> (RequestHandle, Self) {
+        match event {
+            NetworkerEvent::NewRequest(handle, sub_id, body, _) => (
+                *handle,
+                Self::NewRequest {
+                    sub_id: sub_id.clone(),
+                    body: body.clone(),
+                },
+            ),
+            NetworkerEvent::Dispatch(handle, nodes, timeout) => (
+                *handle,
+                Self::Dispatch {
+                    nodes: nodes.clone(),
+                    timeout: *timeout,
+                },
+            ),
+            NetworkerEvent::CleanTimeout(handle, node) => {
+                (*handle, Self::CleanTimeout { node: node.clone() })
+            }
+            NetworkerEvent::ExtendTimeout(handle, node, timeout) => (
+                *handle,
+                Self::ExtendTimeout {
+                    node: node.clone(),
+                    timeout: *timeout,
+                },
+            ),
+            NetworkerEvent::FinishRequest(handle) => (*handle, Self::FinishRequest),
+        }
+    }
+
+    /// Check whether a replayed outgoing event corresponds to this recorded event.
+    /// Dispatched nodes are only compared by count, as the node order is randomized.
+    fn replayed_by(&self, event: &Self) -> bool {
+        match (self, event) {
+            (
+                Self::Dispatch { nodes, timeout },
+                Self::Dispatch {
+                    nodes: replay_nodes,
+                    timeout: replay_timeout,
+                },
+            ) => nodes.len() == replay_nodes.len() && timeout == replay_timeout,
+            _ => self == event,
+        }
+    }
+
+    fn from_request_event(event: &RequestExtEvent) -> (Self, Option<SystemTime>) {
+        match event {
+            RequestExtEvent::Init => (Self::Init, None),
+            RequestExtEvent::Sent(node, time) => (Self::Sent { node: node.clone() }, Some(*time)),
+            RequestExtEvent::Received(node, message, _, time) => (
+                Self::Received {
+                    node: node.clone(),
+                    message: message.clone(),
+                },
+                Some(*time),
+            ),
+            RequestExtEvent::Timeout(node) => (Self::Timeout { node: node.clone() }, None),
+        }
+    }
+
+    fn to_request_event(&self, time: SystemTime) -> VdrResult<Option<RequestExtEvent>> {
+        Ok(match self {
+            Self::Init => Some(RequestExtEvent::Init),
+            Self::Sent { node } => Some(RequestExtEvent::Sent(node.clone(), time)),
+            Self::Received { node, message } => Some(RequestExtEvent::Received(
+                node.clone(),
+                message.clone(),
+                Message::from_raw_str(message)?,
+                time,
+            )),
+            Self::Timeout { node } => Some(RequestExtEvent::Timeout(node.clone())),
+            _ => None,
+        })
+    }
+}
+
+/// A recorded networker event with its request handle and timestamp
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedEntry {
+    /// The request handle assigned in the recording process
+    pub handle: i64,
+    /// Microseconds since the Unix epoch
+    pub time: u64,
+    #[serde(flatten)]
+    pub event: RecordedEvent,
+}
+
+impl RecordedEntry {
+    fn system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_micros(self.time)
+    }
+}
+
+/// A sequence of networker events, stored as JSON lines
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Recording {
+    pub entries: Vec<RecordedEntry>,
+}
+
+impl Recording {
+    /// Load a recording from a file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> VdrResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|err| {
+            err_msg(
+                VdrErrorKind::FileSystem,
+                format!("Error reading recording {}", path.display()),
+            )
+            .with_source(err)
+        })?;
+        contents.parse()
+    }
+
+    /// Group the recorded entries by request, in the order the requests were created
+    fn requests(&self) -> VecDeque<VecDeque<RecordedEntry>> {
+        let mut order = vec![];
+        let mut requests: HashMap<i64, VecDeque<RecordedEntry>> = HashMap::new();
+        for entry in &self.entries {
+            if let RecordedEvent::NewRequest { .. } = entry.event {
+                if requests.insert(entry.handle, VecDeque::new()).is_none() {
+                    order.push(entry.handle);
+                }
+            }
+            if let Some(events) = requests.get_mut(&entry.handle) {
+                events.push_back(entry.clone());
+            }
+        }
+        order
+            .into_iter()
+            .filter_map(|handle| requests.remove(&handle))
+            .collect()
+    }
+}
+
+impl FromStr for Recording {
+    type Err = VdrError;
+
+    fn from_str(contents: &str) -> VdrResult<Self> {
+        let entries = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).with_input_err("Invalid recording entry"))
+            .collect::<VdrResult<Vec<RecordedEntry>>>()?;
+        Ok(Self { entries })
+    }
+}
+
+/// Shared output for recorded networker events
+#[derive(Clone)]
+struct Recorder {
+    output: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl Recorder {
+    fn record(&self, handle: RequestHandle, time: Option<SystemTime>, event: RecordedEvent) {
+        let time = time
+            .unwrap_or_else(SystemTime::now)
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+        let entry = RecordedEntry {
+            handle: handle.0,
+            time,
+            event,
+        };
+        let result = match (serde_json::to_string(&entry), self.output.lock()) {
+            (Ok(line), Ok(mut output)) => writeln!(output, "{}", line).and_then(|_| output.flush()),
+            _ => Ok(()),
+        };
+        if let Err(err) = result {
+            warn!("Error writing networker recording: {}", err);
+        }
+    }
+}
+
+/// A `NetworkerFactory` wrapping another factory and recording all networker traffic
+pub struct RecordingNetworkerFactory<F> {
+    inner: F,
+    recorder: Recorder,
+}
+
+impl<F> RecordingNetworkerFactory<F> {
+    /// Record the traffic of the wrapped factory's networkers to a new file.
+    pub fn new<P: AsRef<Path>>(inner: F, path: P) -> VdrResult<Self> {
+        let path = path.as_ref();
+        let file = File::create(path).map_err(|err| {
+            err_msg(
+                VdrErrorKind::FileSystem,
+                format!("Error creating recording {}", path.display()),
+            )
+            .with_source(err)
+        })?;
+        Ok(Self::with_writer(inner, file))
+    }
+
+    /// Record the traffic of the wrapped factory's networkers to a writer.
+    pub fn with_writer<W: Write + Send + 'static>(inner: F, writer: W) -> Self {
+        Self {
+            inner,
+            recorder: Recorder {
+                output: Arc::new(Mutex::new(Box::new(writer))),
+            },
+        }
+    }
+}
+
+impl<F: NetworkerFactory> NetworkerFactory for RecordingNetworkerFactory<F> {
+    type Output = RecordingNetworker<F::Output>;
+    fn make_networker(&self, config: PoolConfig, verifiers: &Verifiers) -> VdrResult<Self::Output> {
+        Ok(RecordingNetworker {
+            inner: self.inner.make_networker(config, verifiers)?,
+            recorder: self.recorder.clone(),
+            forwarder: Forwarder::spawn(self.recorder.clone()),
+        })
+    }
+}
+
+type Forward = (
+    RequestHandle,
+    UnboundedReceiver<RequestExtEvent>,
+    UnboundedSender<RequestExtEvent>,
+);
+
+/// Forwards the events for all requests of a networker on a single thread,
+/// recording each event before it is delivered to its request
+struct Forwarder {
+    requests: Option<UnboundedSender<Forward>>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+impl Forwarder {
+    fn spawn(recorder: Recorder) -> Self {
+        let (requests, requests_recv) = unbounded::<Forward>();
+        let worker = thread::spawn(move || {
+            let mut pool = LocalPool::new();
+            let spawner = pool.spawner();
+            // pending requests are abandoned once the networker is dropped
+            pool.run_until(requests_recv.for_each(|(handle, events, sender)| {
+                let recorder = recorder.clone();
+                spawner
+                    .spawn_local(forward_events(recorder, handle, events, sender))
+                    .ok();
+                future::ready(())
+            }));
+            trace!("Recording forwarder exited");
+        });
+        Self {
+            requests: Some(requests),
+            worker: Some(worker),
+        }
+    }
+
+    fn add(&self, forward: Forward) -> VdrResult<()> {
+        self.requests
+            .as_ref()
+            .ok_or_else(|| err_msg(VdrErrorKind::Resource, "Networker closed"))?
+            .unbounded_send(forward)
+            .with_err_msg(VdrErrorKind::Resource, "Error forwarding request events")
+    }
+}
+
+impl Drop for Forwarder {
+    fn drop(&mut self) {
+        // closing the channel stops the worker
+        self.requests.take();
+        if let Some(worker) = self.worker.take() {
+            worker.join().ok();
+        }
+    }
+}
+
+async fn forward_events(
+    recorder: Recorder,
+    handle: RequestHandle,
+    mut events: UnboundedReceiver<RequestExtEvent>,
+    sender: UnboundedSender<RequestExtEvent>,
+) {
+    while let Some(event) = events.next().await {
+        let (recorded, time) = RecordedEvent::from_request_event(&event);
+        recorder.record(handle, time, recorded);
+        if sender.unbounded_send(event).is_err() {
+            break;
+        }
+    }
+}
+
+/// A `Networker` recording the events sent to and produced by another networker
+pub struct RecordingNetworker<T> {
+    inner: T,
+    recorder: Recorder,
+    forwarder: Forwarder,
+}
+
+impl<T: Networker> Networker for RecordingNetworker<T> {
+    fn send(&self, event: NetworkerEvent) -> VdrResult<()> {
+        let (handle, recorded) = RecordedEvent::from_networker_event(&event);
+        self.recorder.record(handle, None, recorded);
+        let event = match event {
+            NetworkerEvent::NewRequest(handle, sub_id, body, sender) => {
+                let (fwd_send, fwd_recv) = unbounded::<RequestExtEvent>();
+                self.forwarder.add((handle, fwd_recv, sender))?;
+                NetworkerEvent::NewRequest(handle, sub_id, body, fwd_send)
+            }
+            event => event,
+        };
+        self.inner.send(event)
+    }
+
+    fn set_events(&self, events: Arc<PoolEvents>) {
+        self.inner.set_events(events)
+    }
+}
+
+#[derive(Default)]
+struct ReplayState {
+    pending: VecDeque<VecDeque<RecordedEntry>>,
+    active: HashMap<RequestHandle, (VecDeque<RecordedEntry>, UnboundedSender<RequestExtEvent>)>,
+}
+
+impl ReplayState {
+    /// Start replaying the next recorded request
+    fn start(
+        &mut self,
+        handle: RequestHandle,
+        sender: UnboundedSender<RequestExtEvent>,
+    ) -> VdrResult<()> {
+        let mut entries = self.pending.pop_front().ok_or_else(|| {
+            err_msg(
+                VdrErrorKind::Resource,
+                "No further requests in networker recording",
+            )
+        })?;
+        // the request body is not compared, as it includes the request ID
+        if let Some(entry) = entries.pop_front() {
+            trace!("Replaying recorded request {} as {}", entry.handle, handle);
+        }
+        deliver(&mut entries, &sender)?;
+        self.active.insert(handle, (entries, sender));
+        Ok(())
+    }
+
+    /// Consume an expected outgoing event and send the recorded responses which followed it.
+    /// A request which diverges from the recording is ended.
+    fn advance(&mut self, handle: RequestHandle, event: RecordedEvent) -> VdrResult<()> {
+        let (entries, sender) = match self.active.get_mut(&handle) {
+            Some(active) => active,
+            None => {
+                debug!("Replay event for unknown request {}: {:?}", handle, event);
+                return Ok(());
+            }
+        };
+        let error = match entries.front() {
+            Some(entry) if entry.event.replayed_by(&event) => {
+                entries.pop_front();
+                return deliver(entries, sender);
+            }
+            expected => format!(
+                "Replay diverged from recording for request {}: expected {:?}, got {:?}",
+                handle,
+                expected.map(|entry| &entry.event),
+                event
+            ),
+        };
+        // dropping the sender terminates the request
+        self.active.remove(&handle);
+        Err(err_msg(VdrErrorKind::Unexpected, error))
+    }
+}
+
+/// Send the recorded responses up to the next outgoing event
+fn deliver(
+    entries: &mut VecDeque<RecordedEntry>,
+    sender: &UnboundedSender<RequestExtEvent>,
+) -> VdrResult<()> {
+    while let Some(entry) = entries.front() {
+        if entry.event.is_outgoing() {
+            break;
+        }
+        if let Some(event) = entry.event.to_request_event(entry.system_time())? {
+            sender.unbounded_send(event).ok();
+        }
+        entries.pop_front();
+    }
+    Ok(())
+}
+
+/// A `NetworkerFactory` producing networkers which replay a `Recording`
+///
+/// Requests are matched to the recorded requests in order of creation, and each
+/// recorded node response is delivered once the request has sent the event which
+/// preceded it in the recording. A request sending any other event is ended with an error.
+#[derive(Clone)]
+pub struct ReplayNetworkerFactory {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl ReplayNetworkerFactory {
+    pub fn new(recording: &Recording) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ReplayState {
+                pending: recording.requests(),
+                active: HashMap::new(),
+            })),
+        }
+    }
+}
+
+impl NetworkerFactory for ReplayNetworkerFactory {
+    type Output = ReplayNetworker;
+    fn make_networker(
+        &self,
+        _config: PoolConfig,
+        _verifiers: &Verifiers,
+    ) -> VdrResult<ReplayNetworker> {
+        Ok(ReplayNetworker {
+            state: self.state.clone(),
+        })
+    }
+}
+
+/// A `Networker` replaying recorded node responses
+pub struct ReplayNetworker {
+    state: Arc<Mutex<ReplayState>>,
+}
+
+impl Networker for ReplayNetworker {
+    fn send(&self, event: NetworkerEvent) -> VdrResult<()> {
+        let mut state = self.state.lock().map_err(|_| {
+            err_msg(
+                VdrErrorKind::Unexpected,
+                "Error acquiring replay state, mutex poisoned",
+            )
+        })?;
+        let (handle, recorded) = RecordedEvent::from_networker_event(&event);
+        match event {
+            NetworkerEvent::NewRequest(handle, _, _, sender) => state.start(handle, sender),
+            NetworkerEvent::FinishRequest(handle) => {
+                state.active.remove(&handle);
+                Ok(())
+            }
+            _ => state.advance(handle, recorded),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures_executor::block_on;
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Replies to every dispatch with a canned REPLY message
+    struct EchoNetworker {
+        senders: Mutex<HashMap<RequestHandle, UnboundedSender<RequestExtEvent>>>,
+    }
+
+    impl Networker for EchoNetworker {
+        fn send(&self, event: NetworkerEvent) -> VdrResult<()> {
+            let mut senders = self.senders.lock().unwrap();
+            match event {
+                NetworkerEvent::NewRequest(handle, _, _, sender) => {
+                    sender.unbounded_send(RequestExtEvent::Init).unwrap();
+                    senders.insert(handle, sender);
+                }
+                NetworkerEvent::Dispatch(handle, nodes, _) => {
+                    let sender = &senders[&handle];
+                    for node in nodes {
+                        let message = format!(r#"{{"op":"REPLY","result":{{"from":"{}"}}}}"#, node);
+                        let parsed = Message::from_raw_str(&message).unwrap();
+                        let now = SystemTime::now();
+                        sender
+                            .unbounded_send(RequestExtEvent::Sent(node.clone(), now))
+                            .unwrap();
+                        sender
+                            .unbounded_send(RequestExtEvent::Received(node, message, parsed, now))
+                            .unwrap();
+                    }
+                }
+                NetworkerEvent::FinishRequest(handle) => {
+                    senders.remove(&handle);
+                }
+                _ => (),
+            }
+            Ok(())
+        }
+    }
+
+    struct EchoFactory;
+
+    impl NetworkerFactory for EchoFactory {
+        type Output = EchoNetworker;
+        fn make_networker(&self, _: PoolConfig, _: &Verifiers) -> VdrResult<EchoNetworker> {
+            Ok(EchoNetworker {
+                senders: Mutex::new(HashMap::new()),
+            })
+        }
+    }
+
+    fn run_request<N: Networker>(networker: &N, nodes: &[&str]) -> Vec<(String, Option<String>)> {
+        let handle = RequestHandle::next();
+        let (sender, receiver) = unbounded();
+        networker
+            .send(NetworkerEvent::NewRequest(
+                handle,
+                "1".to_string(),
+                "{}".to_string(),
+                sender,
+            ))
+            .unwrap();
+        networker
+            .send(NetworkerEvent::Dispatch(
+                handle,
+                nodes.iter().map(|node| node.to_string()).collect(),
+                5,
+            ))
+            .unwrap();
+        let events = block_on(receiver.take(5).collect::<Vec<_>>());
+        networker
+            .send(NetworkerEvent::FinishRequest(handle))
+            .unwrap();
+        events
+            .into_iter()
+            .map(|event| match event {
+                RequestExtEvent::Init => ("init".to_string(), None),
+                RequestExtEvent::Sent(node, _) => ("sent".to_string(), Some(node)),
+                RequestExtEvent::Received(_, message, _, _) => {
+                    ("received".to_string(), Some(message))
+                }
+                RequestExtEvent::Timeout(node) => ("timeout".to_string(), Some(node)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let buffer = SharedBuffer::default();
+        let factory = RecordingNetworkerFactory::with_writer(EchoFactory, buffer.clone());
+        let networker = factory
+            .make_networker(PoolConfig::default(), &Verifiers::new())
+            .unwrap();
+        let live = run_request(&networker, &["Node1", "Node2"]);
+        assert_eq!(live.len(), 5);
+
+        let recording: Recording = String::from_utf8(buffer.0.lock().unwrap().clone())
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(recording.entries.len(), 8);
+
+        let replay = ReplayNetworkerFactory::new(&recording)
+            .make_networker(PoolConfig::default(), &Verifiers::new())
+            .unwrap();
+        // the node order chosen by a pool is not reproducible
+        assert_eq!(run_request(&replay, &["Node2", "Node1"]), live);
+        assert!(replay
+            .send(NetworkerEvent::NewRequest(
+                RequestHandle::next(),
+                "2".to_string(),
+                "{}".to_string(),
+                unbounded().0,
+            ))
+            .is_err());
+    }
+
+    #[test]
+    fn replay_divergence_ends_request() {
+        let buffer = SharedBuffer::default();
+        let factory = RecordingNetworkerFactory::with_writer(EchoFactory, buffer.clone());
+        let networker = factory
+            .make_networker(PoolConfig::default(), &Verifiers::new())
+            .unwrap();
+        run_request(&networker, &["Node1", "Node2"]);
+        drop(networker);
+        let recording: Recording = String::from_utf8(buffer.0.lock().unwrap().clone())
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        let replay = ReplayNetworkerFactory::new(&recording)
+            .make_networker(PoolConfig::default(), &Verifiers::new())
+            .unwrap();
+        let handle = RequestHandle::next();
+        let (sender, receiver) = unbounded();
+        replay
+            .send(NetworkerEvent::NewRequest(
+                handle,
+                "1".to_string(),
+                "{}".to_string(),
+                sender,
+            ))
+            .unwrap();
+        assert!(replay
+            .send(NetworkerEvent::Dispatch(
+                handle,
+                vec!["Node1".to_string()],
+                5
+            ))
+            .is_err());
+        let events = block_on(receiver.collect::<Vec<_>>());
+        assert!(events
+            .iter()
+            .all(|event| matches!(event, RequestExtEvent::Init)));
+    }
+}
+
+#[cfg(all(test, feature = "simulated_pool"))]
+mod simulated_tests {
+    use futures_executor::block_on;
+
+    use super::super::fixtures::{get_nym_request, read_nym, simulated_pool, write_nym};
+    use super::super::MakeLocal;
+    use super::{Recording, RecordingNetworkerFactory, ReplayNetworkerFactory};
+    use crate::config::PoolConfig;
+    use crate::pool::helpers::perform_ledger_request;
+    use crate::pool::{new_request_id, LocalPool, Pool, StateProofResult};
+    use crate::utils::environment::EnvironmentUtils;
+
+    #[test]
+    fn record_and_replay() {
+        let sim = simulated_pool();
+        let genesis = sim.genesis().unwrap();
+        std::fs::create_dir_all(EnvironmentUtils::tmp_path()).unwrap();
+        let path =
+            EnvironmentUtils::tmp_file_path(&format!("recording-{}.jsonl", new_request_id()));
+
+        let factory = RecordingNetworkerFactory::new(sim.clone(), &path).unwrap();
+        let pool = LocalPool::build(
+            PoolConfig::default(),
+            genesis.merkle_tree().unwrap(),
+            MakeLocal(factory),
+            None,
+            false,
+        )
+        .unwrap();
+        let builder = pool.get_request_builder();
+        write_nym(&pool, &builder);
+        let (live, _) = read_nym(&pool, &builder);
+        drop(pool);
+        let recording = Recording::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // the replayed pool chooses its own node order
+        let pool = LocalPool::build(
+            PoolConfig::default(),
+            genesis.merkle_tree().unwrap(),
+            MakeLocal(ReplayNetworkerFactory::new(&recording)),
+            None,
+            false,
+        )
+        .unwrap();
+        write_nym(&pool, &builder);
+        let (replayed, proofs) = read_nym(&pool, &builder);
+        assert_eq!(replayed, live);
+        assert!(proofs.iter().any(StateProofResult::is_verified));
+
+        // no further requests were recorded
+        let req = get_nym_request(&builder);
+        assert!(block_on(perform_ledger_request(&pool, &req)).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+//! Fixtures shared by the tests which run against a simulated pool
+
+use futures_executor::block_on;
+
+use crate::config::PoolConfig;
+use crate::ledger::RequestBuilder;
+use crate::pool::helpers::perform_ledger_request;
+use crate::pool::{LocalPool, Pool, PreparedRequest, RequestResult, StateProofResult};
+use crate::utils::did::DidValue;
+
+use super::SimulatedPool;
+
+pub const DID: &str = "V4SGRU86Z58d6TV7PBUe6f";
+pub const VERKEY: &str = "GJ1SzoWzavQYfNL9XkaJdrQejfztN4XqdsiV4ct3LXKL";
+
+/// Generate a simulated pool of four validator nodes
+pub fn simulated_pool() -> SimulatedPool {
+    SimulatedPool::generate(4).unwrap()
+}
+
+/// Connect to a simulated pool with the default configuration and write the test NYM
+pub fn pool_with_nym(sim: &SimulatedPool) -> (LocalPool, RequestBuilder) {
+    let pool = sim.local_pool(PoolConfig::default()).unwrap();
+    let builder = pool.get_request_builder();
+    write_nym(&pool, &builder);
+    (pool, builder)
+}
+
+/// Build a `GET_NYM` request for the test NYM
+pub fn get_nym_request(builder: &RequestBuilder) -> PreparedRequest {
+    builder
+        .build_get_nym_request(None, &DidValue::new(DID, None), None, None)
+        .unwrap()
+}
+
+/// Write the test NYM to the ledger and return the reply
+pub fn write_nym(pool: &LocalPool, builder: &RequestBuilder) -> String {
+    let did = DidValue::new(DID, None);
+    let req = builder
+        .build_nym_request(&did, &did, Some(VERKEY.to_string()), None, None, None, None)
+        .unwrap();
+    match block_on(perform_ledger_request(pool, &req)).unwrap().0 {
+        RequestResult::Reply(reply) => reply,
+        RequestResult::Failed(err) => panic!("Write failed: {}", err),
+    }
+}
+
+/// Read the test NYM from the ledger, returning the reply and the state proof results
+pub fn read_nym(pool: &LocalPool, builder: &RequestBuilder) -> (String, Vec<StateProofResult>) {
+    let req = get_nym_request(builder);
+    let (result, meta) = block_on(perform_ledger_request(pool, &req)).unwrap();
+    match result {
+        RequestResult::Reply(reply) => (reply, meta.state_proof.into_values().collect()),
+        RequestResult::Failed(err) => panic!("Read failed: {}", err),
+    }
+}
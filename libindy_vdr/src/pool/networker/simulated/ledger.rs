@@ -0,0 +1,370 @@
+use std::collections::BTreeMap;
+
+use rlp::RlpStream;
+use serde_json::Value as SJsonValue;
+use sha2::Sha256;
+use sha3::{Digest, Sha3_256};
+
+use crate::common::error::prelude::*;
+
+/// A single ledger with its transaction log and the associated state trie
+#[derive(Debug, Default)]
+pub(super) struct Ledger {
+    txns: Vec<SJsonValue>,
+    leaf_hashes: Vec<Vec<u8>>,
+    pub state: StateTrie,
+}
+
+impl Ledger {
+    /// Append a transaction, given the msgpack-encoded form used as the merkle tree leaf
+    pub fn append(&mut self, txn: SJsonValue, leaf: &[u8]) -> usize {
+        self.txns.push(txn);
+        self.leaf_hashes.push(hash_leaf(leaf));
+        self.txns.len()
+    }
+
+    /// Look up a transaction by sequence number (starting at 1)
+    pub fn get(&self, seq_no: usize) -> Option<&SJsonValue> {
+        seq_no.checked_sub(1).and_then(|idx| self.txns.get(idx))
+    }
+
+    pub fn size(&self) -> usize {
+        self.txns.len()
+    }
+
+    pub fn root_hash(&self) -> Vec<u8> {
+        merkle_root(&self.leaf_hashes)
+    }
+
+    /// The root hash of the ledger when it contained `size` transactions
+    pub fn root_hash_at(&self, size: usize) -> Vec<u8> {
+        merkle_root(&self.leaf_hashes[..size.min(self.leaf_hashes.len())])
+    }
+
+    /// The audit path for the transaction with the given sequence number
+    pub fn audit_path(&self, seq_no: usize) -> Vec<Vec<u8>> {
+        let mut path = vec![];
+        if seq_no > 0 && seq_no <= self.leaf_hashes.len() {
+            audit_path(seq_no - 1, &self.leaf_hashes, &mut path);
+        }
+        path
+    }
+
+    /// A consistency proof between the ledger of size `old_size` and the ledger of size `new_size`
+    pub fn consistency_proof(&self, old_size: usize, new_size: usize) -> Vec<Vec<u8>> {
+        let mut proof = vec![];
+        let new_size = new_size.min(self.leaf_hashes.len());
+        if old_size > 0 && old_size < new_size {
+            subproof(old_size, &self.leaf_hashes[..new_size], true, &mut proof);
+        }
+        proof
+    }
+}
+
+fn hash_leaf(leaf: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00u8]);
+    hasher.update(leaf);
+    hasher.finalize().to_vec()
+}
+
+fn hash_nodes(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
+}
+
+/// The largest power of two smaller than `n`
+fn split_point(n: usize) -> usize {
+    n.next_power_of_two() / 2
+}
+
+fn merkle_root(hashes: &[Vec<u8>]) -> Vec<u8> {
+    match hashes.len() {
+        0 => Sha256::digest(b"").to_vec(),
+        1 => hashes[0].clone(),
+        n => {
+            let k = split_point(n);
+            hash_nodes(&merkle_root(&hashes[..k]), &merkle_root(&hashes[k..]))
+        }
+    }
+}
+
+fn audit_path(idx: usize, hashes: &[Vec<u8>], path: &mut Vec<Vec<u8>>) {
+    let n = hashes.len();
+    if n < 2 {
+        return;
+    }
+    let k = split_point(n);
+    if idx < k {
+        audit_path(idx, &hashes[..k], path);
+        path.push(merkle_root(&hashes[k..]));
+    } else {
+        audit_path(idx - k, &hashes[k..], path);
+        path.push(merkle_root(&hashes[..k]));
+    }
+}
+
+fn subproof(m: usize, hashes: &[Vec<u8>], complete: bool, proof: &mut Vec<Vec<u8>>) {
+    let n = hashes.len();
+    if m == n {
+        if !complete {
+            proof.push(merkle_root(hashes));
+        }
+        return;
+    }
+    let k = split_point(n);
+    if m <= k {
+        subproof(m, &hashes[..k], complete, proof);
+        proof.push(merkle_root(&hashes[k..]));
+    } else {
+        subproof(m - k, &hashes[k..], false, proof);
+        proof.push(merkle_root(&hashes[..k]));
+    }
+}
+
+/// An in-memory Patricia Merkle trie producing proofs in the format returned by Indy nodes
+#[derive(Clone, Debug, Default)]
+pub(super) struct StateTrie {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl StateTrie {
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(Vec::as_slice)
+    }
+
+    pub fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.entries.insert(key, value);
+    }
+
+    pub fn root_hash(&self) -> Vec<u8> {
+        Sha3_256::digest(self.build().encode()).to_vec()
+    }
+
+    /// Collect the RLP-encoded trie nodes required to look up `key`
+    pub fn proof(&self, key: &[u8]) -> Vec<u8> {
+        let mut nodes = vec![];
+        self.build()
+            .collect_proof(&to_nibbles(key), true, &mut nodes);
+        let mut stream = RlpStream::new_list(nodes.len());
+        for node in nodes {
+            stream.append_raw(&node, 1);
+        }
+        stream.out().to_vec()
+    }
+
+    fn build(&self) -> TrieNode {
+        let entries = self
+            .entries
+            .iter()
+            .map(|(key, value)| (to_nibbles(key), wrap_value(value)))
+            .collect::<Vec<_>>();
+        TrieNode::build(&entries, 0)
+    }
+}
+
+fn to_nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter().flat_map(|b| [b >> 4, b & 0x0F]).collect()
+}
+
+/// Trie values are stored as a single-element RLP list
+fn wrap_value(value: &[u8]) -> Vec<u8> {
+    let mut stream = RlpStream::new_list(1);
+    stream.append(&value.to_vec());
+    stream.out().to_vec()
+}
+
+/// Hex-prefix encoding of a nibble path
+fn encode_path(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 0x20 } else { 0x00 };
+    let mut path = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        path.push(flag | 0x10 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        path.push(flag);
+        nibbles
+    };
+    path.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    path
+}
+
+enum TrieNode {
+    Blank,
+    Leaf(Vec<u8>, Vec<u8>),
+    Extension(Vec<u8>, Box<TrieNode>),
+    Branch(Vec<Option<TrieNode>>, Option<Vec<u8>>),
+}
+
+impl TrieNode {
+    fn build(entries: &[(Vec<u8>, Vec<u8>)], depth: usize) -> Self {
+        match entries {
+            [] => Self::Blank,
+            [(key, value)] => Self::Leaf(key[depth..].to_vec(), value.clone()),
+            _ => {
+                let first = &entries[0].0[depth..];
+                let common = entries[1..].iter().fold(first.len(), |len, (key, _)| {
+                    first[..len]
+                        .iter()
+                        .zip(&key[depth..])
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                if common > 0 {
+                    return Self::Extension(
+                        first[..common].to_vec(),
+                        Box::new(Self::build(entries, depth + common)),
+                    );
+                }
+                let mut children = Vec::with_capacity(16);
+                let mut value = None;
+                let mut start = 0;
+                if entries[0].0.len() == depth {
+                    value = Some(entries[0].1.clone());
+                    start = 1;
+                }
+                for nibble in 0..16u8 {
+                    let end = start
+                        + entries[start..]
+                            .iter()
+                            .take_while(|(key, _)| key[depth] == nibble)
+                            .count();
+                    children.push(if end > start {
+                        Some(Self::build(&entries[start..end], depth + 1))
+                    } else {
+                        None
+                    });
+                    start = end;
+                }
+                Self::Branch(children, value)
+            }
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new();
+        match self {
+            Self::Blank => {
+                stream.append_empty_data();
+            }
+            Self::Leaf(path, value) => {
+                stream.begin_list(2);
+                stream.append(&encode_path(path, true));
+                stream.append(value);
+            }
+            Self::Extension(path, next) => {
+                stream.begin_list(2);
+                stream.append(&encode_path(path, false));
+                next.append_ref(&mut stream);
+            }
+            Self::Branch(children, value) => {
+                stream.begin_list(17);
+                for child in children {
+                    match child {
+                        Some(child) => child.append_ref(&mut stream),
+                        None => {
+                            stream.append_empty_data();
+                        }
+                    }
+                }
+                match value {
+                    Some(value) => {
+                        stream.append(value);
+                    }
+                    None => {
+                        stream.append_empty_data();
+                    }
+                }
+            }
+        }
+        stream.out().to_vec()
+    }
+
+    /// Small nodes are embedded in their parent, others are referenced by hash
+    fn append_ref(&self, stream: &mut RlpStream) {
+        let encoded = self.encode();
+        if encoded.len() < 32 {
+            stream.append_raw(&encoded, 1);
+        } else {
+            stream.append(&Sha3_256::digest(&encoded).to_vec());
+        }
+    }
+
+    fn collect_proof(&self, path: &[u8], root: bool, nodes: &mut Vec<Vec<u8>>) {
+        let encoded = self.encode();
+        if root || encoded.len() >= 32 {
+            nodes.push(encoded);
+        }
+        match self {
+            Self::Extension(ext_path, next) if path.starts_with(ext_path) => {
+                next.collect_proof(&path[ext_path.len()..], false, nodes)
+            }
+            Self::Branch(children, _) if !path.is_empty() => {
+                if let Some(child) = &children[path[0] as usize] {
+                    child.collect_proof(&path[1..], false, nodes)
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Encode a transaction as a merkle tree leaf
+pub(super) fn encode_leaf(txn: &SJsonValue) -> VdrResult<Vec<u8>> {
+    rmp_serde::to_vec(txn).with_input_err("Error encoding transaction")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::merkle_tree::MerkleTree;
+    use crate::state_proof::node::{Node, TrieDB};
+
+    use rlp::UntrustedRlp;
+    use std::collections::HashMap;
+
+    #[test]
+    fn merkle_root_matches_merkle_tree() {
+        let leaves: Vec<Vec<u8>> = (0..7u8).map(|i| vec![i; 5]).collect();
+        let mut ledger = Ledger::default();
+        for leaf in &leaves {
+            ledger.append(SJsonValue::Null, leaf);
+        }
+        let tree = MerkleTree::from_vec(leaves.clone()).unwrap();
+        assert_eq!(ledger.root_hash(), tree.root_hash().to_vec());
+
+        let old_tree = MerkleTree::from_vec(leaves[..3].to_vec()).unwrap();
+        let proof = ledger.consistency_proof(3, 7);
+        assert!(old_tree
+            .consistency_proof(&ledger.root_hash(), 7, &proof)
+            .unwrap());
+    }
+
+    #[test]
+    fn trie_proof_verifies() {
+        let mut trie = StateTrie::default();
+        trie.insert(b"did:1:abc".to_vec(), b"first".to_vec());
+        trie.insert(b"did:1:abd".to_vec(), b"second".to_vec());
+        trie.insert(vec![0xAB; 32], b"third".to_vec());
+        let root = trie.root_hash();
+
+        for (key, expected) in [
+            (&b"did:1:abd"[..], Some("second")),
+            (&[0xAB; 32][..], Some("third")),
+            (&b"did:1:abe"[..], None),
+        ] {
+            let proof = trie.proof(key);
+            let nodes: Vec<Node> = UntrustedRlp::new(&proof).as_list().unwrap();
+            let db: TrieDB = nodes
+                .iter()
+                .map(|node| (node.get_hash(), node))
+                .collect::<HashMap<_, _>>();
+            let root_node = db.get(root.as_slice()).unwrap();
+            let value = root_node.get_str_value(&db, key).unwrap();
+            assert_eq!(value.as_deref(), expected);
+        }
+    }
+}
@@ -0,0 +1,558 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{mpsc, Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use futures_channel::mpsc::UnboundedSender;
+use indy_blssignatures::{ProofOfPossession, SignKey, VerKey as BlsVerKey};
+use serde_json::Value as SJsonValue;
+use sha2::{Digest, Sha256};
+
+use crate::common::error::prelude::*;
+use crate::config::constants::DEFAULT_GENERATOR;
+use crate::config::PoolConfig;
+use crate::pool::{LocalPool, PoolTransactions, SharedPool};
+use crate::utils::{base58, did::generate_did};
+
+use super::types::{LedgerType, Message, Verifiers};
+use super::{
+    MakeLocal, MakeShared, Networker, NetworkerEvent, NetworkerFactory, RequestExtEvent,
+    RequestHandle,
+};
+
+#[cfg(test)]
+pub(crate) mod fixtures;
+mod ledger;
+mod state;
+
+use self::ledger::encode_leaf;
+use self::state::{PoolState, SimulatedNode};
+
+/// A fault injected into a simulated validator node
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeFault {
+    /// Delay every message sent by the node
+    Slow(Duration),
+    /// Never respond to any message
+    Silent,
+    /// Respond with altered data alongside the genuine state proof
+    Lying,
+    /// Respond with altered data and a state proof over a forked state, signed only by the node
+    Byzantine,
+}
+
+/// An in-process validator pool for deterministic testing.
+///
+/// Each virtual node answers reads with BLS multi-signed state proofs over a shared
+/// ledger and state trie, and writes are ordered as they are received. Signatures
+/// on write requests are not verified.
+#[derive(Clone)]
+pub struct SimulatedPool {
+    state: Arc<Mutex<PoolState>>,
+}
+
+impl SimulatedPool {
+    /// Create a simulated pool with `count` generated validator nodes.
+    pub fn generate(count: usize) -> VdrResult<Self> {
+        let txns = (1..=count)
+            .map(|idx| node_transaction(&format!("Node{}", idx), idx))
+            .collect::<VdrResult<Vec<_>>>()?;
+        let genesis =
+            PoolTransactions::from_json_transactions(txns.iter().map(SJsonValue::to_string))?;
+        Self::from_genesis(&genesis)
+    }
+
+    /// Create a simulated pool from the node transactions of a genesis file.
+    ///
+    /// The BLS keys of the nodes are replaced with generated keys, so the updated
+    /// transactions returned by `genesis` must be used to connect to the pool.
+    pub fn from_genesis(genesis: &PoolTransactions) -> VdrResult<Self> {
+        let mut nodes: Vec<SimulatedNode> = vec![];
+        let mut pool_txns = vec![];
+        for mut txn in genesis.json_values()? {
+            let data = &mut txn["txn"]["data"]["data"];
+            if let Some(alias) = data["alias"].as_str().map(str::to_owned) {
+                if data.get("blskey").is_some() || nodes.iter().all(|node| node.alias != alias) {
+                    let (sign_key, blskey, blskey_pop) = bls_keys(&alias)?;
+                    data["blskey"] = SJsonValue::from(blskey);
+                    data["blskey_pop"] = SJsonValue::from(blskey_pop);
+                    nodes.retain(|node| node.alias != alias);
+                    nodes.push(SimulatedNode {
+                        alias,
+                        sign_key,
+                        fault: None,
+                    });
+                }
+            }
+            let leaf = encode_leaf(&txn)?;
+            pool_txns.push((txn, leaf));
+        }
+        if nodes.is_empty() {
+            return Err(input_err("No node transactions found in genesis"));
+        }
+        Ok(Self {
+            state: Arc::new(Mutex::new(PoolState::new(nodes, pool_txns))),
+        })
+    }
+
+    /// The genesis transactions for connecting to the simulated pool.
+    pub fn genesis(&self) -> VdrResult<PoolTransactions> {
+        let state = self.lock()?;
+        let pool = state.ledger(LedgerType::POOL);
+        let txns = (1..=state.genesis_size)
+            .filter_map(|seq_no| pool.get(seq_no))
+            .map(SJsonValue::to_string)
+            .collect::<Vec<_>>();
+        PoolTransactions::from_json_transactions(txns)
+    }
+
+    /// The aliases of all simulated validator nodes.
+    pub fn node_aliases(&self) -> VdrResult<Vec<String>> {
+        Ok(self
+            .lock()?
+            .nodes
+            .iter()
+            .map(|node| node.alias.clone())
+            .collect())
+    }
+
+    /// Add a validator node to the pool ledger, for exercising catchup.
+    pub fn add_node(&self, alias: &str) -> VdrResult<()> {
+        let mut state = self.lock()?;
+        if state.node(alias).is_some() {
+            return Err(input_err(format!("Duplicate node alias: {}", alias)));
+        }
+        let (sign_key, _, _) = bls_keys(alias)?;
+        let pool = state
+            .ledgers
+            .get_mut(&LedgerType::POOL.to_id())
+            .ok_or_else(|| err_msg(VdrErrorKind::Unexpected, "Missing pool ledger"))?;
+        let txn = node_transaction(alias, pool.size() + 1)?;
+        let leaf = encode_leaf(&txn)?;
+        pool.append(txn, &leaf);
+        state.nodes.push(SimulatedNode {
+            alias: alias.to_string(),
+            sign_key,
+            fault: None,
+        });
+        Ok(())
+    }
+
+    /// Inject a fault into a node, or restore its normal behaviour with `None`.
+    pub fn set_fault(&self, alias: &str, fault: Option<NodeFault>) -> VdrResult<()> {
+        let mut state = self.lock()?;
+        let node = state
+            .nodes
+            .iter_mut()
+            .find(|node| node.alias == alias)
+            .ok_or_else(|| input_err(format!("Unknown node alias: {}", alias)))?;
+        node.fault = fault;
+        Ok(())
+    }
+
+    /// Get the number of transactions on a ledger.
+    pub fn ledger_size(&self, ledger_type: LedgerType) -> VdrResult<usize> {
+        Ok(self.lock()?.ledger(ledger_type).size())
+    }
+
+    /// Look up a transaction on a ledger by sequence number.
+    pub fn get_transaction(
+        &self,
+        ledger_type: LedgerType,
+        seq_no: usize,
+    ) -> VdrResult<Option<SJsonValue>> {
+        Ok(self.lock()?.ledger(ledger_type).get(seq_no).cloned())
+    }
+
+    /// Create a `LocalPool` instance connected to the simulated pool.
+    pub fn local_pool(&self, config: PoolConfig) -> VdrResult<LocalPool> {
+        LocalPool::build(
+            config,
+            self.genesis()?.merkle_tree()?,
+            MakeLocal(self.clone()),
+            None,
+            false,
+        )
+    }
+
+    /// Create a `SharedPool` instance connected to the simulated pool.
+    pub fn shared_pool(&self, config: PoolConfig) -> VdrResult<SharedPool> {
+        SharedPool::build(
+            config,
+            self.genesis()?.merkle_tree()?,
+            MakeShared(self.clone()),
+            None,
+            false,
+        )
+    }
+
+    fn lock(&self) -> VdrResult<MutexGuard<'_, PoolState>> {
+        self.state.lock().map_err(|_| {
+            err_msg(
+                VdrErrorKind::Unexpected,
+                "Error acquiring simulated pool, mutex poisoned",
+            )
+        })
+    }
+}
+
+impl NetworkerFactory for SimulatedPool {
+    type Output = SimulatedNetworker;
+    fn make_networker(
+        &self,
+        _config: PoolConfig,
+        _verifiers: &Verifiers,
+    ) -> VdrResult<SimulatedNetworker> {
+        let (evt_send, evt_recv) = mpsc::channel::<NetworkerEvent>();
+        let mut worker = SimulatedWorker::new(self.clone(), evt_recv);
+        let worker = thread::spawn(move || {
+            worker.work();
+            trace!("Simulated networker exited");
+        });
+        Ok(SimulatedNetworker {
+            evt_send: Some(evt_send),
+            worker: Some(worker),
+        })
+    }
+}
+
+/// `Networker` implementation delivering requests to a `SimulatedPool`
+pub struct SimulatedNetworker {
+    evt_send: Option<mpsc::Sender<NetworkerEvent>>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+impl Networker for SimulatedNetworker {
+    fn send(&self, event: NetworkerEvent) -> VdrResult<()> {
+        self.evt_send
+            .as_ref()
+            .ok_or_else(|| err_msg(VdrErrorKind::Resource, "Networker closed"))?
+            .send(event)
+            .with_err_msg(VdrErrorKind::Resource, "Error sending networker event")
+    }
+}
+
+impl Drop for SimulatedNetworker {
+    fn drop(&mut self) {
+        // closing the channel stops the worker
+        self.evt_send.take();
+        if let Some(worker) = self.worker.take() {
+            debug!("Drop simulated networker thread");
+            worker.join().unwrap()
+        }
+    }
+}
+
+struct PendingRequest {
+    body: String,
+    sender: UnboundedSender<RequestExtEvent>,
+    timeouts: HashMap<String, Instant>,
+}
+
+impl PendingRequest {
+    fn send_event(&self, event: RequestExtEvent) -> bool {
+        self.sender.unbounded_send(event).is_ok()
+    }
+}
+
+struct SimulatedWorker {
+    pool: SimulatedPool,
+    evt_recv: mpsc::Receiver<NetworkerEvent>,
+    requests: HashMap<RequestHandle, PendingRequest>,
+    deliveries: BTreeMap<(Instant, u64), (RequestHandle, String, String)>,
+    delivery_count: u64,
+}
+
+impl SimulatedWorker {
+    fn new(pool: SimulatedPool, evt_recv: mpsc::Receiver<NetworkerEvent>) -> Self {
+        Self {
+            pool,
+            evt_recv,
+            requests: HashMap::new(),
+            deliveries: BTreeMap::new(),
+            delivery_count: 0,
+        }
+    }
+
+    fn work(&mut self) {
+        loop {
+            let event = match self.next_deadline() {
+                Some(deadline) => {
+                    match self
+                        .evt_recv
+                        .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    {
+                        Ok(event) => Some(event),
+                        Err(mpsc::RecvTimeoutError::Timeout) => None,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                None => match self.evt_recv.recv() {
+                    Ok(event) => Some(event),
+                    Err(_) => break,
+                },
+            };
+            if let Some(event) = event {
+                self.process_event(event);
+            }
+            self.process_due(Instant::now());
+        }
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        let delivery = self.deliveries.keys().next().map(|(when, _)| *when);
+        let timeout = self
+            .requests
+            .values()
+            .flat_map(|req| req.timeouts.values())
+            .min()
+            .copied();
+        match (delivery, timeout) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn process_event(&mut self, event: NetworkerEvent) {
+        match event {
+            NetworkerEvent::NewRequest(handle, _sub_id, body, sender) => {
+                trace!("New request {}", handle);
+                let request = PendingRequest {
+                    body,
+                    sender,
+                    timeouts: HashMap::new(),
+                };
+                if request.send_event(RequestExtEvent::Init) {
+                    self.requests.insert(handle, request);
+                } else {
+                    trace!("Sender dropped before Init {}", handle);
+                }
+            }
+            NetworkerEvent::FinishRequest(handle) => {
+                trace!("Removing, finished {}", handle);
+                self.remove_request(handle);
+            }
+            NetworkerEvent::Dispatch(handle, node_aliases, timeout) => {
+                trace!("Dispatch {} {:?}", handle, node_aliases);
+                self.dispatch_request(handle, node_aliases, timeout);
+            }
+            NetworkerEvent::CleanTimeout(handle, node_alias) => {
+                if let Some(request) = self.requests.get_mut(&handle) {
+                    request.timeouts.remove(&node_alias);
+                }
+            }
+            NetworkerEvent::ExtendTimeout(handle, node_alias, timeout) => {
+                if let Some(request) = self.requests.get_mut(&handle) {
+                    request
+                        .timeouts
+                        .insert(node_alias, Instant::now() + timeout_duration(timeout));
+                }
+            }
+        }
+    }
+
+    fn dispatch_request(&mut self, handle: RequestHandle, node_aliases: Vec<String>, timeout: i64) {
+        let body = match self.requests.get(&handle) {
+            Some(request) => request.body.clone(),
+            None => {
+                debug!("Dispatch for unknown request {}", handle);
+                return;
+            }
+        };
+        for node_alias in node_aliases {
+            let now = Instant::now();
+            let (delay, replies) = match self.pool.lock() {
+                Ok(mut state) => state.handle_message(&node_alias, &body),
+                Err(err) => {
+                    warn!("{}", err);
+                    (Duration::ZERO, vec![])
+                }
+            };
+            let request = match self.requests.get_mut(&handle) {
+                Some(request) => request,
+                None => return,
+            };
+            request
+                .timeouts
+                .insert(node_alias.clone(), now + timeout_duration(timeout));
+            if !request.send_event(RequestExtEvent::Sent(node_alias.clone(), SystemTime::now())) {
+                trace!("Removing, sender disconnected {}", handle);
+                self.remove_request(handle);
+                return;
+            }
+            for reply in replies {
+                self.delivery_count += 1;
+                self.deliveries.insert(
+                    (now + delay, self.delivery_count),
+                    (handle, node_alias.clone(), reply),
+                );
+            }
+        }
+    }
+
+    fn process_due(&mut self, now: Instant) {
+        while let Some(key) = self.deliveries.keys().next().copied() {
+            if key.0 > now {
+                break;
+            }
+            let (handle, node_alias, message) = match self.deliveries.remove(&key) {
+                Some(delivery) => delivery,
+                None => break,
+            };
+            let parsed = match Message::from_raw_str(&message) {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    warn!("Error parsing simulated reply: {}", err);
+                    continue;
+                }
+            };
+            if let Some(request) = self.requests.get(&handle) {
+                let event =
+                    RequestExtEvent::Received(node_alias, message, parsed, SystemTime::now());
+                if !request.send_event(event) {
+                    self.remove_request(handle);
+                }
+            }
+        }
+
+        let mut expired = vec![];
+        for (handle, request) in self.requests.iter_mut() {
+            request.timeouts.retain(|node_alias, deadline| {
+                if *deadline <= now {
+                    expired.push((*handle, node_alias.clone()));
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        for (handle, node_alias) in expired {
+            if let Some(request) = self.requests.get(&handle) {
+                if !request.send_event(RequestExtEvent::Timeout(node_alias)) {
+                    self.remove_request(handle);
+                }
+            }
+        }
+    }
+
+    fn remove_request(&mut self, handle: RequestHandle) {
+        self.requests.remove(&handle);
+        self.deliveries.retain(|_, (req, _, _)| *req != handle);
+    }
+}
+
+fn timeout_duration(timeout: i64) -> Duration {
+    Duration::from_secs(timeout.max(0) as u64)
+}
+
+/// Derive deterministic BLS keys for a node alias
+fn bls_keys(alias: &str) -> VdrResult<(SignKey, String, String)> {
+    let seed = Sha256::digest(format!("simulated-bls:{}", alias).as_bytes());
+    let sign_key = SignKey::new(Some(seed.as_slice()))
+        .map_err(|_| err_msg(VdrErrorKind::Unexpected, "Error creating BLS signing key"))?;
+    let ver_key = BlsVerKey::new(&DEFAULT_GENERATOR, &sign_key)
+        .map_err(|_| err_msg(VdrErrorKind::Unexpected, "Error creating BLS verkey"))?;
+    let pop = ProofOfPossession::new(&ver_key, &sign_key).map_err(|_| {
+        err_msg(
+            VdrErrorKind::Unexpected,
+            "Error creating BLS proof of possession",
+        )
+    })?;
+    Ok((
+        sign_key,
+        base58::encode(ver_key.as_bytes()),
+        base58::encode(pop.as_bytes()),
+    ))
+}
+
+/// Construct a NODE transaction for a generated validator
+fn node_transaction(alias: &str, seq_no: usize) -> VdrResult<SJsonValue> {
+    let seed = Sha256::digest(format!("simulated-node:{}", alias).as_bytes());
+    let (did, _, verkey) = generate_did(Some(seed.as_slice()), None)?;
+    let (_, blskey, blskey_pop) = bls_keys(alias)?;
+    let port = 9700 + 2 * seq_no;
+    Ok(json!({
+        "reqSignature": {},
+        "txn": {
+            "data": {
+                "data": {
+                    "alias": alias,
+                    "blskey": blskey,
+                    "blskey_pop": blskey_pop,
+                    "client_ip": "127.0.0.1",
+                    "client_port": port,
+                    "node_ip": "127.0.0.1",
+                    "node_port": port - 1,
+                    "services": ["VALIDATOR"],
+                },
+                "dest": base58::encode(verkey.key_bytes()),
+            },
+            "metadata": {"from": &*did},
+            "type": "0",
+        },
+        "txnMetadata": {
+            "seqNo": seq_no,
+            "txnId": hex::encode(Sha256::digest(alias.as_bytes())),
+        },
+        "ver": "1",
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{pool_with_nym, read_nym, simulated_pool, VERKEY};
+    use super::*;
+    use crate::pool::helpers::perform_refresh;
+    use crate::pool::StateProofResult;
+
+    use futures_executor::block_on;
+
+    #[test]
+    fn read_with_state_proof() {
+        let sim = simulated_pool();
+        let (pool, builder) = pool_with_nym(&sim);
+        assert_eq!(sim.ledger_size(LedgerType::DOMAIN).unwrap(), 1);
+
+        let (reply, proofs) = read_nym(&pool, &builder);
+        assert!(reply.contains(VERKEY));
+        assert!(proofs.iter().any(StateProofResult::is_verified));
+    }
+
+    #[test]
+    fn faulty_nodes_are_rejected() {
+        let sim = simulated_pool();
+        let (pool, builder) = pool_with_nym(&sim);
+
+        sim.set_fault("Node1", Some(NodeFault::Lying)).unwrap();
+        sim.set_fault("Node2", Some(NodeFault::Byzantine)).unwrap();
+        let (reply, proofs) = read_nym(&pool, &builder);
+        assert!(reply.contains(VERKEY));
+        assert!(proofs.iter().any(StateProofResult::is_verified));
+        assert!(proofs
+            .iter()
+            .any(|result| matches!(result, StateProofResult::Invalid(..))));
+    }
+
+    #[test]
+    fn catchup_new_node() {
+        let sim = simulated_pool();
+        let genesis = sim.genesis().unwrap();
+        sim.add_node("Node5").unwrap();
+        sim.set_fault("Node4", Some(NodeFault::Silent)).unwrap();
+
+        let pool = LocalPool::build(
+            PoolConfig {
+                ack_timeout: 1,
+                ..PoolConfig::default()
+            },
+            genesis.merkle_tree().unwrap(),
+            MakeLocal(sim.clone()),
+            None,
+            false,
+        )
+        .unwrap();
+        let (txns, _timing) = block_on(perform_refresh(&pool)).unwrap();
+        let txns = txns.expect("Expected new transactions");
+        assert_eq!(txns.len(), 5);
+    }
+}
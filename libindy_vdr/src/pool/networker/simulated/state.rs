@@ -0,0 +1,735 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use indy_blssignatures::{Bls, MultiSignature, SignKey};
+use serde::Deserialize;
+use serde_json::Value as SJsonValue;
+use sha2::{Digest, Sha256};
+
+use crate::common::error::prelude::*;
+use crate::ledger::constants;
+use crate::state_proof::parse_generic_reply_for_proof_checking;
+use crate::state_proof::types::KeyValuesInSP;
+use crate::utils::{base58, base64};
+
+use super::super::types::{CatchupRep, CatchupReq, ConsistencyProof, LedgerStatus, LedgerType};
+use super::ledger::{encode_leaf, Ledger, StateTrie};
+use super::NodeFault;
+
+/// A single simulated validator node
+pub(super) struct SimulatedNode {
+    pub alias: String,
+    pub sign_key: SignKey,
+    pub fault: Option<NodeFault>,
+}
+
+impl SimulatedNode {
+    fn is_honest(&self) -> bool {
+        matches!(self.fault, None | Some(NodeFault::Slow(_)))
+    }
+}
+
+/// The shared state of the simulated pool
+pub(super) struct PoolState {
+    pub nodes: Vec<SimulatedNode>,
+    pub ledgers: HashMap<i32, Ledger>,
+    pub genesis_size: usize,
+    ordered: HashMap<(String, u64), SJsonValue>,
+}
+
+impl PoolState {
+    pub fn new(nodes: Vec<SimulatedNode>, pool_txns: Vec<(SJsonValue, Vec<u8>)>) -> Self {
+        let mut ledgers = HashMap::new();
+        for ledger_type in [
+            LedgerType::POOL,
+            LedgerType::DOMAIN,
+            LedgerType::CONFIG,
+            LedgerType::AUDIT,
+        ] {
+            ledgers.insert(ledger_type.to_id(), Ledger::default());
+        }
+        let pool = ledgers.get_mut(&LedgerType::POOL.to_id()).unwrap();
+        for (txn, leaf) in pool_txns.iter() {
+            pool.append(txn.clone(), leaf);
+        }
+        Self {
+            nodes,
+            ledgers,
+            genesis_size: pool_txns.len(),
+            ordered: HashMap::new(),
+        }
+    }
+
+    pub fn node(&self, alias: &str) -> Option<&SimulatedNode> {
+        self.nodes.iter().find(|node| node.alias == alias)
+    }
+
+    pub fn ledger(&self, ledger_type: LedgerType) -> &Ledger {
+        &self.ledgers[&ledger_type.to_id()]
+    }
+
+    fn ledger_mut(&mut self, ledger_type: LedgerType) -> &mut Ledger {
+        self.ledgers.get_mut(&ledger_type.to_id()).unwrap()
+    }
+
+    /// Process a message sent to a node, returning the delay and the messages sent in response
+    pub fn handle_message(&mut self, alias: &str, body: &str) -> (Duration, Vec<String>) {
+        let fault = match self.node(alias) {
+            Some(node) => node.fault.clone(),
+            None => return (Duration::ZERO, vec![]),
+        };
+        let delay = match fault {
+            Some(NodeFault::Silent) => return (Duration::ZERO, vec![]),
+            Some(NodeFault::Slow(delay)) => delay,
+            _ => Duration::ZERO,
+        };
+        if body == "pi" {
+            return (delay, vec!["po".to_string()]);
+        }
+        let message = match serde_json::from_str::<SJsonValue>(body) {
+            Ok(message) => message,
+            Err(_) => {
+                debug!("Simulated node {} received malformed message", alias);
+                return (delay, vec![]);
+            }
+        };
+        let replies = match message["op"].as_str() {
+            Some("LEDGER_STATUS") => self.handle_ledger_status(alias, &message),
+            Some("CATCHUP_REQ") => self.handle_catchup(alias, &message),
+            _ if message["operation"].is_object() => self.handle_request(alias, &message),
+            _ => Ok(vec![]),
+        };
+        match replies {
+            Ok(replies) => (
+                delay,
+                replies.into_iter().map(|reply| reply.to_string()).collect(),
+            ),
+            Err(err) => {
+                warn!(
+                    "Simulated node {} failed to process message: {}",
+                    alias, err
+                );
+                (delay, vec![])
+            }
+        }
+    }
+
+    fn handle_ledger_status(
+        &self,
+        alias: &str,
+        message: &SJsonValue,
+    ) -> VdrResult<Vec<SJsonValue>> {
+        let status = LedgerStatus::deserialize(message).with_input_err("Invalid ledger status")?;
+        let ledger = match self.ledgers.get(&(status.ledgerId as i32)) {
+            Some(ledger) => ledger,
+            None => return Ok(vec![]),
+        };
+        let mut size = ledger.size();
+        let mut root = base58::encode(ledger.root_hash());
+        if !self.is_honest(alias) {
+            root = forged_hash(alias);
+            if matches!(self.fault(alias), Some(NodeFault::Byzantine)) {
+                size += 1;
+            }
+        }
+        let reply = if status.txnSeqNo > 0 && status.txnSeqNo < size {
+            let proof = ConsistencyProof {
+                seqNoEnd: size,
+                seqNoStart: status.txnSeqNo,
+                ledgerId: status.ledgerId as usize,
+                hashes: ledger
+                    .consistency_proof(status.txnSeqNo, size)
+                    .iter()
+                    .map(base58::encode)
+                    .collect(),
+                oldMerkleRoot: base58::encode(ledger.root_hash_at(status.txnSeqNo)),
+                newMerkleRoot: root,
+            };
+            op_message("CONSISTENCY_PROOF", &proof)?
+        } else {
+            let reply = LedgerStatus {
+                txnSeqNo: size,
+                merkleRoot: root,
+                ledgerId: status.ledgerId,
+                ppSeqNo: None,
+                viewNo: None,
+                protocolVersion: status.protocolVersion,
+            };
+            op_message("LEDGER_STATUS", &reply)?
+        };
+        Ok(vec![reply])
+    }
+
+    fn handle_catchup(&self, alias: &str, message: &SJsonValue) -> VdrResult<Vec<SJsonValue>> {
+        let req = CatchupReq::deserialize(message).with_input_err("Invalid catchup request")?;
+        let ledger = match self.ledgers.get(&(req.ledgerId as i32)) {
+            Some(ledger) => ledger,
+            None => return Ok(vec![]),
+        };
+        let end = req.seqNoEnd.min(ledger.size());
+        let mut txns = HashMap::new();
+        for seq_no in req.seqNoStart..=end {
+            if let Some(txn) = ledger.get(seq_no) {
+                let mut txn = txn.clone();
+                if !self.is_honest(alias) {
+                    tamper(&mut txn["txnMetadata"]);
+                }
+                txns.insert(seq_no.to_string(), txn);
+            }
+        }
+        let reply = CatchupRep {
+            ledgerId: req.ledgerId,
+            consProof: ledger
+                .consistency_proof(end, req.catchupTill)
+                .iter()
+                .map(base58::encode)
+                .collect(),
+            txns,
+        };
+        Ok(vec![op_message("CATCHUP_REP", &reply)?])
+    }
+
+    fn handle_request(&mut self, alias: &str, request: &SJsonValue) -> VdrResult<Vec<SJsonValue>> {
+        let identifier = request["identifier"]
+            .as_str()
+            .or_else(|| request["endorser"].as_str())
+            .unwrap_or_default()
+            .to_string();
+        let req_id = request["reqId"]
+            .as_u64()
+            .ok_or_else(|| input_err("Missing request ID"))?;
+        let ack = json!({"op": "REQACK", "identifier": identifier, "reqId": req_id});
+        let txn_type = request["operation"]["type"].as_str().unwrap_or_default();
+        let result = match txn_type {
+            constants::GET_NYM
+            | constants::GET_ATTR
+            | constants::GET_SCHEMA
+            | constants::GET_CRED_DEF => self.read_state(alias, request),
+            constants::GET_TXN => self.read_txn(alias, request),
+            constants::NYM | constants::ATTRIB | constants::SCHEMA | constants::CRED_DEF => {
+                self.write(alias, request, identifier.clone(), req_id)
+            }
+            _ => Err(input_err(format!(
+                "Transaction type not supported by the simulated pool: {}",
+                txn_type
+            ))),
+        };
+        Ok(match result {
+            Ok(result) => vec![ack, json!({"op": "REPLY", "result": result})],
+            Err(err) => vec![json!({
+                "op": "REQNACK",
+                "identifier": identifier,
+                "reqId": req_id,
+                "reason": err.to_string(),
+            })],
+        })
+    }
+
+    fn read_state(&self, alias: &str, request: &SJsonValue) -> VdrResult<SJsonValue> {
+        let operation = &request["operation"];
+        let txn_type = operation["type"].as_str().unwrap_or_default();
+        let mut result = json!({
+            "type": txn_type,
+            "identifier": request["identifier"],
+            "reqId": request["reqId"],
+            "seqNo": null,
+            "txnTime": null,
+        });
+        let key = match txn_type {
+            constants::GET_NYM => {
+                result["dest"] = operation["dest"].clone();
+                Sha256::digest(str_field(operation, "dest")?.as_bytes()).to_vec()
+            }
+            constants::GET_ATTR => {
+                result["dest"] = operation["dest"].clone();
+                let (field, name) = ["raw", "enc", "hash"]
+                    .iter()
+                    .find_map(|field| operation[*field].as_str().map(|name| (*field, name)))
+                    .ok_or_else(|| input_err("Missing attribute name"))?;
+                result[field] = SJsonValue::from(name);
+                attrib_key(str_field(operation, "dest")?, name)
+            }
+            constants::GET_SCHEMA => {
+                result["dest"] = operation["dest"].clone();
+                result["data"] = json!({
+                    "name": operation["data"]["name"],
+                    "version": operation["data"]["version"],
+                });
+                format!(
+                    "{}:2:{}:{}",
+                    str_field(operation, "dest")?,
+                    str_field(&operation["data"], "name")?,
+                    str_field(&operation["data"], "version")?
+                )
+                .into_bytes()
+            }
+            _ => {
+                for field in ["origin", "ref", "signature_type", "tag"] {
+                    result[field] = operation[field].clone();
+                }
+                cred_def_key(
+                    str_field(operation, "origin")?,
+                    str_field(operation, "signature_type")?,
+                    &operation["ref"],
+                    operation["tag"].as_str(),
+                )
+            }
+        };
+
+        let ledger = self.ledger(LedgerType::DOMAIN);
+        if let Some(stored) = ledger.state.get(&key) {
+            let stored: SJsonValue =
+                serde_json::from_slice(stored).with_input_err("Invalid state value")?;
+            match txn_type {
+                constants::GET_NYM => {
+                    result["seqNo"] = stored["seqNo"].clone();
+                    result["txnTime"] = stored["txnTime"].clone();
+                    let mut data = stored.clone();
+                    data["dest"] = operation["dest"].clone();
+                    result["data"] = SJsonValue::from(data.to_string());
+                }
+                _ => {
+                    result["seqNo"] = stored["lsn"].clone();
+                    result["txnTime"] = stored["lut"].clone();
+                    let seq_no = stored["lsn"].as_u64().unwrap_or_default() as usize;
+                    let txn_data = ledger
+                        .get(seq_no)
+                        .map(|txn| txn["txn"]["data"].clone())
+                        .unwrap_or_default();
+                    result["data"] = match txn_type {
+                        constants::GET_ATTR => ["raw", "enc", "hash"]
+                            .iter()
+                            .find_map(|field| txn_data.get(*field).cloned())
+                            .unwrap_or_default(),
+                        constants::GET_SCHEMA => txn_data["data"].clone(),
+                        _ => stored["val"].clone(),
+                    };
+                }
+            }
+        }
+
+        let state = &ledger.state;
+        if self.is_honest(alias) {
+            result["state_proof"] = self.state_proof(state, &key, &self.signers())?;
+        } else {
+            tamper(&mut result);
+            if let Some(data) = result["data"].as_str() {
+                if let Ok(mut data) = serde_json::from_str::<SJsonValue>(data) {
+                    tamper(&mut data);
+                    result["data"] = SJsonValue::from(data.to_string());
+                }
+            }
+            if matches!(self.fault(alias), Some(NodeFault::Byzantine)) {
+                // Answer from a forked state which agrees with the tampered reply
+                let mut forked = state.clone();
+                if let Some(value) = expected_state_value(&result, &key) {
+                    forked.insert(key.clone(), value.into_bytes());
+                }
+                let signers = self
+                    .nodes
+                    .iter()
+                    .filter(|node| node.alias == alias)
+                    .collect::<Vec<_>>();
+                result["state_proof"] = self.state_proof(&forked, &key, &signers)?;
+            } else {
+                result["state_proof"] = self.state_proof(state, &key, &self.signers())?;
+            }
+        }
+        Ok(result)
+    }
+
+    fn read_txn(&self, alias: &str, request: &SJsonValue) -> VdrResult<SJsonValue> {
+        let operation = &request["operation"];
+        let ledger_id = operation["ledgerId"]
+            .as_i64()
+            .unwrap_or(LedgerType::DOMAIN.to_id() as i64) as i32;
+        let ledger = self
+            .ledgers
+            .get(&ledger_id)
+            .ok_or_else(|| input_err(format!("Unknown ledger: {}", ledger_id)))?;
+        let seq_no = operation["data"].as_u64().unwrap_or_default() as usize;
+        let mut result = json!({
+            "type": constants::GET_TXN,
+            "identifier": request["identifier"],
+            "reqId": request["reqId"],
+            "seqNo": seq_no,
+            "data": null,
+        });
+        if let Some(txn) = ledger.get(seq_no) {
+            let mut data = txn.clone();
+            data["rootHash"] = SJsonValue::from(base58::encode(ledger.root_hash()));
+            data["auditPath"] = ledger
+                .audit_path(seq_no)
+                .iter()
+                .map(base58::encode)
+                .collect();
+            data["ledgerSize"] = SJsonValue::from(ledger.size());
+            if !self.is_honest(alias) {
+                tamper(&mut data["txnMetadata"]);
+            }
+            result["data"] = data;
+        }
+        let signers = if matches!(self.fault(alias), Some(NodeFault::Byzantine)) {
+            self.nodes
+                .iter()
+                .filter(|node| node.alias == alias)
+                .collect::<Vec<_>>()
+        } else {
+            self.signers()
+        };
+        result["state_proof"] = json!({
+            "multi_signature": self.multi_signature(
+                ledger_id,
+                &ledger.state.root_hash(),
+                &ledger.root_hash(),
+                &signers,
+            )?,
+        });
+        Ok(result)
+    }
+
+    /// Order a write request, or return the previous result for a repeated request
+    fn write(
+        &mut self,
+        alias: &str,
+        request: &SJsonValue,
+        identifier: String,
+        req_id: u64,
+    ) -> VdrResult<SJsonValue> {
+        let order_key = (identifier.clone(), req_id);
+        let mut result = match self.ordered.get(&order_key) {
+            Some(result) => result.clone(),
+            None => {
+                let result = self.order(request, identifier, req_id)?;
+                self.ordered.insert(order_key, result.clone());
+                result
+            }
+        };
+        if !self.is_honest(alias) {
+            tamper(&mut result["txnMetadata"]);
+        }
+        Ok(result)
+    }
+
+    fn order(
+        &mut self,
+        request: &SJsonValue,
+        identifier: String,
+        req_id: u64,
+    ) -> VdrResult<SJsonValue> {
+        let mut data = request["operation"].clone();
+        let txn_type = data["type"].as_str().unwrap_or_default().to_string();
+        if let Some(data) = data.as_object_mut() {
+            data.remove("type");
+        }
+        let txn_time = now();
+        let ledger = self.ledger_mut(LedgerType::DOMAIN);
+        let seq_no = ledger.size() + 1;
+        let req_signature = match request["signature"].as_str() {
+            Some(signature) => json!({
+                "type": "ED25519",
+                "values": [{"from": identifier, "value": signature}],
+            }),
+            None => json!({}),
+        };
+        let txn = json!({
+            "reqSignature": req_signature,
+            "txn": {
+                "data": data,
+                "metadata": {
+                    "from": identifier,
+                    "reqId": req_id,
+                },
+                "protocolVersion": request["protocolVersion"],
+                "type": txn_type,
+            },
+            "txnMetadata": {
+                "seqNo": seq_no,
+                "txnTime": txn_time,
+            },
+            "ver": "1",
+        });
+
+        let (key, value) = match txn_type.as_str() {
+            constants::NYM => {
+                let dest = str_field(&data, "dest")?;
+                let key = Sha256::digest(dest.as_bytes()).to_vec();
+                let existing = ledger
+                    .state
+                    .get(&key)
+                    .and_then(|value| serde_json::from_slice::<SJsonValue>(value).ok())
+                    .unwrap_or_default();
+                let owner = existing
+                    .get("identifier")
+                    .cloned()
+                    .unwrap_or_else(|| SJsonValue::from(identifier.clone()));
+                let role = data.get("role").unwrap_or(&existing["role"]);
+                let verkey = data.get("verkey").unwrap_or(&existing["verkey"]);
+                let value = json!({
+                    "identifier": owner,
+                    "role": role,
+                    "seqNo": seq_no,
+                    "txnTime": txn_time,
+                    "verkey": verkey,
+                });
+                (key, value)
+            }
+            constants::ATTRIB => {
+                let dest = str_field(&data, "dest")?;
+                let (name, val) = if let Some(raw) = data["raw"].as_str() {
+                    let raw_json: SJsonValue =
+                        serde_json::from_str(raw).with_input_err("Invalid raw attribute")?;
+                    let name = raw_json
+                        .as_object()
+                        .and_then(|map| map.keys().next().cloned())
+                        .ok_or_else(|| input_err("Empty raw attribute"))?;
+                    (name, hex::encode(Sha256::digest(raw.as_bytes())))
+                } else if let Some(enc) = data["enc"].as_str() {
+                    (enc.to_string(), hex::encode(Sha256::digest(enc.as_bytes())))
+                } else {
+                    let hash = str_field(&data, "hash")?;
+                    (hash.to_string(), hash.to_string())
+                };
+                let value = json!({"lsn": seq_no, "lut": txn_time, "val": val});
+                (attrib_key(dest, &name), value)
+            }
+            constants::SCHEMA => {
+                let mut schema = data["data"].clone();
+                let key = format!(
+                    "{}:2:{}:{}",
+                    identifier,
+                    str_field(&schema, "name")?,
+                    str_field(&schema, "version")?
+                );
+                if let Some(schema) = schema.as_object_mut() {
+                    schema.remove("name");
+                    schema.remove("version");
+                }
+                let value = json!({"lsn": seq_no, "lut": txn_time, "val": schema});
+                (key.into_bytes(), value)
+            }
+            _ => {
+                let key = cred_def_key(
+                    &identifier,
+                    str_field(&data, "signature_type")?,
+                    &data["ref"],
+                    data["tag"].as_str(),
+                );
+                let value = json!({"lsn": seq_no, "lut": txn_time, "val": data["data"]});
+                (key, value)
+            }
+        };
+
+        ledger.append(txn.clone(), &encode_leaf(&stored_txn(&txn))?);
+        ledger.state.insert(key, value.to_string().into_bytes());
+
+        let mut result = txn;
+        result["rootHash"] = SJsonValue::from(base58::encode(ledger.root_hash()));
+        result["auditPath"] = ledger
+            .audit_path(seq_no)
+            .iter()
+            .map(base58::encode)
+            .collect();
+        self.append_audit(txn_time)?;
+        Ok(result)
+    }
+
+    /// Record the root of each ledger in a new audit transaction
+    fn append_audit(&mut self, txn_time: u64) -> VdrResult<()> {
+        let previous = {
+            let audit = self.ledger(LedgerType::AUDIT);
+            audit
+                .get(audit.size())
+                .map(|txn| txn["txn"]["data"].clone())
+        };
+        let mut sizes = serde_json::Map::new();
+        let mut roots = serde_json::Map::new();
+        for ledger_type in [LedgerType::POOL, LedgerType::DOMAIN, LedgerType::CONFIG] {
+            let ledger = self.ledger(ledger_type);
+            let id = ledger_type.to_id().to_string();
+            // unchanged ledger roots refer back to the audit transaction recording them
+            let root = match previous.as_ref() {
+                Some(prev)
+                    if prev["ledgerSize"][id.as_str()].as_u64() == Some(ledger.size() as u64) =>
+                {
+                    SJsonValue::from(
+                        prev["ledgerRoot"][id.as_str()].as_u64().unwrap_or_default() + 1,
+                    )
+                }
+                _ => SJsonValue::from(base58::encode(ledger.root_hash())),
+            };
+            sizes.insert(id.clone(), SJsonValue::from(ledger.size()));
+            roots.insert(id, root);
+        }
+        let audit = self.ledger_mut(LedgerType::AUDIT);
+        let seq_no = audit.size() + 1;
+        let txn = json!({
+            "reqSignature": {},
+            "txn": {
+                "data": {
+                    "ledgerRoot": roots,
+                    "ledgerSize": sizes,
+                    "ppSeqNo": seq_no,
+                    "ver": "1",
+                    "viewNo": 0,
+                },
+                "metadata": {},
+                "protocolVersion": 2,
+                "type": "2",
+            },
+            "txnMetadata": {
+                "seqNo": seq_no,
+                "txnTime": txn_time,
+            },
+            "ver": "1",
+        });
+        audit.append(txn.clone(), &encode_leaf(&txn)?);
+        Ok(())
+    }
+
+    fn state_proof(
+        &self,
+        state: &StateTrie,
+        key: &[u8],
+        signers: &[&SimulatedNode],
+    ) -> VdrResult<SJsonValue> {
+        let ledger = self.ledger(LedgerType::DOMAIN);
+        let root_hash = state.root_hash();
+        Ok(json!({
+            "root_hash": base58::encode(&root_hash),
+            "proof_nodes": base64::encode(state.proof(key)),
+            "multi_signature": self.multi_signature(
+                LedgerType::DOMAIN.to_id(),
+                &root_hash,
+                &ledger.root_hash(),
+                signers,
+            )?,
+        }))
+    }
+
+    /// Produce a BLS multi-signature over the ledger and state roots
+    fn multi_signature(
+        &self,
+        ledger_id: i32,
+        state_root: &[u8],
+        txn_root: &[u8],
+        signers: &[&SimulatedNode],
+    ) -> VdrResult<SJsonValue> {
+        let value = json!({
+            "ledger_id": ledger_id,
+            "pool_state_root_hash": base58::encode(self.ledger(LedgerType::POOL).state.root_hash()),
+            "state_root_hash": base58::encode(state_root),
+            "timestamp": now(),
+            "txn_root_hash": base58::encode(txn_root),
+        });
+        let message = rmp_serde::to_vec_named(&value)
+            .with_input_err("Error encoding multi-signature value")?;
+        let signatures = signers
+            .iter()
+            .map(|node| Bls::sign(&message, &node.sign_key))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| err_msg(VdrErrorKind::Unexpected, "Error creating BLS signature"))?;
+        let signature = MultiSignature::new(&signatures.iter().collect::<Vec<_>>())
+            .map_err(|_| err_msg(VdrErrorKind::Unexpected, "Error creating multi-signature"))?;
+        Ok(json!({
+            "participants": signers.iter().map(|node| node.alias.as_str()).collect::<Vec<_>>(),
+            "signature": base58::encode(signature.as_bytes()),
+            "value": value,
+        }))
+    }
+
+    /// The nodes participating in multi-signatures over the agreed state
+    fn signers(&self) -> Vec<&SimulatedNode> {
+        self.nodes.iter().filter(|node| node.is_honest()).collect()
+    }
+
+    fn fault(&self, alias: &str) -> Option<&NodeFault> {
+        self.node(alias).and_then(|node| node.fault.as_ref())
+    }
+
+    fn is_honest(&self, alias: &str) -> bool {
+        self.node(alias)
+            .map(SimulatedNode::is_honest)
+            .unwrap_or(false)
+    }
+}
+
+fn op_message<T: serde::Serialize>(op: &str, message: &T) -> VdrResult<SJsonValue> {
+    let mut value = serde_json::to_value(message).with_input_err("Error encoding message")?;
+    value["op"] = SJsonValue::from(op);
+    Ok(value)
+}
+
+fn str_field<'a>(value: &'a SJsonValue, field: &str) -> VdrResult<&'a str> {
+    value[field]
+        .as_str()
+        .ok_or_else(|| input_err(format!("Missing field: {}", field)))
+}
+
+fn attrib_key(dest: &str, name: &str) -> Vec<u8> {
+    format!(
+        "{}:1:{}",
+        dest,
+        hex::encode(Sha256::digest(name.as_bytes()))
+    )
+    .into_bytes()
+}
+
+fn cred_def_key(
+    origin: &str,
+    signature_type: &str,
+    schema_ref: &SJsonValue,
+    tag: Option<&str>,
+) -> Vec<u8> {
+    let tag = tag.map(|tag| format!(":{}", tag)).unwrap_or_default();
+    format!("{}:3:{}:{}{}", origin, signature_type, schema_ref, tag).into_bytes()
+}
+
+/// The form of a transaction used in the ledger merkle tree, with attribute values hashed
+fn stored_txn(txn: &SJsonValue) -> SJsonValue {
+    let mut txn = txn.clone();
+    if txn["txn"]["type"].as_str() == Some(constants::ATTRIB) {
+        for field in ["raw", "enc"] {
+            if let Some(value) = txn["txn"]["data"][field].as_str() {
+                if !value.is_empty() {
+                    txn["txn"]["data"][field] =
+                        SJsonValue::from(hex::encode(Sha256::digest(value.as_bytes())));
+                }
+            }
+        }
+    }
+    txn
+}
+
+/// The state value a client would expect for the given reply
+fn expected_state_value(result: &SJsonValue, key: &[u8]) -> Option<String> {
+    let parsed =
+        parse_generic_reply_for_proof_checking(result, &result.to_string(), Some(key), None)?;
+    parsed
+        .into_iter()
+        .next()
+        .and_then(|sp| match sp.kvs_to_verify {
+            KeyValuesInSP::Simple(data) => data.kvs.into_iter().next().and_then(|(_, value)| value),
+            _ => None,
+        })
+}
+
+/// Alter the sequence number and time of a reply, as a dishonest node might
+fn tamper(value: &mut SJsonValue) {
+    for field in ["seqNo", "txnTime"] {
+        if let Some(val) = value[field].as_u64() {
+            value[field] = SJsonValue::from(val + 1);
+        }
+    }
+}
+
+fn forged_hash(alias: &str) -> String {
+    base58::encode(Sha256::digest(format!("forged:{}", alias).as_bytes()))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time has gone backwards")
+        .as_secs()
+}
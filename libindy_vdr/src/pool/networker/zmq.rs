@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::iter::FromIterator;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime};
 
@@ -12,10 +12,13 @@ use zmq::Socket as ZSocket;
 use crate::common::error::prelude::*;
 use crate::common::handle::ResourceHandle;
 use crate::config::PoolConfig;
+use crate::pool::events::{PoolEvent, PoolEvents};
 use crate::utils::{base58, base64};
 
 use super::types::{Message, Verifiers};
-use super::{Networker, NetworkerEvent, NetworkerFactory, RequestExtEvent, RequestHandle};
+use super::{
+    EventSink, Networker, NetworkerEvent, NetworkerFactory, RequestExtEvent, RequestHandle,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[repr(transparent)]
@@ -41,8 +44,11 @@ impl NetworkerFactory for ZMQNetworkerFactory {
         let (zmq_ctx, cmd_send, cmd_recv) =
             _create_pair_of_sockets(&format!("zmqnet_{}", socket_handle));
         let (evt_send, evt_recv) = mpsc::channel::<NetworkerEvent>();
+        let events = EventSink::default();
+        let thread_events = events.clone();
         let worker = thread::spawn(move || {
-            let mut zmq_thread = ZMQThread::new(config, zmq_ctx, cmd_recv, evt_recv, remotes);
+            let mut zmq_thread =
+                ZMQThread::new(config, zmq_ctx, cmd_recv, evt_recv, remotes, thread_events);
             if let Err(err) = zmq_thread.work() {
                 warn!("ZMQ worker exited with error: {}", err)
             } else {
@@ -52,6 +58,7 @@ impl NetworkerFactory for ZMQNetworkerFactory {
         Ok(ZMQNetworker {
             cmd_send,
             evt_send,
+            events,
             worker: Some(worker),
         })
     }
@@ -61,6 +68,7 @@ impl NetworkerFactory for ZMQNetworkerFactory {
 pub struct ZMQNetworker {
     cmd_send: zmq::Socket,
     evt_send: mpsc::Sender<NetworkerEvent>,
+    events: EventSink,
     worker: Option<thread::JoinHandle<()>>,
 }
 
@@ -74,6 +82,10 @@ impl Networker for ZMQNetworker {
             .send("", 0)
             .with_err_msg(VdrErrorKind::Resource, "Error sending networker command")
     }
+
+    fn set_events(&self, events: Arc<PoolEvents>) {
+        self.events.set(events)
+    }
 }
 
 impl Drop for ZMQNetworker {
@@ -100,6 +112,7 @@ struct ZMQThread {
     requests: BTreeMap<RequestHandle, PendingRequest>,
     last_connection: Option<ZMQConnectionHandle>,
     pool_connections: BTreeMap<ZMQConnectionHandle, ZMQConnection>,
+    events: EventSink,
 }
 
 impl ZMQThread {
@@ -109,6 +122,7 @@ impl ZMQThread {
         cmd_recv: zmq::Socket,
         evt_recv: mpsc::Receiver<NetworkerEvent>,
         remotes: Vec<RemoteNode>,
+        events: EventSink,
     ) -> Self {
         let node_aliases = HashSet::from_iter(remotes.iter().map(|r| r.name.clone()));
         ZMQThread {
@@ -121,6 +135,7 @@ impl ZMQThread {
             requests: BTreeMap::new(),
             last_connection: None,
             pool_connections: BTreeMap::new(),
+            events,
         }
     }
 
@@ -325,7 +340,14 @@ impl ZMQThread {
             }
         }) {
             trace!("Removing pool connection {}", delete);
-            self.pool_connections.remove(delete);
+            if let Some(conn) = self.pool_connections.remove(delete) {
+                for node in conn.open_nodes() {
+                    self.events.emit(PoolEvent::NodeDisconnected { node });
+                }
+                self.events.emit(PoolEvent::ConnectionClosed {
+                    connections: self.pool_connections.len(),
+                });
+            }
             // DEBUG test active sockets
             let (_, poll_items) = self.get_poll_items();
             if poll_items.is_empty() {
@@ -415,12 +437,16 @@ impl ZMQThread {
             conn.init_request(sub_id);
             Ok(conn_id.unwrap())
         } else {
+            let rollover = conn_id
+                .and_then(|conn_id| self.pool_connections.get(&conn_id))
+                .map_or(false, |conn| conn.is_active() && conn.req_cnt >= req_limit);
             let mut conn = ZMQConnection::new(
                 self.zmq_ctx.clone(),
                 self.remotes.clone(),
                 self.config.conn_active_timeout,
                 self.config.ack_timeout,
                 self.config.socks_proxy.clone(),
+                self.events.clone(),
             );
             trace!("Created new pool connection");
             conn.init_request(sub_id);
@@ -428,6 +454,10 @@ impl ZMQThread {
             self.pool_connections.insert(pc_id, conn);
             self.last_connection.replace(pc_id);
             debug!("New {}", pc_id);
+            self.events.emit(PoolEvent::ConnectionOpened {
+                connections: self.pool_connections.len(),
+                rollover,
+            });
             Ok(pc_id)
         }
     }
@@ -489,6 +519,7 @@ pub struct ZMQConnection {
     active_timeout: i64,
     idle_timeout: i64,
     socks_proxy: Option<String>,
+    events: EventSink,
 }
 
 impl ZMQConnection {
@@ -498,6 +529,7 @@ impl ZMQConnection {
         active_timeout: i64,
         idle_timeout: i64,
         socks_proxy: Option<String>,
+        events: EventSink,
     ) -> Self {
         trace!("ZMQConnection::new: from remotes {:?}", remotes);
 
@@ -518,6 +550,7 @@ impl ZMQConnection {
             active_timeout,
             idle_timeout,
             socks_proxy,
+            events,
         }
     }
 
@@ -709,10 +742,22 @@ impl ZMQConnection {
             debug!("Open new socket for node {}", &self.remotes[idx].name);
             let s: ZSocket =
                 self.remotes[idx].connect(&self.ctx, &self.key_pair, self.socks_proxy.clone())?;
-            self.sockets[idx] = Some(s)
+            self.sockets[idx] = Some(s);
+            self.events.emit(PoolEvent::NodeConnected {
+                node: self.remotes[idx].name.clone(),
+            });
         }
         Ok(self.sockets[idx].as_ref().unwrap())
     }
+
+    /// Get the aliases of the nodes with open sockets
+    fn open_nodes(&self) -> Vec<String> {
+        self.remotes
+            .iter()
+            .zip(self.sockets.iter())
+            .filter_map(|(remote, socket)| socket.as_ref().map(|_| remote.name.clone()))
+            .collect()
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Hash)]
@@ -0,0 +1,330 @@
+use crypto_box::aead::{generic_array::GenericArray, Aead};
+use crypto_box::{PublicKey, SalsaBox, SecretKey};
+use rand::{rngs::OsRng, RngCore};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::common::error::prelude::*;
+
+use super::frame::{
+    check_greeting, command_body, encode_metadata, greeting, parse_command, Frame, GREETING_LEN,
+};
+
+const MECHANISM: &str = "CURVE";
+
+const HELLO_NONCE_PREFIX: &[u8; 16] = b"CurveZMQHELLO---";
+const WELCOME_NONCE_PREFIX: &[u8; 8] = b"WELCOME-";
+const INITIATE_NONCE_PREFIX: &[u8; 16] = b"CurveZMQINITIATE";
+const VOUCH_NONCE_PREFIX: &[u8; 8] = b"VOUCH---";
+const READY_NONCE_PREFIX: &[u8; 16] = b"CurveZMQREADY---";
+const CLIENT_MESSAGE_NONCE_PREFIX: &[u8; 16] = b"CurveZMQMESSAGEC";
+const SERVER_MESSAGE_NONCE_PREFIX: &[u8; 16] = b"CurveZMQMESSAGES";
+
+const COOKIE_LEN: usize = 96;
+const KEY_LEN: usize = 32;
+const MAC_LEN: usize = 16;
+
+/// The permanent CURVE key pair of a client
+pub struct CurveKeyPair {
+    secret_key: SecretKey,
+    public_key: PublicKey,
+}
+
+impl CurveKeyPair {
+    pub fn generate() -> Self {
+        let secret_key = SecretKey::generate(&mut OsRng);
+        let public_key = secret_key.public_key();
+        Self {
+            secret_key,
+            public_key,
+        }
+    }
+
+    pub fn public_key(&self) -> &[u8; KEY_LEN] {
+        self.public_key.as_bytes()
+    }
+}
+
+/// Encrypts messages sent over an established CURVE session
+pub struct CurveEncoder {
+    cipher: SalsaBox,
+    nonce: u64,
+}
+
+impl CurveEncoder {
+    /// Encrypt a message frame for the server
+    pub fn encode_message(&mut self, payload: &[u8], more: bool) -> VdrResult<Frame> {
+        let mut plaintext = Vec::with_capacity(payload.len() + 1);
+        plaintext.push(if more { 0x01 } else { 0x00 });
+        plaintext.extend_from_slice(payload);
+        self.nonce += 1;
+        let short_nonce = self.nonce.to_be_bytes();
+        let boxed = seal(
+            &self.cipher,
+            CLIENT_MESSAGE_NONCE_PREFIX,
+            &short_nonce,
+            &plaintext,
+        )?;
+        let mut data = short_nonce.to_vec();
+        data.extend_from_slice(&boxed);
+        Ok(Frame::message(command_body("MESSAGE", &data), more))
+    }
+}
+
+/// Decrypts messages received over an established CURVE session
+pub struct CurveDecoder {
+    cipher: SalsaBox,
+    peer_nonce: u64,
+}
+
+impl CurveDecoder {
+    /// Decrypt a message frame received from the server
+    pub fn decode_message(&mut self, frame: &Frame) -> VdrResult<(Vec<u8>, bool)> {
+        let (name, data) = parse_command(&frame.body)?;
+        if name != b"MESSAGE" {
+            return Err(unexpected_command(name));
+        }
+        if data.len() < 8 + MAC_LEN + 1 {
+            return Err(err_msg(VdrErrorKind::Connection, "Truncated MESSAGE"));
+        }
+        let (short_nonce, boxed) = data.split_at(8);
+        let nonce = u64::from_be_bytes(short_nonce.try_into().unwrap());
+        if nonce <= self.peer_nonce {
+            return Err(err_msg(VdrErrorKind::Connection, "Invalid MESSAGE nonce"));
+        }
+        let mut plaintext = open(
+            &self.cipher,
+            SERVER_MESSAGE_NONCE_PREFIX,
+            short_nonce,
+            boxed,
+        )?;
+        self.peer_nonce = nonce;
+        let flags = plaintext.remove(0);
+        Ok((plaintext, flags & 0x01 != 0))
+    }
+}
+
+/// Perform the ZMTP greeting and CURVE client handshake over a stream
+pub async fn handshake<S>(
+    stream: &mut S,
+    keys: &CurveKeyPair,
+    server_key: &[u8],
+    socket_type: &str,
+    identity: &[u8],
+) -> VdrResult<(CurveEncoder, CurveDecoder)>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let server_key = public_key(server_key)?;
+
+    stream
+        .write_all(&greeting(MECHANISM))
+        .await
+        .with_err_msg(VdrErrorKind::Connection, "Error sending greeting")?;
+    let mut peer_greeting = [0u8; GREETING_LEN];
+    stream
+        .read_exact(&mut peer_greeting)
+        .await
+        .with_err_msg(VdrErrorKind::Connection, "Error reading greeting")?;
+    check_greeting(&peer_greeting, MECHANISM)?;
+
+    // transient key pair for this connection
+    let transient_secret = SecretKey::generate(&mut OsRng);
+    let transient_public = transient_secret.public_key();
+    let mut nonce = 0u64;
+
+    // HELLO: C', Box[64 * 0x00](C'->S)
+    nonce += 1;
+    let short_nonce = nonce.to_be_bytes();
+    let hello_cipher = SalsaBox::new(&server_key, &transient_secret);
+    let signature = seal(&hello_cipher, HELLO_NONCE_PREFIX, &short_nonce, &[0u8; 64])?;
+    let mut hello = vec![1, 0];
+    hello.extend_from_slice(&[0u8; 72]);
+    hello.extend_from_slice(transient_public.as_bytes());
+    hello.extend_from_slice(&short_nonce);
+    hello.extend_from_slice(&signature);
+    Frame::command(command_body("HELLO", &hello))
+        .write(stream)
+        .await?;
+
+    // WELCOME: Box[S' + cookie](S->C')
+    let welcome = read_command(stream, b"WELCOME").await?;
+    if welcome.len() != 16 + MAC_LEN + KEY_LEN + COOKIE_LEN {
+        return Err(err_msg(VdrErrorKind::Connection, "Invalid WELCOME size"));
+    }
+    let (long_nonce, boxed) = welcome.split_at(16);
+    let welcome = open(&hello_cipher, WELCOME_NONCE_PREFIX, long_nonce, boxed)?;
+    let server_transient = public_key(&welcome[..KEY_LEN])?;
+    let cookie = &welcome[KEY_LEN..];
+    let cipher = SalsaBox::new(&server_transient, &transient_secret);
+
+    // INITIATE: cookie, Box[C + vouch + metadata](C'->S')
+    let mut vouch_nonce = [0u8; 16];
+    OsRng.fill_bytes(&mut vouch_nonce);
+    let vouch_cipher = SalsaBox::new(&server_transient, &keys.secret_key);
+    let mut vouch_plaintext = transient_public.as_bytes().to_vec();
+    vouch_plaintext.extend_from_slice(server_key.as_bytes());
+    let mut vouch = vouch_nonce.to_vec();
+    vouch.extend(seal(
+        &vouch_cipher,
+        VOUCH_NONCE_PREFIX,
+        &vouch_nonce,
+        &vouch_plaintext,
+    )?);
+    let mut initiate_plaintext = keys.public_key.as_bytes().to_vec();
+    initiate_plaintext.extend_from_slice(&vouch);
+    initiate_plaintext.extend(encode_metadata(&[
+        ("Socket-Type", socket_type.as_bytes()),
+        ("Identity", identity),
+    ]));
+    nonce += 1;
+    let short_nonce = nonce.to_be_bytes();
+    let mut initiate = cookie.to_vec();
+    initiate.extend_from_slice(&short_nonce);
+    initiate.extend(seal(
+        &cipher,
+        INITIATE_NONCE_PREFIX,
+        &short_nonce,
+        &initiate_plaintext,
+    )?);
+    Frame::command(command_body("INITIATE", &initiate))
+        .write(stream)
+        .await?;
+
+    // READY: Box[metadata](S'->C')
+    let ready = read_command(stream, b"READY").await?;
+    if ready.len() < 8 + MAC_LEN {
+        return Err(err_msg(VdrErrorKind::Connection, "Invalid READY size"));
+    }
+    let (short_nonce, boxed) = ready.split_at(8);
+    open(&cipher, READY_NONCE_PREFIX, short_nonce, boxed)?;
+    let peer_nonce = u64::from_be_bytes(short_nonce.try_into().unwrap());
+
+    Ok((
+        CurveEncoder { cipher, nonce },
+        CurveDecoder {
+            cipher: SalsaBox::new(&server_transient, &transient_secret),
+            peer_nonce,
+        },
+    ))
+}
+
+async fn read_command<R: AsyncRead + Unpin>(reader: &mut R, expected: &[u8]) -> VdrResult<Vec<u8>> {
+    let frame = Frame::read(reader).await?;
+    if !frame.is_command() {
+        return Err(err_msg(
+            VdrErrorKind::Connection,
+            "Expected a command frame during handshake",
+        ));
+    }
+    let (name, data) = parse_command(&frame.body)?;
+    if name == b"ERROR" {
+        let reason = data
+            .split_first()
+            .map(|(len, reason)| &reason[..(*len as usize).min(reason.len())])
+            .unwrap_or_default();
+        return Err(err_msg(
+            VdrErrorKind::Connection,
+            format!(
+                "Handshake rejected by server: {}",
+                String::from_utf8_lossy(reason)
+            ),
+        ));
+    }
+    if name != expected {
+        return Err(unexpected_command(name));
+    }
+    Ok(data.to_vec())
+}
+
+fn unexpected_command(name: &[u8]) -> VdrError {
+    err_msg(
+        VdrErrorKind::Connection,
+        format!("Unexpected command: {}", String::from_utf8_lossy(name)),
+    )
+}
+
+fn public_key(key: &[u8]) -> VdrResult<PublicKey> {
+    let key: [u8; KEY_LEN] = key
+        .try_into()
+        .map_err(|_| err_msg(VdrErrorKind::Connection, "Invalid CURVE public key"))?;
+    Ok(PublicKey::from(key))
+}
+
+fn full_nonce(prefix: &[u8], short_nonce: &[u8]) -> [u8; 24] {
+    let mut nonce = [0u8; 24];
+    nonce[..prefix.len()].copy_from_slice(prefix);
+    nonce[prefix.len()..].copy_from_slice(short_nonce);
+    nonce
+}
+
+fn seal(
+    cipher: &SalsaBox,
+    prefix: &[u8],
+    short_nonce: &[u8],
+    plaintext: &[u8],
+) -> VdrResult<Vec<u8>> {
+    let nonce = full_nonce(prefix, short_nonce);
+    cipher
+        .encrypt(GenericArray::from_slice(&nonce), plaintext)
+        .map_err(|_| err_msg(VdrErrorKind::Unexpected, "Error encrypting CURVE box"))
+}
+
+fn open(cipher: &SalsaBox, prefix: &[u8], short_nonce: &[u8], boxed: &[u8]) -> VdrResult<Vec<u8>> {
+    let nonce = full_nonce(prefix, short_nonce);
+    cipher
+        .decrypt(GenericArray::from_slice(&nonce), boxed)
+        .map_err(|_| err_msg(VdrErrorKind::Connection, "Error decrypting CURVE box"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_round_trip() {
+        let client = SecretKey::generate(&mut OsRng);
+        let server = SecretKey::generate(&mut OsRng);
+        let mut encoder = CurveEncoder {
+            cipher: SalsaBox::new(&server.public_key(), &client),
+            nonce: 1,
+        };
+        let mut decoder = CurveDecoder {
+            cipher: SalsaBox::new(&server.public_key(), &client),
+            peer_nonce: 0,
+        };
+        let server_cipher = SalsaBox::new(&client.public_key(), &server);
+
+        // client to server
+        let frame = encoder.encode_message(b"request", false).unwrap();
+        let (_, data) = parse_command(&frame.body).unwrap();
+        let (short_nonce, boxed) = data.split_at(8);
+        let plaintext = open(
+            &server_cipher,
+            CLIENT_MESSAGE_NONCE_PREFIX,
+            short_nonce,
+            boxed,
+        )
+        .unwrap();
+        assert_eq!(plaintext, b"\x00request");
+
+        // server to client
+        let short_nonce = 5u64.to_be_bytes();
+        let mut data = short_nonce.to_vec();
+        data.extend(
+            seal(
+                &server_cipher,
+                SERVER_MESSAGE_NONCE_PREFIX,
+                &short_nonce,
+                b"\x00reply",
+            )
+            .unwrap(),
+        );
+        let frame = Frame::message(command_body("MESSAGE", &data), false);
+        let (payload, more) = decoder.decode_message(&frame).unwrap();
+        assert_eq!(payload, b"reply");
+        assert!(!more);
+
+        // replayed messages are rejected
+        decoder.decode_message(&frame).unwrap_err();
+    }
+}
@@ -0,0 +1,205 @@
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::common::error::prelude::*;
+
+/// The length of the ZMTP 3 greeting
+pub const GREETING_LEN: usize = 64;
+
+/// The maximum accepted frame body size
+pub const MAX_FRAME_SIZE: u64 = 64 * 1024 * 1024;
+
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+/// A single ZMTP frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub flags: u8,
+    pub body: Vec<u8>,
+}
+
+impl Frame {
+    pub fn command(body: Vec<u8>) -> Self {
+        Self {
+            flags: FLAG_COMMAND,
+            body,
+        }
+    }
+
+    pub fn message(body: Vec<u8>, more: bool) -> Self {
+        Self {
+            flags: if more { FLAG_MORE } else { 0 },
+            body,
+        }
+    }
+
+    pub fn is_command(&self) -> bool {
+        self.flags & FLAG_COMMAND != 0
+    }
+
+    pub fn more(&self) -> bool {
+        self.flags & FLAG_MORE != 0
+    }
+
+    /// Encode the frame header and body for transmission
+    pub fn encode(&self) -> Vec<u8> {
+        let size = self.body.len();
+        let mut buf = Vec::with_capacity(size + 9);
+        let flags = self.flags & (FLAG_MORE | FLAG_COMMAND);
+        if size > u8::MAX as usize {
+            buf.push(flags | FLAG_LONG);
+            buf.extend_from_slice(&(size as u64).to_be_bytes());
+        } else {
+            buf.push(flags);
+            buf.push(size as u8);
+        }
+        buf.extend_from_slice(&self.body);
+        buf
+    }
+
+    /// Read a single frame from a stream
+    pub async fn read<R: AsyncRead + Unpin>(reader: &mut R) -> VdrResult<Self> {
+        let flags = reader
+            .read_u8()
+            .await
+            .with_err_msg(VdrErrorKind::Connection, "Error reading frame header")?;
+        let size = if flags & FLAG_LONG != 0 {
+            reader.read_u64().await
+        } else {
+            reader.read_u8().await.map(u64::from)
+        }
+        .with_err_msg(VdrErrorKind::Connection, "Error reading frame size")?;
+        if size > MAX_FRAME_SIZE {
+            return Err(err_msg(
+                VdrErrorKind::Connection,
+                format!("Frame size exceeds limit: {}", size),
+            ));
+        }
+        let mut body = vec![0u8; size as usize];
+        reader
+            .read_exact(&mut body)
+            .await
+            .with_err_msg(VdrErrorKind::Connection, "Error reading frame body")?;
+        Ok(Self { flags, body })
+    }
+
+    /// Write the frame to a stream
+    pub async fn write<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> VdrResult<()> {
+        writer
+            .write_all(&self.encode())
+            .await
+            .with_err_msg(VdrErrorKind::Connection, "Error writing frame")
+    }
+}
+
+/// Construct the client greeting for the given security mechanism
+pub fn greeting(mechanism: &str) -> [u8; GREETING_LEN] {
+    let mut greeting = [0u8; GREETING_LEN];
+    greeting[0] = 0xFF;
+    greeting[8] = 0x01;
+    greeting[9] = 0x7F;
+    greeting[10] = 3; // major version
+    greeting[11] = 0; // minor version
+    greeting[12..12 + mechanism.len()].copy_from_slice(mechanism.as_bytes());
+    // as-server (byte 32) and filler are left as zeros
+    greeting
+}
+
+/// Check the greeting received from a peer
+pub fn check_greeting(greeting: &[u8; GREETING_LEN], mechanism: &str) -> VdrResult<()> {
+    if greeting[0] != 0xFF || greeting[9] != 0x7F {
+        return Err(err_msg(VdrErrorKind::Connection, "Invalid ZMTP greeting"));
+    }
+    if greeting[10] < 3 {
+        return Err(err_msg(
+            VdrErrorKind::Connection,
+            format!("Unsupported ZMTP version: {}", greeting[10]),
+        ));
+    }
+    let peer_mechanism = &greeting[12..32];
+    let len = peer_mechanism
+        .iter()
+        .position(|b| *b == 0)
+        .unwrap_or(peer_mechanism.len());
+    if &peer_mechanism[..len] != mechanism.as_bytes() {
+        return Err(err_msg(
+            VdrErrorKind::Connection,
+            format!(
+                "Unexpected ZMTP security mechanism: {}",
+                String::from_utf8_lossy(&peer_mechanism[..len])
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Construct the body of a command frame
+pub fn command_body(name: &str, data: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(1 + name.len() + data.len());
+    body.push(name.len() as u8);
+    body.extend_from_slice(name.as_bytes());
+    body.extend_from_slice(data);
+    body
+}
+
+/// Split the body of a command frame into the command name and data
+pub fn parse_command(body: &[u8]) -> VdrResult<(&[u8], &[u8])> {
+    let len = *body
+        .first()
+        .ok_or_else(|| err_msg(VdrErrorKind::Connection, "Empty command frame"))?
+        as usize;
+    if body.len() < len + 1 {
+        return Err(err_msg(VdrErrorKind::Connection, "Truncated command frame"));
+    }
+    Ok((&body[1..=len], &body[len + 1..]))
+}
+
+/// Encode ZMTP metadata properties
+pub fn encode_metadata(properties: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut buf = vec![];
+    for (name, value) in properties {
+        buf.push(name.len() as u8);
+        buf.extend_from_slice(name.as_bytes());
+        buf.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        buf.extend_from_slice(value);
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn frame_round_trip() {
+        for (frame, header_len) in [
+            (Frame::message(b"short".to_vec(), false), 2),
+            (Frame::message(vec![7u8; 300], true), 9),
+            (Frame::command(command_body("READY", b"")), 2),
+        ] {
+            let encoded = frame.encode();
+            assert_eq!(encoded.len(), frame.body.len() + header_len);
+            let decoded = Frame::read(&mut encoded.as_slice()).await.unwrap();
+            assert_eq!(decoded.is_command(), frame.is_command());
+            assert_eq!(decoded.more(), frame.more());
+            assert_eq!(decoded.body, frame.body);
+        }
+    }
+
+    #[test]
+    fn greeting_check() {
+        let greeting = greeting("CURVE");
+        check_greeting(&greeting, "CURVE").unwrap();
+        check_greeting(&greeting, "NULL").unwrap_err();
+    }
+
+    #[test]
+    fn command_parse() {
+        let body = command_body("WELCOME", b"data");
+        let (name, data) = parse_command(&body).unwrap();
+        assert_eq!(name, b"WELCOME");
+        assert_eq!(data, b"data");
+        parse_command(&[10, b'a']).unwrap_err();
+    }
+}
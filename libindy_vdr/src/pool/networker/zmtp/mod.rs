@@ -0,0 +1,904 @@
+//! A pure-Rust networker speaking ZMTP 3 with CURVE encryption over tokio sockets
+
+mod curve;
+mod frame;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::iter::FromIterator;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use futures_channel::mpsc::UnboundedSender;
+use once_cell::sync::Lazy;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use tokio::time::{sleep_until, timeout, Instant};
+
+use crate::common::error::prelude::*;
+use crate::common::handle::ResourceHandle;
+use crate::config::PoolConfig;
+use crate::pool::events::{PoolEvent, PoolEvents};
+use crate::utils::{base58, base64};
+
+use self::curve::{CurveDecoder, CurveEncoder, CurveKeyPair};
+use self::frame::Frame;
+
+use super::types::{Message, Verifiers};
+use super::{
+    EventSink, Networker, NetworkerEvent, NetworkerFactory, RequestExtEvent, RequestHandle,
+};
+
+/// Shared runtime driving all ZMTP networker instances
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("indy-vdr-zmtp")
+        .enable_all()
+        .build()
+        .expect("Error creating networker runtime")
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct ZmtpConnectionHandle(pub i64);
+
+impl_sequence_handle!(ZmtpConnectionHandle, ZMTP_CH_COUNTER);
+
+/// Pure-Rust ZMTP `NetworkerFactory` implementation
+#[derive(Default)]
+pub struct ZmtpNetworkerFactory;
+
+impl NetworkerFactory for ZmtpNetworkerFactory {
+    type Output = ZmtpNetworker;
+    fn make_networker(
+        &self,
+        config: PoolConfig,
+        verifiers: &Verifiers,
+    ) -> VdrResult<ZmtpNetworker> {
+        let remotes = _get_remotes(verifiers);
+        let (evt_send, evt_recv) = mpsc::unbounded_channel();
+        let (socket_send, socket_recv) = mpsc::unbounded_channel();
+        let events = EventSink::default();
+        let worker = ZmtpWorker::new(config, remotes, socket_send, events.clone());
+        RUNTIME.spawn(worker.work(evt_recv, socket_recv));
+        Ok(ZmtpNetworker { evt_send, events })
+    }
+}
+
+/// Pure-Rust ZMTP `Networker` implementation
+///
+/// Dropping the networker stops its worker task and closes all node connections.
+pub struct ZmtpNetworker {
+    evt_send: mpsc::UnboundedSender<NetworkerEvent>,
+    events: EventSink,
+}
+
+impl Networker for ZmtpNetworker {
+    fn send(&self, event: NetworkerEvent) -> VdrResult<()> {
+        self.evt_send
+            .send(event)
+            .map_err(|_| err_msg(VdrErrorKind::Resource, "Error sending networker event"))
+    }
+
+    fn set_events(&self, events: Arc<PoolEvents>) {
+        self.events.set(events)
+    }
+}
+
+struct ZmtpWorker {
+    config: PoolConfig,
+    node_aliases: HashSet<String>,
+    remotes: Arc<Vec<RemoteNode>>,
+    requests: BTreeMap<RequestHandle, PendingRequest>,
+    last_connection: Option<ZmtpConnectionHandle>,
+    pool_connections: BTreeMap<ZmtpConnectionHandle, ZmtpConnection>,
+    socket_send: mpsc::UnboundedSender<SocketEvent>,
+    connected: HashSet<(ZmtpConnectionHandle, String)>,
+    events: EventSink,
+}
+
+impl ZmtpWorker {
+    fn new(
+        config: PoolConfig,
+        remotes: Vec<RemoteNode>,
+        socket_send: mpsc::UnboundedSender<SocketEvent>,
+        events: EventSink,
+    ) -> Self {
+        let node_aliases = HashSet::from_iter(remotes.iter().map(|r| r.name.clone()));
+        Self {
+            config,
+            node_aliases,
+            remotes: Arc::new(remotes),
+            requests: BTreeMap::new(),
+            last_connection: None,
+            pool_connections: BTreeMap::new(),
+            socket_send,
+            connected: HashSet::new(),
+            events,
+        }
+    }
+
+    async fn work(
+        mut self,
+        mut evt_recv: mpsc::UnboundedReceiver<NetworkerEvent>,
+        mut socket_recv: mpsc::UnboundedReceiver<SocketEvent>,
+    ) {
+        loop {
+            let next_timeout = self.get_timeout();
+            let deadline = next_timeout
+                .as_ref()
+                .map(|(_, _, deadline)| *deadline)
+                .unwrap_or_else(Instant::now);
+            tokio::select! {
+                event = evt_recv.recv() => match event {
+                    Some(event) => self.process_networker_event(event),
+                    None => {
+                        trace!("Networker worker ended");
+                        break;
+                    }
+                },
+                Some(event) = socket_recv.recv() => self.process_socket_event(event),
+                _ = sleep_until(deadline), if next_timeout.is_some() => {
+                    let (conn_id, target, _) = next_timeout.unwrap();
+                    self.process_timeout(conn_id, target);
+                }
+            }
+        }
+    }
+
+    fn process_networker_event(&mut self, event: NetworkerEvent) {
+        match event {
+            NetworkerEvent::NewRequest(handle, sub_id, body, sender) => {
+                trace!("New request {}", handle);
+                match self.add_request(handle, sub_id, body, sender) {
+                    Ok(pending) => {
+                        if !pending.send_event(RequestExtEvent::Init) {
+                            trace!("Removing, sender dropped before Init {}", handle);
+                            self.remove_request(handle);
+                        }
+                    }
+                    Err(err) => warn!("Error adding request {}: {}", handle, err),
+                }
+            }
+            NetworkerEvent::FinishRequest(handle) => {
+                trace!("Removing, finished {}", handle);
+                self.remove_request(handle);
+            }
+            NetworkerEvent::Dispatch(handle, node_aliases, timeout) => {
+                trace!("Dispatch {} {:?}", handle, node_aliases);
+                self.dispatch_request(handle, node_aliases, timeout);
+            }
+            NetworkerEvent::CleanTimeout(handle, node_alias) => {
+                if let Some((conn, sub_id)) = self.request_connection(handle) {
+                    conn.clean_timeout(&sub_id, Some(node_alias))
+                }
+            }
+            NetworkerEvent::ExtendTimeout(handle, node_alias, timeout) => {
+                if let Some((conn, sub_id)) = self.request_connection(handle) {
+                    conn.extend_timeout(&sub_id, &node_alias, timeout)
+                }
+            }
+        }
+    }
+
+    fn process_socket_event(&mut self, event: SocketEvent) {
+        match event {
+            SocketEvent::Reply(conn_id, node_alias, message) => {
+                let meta = match Message::from_raw_str(message.as_str()) {
+                    Ok(meta) => meta,
+                    Err(err) => {
+                        debug!("Error parsing received message: {:?}", err);
+                        return;
+                    }
+                };
+                let req_id = meta.request_id().unwrap_or_default();
+                if let Some(conn) = self.pool_connections.get_mut(&conn_id) {
+                    conn.clean_idle_timeout(&req_id);
+                }
+                self.forward_event(
+                    conn_id,
+                    &req_id,
+                    RequestExtEvent::Received(node_alias, message, meta, SystemTime::now()),
+                );
+            }
+            SocketEvent::Connected(conn_id, node_alias) => {
+                self.connected.insert((conn_id, node_alias.clone()));
+                self.events
+                    .emit(PoolEvent::NodeConnected { node: node_alias });
+            }
+            SocketEvent::Closed(conn_id, node_alias) => {
+                if let Some(conn) = self.pool_connections.get_mut(&conn_id) {
+                    conn.close_socket(&node_alias);
+                }
+                if self.connected.remove(&(conn_id, node_alias.clone())) {
+                    self.events
+                        .emit(PoolEvent::NodeDisconnected { node: node_alias });
+                }
+            }
+        }
+    }
+
+    fn process_timeout(&mut self, conn_id: ZmtpConnectionHandle, target: Option<(String, String)>) {
+        match target {
+            Some((req_id, node_alias)) if node_alias.is_empty() => {
+                if let Some(conn) = self.pool_connections.get_mut(&conn_id) {
+                    conn.clean_timeout(&req_id, None);
+                }
+                if let Some(handle) = self.select_request(conn_id, &req_id) {
+                    trace!("Remove idle {}", handle);
+                    self.remove_request(handle);
+                }
+            }
+            Some((req_id, node_alias)) => {
+                if let Some(conn) = self.pool_connections.get_mut(&conn_id) {
+                    conn.clean_timeout(&req_id, Some(node_alias.clone()));
+                }
+                self.forward_event(conn_id, &req_id, RequestExtEvent::Timeout(node_alias));
+            }
+            None => self.check_remove_connection(conn_id, None),
+        }
+    }
+
+    fn forward_event(
+        &mut self,
+        conn_id: ZmtpConnectionHandle,
+        req_id: &str,
+        event: RequestExtEvent,
+    ) {
+        if let Some(handle) = self.select_request(conn_id, req_id) {
+            if let Some(req) = self.requests.get_mut(&handle) {
+                if !req.send_event(event) {
+                    trace!("Removing, sender disconnected {}", handle);
+                    self.remove_request(handle)
+                }
+            }
+        } else {
+            trace!("Unknown request ID: {}", req_id)
+        }
+    }
+
+    fn select_request(&self, conn_id: ZmtpConnectionHandle, sub_id: &str) -> Option<RequestHandle> {
+        self.requests.iter().find_map(|(handle, req)| {
+            if req.conn_id == conn_id && req.sub_id == sub_id {
+                Some(*handle)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn request_connection(
+        &mut self,
+        handle: RequestHandle,
+    ) -> Option<(&mut ZmtpConnection, String)> {
+        let request = self.requests.get(&handle)?;
+        let sub_id = request.sub_id.clone();
+        self.pool_connections
+            .get_mut(&request.conn_id)
+            .map(|conn| (conn, sub_id))
+    }
+
+    fn add_request(
+        &mut self,
+        handle: RequestHandle,
+        sub_id: String,
+        body: String,
+        sender: UnboundedSender<RequestExtEvent>,
+    ) -> VdrResult<&mut PendingRequest> {
+        let conn_id = self.get_active_connection(sub_id.clone());
+        self.requests.insert(
+            handle,
+            PendingRequest {
+                conn_id,
+                sender,
+                sub_id,
+                body,
+            },
+        );
+        self.requests
+            .get_mut(&handle)
+            .ok_or_else(|| input_err("Error adding request"))
+    }
+
+    fn remove_request(&mut self, handle: RequestHandle) {
+        if let Some(req) = self.requests.remove(&handle) {
+            self.check_remove_connection(req.conn_id, Some(&req.sub_id));
+        }
+    }
+
+    fn check_remove_connection(&mut self, handle: ZmtpConnectionHandle, sub_id: Option<&str>) {
+        let idle = self.pool_connections.get_mut(&handle).map(|conn| {
+            if let Some(sub_id) = sub_id {
+                conn.clean_timeout(sub_id, None);
+            }
+            conn.is_idle()
+        });
+        if idle == Some(true) {
+            trace!("Removing pool connection {}", handle);
+            self.pool_connections.remove(&handle);
+            self.events.emit(PoolEvent::ConnectionClosed {
+                connections: self.pool_connections.len(),
+            });
+        }
+    }
+
+    fn dispatch_request(&mut self, handle: RequestHandle, node_aliases: Vec<String>, timeout: i64) {
+        let request = match self.requests.get_mut(&handle) {
+            Some(request) => request,
+            None => {
+                debug!("Unknown request ID for dispatch: {}", handle);
+                return;
+            }
+        };
+        let conn = match self.pool_connections.get_mut(&request.conn_id) {
+            Some(conn) => conn,
+            None => {
+                warn!("Removing, pool connection expired {}", handle);
+                self.remove_request(handle);
+                return;
+            }
+        };
+        for node_alias in node_aliases {
+            if !self.node_aliases.contains(&node_alias) {
+                warn!("Cannot send to unknown node alias: {}", node_alias);
+                continue;
+            }
+            conn.send_request(
+                request.conn_id,
+                request.sub_id.clone(),
+                request.body.clone(),
+                node_alias.clone(),
+                timeout,
+                &self.socket_send,
+            );
+            if !request.send_event(RequestExtEvent::Sent(node_alias, SystemTime::now())) {
+                trace!("Removing, sender disconnected {}", handle);
+                self.remove_request(handle);
+                break;
+            }
+        }
+    }
+
+    fn get_active_connection(&mut self, sub_id: String) -> ZmtpConnectionHandle {
+        let req_limit = self.config.conn_request_limit;
+        let last_connection = self.last_connection.and_then(|conn_id| {
+            self.pool_connections
+                .get_mut(&conn_id)
+                .filter(|conn| {
+                    conn.is_active() && conn.req_cnt < req_limit && !conn.seen_request(&sub_id)
+                })
+                .map(|conn| (conn_id, conn))
+        });
+        if let Some((conn_id, conn)) = last_connection {
+            conn.init_request(sub_id);
+            conn_id
+        } else {
+            let rollover = self
+                .last_connection
+                .and_then(|conn_id| self.pool_connections.get(&conn_id))
+                .map_or(false, |conn| conn.is_active() && conn.req_cnt >= req_limit);
+            let mut conn = ZmtpConnection::new(
+                self.remotes.clone(),
+                self.config.conn_active_timeout,
+                self.config.ack_timeout,
+                self.config.socks_proxy.clone(),
+            );
+            conn.init_request(sub_id);
+            let conn_id = ZmtpConnectionHandle::next();
+            self.pool_connections.insert(conn_id, conn);
+            self.last_connection.replace(conn_id);
+            debug!("New {}", conn_id);
+            self.events.emit(PoolEvent::ConnectionOpened {
+                connections: self.pool_connections.len(),
+                rollover,
+            });
+            conn_id
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn get_timeout(&self) -> Option<(ZmtpConnectionHandle, Option<(String, String)>, Instant)> {
+        self.pool_connections
+            .iter()
+            .map(|(&handle, conn)| {
+                let (target, deadline) = conn.get_timeout();
+                (handle, target, deadline)
+            })
+            .min_by_key(|(_, _, deadline)| *deadline)
+    }
+}
+
+/// A set of node sockets sharing a single client key pair
+struct ZmtpConnection {
+    remotes: Arc<Vec<RemoteNode>>,
+    sockets: Vec<Option<mpsc::UnboundedSender<String>>>,
+    key_pair: Arc<CurveKeyPair>,
+    idle_timeouts: HashMap<String, Instant>,
+    socket_timeouts: HashMap<(String, String), Instant>,
+    time_created: Instant,
+    req_cnt: usize,
+    req_log: HashSet<String>,
+    active_timeout: i64,
+    idle_timeout: i64,
+    socks_proxy: Option<String>,
+}
+
+impl ZmtpConnection {
+    fn new(
+        remotes: Arc<Vec<RemoteNode>>,
+        active_timeout: i64,
+        idle_timeout: i64,
+        socks_proxy: Option<String>,
+    ) -> Self {
+        trace!("ZmtpConnection::new: from remotes {:?}", remotes);
+        let sockets = vec![None; remotes.len()];
+        Self {
+            remotes,
+            sockets,
+            key_pair: Arc::new(CurveKeyPair::generate()),
+            idle_timeouts: HashMap::new(),
+            socket_timeouts: HashMap::new(),
+            time_created: Instant::now(),
+            req_cnt: 0,
+            req_log: HashSet::new(),
+            active_timeout,
+            idle_timeout,
+            socks_proxy,
+        }
+    }
+
+    fn get_timeout(&self) -> (Option<(String, String)>, Instant) {
+        let min_idle = self
+            .idle_timeouts
+            .iter()
+            .map(|(req_id, inst)| ((req_id.as_str(), ""), inst));
+        if let Some(((req_id, node_alias), timeout)) = self
+            .socket_timeouts
+            .iter()
+            .map(|((req_id, alias), inst)| ((req_id.as_str(), alias.as_str()), inst))
+            .chain(min_idle)
+            .min_by_key(|(_, inst)| *inst)
+        {
+            (Some((req_id.to_string(), node_alias.to_string())), *timeout)
+        } else {
+            (None, self.time_created + secs(self.active_timeout))
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.time_created.elapsed() < secs(self.active_timeout)
+    }
+
+    fn send_request(
+        &mut self,
+        conn_id: ZmtpConnectionHandle,
+        req_id: String,
+        msg: String,
+        node_alias: String,
+        timeout: i64,
+        socket_send: &mpsc::UnboundedSender<SocketEvent>,
+    ) {
+        trace!("send_request >> req_id: {} node: {}", req_id, node_alias);
+        let node_index = match self.remotes.iter().position(|node| node.name == node_alias) {
+            Some(index) => index,
+            None => {
+                warn!("Cannot send to unknown node alias: {}", node_alias);
+                return;
+            }
+        };
+        let socket = self.sockets[node_index]
+            .as_ref()
+            .filter(|socket| !socket.is_closed())
+            .cloned()
+            .unwrap_or_else(|| {
+                debug!("Open new socket for node {}", node_alias);
+                let socket = NodeSocket {
+                    conn_id,
+                    remote: self.remotes[node_index].clone(),
+                    key_pair: self.key_pair.clone(),
+                    socks_proxy: self.socks_proxy.clone(),
+                    connect_timeout: secs(self.active_timeout),
+                }
+                .spawn(socket_send.clone());
+                self.sockets[node_index] = Some(socket.clone());
+                socket
+            });
+        if socket.send(msg).is_err() {
+            debug!("Socket closed before sending to node {}", node_alias);
+        }
+        if self.idle_timeouts.contains_key(&req_id) {
+            // will only be present if this request has received no responses
+            self.set_idle_timeout(req_id.clone());
+        }
+        self.socket_timeouts
+            .insert((req_id, node_alias), Instant::now() + secs(timeout));
+    }
+
+    fn close_socket(&mut self, node_alias: &str) {
+        if let Some(index) = self.remotes.iter().position(|node| node.name == node_alias) {
+            // a replacement socket may already have been opened
+            if self.sockets[index]
+                .as_ref()
+                .map_or(false, |socket| socket.is_closed())
+            {
+                self.sockets[index] = None;
+            }
+        }
+    }
+
+    fn extend_timeout(&mut self, req_id: &str, node_alias: &str, extended_timeout: i64) {
+        if let Some(timeout) = self
+            .socket_timeouts
+            .get_mut(&(req_id.to_string(), node_alias.to_string()))
+        {
+            *timeout = Instant::now() + secs(extended_timeout)
+        } else {
+            debug!("late REQACK for req_id {}, node {}", req_id, node_alias);
+        }
+    }
+
+    fn clean_timeout(&mut self, req_id: &str, node_alias: Option<String>) {
+        match node_alias {
+            Some(node_alias) => {
+                if self
+                    .socket_timeouts
+                    .remove(&(req_id.to_string(), node_alias))
+                    .is_some()
+                    && !self
+                        .socket_timeouts
+                        .keys()
+                        .any(|(req_id_timeout, _)| req_id == req_id_timeout)
+                {
+                    self.set_idle_timeout(req_id.to_string())
+                }
+            }
+            None => {
+                self.socket_timeouts
+                    .retain(|(req_id_timeout, _), _| req_id != req_id_timeout);
+                self.idle_timeouts.remove(req_id);
+            }
+        }
+    }
+
+    fn set_idle_timeout(&mut self, req_id: String) {
+        self.idle_timeouts
+            .insert(req_id, Instant::now() + secs(self.idle_timeout));
+    }
+
+    fn clean_idle_timeout(&mut self, req_id: &str) {
+        self.idle_timeouts.remove(req_id);
+    }
+
+    fn init_request(&mut self, req_id: String) {
+        self.req_cnt += 1;
+        self.req_log.insert(req_id.clone());
+        self.set_idle_timeout(req_id);
+    }
+
+    fn seen_request(&self, req_id: &str) -> bool {
+        self.req_log.contains(req_id)
+    }
+
+    fn is_idle(&self) -> bool {
+        !self.is_active() && self.socket_timeouts.is_empty() && self.idle_timeouts.is_empty()
+    }
+}
+
+/// A connection to a single node, driven by its own task
+struct NodeSocket {
+    conn_id: ZmtpConnectionHandle,
+    remote: RemoteNode,
+    key_pair: Arc<CurveKeyPair>,
+    socks_proxy: Option<String>,
+    connect_timeout: Duration,
+}
+
+impl NodeSocket {
+    /// Start the socket task, returning the sender for outgoing messages
+    fn spawn(self, events: mpsc::UnboundedSender<SocketEvent>) -> mpsc::UnboundedSender<String> {
+        let (msg_send, msg_recv) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let node_alias = self.remote.name.clone();
+            let conn_id = self.conn_id;
+            if let Err(err) = self.run(msg_recv, &events).await {
+                debug!("Connection to node {} closed: {}", node_alias, err);
+            } else {
+                trace!("Connection to node {} closed", node_alias);
+            }
+            events.send(SocketEvent::Closed(conn_id, node_alias)).ok();
+        });
+        msg_send
+    }
+
+    async fn run(
+        self,
+        msg_recv: mpsc::UnboundedReceiver<String>,
+        events: &mpsc::UnboundedSender<SocketEvent>,
+    ) -> VdrResult<()> {
+        let identity = base64::encode(self.key_pair.public_key());
+        let (stream, encoder, decoder) = timeout(self.connect_timeout, async {
+            let mut stream = self.connect().await?;
+            let (encoder, decoder) = curve::handshake(
+                &mut stream,
+                &self.key_pair,
+                &self.remote.enc_key,
+                "DEALER",
+                identity.as_bytes(),
+            )
+            .await?;
+            VdrResult::Ok((stream, encoder, decoder))
+        })
+        .await
+        .map_err(|_| err_msg(VdrErrorKind::Connection, "Timed out connecting to node"))??;
+        events
+            .send(SocketEvent::Connected(
+                self.conn_id,
+                self.remote.name.clone(),
+            ))
+            .ok();
+        let (reader, writer) = stream.into_split();
+        tokio::select! {
+            res = self.read_replies(reader, decoder, events) => res,
+            res = write_requests(writer, encoder, msg_recv) => res,
+        }
+    }
+
+    async fn connect(&self) -> VdrResult<TcpStream> {
+        let addr =
+            self.remote.zaddr.strip_prefix("tcp://").ok_or_else(|| {
+                input_err(format!("Unsupported node address: {}", self.remote.zaddr))
+            })?;
+        if let Some(proxy) = self.socks_proxy.as_deref() {
+            debug!("Use socks proxy: {}", proxy);
+            let mut stream = TcpStream::connect(proxy)
+                .await
+                .with_err_msg(VdrErrorKind::Connection, "Error connecting to socks proxy")?;
+            socks5_connect(&mut stream, addr).await?;
+            Ok(stream)
+        } else {
+            TcpStream::connect(addr)
+                .await
+                .with_err_msg(VdrErrorKind::Connection, "Error connecting to node")
+        }
+    }
+
+    async fn read_replies(
+        &self,
+        mut reader: OwnedReadHalf,
+        mut decoder: CurveDecoder,
+        events: &mpsc::UnboundedSender<SocketEvent>,
+    ) -> VdrResult<()> {
+        let mut message = vec![];
+        loop {
+            let frame = Frame::read(&mut reader).await?;
+            if frame.is_command() {
+                trace!("Ignoring command frame from node {}", self.remote.name);
+                continue;
+            }
+            let (part, more) = decoder.decode_message(&frame)?;
+            message.extend(part);
+            if more {
+                continue;
+            }
+            match String::from_utf8(std::mem::take(&mut message)) {
+                Ok(msg) => {
+                    trace!("Socket reply {} {}", &self.remote.name, &msg);
+                    if events
+                        .send(SocketEvent::Reply(
+                            self.conn_id,
+                            self.remote.name.clone(),
+                            msg,
+                        ))
+                        .is_err()
+                    {
+                        return Ok(());
+                    }
+                }
+                Err(_) => debug!("Received non-UTF-8 message from node {}", self.remote.name),
+            }
+        }
+    }
+}
+
+async fn write_requests(
+    mut writer: OwnedWriteHalf,
+    mut encoder: CurveEncoder,
+    mut msg_recv: mpsc::UnboundedReceiver<String>,
+) -> VdrResult<()> {
+    while let Some(msg) = msg_recv.recv().await {
+        encoder
+            .encode_message(msg.as_bytes(), false)?
+            .write(&mut writer)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Establish a tunnel through a SOCKS5 proxy without authentication
+async fn socks5_connect(stream: &mut TcpStream, addr: &str) -> VdrResult<()> {
+    let (host, port) = addr
+        .rsplit_once(':')
+        .and_then(|(host, port)| Some((host, port.parse::<u16>().ok()?)))
+        .ok_or_else(|| input_err(format!("Invalid node address: {}", addr)))?;
+    if host.len() > u8::MAX as usize {
+        return Err(input_err(format!("Invalid node address: {}", addr)));
+    }
+    stream
+        .write_all(&[5, 1, 0])
+        .await
+        .with_err_msg(VdrErrorKind::Connection, "Error sending socks greeting")?;
+    let mut reply = [0u8; 2];
+    stream
+        .read_exact(&mut reply)
+        .await
+        .with_err_msg(VdrErrorKind::Connection, "Error reading socks greeting")?;
+    if reply != [5, 0] {
+        return Err(err_msg(
+            VdrErrorKind::Connection,
+            "Socks proxy requires unsupported authentication",
+        ));
+    }
+
+    let mut request = vec![5, 1, 0, 3, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream
+        .write_all(&request)
+        .await
+        .with_err_msg(VdrErrorKind::Connection, "Error sending socks request")?;
+    let mut reply = [0u8; 4];
+    stream
+        .read_exact(&mut reply)
+        .await
+        .with_err_msg(VdrErrorKind::Connection, "Error reading socks reply")?;
+    if reply[1] != 0 {
+        return Err(err_msg(
+            VdrErrorKind::Connection,
+            format!("Socks proxy connection failed: {}", reply[1]),
+        ));
+    }
+    let addr_len = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => stream
+            .read_u8()
+            .await
+            .with_err_msg(VdrErrorKind::Connection, "Error reading socks reply")?
+            as usize,
+        _ => return Err(err_msg(VdrErrorKind::Connection, "Invalid socks reply")),
+    };
+    let mut bound_addr = vec![0u8; addr_len + 2];
+    stream
+        .read_exact(&mut bound_addr)
+        .await
+        .with_err_msg(VdrErrorKind::Connection, "Error reading socks reply")?;
+    Ok(())
+}
+
+fn secs(timeout: i64) -> Duration {
+    Duration::from_secs(std::cmp::max(timeout, 0) as u64)
+}
+
+#[derive(Clone, Eq, PartialEq, Hash)]
+struct RemoteNode {
+    pub name: String,
+    pub enc_key: Vec<u8>,
+    pub zaddr: String,
+}
+
+impl std::fmt::Debug for RemoteNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pubkey = base58::encode(&self.enc_key);
+        write!(
+            f,
+            "RemoteNode {{ name: {}, public_key: {}, zaddr: {} }}",
+            self.name, pubkey, self.zaddr
+        )
+    }
+}
+
+fn _get_remotes(verifiers: &Verifiers) -> Vec<RemoteNode> {
+    verifiers
+        .iter()
+        .map(|(alias, info)| RemoteNode {
+            name: alias.clone(),
+            enc_key: info.enc_key.clone(),
+            zaddr: info.client_addr.clone(),
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+enum SocketEvent {
+    Connected(
+        ZmtpConnectionHandle,
+        String, // node alias
+    ),
+    Reply(
+        ZmtpConnectionHandle,
+        String, // node alias
+        String, // message
+    ),
+    Closed(
+        ZmtpConnectionHandle,
+        String, // node alias
+    ),
+}
+
+#[derive(Debug)]
+struct PendingRequest {
+    conn_id: ZmtpConnectionHandle,
+    sender: UnboundedSender<RequestExtEvent>,
+    sub_id: String,
+    body: String,
+}
+
+impl PendingRequest {
+    fn send_event(&mut self, event: RequestExtEvent) -> bool {
+        self.sender.unbounded_send(event).is_ok()
+    }
+}
+
+#[cfg(all(test, feature = "zmq_networker"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loopback_with_zmq_curve_server() {
+        let context = zmq::Context::new();
+        let server = context.socket(zmq::ROUTER).unwrap();
+        let server_keys = zmq::CurveKeyPair::new().unwrap();
+        server.set_curve_server(true).unwrap();
+        server.set_curve_secretkey(&server_keys.secret_key).unwrap();
+        server.set_rcvtimeo(10_000).unwrap();
+        server.bind("tcp://127.0.0.1:*").unwrap();
+        let zaddr = server.get_last_endpoint().unwrap().unwrap();
+
+        let (events_send, mut events_recv) = mpsc::unbounded_channel();
+        let socket = NodeSocket {
+            conn_id: ZmtpConnectionHandle::next(),
+            remote: RemoteNode {
+                name: "Node1".to_string(),
+                enc_key: server_keys.public_key.to_vec(),
+                zaddr,
+            },
+            key_pair: Arc::new(CurveKeyPair::generate()),
+            socks_proxy: None,
+            connect_timeout: Duration::from_secs(10),
+        };
+        let sender = {
+            let _guard = RUNTIME.enter();
+            socket.spawn(events_send)
+        };
+        sender.send("ping".to_string()).unwrap();
+
+        // the client identity is followed by the message
+        let parts = server.recv_multipart(0).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[1], b"ping");
+        server
+            .send_multipart([parts[0].as_slice(), b"pong"], 0)
+            .unwrap();
+
+        let events = RUNTIME.block_on(async {
+            let mut events = vec![];
+            while events.len() < 2 {
+                match timeout(Duration::from_secs(10), events_recv.recv()).await {
+                    Ok(Some(event)) => events.push(event),
+                    _ => break,
+                }
+            }
+            events
+        });
+        assert!(matches!(
+            &events[..],
+            [
+                SocketEvent::Connected(_, connected),
+                SocketEvent::Reply(_, replied, reply),
+            ] if connected == "Node1" && replied == "Node1" && reply == "pong"
+        ));
+    }
+}
@@ -0,0 +1,307 @@
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures_executor::block_on;
+
+use crate::common::error::prelude::*;
+use crate::config::RefreshPolicy;
+
+use super::genesis::PoolTransactions;
+use super::helpers::perform_refresh;
+use super::manager::{Pool, SharedPool};
+use super::types::{LedgerType, Verifiers};
+
+/// The longest time between checks for a triggered refresh
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A callback receiving the changes found by a background refresh
+pub type RefreshListener = Arc<dyn Fn(PoolRefreshEvent) + Send + Sync>;
+
+/// The cause of a background refresh
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshTrigger {
+    /// The refresh interval elapsed
+    Interval,
+    /// Nodes timed out or returned invalid state proofs
+    NodeFailures,
+    /// A verified state proof reported a different pool ledger root
+    StatusMismatch,
+}
+
+/// The changes in validator pool membership applied by a background refresh
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PoolRefreshEvent {
+    pub trigger: RefreshTrigger,
+    /// The root hash of the new pool transactions
+    pub mt_root: String,
+    /// The number of new pool transactions
+    pub mt_size: usize,
+    /// The aliases of nodes added to the pool
+    pub added: Vec<String>,
+    /// The aliases of nodes removed from the pool
+    pub removed: Vec<String>,
+    /// The aliases of nodes with changed addresses or keys
+    pub updated: Vec<String>,
+}
+
+impl PoolRefreshEvent {
+    pub(crate) fn new<T: Pool>(
+        trigger: RefreshTrigger,
+        previous: &Verifiers,
+        pool: &T,
+    ) -> VdrResult<Self> {
+        let current = pool.get_verifier_info()?;
+        let (mt_root, mt_size) = pool.get_merkle_tree_info();
+        let mut added = vec![];
+        let mut updated = vec![];
+        for (alias, info) in current.iter() {
+            match previous.get(alias) {
+                None => added.push(alias.clone()),
+                Some(prev) => {
+                    if serde_json::to_value(prev).ok() != serde_json::to_value(info).ok() {
+                        updated.push(alias.clone())
+                    }
+                }
+            }
+        }
+        let mut removed = previous
+            .keys()
+            .filter(|alias| !current.contains_key(*alias))
+            .cloned()
+            .collect::<Vec<_>>();
+        added.sort();
+        removed.sort();
+        updated.sort();
+        Ok(Self {
+            trigger,
+            mt_root,
+            mt_size,
+            added,
+            removed,
+            updated,
+        })
+    }
+}
+
+/// Tracks when a background refresh is due under a `RefreshPolicy`
+pub(crate) struct RefreshSchedule {
+    policy: RefreshPolicy,
+    next_refresh: Option<Instant>,
+    failures: u64,
+    mismatch_root: Option<String>,
+}
+
+impl RefreshSchedule {
+    pub fn new<T: Pool>(policy: RefreshPolicy, pool: &T) -> Self {
+        let mut schedule = Self {
+            policy,
+            next_refresh: None,
+            failures: 0,
+            mismatch_root: None,
+        };
+        schedule.reset(pool);
+        schedule
+    }
+
+    /// Get the time to wait before the next check
+    pub fn check_delay(&self) -> Duration {
+        self.next_refresh
+            .map(|at| at.saturating_duration_since(Instant::now()))
+            .unwrap_or(CHECK_INTERVAL)
+            .min(CHECK_INTERVAL)
+    }
+
+    /// Determine whether a refresh of the pool is due
+    pub fn check<T: Pool>(&mut self, pool: &T) -> Option<RefreshTrigger> {
+        if self.next_refresh.map(|at| at <= Instant::now()) == Some(true) {
+            return Some(RefreshTrigger::Interval);
+        }
+        let threshold = self.policy.failure_threshold;
+        if threshold > 0 && failure_count(pool).saturating_sub(self.failures) >= threshold {
+            return Some(RefreshTrigger::NodeFailures);
+        }
+        if self.policy.on_status_mismatch {
+            if let Some(status) = pool.get_ledger_status(LedgerType::POOL) {
+                let (mt_root, _) = pool.get_merkle_tree_info();
+                // only trigger once for each reported root
+                if status.root_hash != mt_root
+                    && self.mismatch_root.as_ref() != Some(&status.root_hash)
+                {
+                    self.mismatch_root = Some(status.root_hash);
+                    return Some(RefreshTrigger::StatusMismatch);
+                }
+            }
+        }
+        None
+    }
+
+    /// Restart the schedule after a refresh of the pool
+    pub fn reset<T: Pool>(&mut self, pool: &T) {
+        self.next_refresh = self.policy.next_delay().map(|delay| Instant::now() + delay);
+        self.failures = failure_count(pool);
+    }
+}
+
+fn failure_count<T: Pool>(pool: &T) -> u64 {
+    pool.get_node_health()
+        .values()
+        .map(|status| status.timeouts + status.invalid_proofs)
+        .sum()
+}
+
+/// Refreshes a `SharedPool` in a background thread according to a `RefreshPolicy`.
+///
+/// The refresh thread is stopped when the instance is dropped.
+pub struct SharedPoolRefresher {
+    stop: Option<mpsc::Sender<()>>,
+    worker: Option<thread::JoinHandle<()>>,
+}
+
+impl SharedPoolRefresher {
+    /// Start refreshing a pool. When new transactions are found, `connect` is used to
+    /// create the replacement pool, which is passed to `on_update` with the membership changes.
+    ///
+    /// Requests already sent to the previous pool instance are not interrupted, and
+    /// its event listeners are registered with the replacement pool.
+    pub fn start<C, U>(pool: SharedPool, policy: RefreshPolicy, connect: C, on_update: U) -> Self
+    where
+        C: Fn(PoolTransactions) -> VdrResult<SharedPool> + Send + 'static,
+        U: Fn(SharedPool, PoolRefreshEvent) + Send + 'static,
+    {
+        let (stop, stopped) = mpsc::channel::<()>();
+        let worker = thread::spawn(move || {
+            let mut pool = pool;
+            let mut schedule = RefreshSchedule::new(policy, &pool);
+            loop {
+                match stopped.recv_timeout(schedule.check_delay()) {
+                    Err(RecvTimeoutError::Timeout) => (),
+                    _ => break,
+                }
+                let trigger = match schedule.check(&pool) {
+                    Some(trigger) => trigger,
+                    None => continue,
+                };
+                debug!("Starting background pool refresh: {:?}", trigger);
+                let result = block_on(perform_refresh(&pool)).and_then(|(txns, _meta)| {
+                    txns.map(|txns| -> VdrResult<_> {
+                        let previous = pool.get_verifier_info()?;
+                        let new_pool = connect(txns)?;
+                        new_pool.get_events().inherit(pool.get_events());
+                        let event = PoolRefreshEvent::new(trigger, &previous, &new_pool)?;
+                        Ok((new_pool, event))
+                    })
+                    .transpose()
+                });
+                match result {
+                    Ok(Some((new_pool, event))) => {
+                        debug!("Pool refreshed: {:?}", event);
+                        on_update(new_pool.clone(), event);
+                        pool = new_pool;
+                    }
+                    Ok(None) => debug!("Background refresh found no changes"),
+                    Err(err) => warn!("Error performing background refresh: {}", err),
+                }
+                schedule.reset(&pool);
+            }
+            debug!("Background refresh thread ended")
+        });
+        Self {
+            stop: Some(stop),
+            worker: Some(worker),
+        }
+    }
+
+    /// Stop the refresh thread.
+    pub fn stop(&mut self) {
+        self.stop.take();
+        if let Some(worker) = self.worker.take() {
+            worker.join().ok();
+        }
+    }
+}
+
+impl Drop for SharedPoolRefresher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_policy_delay() {
+        let policy = RefreshPolicy {
+            interval: 100,
+            jitter: 0.5,
+            ..RefreshPolicy::default()
+        };
+        for _ in 0..10 {
+            let delay = policy.next_delay().unwrap();
+            assert!(delay >= Duration::from_secs(100) && delay <= Duration::from_secs(150));
+        }
+        let policy = RefreshPolicy {
+            interval: 0,
+            ..RefreshPolicy::default()
+        };
+        assert_eq!(policy.next_delay(), None);
+    }
+}
+
+#[cfg(all(test, feature = "simulated_pool"))]
+mod simulated_tests {
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    use super::RefreshTrigger;
+    use crate::config::{PoolConfig, RefreshPolicy};
+    use crate::pool::networker::fixtures::simulated_pool;
+    use crate::pool::networker::MakeLocal;
+    use crate::pool::{InMemoryCache, PoolRunner, PoolTransactionsCache, SnapshotStore};
+
+    #[test]
+    fn background_refresh() {
+        let sim = simulated_pool();
+        let genesis = sim.genesis().unwrap();
+        sim.add_node("Node5").unwrap();
+
+        let config = PoolConfig {
+            refresh_policy: Some(RefreshPolicy {
+                interval: 1,
+                jitter: 0.0,
+                ..RefreshPolicy::default()
+            }),
+            ..PoolConfig::default()
+        };
+        let runner = PoolRunner::new(
+            config,
+            genesis.merkle_tree().unwrap(),
+            MakeLocal(sim.clone()),
+            None,
+            false,
+        );
+        let cache: Arc<dyn PoolTransactionsCache> = Arc::new(InMemoryCache::new());
+        runner
+            .persist_snapshots(Arc::new(SnapshotStore::new(cache.clone(), genesis.clone())))
+            .unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        runner
+            .set_refresh_listener(Some(Arc::new(move |event| {
+                sender.lock().unwrap().send(event).ok();
+            })))
+            .unwrap();
+        let event = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(event.trigger, RefreshTrigger::Interval);
+        assert_eq!(event.added, vec!["Node5".to_string()]);
+        assert!(event.removed.is_empty());
+        assert_eq!(event.mt_size, 5);
+        // the refreshed transactions are resolved from the initial ones
+        let latest = cache.resolve_latest(&genesis).unwrap().unwrap();
+        assert_eq!(latest.len(), 5);
+    }
+}
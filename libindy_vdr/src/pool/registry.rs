@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::common::error::prelude::*;
+use crate::config::PoolConfig;
+
+use super::builder::PoolBuilder;
+use super::genesis::PoolTransactions;
+use super::helpers::perform_refresh;
+use super::manager::{Pool, SharedPool};
+
+type Connector<T> = Box<dyn Fn(&str, PoolBuilder) -> VdrResult<T> + Send + Sync>;
+
+struct RegistryEntry<T> {
+    transactions: PoolTransactions,
+    node_weights: Option<HashMap<String, f32>>,
+    refreshed: bool,
+    pool: Option<T>,
+    last_used: Instant,
+    last_refresh: Option<SystemTime>,
+}
+
+/// The registration details of a pool in a `PoolRegistry`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RegisteredPool {
+    pub namespace: String,
+    /// Whether a pool instance is currently connected
+    pub connected: bool,
+    /// The number of known pool transactions
+    pub txn_count: usize,
+    /// The time of the last refresh, in seconds since the Unix epoch
+    pub last_refresh: Option<u64>,
+}
+
+/// A set of verifier pools identified by their `did:indy` namespace.
+///
+/// Pools are connected when first requested, and may be refreshed or evicted when idle.
+pub struct PoolRegistry<T: Pool = SharedPool> {
+    config: RwLock<PoolConfig>,
+    connect: Connector<T>,
+    entries: RwLock<HashMap<String, RegistryEntry<T>>>,
+}
+
+impl PoolRegistry<SharedPool> {
+    /// Create a new registry of `SharedPool` instances.
+    pub fn new(config: PoolConfig) -> Self {
+        Self::with_connector(config, |_namespace, builder| builder.into_shared())
+    }
+}
+
+impl<T: Pool> PoolRegistry<T> {
+    /// Create a new registry using a custom method to connect pools.
+    ///
+    /// The connector receives the namespace of the pool and a builder for its transactions.
+    pub fn with_connector<F>(config: PoolConfig, connect: F) -> Self
+    where
+        F: Fn(&str, PoolBuilder) -> VdrResult<T> + Send + Sync + 'static,
+    {
+        Self {
+            config: RwLock::new(config),
+            connect: Box::new(connect),
+            entries: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn read_entries(&self) -> VdrResult<RwLockReadGuard<'_, HashMap<String, RegistryEntry<T>>>> {
+        self.entries.read().map_err(|_| {
+            err_msg(
+                VdrErrorKind::Unexpected,
+                "Error acquiring pool registry, lock poisoned",
+            )
+        })
+    }
+
+    fn write_entries(&self) -> VdrResult<RwLockWriteGuard<'_, HashMap<String, RegistryEntry<T>>>> {
+        self.entries.write().map_err(|_| {
+            err_msg(
+                VdrErrorKind::Unexpected,
+                "Error acquiring pool registry, lock poisoned",
+            )
+        })
+    }
+
+    fn get_config(&self) -> VdrResult<PoolConfig> {
+        Ok(self
+            .config
+            .read()
+            .map_err(|_| {
+                err_msg(
+                    VdrErrorKind::Unexpected,
+                    "Error acquiring pool registry config, lock poisoned",
+                )
+            })?
+            .clone())
+    }
+
+    /// Update the configuration used when connecting pools.
+    ///
+    /// Pools which are already connected are not affected until they are evicted.
+    pub fn set_config(&self, config: PoolConfig) -> VdrResult<()> {
+        *self.config.write().map_err(|_| {
+            err_msg(
+                VdrErrorKind::Unexpected,
+                "Error acquiring pool registry config, lock poisoned",
+            )
+        })? = config;
+        Ok(())
+    }
+
+    /// Register a pool for a namespace, replacing any existing registration.
+    pub fn register(
+        &self,
+        namespace: &str,
+        transactions: PoolTransactions,
+        node_weights: Option<HashMap<String, f32>>,
+    ) -> VdrResult<()> {
+        if namespace.is_empty() {
+            return Err(input_err("Pool namespace must not be empty"));
+        }
+        // validate the transactions before they are used to connect
+        transactions.merkle_tree()?;
+        let entry = RegistryEntry {
+            transactions,
+            node_weights,
+            refreshed: false,
+            pool: None,
+            last_used: Instant::now(),
+            last_refresh: None,
+        };
+        self.write_entries()?.insert(namespace.to_string(), entry);
+        Ok(())
+    }
+
+    /// Remove the pool registered for a namespace, returning `false` if it was not found.
+    pub fn deregister(&self, namespace: &str) -> VdrResult<bool> {
+        Ok(self.write_entries()?.remove(namespace).is_some())
+    }
+
+    /// List the registered pools.
+    pub fn list(&self) -> VdrResult<Vec<RegisteredPool>> {
+        let mut pools = self
+            .read_entries()?
+            .iter()
+            .map(|(namespace, entry)| RegisteredPool {
+                namespace: namespace.clone(),
+                connected: entry.pool.is_some(),
+                txn_count: entry.transactions.len(),
+                last_refresh: entry.last_refresh.and_then(|time| {
+                    time.duration_since(UNIX_EPOCH)
+                        .ok()
+                        .map(|elapsed| elapsed.as_secs())
+                }),
+            })
+            .collect::<Vec<_>>();
+        pools.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+        Ok(pools)
+    }
+
+    /// Get the pool for a namespace, connecting to it if necessary.
+    pub fn get_pool(&self, namespace: &str) -> VdrResult<T> {
+        let config = self.get_config()?;
+        let mut entries = self.write_entries()?;
+        let entry = entries.get_mut(namespace).ok_or_else(|| {
+            err_msg(
+                VdrErrorKind::Resolver,
+                format!("No pool registered for namespace: {}", namespace),
+            )
+        })?;
+        entry.last_used = Instant::now();
+        if let Some(pool) = entry.pool.as_ref() {
+            return Ok(pool.clone());
+        }
+        debug!("Connecting to pool for namespace: {}", namespace);
+        let builder = PoolBuilder::new(config, entry.transactions.clone())
+            .node_weights(entry.node_weights.clone())
+            .refreshed(entry.refreshed);
+        let pool = (self.connect)(namespace, builder)?;
+        entry.pool.replace(pool.clone());
+        Ok(pool)
+    }
+
+    /// Get the pool for the namespace of a `did:indy` DID or DID URL.
+    pub fn get_pool_for_did(&self, did: &str) -> VdrResult<T> {
+        self.get_pool(did_namespace(did)?)
+    }
+
+    /// Fetch the latest pool transactions for a namespace, reconnecting if they have changed.
+    ///
+    /// Returns the new transactions, if any were found.
+    pub async fn refresh(&self, namespace: &str) -> VdrResult<Option<PoolTransactions>> {
+        let pool = self.get_pool(namespace)?;
+        let (new_txns, _meta) = perform_refresh(&pool).await?;
+        let mut entries = self.write_entries()?;
+        let entry = entries.get_mut(namespace).ok_or_else(|| {
+            err_msg(
+                VdrErrorKind::Unexpected,
+                "Pool was deregistered before refresh completed",
+            )
+        })?;
+        entry.last_refresh = Some(SystemTime::now());
+        if let Some(txns) = new_txns.as_ref() {
+            entry.transactions = txns.clone();
+            entry.refreshed = true;
+            entry.pool = None;
+        }
+        Ok(new_txns)
+    }
+
+    /// Disconnect pools which have not been used within `max_idle`, keeping their registration.
+    ///
+    /// Returns the namespaces of the evicted pools.
+    pub fn evict_idle(&self, max_idle: Duration) -> VdrResult<Vec<String>> {
+        let mut evicted = vec![];
+        for (namespace, entry) in self.write_entries()?.iter_mut() {
+            if entry.pool.is_some() && entry.last_used.elapsed() >= max_idle {
+                debug!("Evicting idle pool for namespace: {}", namespace);
+                entry.pool = None;
+                evicted.push(namespace.clone());
+            }
+        }
+        Ok(evicted)
+    }
+}
+
+/// Extract the namespace from a `did:indy:<namespace>:<id>` DID or DID URL
+pub(crate) fn did_namespace(did: &str) -> VdrResult<&str> {
+    let rest = did
+        .strip_prefix("did:indy:")
+        .ok_or_else(|| input_err(format!("Not a did:indy DID: {}", did)))?;
+    let end = rest
+        .find(|c| matches!(c, '/' | '?' | '#'))
+        .unwrap_or(rest.len());
+    rest[..end]
+        .rsplit_once(':')
+        .map(|(namespace, _id)| namespace)
+        .filter(|namespace| !namespace.is_empty())
+        .ok_or_else(|| input_err(format!("Missing namespace in DID: {}", did)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_did_namespace() {
+        assert_eq!(
+            did_namespace("did:indy:sovrin:WRfXPg8dantKVubE3HX8pw").unwrap(),
+            "sovrin"
+        );
+        assert_eq!(
+            did_namespace(
+                "did:indy:sovrin:staging:WRfXPg8dantKVubE3HX8pw/anoncreds/v0/SCHEMA/a/1.0"
+            )
+            .unwrap(),
+            "sovrin:staging"
+        );
+        assert!(did_namespace("did:sov:WRfXPg8dantKVubE3HX8pw").is_err());
+        assert!(did_namespace("did:indy:WRfXPg8dantKVubE3HX8pw").is_err());
+    }
+
+    #[test]
+    fn test_register_and_deregister() {
+        let registry = PoolRegistry::<SharedPool>::with_connector(PoolConfig::default(), |_, _| {
+            Err(err_msg(VdrErrorKind::Connection, "Not connected"))
+        });
+        let txns = PoolTransactions::from_json_transactions([r#"{"txn":{"type":"0"}}"#]).unwrap();
+        assert!(registry.register("", txns.clone(), None).is_err());
+        registry.register("test", txns, None).unwrap();
+        let pools = registry.list().unwrap();
+        assert_eq!(pools.len(), 1);
+        assert_eq!(pools[0].namespace, "test");
+        assert!(!pools[0].connected);
+
+        let err = registry.get_pool("other").unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::Resolver));
+        let err = registry.get_pool_for_did("did:indy:test:abc").unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::Connection));
+
+        assert!(registry.deregister("test").unwrap());
+        assert!(!registry.deregister("test").unwrap());
+    }
+}
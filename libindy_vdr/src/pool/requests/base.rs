@@ -10,6 +10,7 @@ use pin_utils::unsafe_pinned;
 
 use crate::common::error::prelude::*;
 use crate::config::PoolConfig;
+use crate::pool::events::PoolEvent;
 use crate::pool::types::StateProofResult;
 
 use super::networker::{Networker, NetworkerEvent};
@@ -24,7 +25,9 @@ pub trait PoolRequest: std::fmt::Debug + Stream<Item = RequestEvent> + FusedStre
     fn extend_timeout(&self, node_alias: String, timeout: i64) -> VdrResult<()>;
     fn get_meta(&self) -> RequestResultMeta;
     fn get_timing(&self) -> Option<TimingResult>;
+    fn emit_event(&self, event: PoolEvent);
     fn is_active(&self) -> bool;
+    fn latency_percentile(&self, percentile: f32) -> Option<f32>;
     fn node_count(&self) -> usize;
     fn node_keys(&self) -> VerifierKeys;
     fn node_order(&self) -> Vec<String>;
@@ -109,6 +112,8 @@ where
         RequestResultMeta {
             state_proof: self.state_proof.clone(),
             timing: self.timing.result(),
+            receipt: None,
+            verification: None,
         }
     }
 
@@ -116,10 +121,21 @@ where
         self.timing.result()
     }
 
+    fn emit_event(&self, event: PoolEvent) {
+        self.pool_setup.as_ref().events.emit(event)
+    }
+
     fn is_active(&self) -> bool {
         self.state == RequestState::Active
     }
 
+    fn latency_percentile(&self, percentile: f32) -> Option<f32> {
+        self.pool_setup
+            .as_ref()
+            .health
+            .latency_percentile(percentile)
+    }
+
     fn node_order(&self) -> Vec<String> {
         self.node_order.clone()
     }
@@ -207,6 +223,23 @@ where
     }
 
     fn set_state_proof_result(&mut self, node_alias: String, res: StateProofResult) {
+        let setup = self.pool_setup.as_ref();
+        let health = &setup.health;
+        match &res {
+            StateProofResult::Verified(asserts) => {
+                health.record_state_proof(&node_alias, true);
+                setup.ledgers.record_verified(asserts);
+                setup.save_snapshot_periodic();
+            }
+            StateProofResult::Invalid(reason, _) => {
+                health.record_state_proof(&node_alias, false);
+                setup.events.emit(PoolEvent::StateProofFailed {
+                    node: node_alias.clone(),
+                    reason: reason.clone(),
+                });
+            }
+            _ => (),
+        }
         self.state_proof.insert(node_alias, res);
     }
 }
@@ -274,13 +307,23 @@ where
                                 }
                                 Some(RequestExtEvent::Received(alias, message, meta, when)) => {
                                     trace!("{} response from {}", self.handle, alias);
-                                    self.timing.received(&alias, when);
+                                    if let Some(latency) = self.timing.received(&alias, when) {
+                                        self.pool_setup
+                                            .as_ref()
+                                            .health
+                                            .record_reply(&alias, latency);
+                                    }
                                     return Poll::Ready(Some(RequestEvent::Received(
                                         alias, message, meta,
                                     )));
                                 }
                                 Some(RequestExtEvent::Timeout(alias)) => {
                                     trace!("{} timed out {}", self.handle, alias);
+                                    let setup = self.pool_setup.as_ref();
+                                    setup.health.record_timeout(&alias);
+                                    setup.events.emit(PoolEvent::NodeTimeout {
+                                        node: alias.clone(),
+                                    });
                                     return Poll::Ready(Some(RequestEvent::Timeout(alias)));
                                 }
                                 _ => {
@@ -88,14 +88,16 @@ impl RequestTiming {
             .insert(node_alias.to_owned(), (send_time, -1.0));
     }
 
-    pub fn received(&mut self, node_alias: &str, recv_time: SystemTime) {
-        if let Some(node) = self.replies.get_mut(node_alias) {
-            let duration = recv_time
-                .duration_since(node.0)
-                .unwrap_or(Duration::new(0, 0))
-                .as_secs_f32();
-            node.1 = duration;
-        }
+    /// Record a received message, returning the latency if it is the first reply from the node
+    pub fn received(&mut self, node_alias: &str, recv_time: SystemTime) -> Option<f32> {
+        let node = self.replies.get_mut(node_alias)?;
+        let first = node.1 < 0.0;
+        let duration = recv_time
+            .duration_since(node.0)
+            .unwrap_or(Duration::new(0, 0))
+            .as_secs_f32();
+        node.1 = duration;
+        first.then_some(duration)
     }
 
     pub fn result(&self) -> Option<TimingResult> {
@@ -2,6 +2,7 @@ use serde_json::{self, Value as SJsonValue};
 
 use super::new_request_id;
 use crate::common::error::prelude::*;
+use crate::config::{HedgePolicy, StateProofPolicy};
 use crate::ledger::constants::READ_REQUESTS;
 use crate::ledger::TxnAuthrAgrmtAcceptanceData;
 use crate::pool::ProtocolVersion;
@@ -9,23 +10,31 @@ use crate::state_proof::{
     constants::REQUEST_FOR_FULL, parse_key_from_request_for_builtin_sp,
     parse_timestamp_from_req_for_builtin_sp, BoxedSPParser,
 };
+use crate::tools::did::Did;
+use crate::tools::wallet::Wallet;
 use crate::utils::base58;
 use crate::utils::did::{DidValue, DEFAULT_LIBINDY_DID};
+use crate::utils::futures::block_on;
 use crate::utils::txn_signature::serialize_signature;
 use crate::utils::Validatable;
 
 /// Determines the handler and state proof semantics used to process a request
-#[derive(PartialEq, Eq)]
+///
+/// The `hedge_policy` of a state proof method overrides the read hedging policy
+/// of the pool configuration.
+#[derive(PartialEq)]
 pub enum RequestMethod {
     Consensus,
     ReadConsensus,
     BuiltinStateProof {
         sp_key: Vec<u8>,
         sp_timestamps: (Option<u64>, Option<u64>),
+        hedge_policy: Option<HedgePolicy>,
     },
     CustomStateProof {
         sp_parser: BoxedSPParser,
         sp_timestamps: (Option<u64>, Option<u64>),
+        hedge_policy: Option<HedgePolicy>,
     },
     Full {
         node_aliases: Option<Vec<String>>,
@@ -33,6 +42,42 @@ pub enum RequestMethod {
     },
 }
 
+impl RequestMethod {
+    /// Check whether a request may be hedged, completing on the first verified state proof
+    pub fn supports_hedging(&self) -> bool {
+        matches!(
+            self,
+            Self::BuiltinStateProof { .. } | Self::CustomStateProof { .. }
+        )
+    }
+
+    /// Get the hedging policy override of the request
+    pub fn hedge_policy(&self) -> Option<HedgePolicy> {
+        match self {
+            Self::BuiltinStateProof { hedge_policy, .. }
+            | Self::CustomStateProof { hedge_policy, .. } => *hedge_policy,
+            _ => None,
+        }
+    }
+
+    /// Override the read hedging policy of the pool configuration for the request
+    pub fn set_hedge_policy(&mut self, policy: Option<HedgePolicy>) -> VdrResult<()> {
+        if let Some(policy) = policy.as_ref() {
+            policy.validate()?;
+        }
+        match self {
+            Self::BuiltinStateProof { hedge_policy, .. }
+            | Self::CustomStateProof { hedge_policy, .. } => {
+                *hedge_policy = policy;
+                Ok(())
+            }
+            _ => Err(input_err(
+                "Hedging is only supported for state proof requests",
+            )),
+        }
+    }
+}
+
 impl std::fmt::Debug for RequestMethod {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let desc = match self {
@@ -59,6 +104,10 @@ pub struct PreparedRequest {
     pub req_json: SJsonValue,
     /// Determine the request handler to use
     pub method: RequestMethod,
+    /// Attach a `LedgerReceipt` to the result when the reply has a verified state proof
+    pub receipt: bool,
+    /// The verification of state proofs in the replies to a read request
+    pub state_proof_policy: StateProofPolicy,
 }
 
 impl PreparedRequest {
@@ -77,6 +126,8 @@ impl PreparedRequest {
             req_id,
             req_json,
             method,
+            receipt: false,
+            state_proof_policy: StateProofPolicy::default(),
         }
     }
 
@@ -132,6 +183,68 @@ impl PreparedRequest {
         Ok(())
     }
 
+    /// Check that a DID is allowed to sign the prepared request
+    ///
+    /// A single signature must come from the request identifier, while a
+    /// multi-signature may also come from the endorser.
+    pub fn check_signer(&self, did: &DidValue, multi: bool) -> VdrResult<()> {
+        let signer = did.to_short().to_string();
+        let identifier = self.req_json["identifier"].as_str();
+        if identifier == Some(signer.as_str()) {
+            return Ok(());
+        }
+        if multi && self.req_json["endorser"].as_str() == Some(signer.as_str()) {
+            return Ok(());
+        }
+        Err(input_err(format!(
+            "Signing DID {} does not match the request identifier{}",
+            did,
+            if multi { " or endorser" } else { "" }
+        )))
+    }
+
+    /// Sign the prepared request using the key of a DID held in a wallet
+    pub fn sign_with_wallet(&mut self, wallet: &Wallet, did: &DidValue) -> VdrResult<()> {
+        block_on(self.sign_with_wallet_async(wallet, did))
+    }
+
+    /// Sign the prepared request using the key of a DID held in a wallet
+    pub async fn sign_with_wallet_async(
+        &mut self,
+        wallet: &Wallet,
+        did: &DidValue,
+    ) -> VdrResult<()> {
+        self.wallet_sign(wallet, did, false).await
+    }
+
+    /// Add a signature to the prepared request using the key of a DID held in a wallet
+    pub fn multi_sign_with_wallet(&mut self, wallet: &Wallet, did: &DidValue) -> VdrResult<()> {
+        block_on(self.multi_sign_with_wallet_async(wallet, did))
+    }
+
+    /// Add a signature to the prepared request using the key of a DID held in a wallet
+    pub async fn multi_sign_with_wallet_async(
+        &mut self,
+        wallet: &Wallet,
+        did: &DidValue,
+    ) -> VdrResult<()> {
+        self.wallet_sign(wallet, did, true).await
+    }
+
+    async fn wallet_sign(&mut self, wallet: &Wallet, did: &DidValue, multi: bool) -> VdrResult<()> {
+        self.check_signer(did, multi)?;
+        let sig_input = self.get_signature_input()?;
+        let signature = Did::sign_async(wallet, &did.to_string(), sig_input.as_bytes()).await?;
+        if self.get_signature_input()? != sig_input {
+            return Err(input_err("Request was modified while it was being signed"));
+        }
+        if multi {
+            self.set_multi_signature(&DidValue(did.to_short().to_string()), &signature)
+        } else {
+            self.set_signature(&signature)
+        }
+    }
+
     /// Decorate the prepared request with the transaction author agreement acceptance
     pub fn set_txn_author_agreement_acceptance(
         &mut self,
@@ -196,6 +309,7 @@ impl PreparedRequest {
             sp_key.map(|sp_key| RequestMethod::BuiltinStateProof {
                 sp_key,
                 sp_timestamps,
+                hedge_policy: None,
             })
         };
 
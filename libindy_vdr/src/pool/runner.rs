@@ -1,26 +1,39 @@
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::thread;
 
 use futures_channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures_channel::oneshot;
 use futures_executor::block_on;
+use futures_util::future::{Fuse, FusedFuture, LocalBoxFuture};
 use futures_util::stream::{FuturesUnordered, StreamExt};
 use futures_util::{select, FutureExt};
 
+use super::events::{EventSubscription, PoolEventListener};
+use super::health::NodeHealthStatus;
 use super::helpers::{perform_ledger_request, perform_refresh};
 use super::networker::{Networker, NetworkerFactory};
+use super::refresh::{PoolRefreshEvent, RefreshListener, RefreshSchedule, RefreshTrigger};
 use super::requests::PreparedRequest;
+use super::snapshot::{PoolSnapshot, SnapshotStore};
 use super::types::{RequestResult, RequestResultMeta, Verifiers};
 use super::{LocalPool, Pool, PoolTransactions};
 
 use crate::common::error::prelude::*;
+use crate::common::handle::ResourceHandle;
 use crate::common::merkle_tree::MerkleTree;
 use crate::config::PoolConfig;
 use crate::utils::base58;
+use crate::utils::futures::start_timer;
 
 /// The `PoolRunner` instance creates a separate thread for handling pool events,
 /// allowing the use of callbacks instead of async functions for interacting
 /// with the pool as well as simplifying validator pool refreshes.
+///
+/// When the configuration includes a `refresh_policy`, the pool transactions are
+/// refreshed in the background and the updated pool is swapped in without
+/// interrupting pending requests.
 pub struct PoolRunner {
     sender: Option<UnboundedSender<PoolEvent>>,
     worker: Option<thread::JoinHandle<()>>,
@@ -40,16 +53,23 @@ impl PoolRunner {
     {
         let (sender, receiver) = unbounded();
         let worker = thread::spawn(move || {
+            let factory: Box<dyn NetworkerFactory<Output = Rc<dyn Networker>>> =
+                Box::new(networker_factory);
             // FIXME handle error on build
             let pool = LocalPool::build(
                 config.clone(),
                 merkle_tree,
-                networker_factory,
-                node_weights,
+                factory.as_ref(),
+                node_weights.clone(),
                 refreshed,
             )
             .unwrap();
-            let mut thread = PoolThread::new(pool, receiver);
+            let refresh = config.refresh_policy.map(|policy| BackgroundRefresh {
+                factory,
+                node_weights,
+                schedule: RefreshSchedule::new(policy, &pool),
+            });
+            let mut thread = PoolThread::new(pool, receiver, refresh);
             thread.run();
             debug!("Pool thread ended")
         });
@@ -80,6 +100,41 @@ impl PoolRunner {
         self.send_event(PoolEvent::Refresh(callback))
     }
 
+    /// Capture the persistent state of the pool.
+    pub fn get_snapshot(&self, callback: Callback<GetSnapshotResponse>) -> VdrResult<()> {
+        self.send_event(PoolEvent::GetSnapshot(callback))
+    }
+
+    /// Restore the state of the pool from a persisted snapshot.
+    pub fn restore_snapshot(&self, snapshot: PoolSnapshot) -> VdrResult<()> {
+        self.send_event(PoolEvent::RestoreSnapshot(snapshot))
+    }
+
+    /// Persist snapshots of the pool state to a store, including pools replaced by
+    /// background refreshes. The transactions found by background refreshes are also stored.
+    pub fn persist_snapshots(&self, store: Arc<SnapshotStore>) -> VdrResult<()> {
+        self.send_event(PoolEvent::PersistSnapshots(store))
+    }
+
+    /// Set the callback receiving the changes applied by background refreshes.
+    pub fn set_refresh_listener(&self, listener: Option<RefreshListener>) -> VdrResult<()> {
+        self.send_event(PoolEvent::SetRefreshListener(listener))
+    }
+
+    /// Register a listener for the events reported by the pool.
+    ///
+    /// The listener is retained when the pool is replaced by a background refresh.
+    pub fn subscribe(&self, listener: PoolEventListener) -> VdrResult<EventSubscription> {
+        let sub = EventSubscription::next();
+        self.send_event(PoolEvent::Subscribe(sub, listener))?;
+        Ok(sub)
+    }
+
+    /// Remove a listener for pool events.
+    pub fn unsubscribe(&self, sub: EventSubscription) -> VdrResult<()> {
+        self.send_event(PoolEvent::Unsubscribe(sub))
+    }
+
     /// Submit a request to the validator pool.
     pub fn send_request(
         &self,
@@ -125,6 +180,8 @@ type GetTxnsResponse = VdrResult<Vec<String>>;
 
 type GetVerifiersResponse = VdrResult<Verifiers>;
 
+type GetSnapshotResponse = VdrResult<PoolSnapshot>;
+
 type RefreshResponse = VdrResult<(Option<PoolTransactions>, RequestResultMeta)>;
 
 type SendReqResponse = VdrResult<(RequestResult<String>, RequestResultMeta)>;
@@ -133,6 +190,12 @@ enum PoolEvent {
     GetStatus(Callback<GetStatusResponse>),
     GetTransactions(Callback<GetTxnsResponse>),
     GetVerifiers(Callback<GetVerifiersResponse>),
+    GetSnapshot(Callback<GetSnapshotResponse>),
+    RestoreSnapshot(PoolSnapshot),
+    PersistSnapshots(Arc<SnapshotStore>),
+    SetRefreshListener(Option<RefreshListener>),
+    Subscribe(EventSubscription, PoolEventListener),
+    Unsubscribe(EventSubscription),
     Refresh(Callback<RefreshResponse>),
     SendRequest(PreparedRequest, Callback<SendReqResponse>),
 }
@@ -146,6 +209,8 @@ pub struct PoolRunnerStatus {
     pub mt_size: usize,
     /// The aliases of the validator nodes
     pub nodes: Vec<String>,
+    /// The rolling health scores of the validator nodes
+    pub node_health: HashMap<String, NodeHealthStatus>,
 }
 
 impl PoolRunnerStatus {
@@ -156,14 +221,75 @@ impl PoolRunnerStatus {
     }
 }
 
+type RefreshOutcome = (RefreshTrigger, VdrResult<Option<PoolTransactions>>);
+
+/// The state used to refresh the pool in the background
+struct BackgroundRefresh {
+    factory: Box<dyn NetworkerFactory<Output = Rc<dyn Networker>>>,
+    node_weights: Option<HashMap<String, f32>>,
+    schedule: RefreshSchedule,
+}
+
+impl BackgroundRefresh {
+    /// The timer is driven by the shared timer thread, so no thread is started per check
+    fn check_timer(&self) -> Fuse<oneshot::Receiver<()>> {
+        start_timer(self.schedule.check_delay()).fuse()
+    }
+
+    fn check(&mut self, pool: &LocalPool) -> Option<LocalBoxFuture<'static, RefreshOutcome>> {
+        let trigger = self.schedule.check(pool)?;
+        debug!("Starting background pool refresh: {:?}", trigger);
+        let pool = pool.clone();
+        Some(
+            async move {
+                let result = perform_refresh(&pool).await.map(|(txns, _meta)| txns);
+                (trigger, result)
+            }
+            .boxed_local(),
+        )
+    }
+
+    fn rebuild(
+        &self,
+        pool: &LocalPool,
+        trigger: RefreshTrigger,
+        txns: PoolTransactions,
+    ) -> VdrResult<(LocalPool, PoolRefreshEvent)> {
+        let previous = pool.get_verifier_info()?;
+        let new_pool = LocalPool::build(
+            pool.get_config().clone(),
+            txns.merkle_tree()?,
+            self.factory.as_ref(),
+            self.node_weights.clone(),
+            true,
+        )?;
+        new_pool.get_events().inherit(pool.get_events());
+        let event = PoolRefreshEvent::new(trigger, &previous, &new_pool)?;
+        Ok((new_pool, event))
+    }
+}
+
 struct PoolThread {
     pool: LocalPool,
     receiver: UnboundedReceiver<PoolEvent>,
+    refresh: Option<BackgroundRefresh>,
+    listener: Option<RefreshListener>,
+    snapshot_store: Option<Arc<SnapshotStore>>,
 }
 
 impl PoolThread {
-    fn new(pool: LocalPool, receiver: UnboundedReceiver<PoolEvent>) -> Self {
-        Self { pool, receiver }
+    fn new(
+        pool: LocalPool,
+        receiver: UnboundedReceiver<PoolEvent>,
+        refresh: Option<BackgroundRefresh>,
+    ) -> Self {
+        Self {
+            pool,
+            receiver,
+            refresh,
+            listener: None,
+            snapshot_store: None,
+        }
     }
 
     fn run(&mut self) {
@@ -172,6 +298,11 @@ impl PoolThread {
 
     async fn run_loop(&mut self) {
         let mut futures = FuturesUnordered::new();
+        let mut refreshing: Fuse<LocalBoxFuture<'static, RefreshOutcome>> = Fuse::terminated();
+        let mut timer = match self.refresh.as_ref() {
+            Some(refresh) => refresh.check_timer(),
+            None => Fuse::terminated(),
+        };
         let receiver = &mut self.receiver;
         loop {
             select! {
@@ -183,6 +314,7 @@ impl PoolThread {
                                 mt_root: base58::encode(tree.root_hash()),
                                 mt_size: tree.count(),
                                 nodes: self.pool.get_node_aliases(),
+                                node_health: self.pool.get_node_health(),
                             };
                             callback(Ok(status));
                         }
@@ -194,15 +326,40 @@ impl PoolThread {
                             let vers = self.pool.get_verifier_info();
                             callback(vers);
                         }
+                        Some(PoolEvent::GetSnapshot(callback)) => {
+                            callback(Ok(self.pool.get_snapshot()));
+                        }
+                        Some(PoolEvent::RestoreSnapshot(snapshot)) => {
+                            self.pool.restore_snapshot(&snapshot);
+                        }
+                        Some(PoolEvent::PersistSnapshots(store)) => {
+                            self.pool.persist_snapshots(store.clone());
+                            self.snapshot_store = Some(store);
+                        }
+                        Some(PoolEvent::SetRefreshListener(listener)) => {
+                            self.listener = listener;
+                        }
+                        Some(PoolEvent::Subscribe(sub, listener)) => {
+                            self.pool.get_events().subscribe_with(sub, listener);
+                        }
+                        Some(PoolEvent::Unsubscribe(sub)) => {
+                            self.pool.get_events().unsubscribe(sub);
+                        }
                         Some(PoolEvent::Refresh(callback)) => {
-                            let fut = _perform_refresh(&self.pool, callback);
+                            let fut = _perform_refresh(self.pool.clone(), callback);
                             futures.push(fut.boxed_local());
                         }
                         Some(PoolEvent::SendRequest(request, callback)) => {
-                            let fut = _perform_ledger_request(&self.pool, request, callback);
+                            let pool = self.pool.clone();
+                            let fut = _perform_ledger_request(pool, request, callback);
                             futures.push(fut.boxed_local());
                         }
-                        None => { trace!("Pool runner sender dropped") }
+                        None => {
+                            trace!("Pool runner sender dropped");
+                            // stop scheduling background refreshes so that the thread can exit
+                            self.refresh = None;
+                            timer = Fuse::terminated();
+                        }
                     }
                 }
                 req_evt = futures.next() => {
@@ -210,6 +367,47 @@ impl PoolThread {
                         Some(()) => trace!("Callback response dispatched"),
                         None => trace!("No pending callbacks")
                     }
+                    // check for refreshes triggered by node failures
+                    if let Some(refresh) = self.refresh.as_mut() {
+                        if refreshing.is_terminated() {
+                            if let Some(fut) = refresh.check(&self.pool) {
+                                refreshing = fut.fuse();
+                            }
+                        }
+                    }
+                }
+                _ = timer => {
+                    if let Some(refresh) = self.refresh.as_mut() {
+                        if refreshing.is_terminated() {
+                            if let Some(fut) = refresh.check(&self.pool) {
+                                refreshing = fut.fuse();
+                            }
+                        }
+                        timer = refresh.check_timer();
+                    }
+                }
+                (trigger, result) = refreshing => {
+                    if let Some(refresh) = self.refresh.as_mut() {
+                        match result.and_then(|txns| {
+                            txns.map(|txns| refresh.rebuild(&self.pool, trigger, txns)).transpose()
+                        }) {
+                            Ok(Some((pool, event))) => {
+                                debug!("Pool refreshed: {:?}", event);
+                                if let Some(store) = self.snapshot_store.as_ref() {
+                                    store.store_transactions(&pool.get_transactions());
+                                    pool.persist_snapshots(store.clone());
+                                }
+                                // pending requests keep a reference to the previous instance
+                                self.pool = pool;
+                                if let Some(listener) = self.listener.as_ref() {
+                                    listener(event);
+                                }
+                            }
+                            Ok(None) => debug!("Background refresh found no changes"),
+                            Err(err) => warn!("Error performing background refresh: {}", err),
+                        }
+                        refresh.schedule.reset(&self.pool);
+                    }
                 }
                 complete => break
             }
@@ -217,16 +415,16 @@ impl PoolThread {
     }
 }
 
-async fn _perform_refresh(pool: &LocalPool, callback: Callback<RefreshResponse>) {
-    let result = perform_refresh(pool).await;
+async fn _perform_refresh(pool: LocalPool, callback: Callback<RefreshResponse>) {
+    let result = perform_refresh(&pool).await;
     callback(result);
 }
 
 async fn _perform_ledger_request(
-    pool: &LocalPool,
+    pool: LocalPool,
     request: PreparedRequest,
     callback: Callback<SendReqResponse>,
 ) {
-    let result = perform_ledger_request(pool, &request).await;
+    let result = perform_ledger_request(&pool, &request).await;
     callback(result);
 }
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::common::merkle_tree::MerkleTree;
+use crate::utils::base58;
+
+use super::genesis::{PoolTransactions, PoolTransactionsCache};
+use super::health::NodeHealthRecord;
+use super::types::{StateProofAssertions, Verifiers};
+
+/// The format version of persisted pool snapshots.
+///
+/// Snapshots with a different version are discarded when loaded.
+pub const POOL_SNAPSHOT_VERSION: u32 = 1;
+
+/// The minimum interval between snapshots persisted while a pool is in use
+pub const SNAPSHOT_SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The last verified status of a ledger
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedLedgerStatus {
+    /// The merkle root hash of the ledger transactions
+    pub root_hash: String,
+    /// The number of ledger transactions, when known
+    pub size: Option<u64>,
+    /// The time of the last verified multi-signature, in seconds since the Unix epoch
+    pub timestamp: Option<u64>,
+}
+
+/// The last verified status of each ledger, indexed by ledger ID
+#[derive(Debug, Default)]
+pub struct LedgerStatuses {
+    ledgers: Mutex<HashMap<i32, VerifiedLedgerStatus>>,
+}
+
+impl LedgerStatuses {
+    /// Get the last verified status of a ledger
+    pub fn get(&self, ledger_id: i32) -> Option<VerifiedLedgerStatus> {
+        self.ledgers.lock().ok()?.get(&ledger_id).cloned()
+    }
+
+    /// Get the last verified status of all ledgers
+    pub fn all(&self) -> HashMap<i32, VerifiedLedgerStatus> {
+        self.ledgers
+            .lock()
+            .map(|ledgers| ledgers.clone())
+            .unwrap_or_default()
+    }
+
+    /// Set the status of a ledger
+    pub fn set(&self, ledger_id: i32, status: VerifiedLedgerStatus) {
+        if let Ok(mut ledgers) = self.ledgers.lock() {
+            ledgers.insert(ledger_id, status);
+        }
+    }
+
+    /// Record the ledger root from a verified state proof, if it is newer than the last one
+    pub fn record_verified(&self, asserts: &StateProofAssertions) {
+        let mut ledgers = match self.ledgers.lock() {
+            Ok(ledgers) => ledgers,
+            Err(_) => return,
+        };
+        let status = ledgers.entry(asserts.ledger_id).or_default();
+        if status.timestamp.map(|ts| ts > asserts.timestamp) == Some(true) {
+            return;
+        }
+        if status.root_hash != asserts.txn_root_hash {
+            status.root_hash = asserts.txn_root_hash.clone();
+            status.size = None;
+        }
+        status.timestamp = Some(asserts.timestamp);
+    }
+
+    pub(crate) fn restore(&self, statuses: &HashMap<i32, VerifiedLedgerStatus>) {
+        if let Ok(mut ledgers) = self.ledgers.lock() {
+            for (ledger_id, status) in statuses {
+                ledgers.entry(*ledger_id).or_insert_with(|| status.clone());
+            }
+        }
+    }
+}
+
+/// The persistent state of a verifier pool, used to warm start a new pool instance
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PoolSnapshot {
+    pub version: u32,
+    /// The merkle root hash of the pool transactions
+    pub mt_root: String,
+    /// The number of pool transactions
+    pub mt_size: usize,
+    /// The last verified status of each ledger, indexed by ledger ID
+    pub ledgers: HashMap<i32, VerifiedLedgerStatus>,
+    /// The rolling statistics of the verifier nodes
+    pub node_health: HashMap<String, NodeHealthRecord>,
+    /// The BLS keys of the verifier nodes
+    pub bls_keys: HashMap<String, String>,
+    /// The time the snapshot was taken, in seconds since the Unix epoch
+    pub saved_at: u64,
+}
+
+impl PoolSnapshot {
+    /// Determine whether the snapshot was taken for the same pool transactions and verifiers
+    pub fn applies_to(&self, merkle_tree: &MerkleTree, verifiers: &Verifiers) -> bool {
+        self.version == POOL_SNAPSHOT_VERSION
+            && self.mt_root == base58::encode(merkle_tree.root_hash())
+            && self.mt_size == merkle_tree.count()
+            && self.bls_keys == encode_bls_keys(verifiers)
+    }
+
+    /// Get the time elapsed since the snapshot was taken
+    pub fn age(&self) -> Duration {
+        let saved_at = UNIX_EPOCH + Duration::from_secs(self.saved_at);
+        SystemTime::now()
+            .duration_since(saved_at)
+            .unwrap_or(Duration::ZERO)
+    }
+}
+
+#[derive(Debug, Default)]
+struct SnapshotStoreState {
+    mt_root: Option<String>,
+    last_saved: Option<Instant>,
+}
+
+/// Persists the snapshots of a pool to a `PoolTransactionsCache`, along with the
+/// transactions found by background refreshes.
+///
+/// Both are keyed by the `base` transactions the pool was created from, before any
+/// refreshes, which are also used to resolve the latest transactions from the cache.
+/// Only the most recently attached pool instance may overwrite the stored snapshot.
+pub struct SnapshotStore {
+    cache: Arc<dyn PoolTransactionsCache>,
+    base: PoolTransactions,
+    state: Mutex<SnapshotStoreState>,
+}
+
+impl SnapshotStore {
+    /// Create a new `SnapshotStore` instance.
+    pub fn new(cache: Arc<dyn PoolTransactionsCache>, base: PoolTransactions) -> Self {
+        Self {
+            cache,
+            base,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Load the stored snapshot, if any.
+    pub fn load(&self) -> Option<PoolSnapshot> {
+        self.cache
+            .load_snapshot(&self.base)
+            .map_err(|err| warn!("Error loading pool snapshot: {}", err))
+            .ok()
+            .flatten()
+    }
+
+    /// Store the latest transactions found by a refresh.
+    pub fn store_transactions(&self, latest: &PoolTransactions) {
+        if let Err(err) = self.cache.update(&self.base, latest) {
+            warn!("Error storing refreshed pool transactions: {}", err);
+        }
+    }
+
+    /// Set the merkle root of the pool instance whose snapshots are stored.
+    pub(crate) fn attach(&self, mt_root: String) {
+        if let Ok(mut state) = self.state.lock() {
+            state.mt_root.replace(mt_root);
+        }
+    }
+
+    /// Persist a snapshot, unless one was saved within `SNAPSHOT_SAVE_INTERVAL`.
+    pub(crate) fn save_periodic(&self, snapshot: impl FnOnce() -> PoolSnapshot) {
+        let due = self.state.lock().ok().map_or(false, |state| {
+            state
+                .last_saved
+                .map_or(true, |saved| saved.elapsed() >= SNAPSHOT_SAVE_INTERVAL)
+        });
+        if due {
+            self.save(&snapshot());
+        }
+    }
+
+    /// Persist a snapshot of the attached pool instance.
+    pub fn save(&self, snapshot: &PoolSnapshot) {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(_) => return,
+        };
+        if state.mt_root.as_ref() != Some(&snapshot.mt_root) {
+            trace!("Skipping snapshot of replaced pool instance");
+            return;
+        }
+        if let Err(err) = self.cache.store_snapshot(&self.base, snapshot) {
+            warn!("Error storing pool snapshot: {}", err);
+        }
+        state.last_saved.replace(Instant::now());
+    }
+}
+
+pub(crate) fn encode_bls_keys(verifiers: &Verifiers) -> HashMap<String, String> {
+    verifiers
+        .iter()
+        .filter_map(|(alias, info)| {
+            info.bls_key
+                .as_ref()
+                .map(|key| (alias.clone(), base58::encode(key.as_bytes())))
+        })
+        .collect()
+}
+
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_verified() {
+        let statuses = LedgerStatuses::default();
+        statuses.set(
+            1,
+            VerifiedLedgerStatus {
+                root_hash: "root1".to_string(),
+                size: Some(10),
+                timestamp: Some(100),
+            },
+        );
+        let mut asserts = StateProofAssertions {
+            ledger_id: 1,
+            pool_state_root_hash: "pool".to_string(),
+            state_root_hash: "state".to_string(),
+            timestamp: 200,
+            txn_root_hash: "root1".to_string(),
+        };
+        statuses.record_verified(&asserts);
+        assert_eq!(statuses.get(1).unwrap().size, Some(10));
+        assert_eq!(statuses.get(1).unwrap().timestamp, Some(200));
+
+        asserts.txn_root_hash = "root2".to_string();
+        asserts.timestamp = 150;
+        statuses.record_verified(&asserts);
+        assert_eq!(statuses.get(1).unwrap().root_hash, "root1");
+
+        asserts.timestamp = 300;
+        statuses.record_verified(&asserts);
+        let status = statuses.get(1).unwrap();
+        assert_eq!(status.root_hash, "root2");
+        assert_eq!(status.size, None);
+    }
+}
+
+#[cfg(all(test, feature = "simulated_pool"))]
+mod simulated_tests {
+    use std::sync::Arc;
+
+    use futures_executor::block_on;
+
+    use super::SnapshotStore;
+    use crate::config::PoolConfig;
+    use crate::pool::helpers::perform_refresh;
+    use crate::pool::networker::fixtures::{pool_with_nym, read_nym, simulated_pool, write_nym};
+    use crate::pool::networker::NodeFault;
+    use crate::pool::{InMemoryCache, LedgerType, Pool, PoolTransactionsCache};
+
+    #[test]
+    fn warm_start_from_snapshot() {
+        let sim = simulated_pool();
+        let (pool, builder) = pool_with_nym(&sim);
+        read_nym(&pool, &builder);
+        let snapshot = pool.get_snapshot();
+        assert!(snapshot.ledgers.contains_key(&LedgerType::DOMAIN.to_id()));
+        assert!(!snapshot.node_health.is_empty());
+
+        let restored = sim.local_pool(PoolConfig::default()).unwrap();
+        assert!(restored.restore_snapshot(&snapshot));
+        assert_eq!(restored.get_snapshot().ledgers, snapshot.ledgers);
+        sim.set_fault("Node1", Some(NodeFault::Silent)).unwrap();
+        sim.set_fault("Node2", Some(NodeFault::Silent)).unwrap();
+        // the initial status check is skipped, so no replies are required
+        let (txns, _meta) = block_on(perform_refresh(&restored)).unwrap();
+        assert!(txns.is_none());
+        assert!(!restored.take_warm_start());
+
+        sim.add_node("Node5").unwrap();
+        let other = sim.local_pool(PoolConfig::default()).unwrap();
+        assert!(!other.restore_snapshot(&snapshot));
+    }
+
+    #[test]
+    fn persist_snapshot_to_cache() {
+        let sim = simulated_pool();
+        let genesis = sim.genesis().unwrap();
+        let cache: Arc<dyn PoolTransactionsCache> = Arc::new(InMemoryCache::new());
+        let store = Arc::new(SnapshotStore::new(cache.clone(), genesis.clone()));
+        let pool = sim.local_pool(PoolConfig::default()).unwrap();
+        assert!(pool.persist_snapshots(store.clone()));
+        assert!(!pool.persist_snapshots(store.clone()));
+        let builder = pool.get_request_builder();
+        write_nym(&pool, &builder);
+        read_nym(&pool, &builder);
+        // the first verified state proof is persisted without waiting for the pool to close
+        let saved = cache.load_snapshot(&genesis).unwrap().unwrap();
+        assert!(saved.ledgers.contains_key(&LedgerType::DOMAIN.to_id()));
+
+        // a pool built from refreshed transactions stores under the same key,
+        // and the replaced instance no longer overwrites its snapshot
+        sim.add_node("Node5").unwrap();
+        let refreshed = sim.local_pool(PoolConfig::default()).unwrap();
+        assert!(refreshed.persist_snapshots(store.clone()));
+        let expected = refreshed.get_snapshot();
+        drop(refreshed);
+        drop(pool);
+        let saved = store.load().unwrap();
+        assert_eq!(saved.mt_root, expected.mt_root);
+        assert_eq!(saved.mt_size, expected.mt_size);
+
+        let restored = sim.local_pool(PoolConfig::default()).unwrap();
+        assert!(restored.restore_snapshot(&saved));
+    }
+}
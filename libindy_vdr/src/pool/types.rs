@@ -2,8 +2,12 @@ use std::cmp::Eq;
 use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 pub use indy_blssignatures::VerKey as BlsVerKey;
+use once_cell::sync::OnceCell;
 use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{self, Value as SJsonValue};
 
@@ -11,8 +15,16 @@ use crate::common::error::prelude::*;
 use crate::common::merkle_tree::MerkleTree;
 use crate::config::constants::DEFAULT_PROTOCOL_VERSION;
 use crate::config::PoolConfig;
+use crate::state_proof::LedgerReceipt;
 use crate::utils::{base58, keys::build_full_verkey};
 
+use super::events::PoolEvents;
+use super::health::NodeHealth;
+use super::snapshot::{
+    encode_bls_keys, unix_timestamp, LedgerStatuses, PoolSnapshot, SnapshotStore,
+    VerifiedLedgerStatus, POOL_SNAPSHOT_VERSION,
+};
+
 /// The Indy Node communication protocol version
 #[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
 #[repr(i64)]
@@ -96,6 +108,8 @@ pub enum LedgerType {
     DOMAIN = 1,
     /// `2` - Used for ledger configuration, such as the transaction author agreement
     CONFIG = 2,
+    /// `3` - Used for the audit log of ordered batches, recording the root of each ledger
+    AUDIT = 3,
 }
 
 impl LedgerType {
@@ -116,6 +130,7 @@ impl FromStr for LedgerType {
             "POOL" => Ok(LedgerType::POOL),
             "DOMAIN" => Ok(LedgerType::DOMAIN),
             "CONFIG" => Ok(LedgerType::CONFIG),
+            "AUDIT" => Ok(LedgerType::AUDIT),
             _ => {
                 let ival = value
                     .parse::<i32>()
@@ -134,6 +149,7 @@ impl TryFrom<i32> for LedgerType {
             x if x == LedgerType::POOL as i32 => Ok(LedgerType::POOL),
             x if x == LedgerType::DOMAIN as i32 => Ok(LedgerType::DOMAIN),
             x if x == LedgerType::CONFIG as i32 => Ok(LedgerType::CONFIG),
+            x if x == LedgerType::AUDIT as i32 => Ok(LedgerType::AUDIT),
             _ => Err(input_err(format!("Unknown ledger type: {}", value))),
         }
     }
@@ -637,6 +653,13 @@ pub struct PoolSetup {
     pub node_weights: Option<HashMap<String, f32>>,
     pub verifiers: Verifiers,
     pub refreshed: bool,
+    pub health: NodeHealth,
+    pub ledgers: LedgerStatuses,
+    /// Set when restored from a recent snapshot, until the first status check
+    pub warm_start: AtomicBool,
+    pub events: Arc<PoolEvents>,
+    /// The store receiving snapshots of the pool state, if any
+    pub snapshot_store: OnceCell<Arc<SnapshotStore>>,
 }
 
 impl PoolSetup {
@@ -647,12 +670,88 @@ impl PoolSetup {
         verifiers: Verifiers,
         refreshed: bool,
     ) -> Self {
+        let quarantine = Duration::from_secs(config.node_quarantine_timeout.max(0) as u64);
+        let ledgers = LedgerStatuses::default();
+        ledgers.set(
+            LedgerType::POOL.to_id(),
+            VerifiedLedgerStatus {
+                root_hash: base58::encode(merkle_tree.root_hash()),
+                size: Some(merkle_tree.count() as u64),
+                timestamp: None,
+            },
+        );
         Self {
             config,
             merkle_tree,
             node_weights,
             verifiers,
             refreshed,
+            health: NodeHealth::new(quarantine),
+            ledgers,
+            warm_start: AtomicBool::new(false),
+            events: Arc::new(PoolEvents::default()),
+            snapshot_store: OnceCell::new(),
+        }
+    }
+
+    /// Capture the persistent state of the pool
+    pub fn snapshot(&self) -> PoolSnapshot {
+        PoolSnapshot {
+            version: POOL_SNAPSHOT_VERSION,
+            mt_root: base58::encode(self.merkle_tree.root_hash()),
+            mt_size: self.merkle_tree.count(),
+            ledgers: self.ledgers.all(),
+            node_health: self.health.records(),
+            bls_keys: encode_bls_keys(&self.verifiers),
+            saved_at: unix_timestamp(),
+        }
+    }
+
+    /// Restore the state of the pool from a snapshot, returning `false` if it does not apply.
+    ///
+    /// A snapshot taken within `warm_start_max_age` allows the initial status check to be skipped.
+    pub fn restore(&self, snapshot: &PoolSnapshot) -> bool {
+        if !snapshot.applies_to(&self.merkle_tree, &self.verifiers) {
+            debug!("Discarding pool snapshot for different pool transactions");
+            return false;
+        }
+        self.health.restore(&snapshot.node_health);
+        self.ledgers.restore(&snapshot.ledgers);
+        let max_age = self.config.warm_start_max_age;
+        if max_age > 0 && snapshot.age() <= Duration::from_secs(max_age as u64) {
+            debug!("Restored recent pool snapshot, enabling warm start");
+            self.warm_start.store(true, Ordering::Release);
+        }
+        true
+    }
+
+    /// Check whether the initial status check may be skipped, clearing the flag
+    pub fn take_warm_start(&self) -> bool {
+        self.warm_start.swap(false, Ordering::AcqRel)
+    }
+
+    /// Persist snapshots of the pool state to a store while in use and when dropped,
+    /// returning `false` if a store was already set
+    pub fn persist_snapshots(&self, store: Arc<SnapshotStore>) -> bool {
+        if self.snapshot_store.set(store.clone()).is_err() {
+            return false;
+        }
+        store.attach(base58::encode(self.merkle_tree.root_hash()));
+        true
+    }
+
+    /// Persist a snapshot if the last one was not saved recently
+    pub fn save_snapshot_periodic(&self) {
+        if let Some(store) = self.snapshot_store.get() {
+            store.save_periodic(|| self.snapshot());
+        }
+    }
+}
+
+impl Drop for PoolSetup {
+    fn drop(&mut self) {
+        if let Some(store) = self.snapshot_store.get() {
+            store.save(&self.snapshot());
         }
     }
 }
@@ -719,10 +818,24 @@ impl std::fmt::Display for StateProofResult {
 /// Type representing timing information collected for ledger transaction request
 pub type TimingResult = HashMap<String, f32>;
 
+/// The basis on which the reply to a consensus request was accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyVerification {
+    /// The reply carried a verified state proof
+    StateProof,
+    /// Matching replies were received from `f + 1` nodes
+    Consensus,
+    /// Matching replies were accepted after state proof verification failed
+    ConsensusFallback,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct RequestResultMeta {
     pub state_proof: HashMap<String, StateProofResult>,
     pub timing: Option<TimingResult>,
+    pub receipt: Option<LedgerReceipt>,
+    pub verification: Option<ReplyVerification>,
 }
 
 /// The result of a request to a single validator node
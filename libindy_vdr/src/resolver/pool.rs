@@ -1,31 +1,55 @@
+use std::sync::Arc;
+
 use super::did::DidUrl;
 use crate::common::error::prelude::*;
 
 use crate::ledger::RequestBuilder;
-use crate::pool::{Pool, PoolRunner, RequestResult, RequestResultMeta};
+use crate::pool::{Pool, PoolRegistry, PoolRunner, RequestResult, RequestResultMeta};
 
 use super::types::*;
 use super::utils::*;
 
 /// DID (URL) Resolver interface for a pool compliant with did:indy method spec
-/// The resolver interface is bound to a specific indy network and does not evaluate
-/// the namespace part of the DID. You need to create a resolver instance for each
-/// indy network you want to support.
+/// A resolver created with `new` is bound to a specific indy network and does not evaluate
+/// the namespace part of the DID. A resolver created with `with_registry` selects the pool
+/// registered for the namespace of each DID.
 /// The `PoolResolver` uses async/await.
 pub struct PoolResolver<T: Pool> {
-    pool: T,
+    pools: ResolverPools<T>,
+}
+
+enum ResolverPools<T: Pool> {
+    Single(T),
+    Registry(Arc<PoolRegistry<T>>),
 }
 
 impl<T: Pool> PoolResolver<T> {
     pub fn new(pool: T) -> PoolResolver<T> {
-        PoolResolver { pool }
+        PoolResolver {
+            pools: ResolverPools::Single(pool),
+        }
+    }
+
+    /// Create a resolver dispatching `did:indy:<namespace>:<id>` to the registered pools
+    pub fn with_registry(registry: Arc<PoolRegistry<T>>) -> PoolResolver<T> {
+        PoolResolver {
+            pools: ResolverPools::Registry(registry),
+        }
+    }
+
+    fn get_pool(&self, did_url: &DidUrl) -> VdrResult<T> {
+        match &self.pools {
+            ResolverPools::Single(pool) => Ok(pool.clone()),
+            ResolverPools::Registry(registry) => registry.get_pool(&did_url.namespace),
+        }
     }
 
     /// Dereference a DID Url and return a serialized `DereferencingResult`
     pub async fn dereference(&self, did_url: &str) -> VdrResult<String> {
         debug!("PoolResolver: Dereference DID Url {}", did_url);
         let did_url = DidUrl::parse(did_url)?;
-        let (data, metadata) = self._resolve(&did_url).await?;
+        let pool = self.get_pool(&did_url)?;
+        let (data, metadata) = self._resolve(&pool, &did_url).await?;
 
         let content = match data {
             Result::Content(c) => Some(c),
@@ -51,7 +75,8 @@ impl<T: Pool> PoolResolver<T> {
     pub async fn resolve(&self, did: &str) -> VdrResult<String> {
         debug!("PoolResolver: Resolve DID {}", did);
         let did = DidUrl::parse(did)?;
-        let (data, metadata) = self._resolve(&did).await?;
+        let pool = self.get_pool(&did)?;
+        let (data, metadata) = self._resolve(&pool, &did).await?;
 
         let md = if let Metadata::DidDocumentMetadata(md) = metadata {
             Some(md)
@@ -85,7 +110,7 @@ impl<T: Pool> PoolResolver<T> {
                     } else {
                         (None, None)
                     };
-                    doc.endpoint = fetch_legacy_endpoint(&self.pool, &did.id, seq_no, timestamp)
+                    doc.endpoint = fetch_legacy_endpoint(&pool, &did.id, seq_no, timestamp)
                         .await
                         .ok();
                 }
@@ -104,14 +129,14 @@ impl<T: Pool> PoolResolver<T> {
     }
 
     // Internal method to resolve and dereference
-    async fn _resolve(&self, did_url: &DidUrl) -> VdrResult<(Result, Metadata)> {
-        let builder = self.pool.get_request_builder();
+    async fn _resolve(&self, pool: &T, did_url: &DidUrl) -> VdrResult<(Result, Metadata)> {
+        let builder = pool.get_request_builder();
         let request = build_request(did_url, &builder)?;
         debug!(
             "PoolResolver: Prepared Request for DID {}: {:#?}",
             did_url.id, request
         );
-        let ledger_data = handle_request(&self.pool, &request).await?;
+        let ledger_data = handle_request(pool, &request).await?;
         let namespace = did_url.namespace.clone();
         let result = handle_internal_resolution_result(namespace.as_str(), &ledger_data)?;
 
@@ -1,8 +1,12 @@
 extern crate rlp;
 
 pub(crate) mod constants;
-mod node;
+pub(crate) mod node;
+mod receipt;
+/// State proof specifications for custom read transaction types
+mod registry;
 pub(crate) mod types;
+mod verifier;
 
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -25,7 +29,13 @@ use self::constants::{
 use self::node::{Node, TrieDB};
 use self::types::*;
 
+pub use receipt::LedgerReceipt;
+pub use registry::{
+    get_state_proof_spec, register_state_proof_spec, unregister_state_proof_spec, StateKeyFn,
+    StateProofSpec, StateValueHashing,
+};
 pub use types::ParsedSP;
+pub use verifier::StateProofVerifier;
 
 /// A `StateProofParser` appropriate for attaching to a `PreparedRequest`
 pub struct BoxedSPParser(Box<dyn StateProofParser + Send + Sync>);
@@ -132,6 +142,23 @@ pub(crate) fn check_freshness(
     requested_timestamps: (Option<u64>, Option<u64>),
     last_write_time: u64,
     threshold: u64,
+) -> bool {
+    check_freshness_at(
+        msg_result,
+        requested_timestamps,
+        last_write_time,
+        threshold,
+        get_cur_time(),
+    )
+}
+
+/// Check the freshness of a reply relative to the given time, in seconds since the epoch
+pub(crate) fn check_freshness_at(
+    msg_result: &SJsonValue,
+    requested_timestamps: (Option<u64>, Option<u64>),
+    last_write_time: u64,
+    threshold: u64,
+    cur_time: u64,
 ) -> bool {
     trace!(
         "check_freshness: requested_timestamps: {:?} >>",
@@ -174,7 +201,7 @@ pub(crate) fn check_freshness(
             trace!("Last right signed time: {}", last_write_time);
 
             let left_time_for_freshness_check = from;
-            let time_for_freshness_check = get_cur_time();
+            let time_for_freshness_check = cur_time;
 
             trace!(
                 "Left time for freshness check: {}",
@@ -186,7 +213,7 @@ pub(crate) fn check_freshness(
                 && time_for_freshness_check <= threshold + last_write_time
         }
         (None, None) => {
-            let time_for_freshness_check = get_cur_time();
+            let time_for_freshness_check = cur_time;
 
             trace!("Last signed time: {}", last_write_time);
             trace!("Time for freshness check: {}", time_for_freshness_check);
@@ -271,6 +298,8 @@ pub(crate) fn parse_generic_reply_for_proof_checking(
         }
     } else if let Some(custom_state_proof_parser_) = custom_state_proof_parser {
         custom_state_proof_parser_.parse(type_, raw_msg)
+    } else if let (Some(spec), Some(sp_key)) = (get_state_proof_spec(type_), sp_key) {
+        spec.parse_reply(json_msg, sp_key)
     } else {
         trace!("parse_generic_reply_for_proof_checking: <<< type not supported");
         None
@@ -563,6 +592,10 @@ pub(crate) fn parse_key_from_request_for_builtin_sp(
             }
         }
         _ => {
+            if let Some(spec) = get_state_proof_spec(type_) {
+                trace!("parse_key_from_request_for_builtin_sp: <<< Registered transaction");
+                return spec.derive_key(json_msg, protocol_version);
+            }
             trace!("parse_key_from_request_for_builtin_sp: <<< Unsupported transaction");
             return None;
         }
@@ -616,7 +649,9 @@ pub(crate) fn parse_timestamp_from_req_for_builtin_sp(
     op: &str,
 ) -> (Option<u64>, Option<u64>) {
     if !REQUESTS_FOR_STATE_PROOFS_IN_THE_PAST.contains(&op) {
-        return (None, None);
+        return get_state_proof_spec(op)
+            .map(|spec| spec.timestamps(&req["operation"]))
+            .unwrap_or((None, None));
     }
 
     if op == constants::GET_TXN {
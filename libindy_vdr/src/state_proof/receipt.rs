@@ -0,0 +1,239 @@
+use serde_json::Value as SJsonValue;
+
+use crate::common::error::prelude::*;
+use crate::pool::{PreparedRequest, RequestMethod};
+
+use super::{get_cur_time, parse_generic_reply_for_proof_checking};
+
+/// A portable record of a ledger reply and the state proof establishing it.
+///
+/// Receipts may be checked without contacting the pool using
+/// `StateProofVerifier::verify_receipt`, or `StateProofVerifier::verify_receipt_with`
+/// for requests using a custom state proof parser.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LedgerReceipt {
+    /// The request body
+    pub request: SJsonValue,
+    /// The reply result, excluding the state proof
+    pub reply: SJsonValue,
+    /// The root hash of the ledger state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_hash: Option<String>,
+    /// The encoded trie nodes proving the reply value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_nodes: Option<String>,
+    /// The BLS multi-signature of the ledger state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multi_signature: Option<SJsonValue>,
+    /// The aliases of the nodes participating in the multi-signature
+    pub participants: Vec<String>,
+    /// The base58-encoded root of the pool transactions merkle tree
+    pub pool_root: String,
+    /// The number of pool transactions
+    pub pool_size: usize,
+    /// The time the receipt was created, in seconds since the epoch.
+    ///
+    /// This is not signed, so freshness is checked against the time of the multi-signature.
+    pub timestamp: u64,
+}
+
+impl LedgerReceipt {
+    /// Create a receipt for a reply containing a state proof
+    pub fn new(
+        request: &PreparedRequest,
+        reply: &str,
+        pool_root: String,
+        pool_size: usize,
+    ) -> VdrResult<Self> {
+        let (sp_key, sp_parser) = match &request.method {
+            RequestMethod::BuiltinStateProof { sp_key, .. } => (Some(sp_key.as_slice()), None),
+            RequestMethod::CustomStateProof { sp_parser, .. } => (None, Some(sp_parser)),
+            _ => return Err(input_err("Request does not support ledger receipts")),
+        };
+        let message: SJsonValue =
+            serde_json::from_str(reply).with_input_err("Invalid reply JSON")?;
+        if message["op"].as_str() != Some("REPLY") {
+            return Err(input_err("Expected a REPLY message"));
+        }
+        let mut result = message["result"].clone();
+        let parsed_sps = parse_generic_reply_for_proof_checking(&result, reply, sp_key, sp_parser)
+            .ok_or_else(|| input_err("Reply does not contain a state proof"))?;
+        let participants = parsed_sps
+            .first()
+            .and_then(|sp| sp.multi_signature["participants"].as_array())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|alias| alias.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let state_proof = result
+            .as_object_mut()
+            .and_then(|obj| obj.remove("state_proof"))
+            .unwrap_or_default();
+        Ok(Self {
+            request: request.req_json.clone(),
+            reply: result,
+            root_hash: state_proof["root_hash"].as_str().map(str::to_string),
+            proof_nodes: state_proof["proof_nodes"].as_str().map(str::to_string),
+            multi_signature: state_proof.get("multi_signature").cloned(),
+            participants,
+            pool_root,
+            pool_size,
+            timestamp: get_cur_time(),
+        })
+    }
+
+    /// Reconstruct the ledger reply message
+    pub fn to_reply(&self) -> SJsonValue {
+        let mut state_proof = serde_json::Map::new();
+        if let Some(root_hash) = &self.root_hash {
+            state_proof.insert("root_hash".to_string(), root_hash.as_str().into());
+        }
+        if let Some(proof_nodes) = &self.proof_nodes {
+            state_proof.insert("proof_nodes".to_string(), proof_nodes.as_str().into());
+        }
+        if let Some(multi_signature) = &self.multi_signature {
+            state_proof.insert("multi_signature".to_string(), multi_signature.clone());
+        }
+        let mut result = self.reply.clone();
+        if !state_proof.is_empty() {
+            if let Some(obj) = result.as_object_mut() {
+                obj.insert("state_proof".to_string(), state_proof.into());
+            }
+        }
+        json!({"op": "REPLY", "result": result})
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::RequestBuilder;
+    use crate::utils::did::DidValue;
+
+    #[test]
+    fn test_receipt_round_trip() {
+        let request = RequestBuilder::default()
+            .build_get_nym_request(
+                None,
+                &DidValue("V4SGRU86Z58d6TV7PBUe6f".to_string()),
+                None,
+                None,
+            )
+            .unwrap();
+        let reply = json!({
+            "op": "REPLY",
+            "result": {
+                "type": "105",
+                "dest": "V4SGRU86Z58d6TV7PBUe6f",
+                "data": null,
+                "state_proof": {
+                    "root_hash": "root",
+                    "proof_nodes": "nodes",
+                    "multi_signature": {
+                        "participants": ["Node1", "Node2", "Node3"],
+                        "signature": "sig",
+                        "value": {}
+                    }
+                }
+            }
+        });
+        let receipt =
+            LedgerReceipt::new(&request, &reply.to_string(), "pool".to_string(), 4).unwrap();
+        assert_eq!(receipt.root_hash.as_deref(), Some("root"));
+        assert_eq!(receipt.participants, ["Node1", "Node2", "Node3"]);
+        assert!(receipt.reply.get("state_proof").is_none());
+        assert_eq!(receipt.to_reply(), reply);
+
+        let encoded = serde_json::to_string(&receipt).unwrap();
+        assert_eq!(
+            serde_json::from_str::<LedgerReceipt>(&encoded).unwrap(),
+            receipt
+        );
+    }
+
+    #[test]
+    fn test_receipt_missing_state_proof() {
+        let request = RequestBuilder::default()
+            .build_get_nym_request(
+                None,
+                &DidValue("V4SGRU86Z58d6TV7PBUe6f".to_string()),
+                None,
+                None,
+            )
+            .unwrap();
+        let reply = json!({
+            "op": "REPLY",
+            "result": {"type": "105", "dest": "V4SGRU86Z58d6TV7PBUe6f", "data": null}
+        });
+        assert!(LedgerReceipt::new(&request, &reply.to_string(), "pool".to_string(), 4).is_err());
+    }
+}
+
+#[cfg(all(test, feature = "simulated_pool"))]
+mod simulated_tests {
+    use std::time::SystemTime;
+
+    use super::LedgerReceipt;
+    use crate::config::PoolConfig;
+    use crate::pool::networker::fixtures::{
+        get_nym_request, pool_with_nym, read_nym, simulated_pool, VERKEY,
+    };
+    use crate::pool::{Pool, StateProofResult};
+    use crate::state_proof::StateProofVerifier;
+
+    #[test]
+    fn verify_ledger_receipt() {
+        let sim = simulated_pool();
+        let (pool, builder) = pool_with_nym(&sim);
+        let (reply, _) = read_nym(&pool, &builder);
+        let request = get_nym_request(&builder);
+        let (mt_root, mt_size) = pool.get_merkle_tree_info();
+        let receipt = LedgerReceipt::new(&request, &reply, mt_root, mt_size).unwrap();
+        let verifier = StateProofVerifier::from_transactions(
+            &sim.genesis().unwrap(),
+            pool.get_config().protocol_version,
+        )
+        .unwrap();
+        assert!(verifier.verify_receipt(&receipt).unwrap().is_verified());
+
+        // a verifier without the pool transactions cannot check the receipt pool
+        let keys_only = StateProofVerifier::new(&pool.get_verifier_info().unwrap());
+        assert!(keys_only.verify_receipt(&receipt).is_err());
+
+        let mut tampered = receipt.clone();
+        let data = tampered.reply["data"].as_str().unwrap().to_string();
+        tampered.reply["data"] = data
+            .replace(VERKEY, "7Sqc3ne7jtTsjLJbHXzHeRz2PSVWnQQpg8LtFdjVBgBW")
+            .into();
+        assert!(matches!(
+            verifier.verify_receipt(&tampered).unwrap(),
+            StateProofResult::Invalid(..)
+        ));
+
+        // the unsigned receipt timestamp does not affect freshness
+        let later = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 10 * PoolConfig::default_freshness_threshold();
+        let mut tampered = receipt.clone();
+        tampered.timestamp = later;
+        assert!(matches!(
+            verifier.verify_receipt_at(&tampered, later).unwrap(),
+            StateProofResult::Expired(..)
+        ));
+        tampered.timestamp = 0;
+        assert!(verifier.verify_receipt(&tampered).unwrap().is_verified());
+
+        let mut tampered = receipt;
+        tampered.pool_root = "3Lr7kqZ3bA3tTJ9yZpEZ9PnAX4K6yfU9GkRrj1YyZR2D".to_string();
+        assert!(matches!(
+            verifier.verify_receipt(&tampered).unwrap(),
+            StateProofResult::Invalid(..)
+        ));
+    }
+}
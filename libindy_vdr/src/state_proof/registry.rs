@@ -0,0 +1,338 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use once_cell::sync::Lazy;
+use serde_json::Value as SJsonValue;
+use sha2::{Digest, Sha256};
+
+use crate::common::error::prelude::*;
+use crate::ledger::requests::auction::get_auction_state_proof_spec;
+use crate::ledger::requests::handle::get_handle_state_proof_spec;
+use crate::pool::ProtocolVersion;
+use crate::utils::base64;
+
+use super::constants::{
+    GET_AUCTION, GET_HANDLE, READ_REQUESTS, REQUESTS, REQUESTS_FOR_STATE_PROOFS,
+};
+use super::types::{KeyValueSimpleData, KeyValueSimpleDataVerificationType, KeyValuesInSP};
+use super::ParsedSP;
+
+static STATE_PROOF_SPECS: Lazy<RwLock<HashMap<String, StateProofSpec>>> = Lazy::new(|| {
+    let mut specs = HashMap::new();
+    specs.insert(GET_HANDLE.to_string(), get_handle_state_proof_spec());
+    specs.insert(GET_AUCTION.to_string(), get_auction_state_proof_spec());
+    RwLock::new(specs)
+});
+
+/// A callback deriving the state key from a request operation
+pub type StateKeyFn = Arc<dyn Fn(&SJsonValue, ProtocolVersion) -> Option<Vec<u8>> + Send + Sync>;
+
+/// The transformation applied to a reply value before it is checked against the state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateValueHashing {
+    /// The value is stored as-is
+    None,
+    /// The hex-encoded SHA-256 digest of the value is stored
+    Sha256,
+}
+
+/// Declares how state proofs are checked for a custom read transaction type
+#[derive(Clone)]
+pub struct StateProofSpec {
+    key: Option<StateKeyFn>,
+    value_path: Option<String>,
+    hashing: StateValueHashing,
+    metadata: bool,
+    timestamp_field: Option<String>,
+}
+
+impl StateProofSpec {
+    /// Create a new specification which checks the reply data with its
+    /// `lsn` and `lut` metadata, as for most built-in transactions
+    pub fn new() -> Self {
+        Self {
+            key: None,
+            value_path: None,
+            hashing: StateValueHashing::None,
+            metadata: true,
+            timestamp_field: None,
+        }
+    }
+
+    /// Derive the state key from the request operation.
+    ///
+    /// The key is only used when the request type does not provide one.
+    pub fn key<F>(mut self, key: F) -> Self
+    where
+        F: Fn(&SJsonValue, ProtocolVersion) -> Option<Vec<u8>> + Send + Sync + 'static,
+    {
+        self.key = Some(Arc::new(key));
+        self
+    }
+
+    /// Check the value at a JSON pointer within the reply data, such as `/value`
+    pub fn value_path(mut self, path: impl Into<String>) -> Self {
+        self.value_path = Some(path.into());
+        self
+    }
+
+    /// Set the transformation applied to the reply value
+    pub fn hashing(mut self, hashing: StateValueHashing) -> Self {
+        self.hashing = hashing;
+        self
+    }
+
+    /// Set whether the stored value is wrapped with the `lsn` and `lut` metadata
+    pub fn metadata(mut self, metadata: bool) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Read the requested state timestamp from a field of the request operation,
+    /// for reads which may return a past state
+    pub fn timestamp_field(mut self, field: impl Into<String>) -> Self {
+        self.timestamp_field = Some(field.into());
+        self
+    }
+
+    pub(crate) fn derive_key(
+        &self,
+        operation: &SJsonValue,
+        protocol_version: ProtocolVersion,
+    ) -> Option<Vec<u8>> {
+        self.key
+            .as_ref()
+            .and_then(|key| key(operation, protocol_version))
+    }
+
+    pub(crate) fn timestamps(&self, operation: &SJsonValue) -> (Option<u64>, Option<u64>) {
+        let timestamp = self
+            .timestamp_field
+            .as_ref()
+            .and_then(|field| operation[field.as_str()].as_u64());
+        (None, timestamp)
+    }
+
+    pub(crate) fn parse_reply(
+        &self,
+        json_msg: &SJsonValue,
+        sp_key: &[u8],
+    ) -> Option<Vec<ParsedSP>> {
+        let state_proof = &json_msg["state_proof"];
+        let proof_nodes = state_proof["proof_nodes"].as_str()?;
+        let root_hash = state_proof["root_hash"].as_str()?;
+        let value = self.state_value(json_msg)?;
+        Some(vec![ParsedSP {
+            root_hash: root_hash.to_string(),
+            proof_nodes: proof_nodes.to_string(),
+            multi_signature: state_proof["multi_signature"].clone(),
+            kvs_to_verify: KeyValuesInSP::Simple(KeyValueSimpleData {
+                kvs: vec![(base64::encode(sp_key), value)],
+                verification_type: KeyValueSimpleDataVerificationType::Simple,
+            }),
+        }])
+    }
+
+    /// Determine the expected state value, or `Some(None)` when the reply proves absence
+    fn state_value(&self, json_msg: &SJsonValue) -> Option<Option<String>> {
+        // keep the original string to avoid hashing a re-encoded value
+        let (raw, parsed) = match &json_msg["data"] {
+            SJsonValue::Null => return Some(None),
+            SJsonValue::String(data) => (data.clone(), serde_json::from_str(data).ok()?),
+            data => (data.to_string(), data.clone()),
+        };
+        let value = match self.value_path.as_deref() {
+            Some(path) => match parsed.pointer(path) {
+                Some(SJsonValue::Null) | None => return Some(None),
+                Some(value) => value.clone(),
+            },
+            None => parsed,
+        };
+        let value = match self.hashing {
+            StateValueHashing::None => value,
+            StateValueHashing::Sha256 => {
+                let text = match (self.value_path.is_some(), value.as_str()) {
+                    (false, _) => raw,
+                    (true, Some(text)) => text.to_string(),
+                    (true, None) => value.to_string(),
+                };
+                SJsonValue::from(hex::encode(Sha256::digest(text.as_bytes())))
+            }
+        };
+        let value = if self.metadata {
+            json!({
+                "lsn": json_msg["seqNo"],
+                "lut": json_msg["txnTime"],
+                "val": value,
+            })
+        } else {
+            value
+        };
+        Some(Some(match value {
+            SJsonValue::String(value) => value,
+            value => value.to_string(),
+        }))
+    }
+}
+
+impl Default for StateProofSpec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for StateProofSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateProofSpec")
+            .field("key", &self.key.is_some())
+            .field("value_path", &self.value_path)
+            .field("hashing", &self.hashing)
+            .field("metadata", &self.metadata)
+            .field("timestamp_field", &self.timestamp_field)
+            .finish()
+    }
+}
+
+/// Register state proof checking for a read transaction type.
+///
+/// Transaction types with built-in state proof rules and known write
+/// transaction types are rejected.
+pub fn register_state_proof_spec(txn_type: &str, spec: StateProofSpec) -> VdrResult<()> {
+    if REQUESTS_FOR_STATE_PROOFS.contains(&txn_type) {
+        return Err(input_err(format!(
+            "Transaction type {} has built-in state proof support",
+            txn_type
+        )));
+    }
+    if REQUESTS.contains(&txn_type) && !READ_REQUESTS.contains(&txn_type) {
+        return Err(input_err(format!(
+            "Transaction type {} is not a read request",
+            txn_type
+        )));
+    }
+    let mut specs = STATE_PROOF_SPECS
+        .write()
+        .map_err(|_| err_msg(VdrErrorKind::Unexpected, "Error locking state proof specs"))?;
+    specs.insert(txn_type.to_string(), spec);
+    Ok(())
+}
+
+/// Remove the state proof specification for a transaction type, if any
+pub fn unregister_state_proof_spec(txn_type: &str) -> Option<StateProofSpec> {
+    STATE_PROOF_SPECS
+        .write()
+        .ok()
+        .and_then(|mut specs| specs.remove(txn_type))
+}
+
+/// Get the registered state proof specification for a transaction type
+pub fn get_state_proof_spec(txn_type: &str) -> Option<StateProofSpec> {
+    STATE_PROOF_SPECS
+        .read()
+        .ok()
+        .and_then(|specs| specs.get(txn_type).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_state_value() {
+        let spec = StateProofSpec::new().value_path("/value");
+        let reply = json!({
+            "type": "99980",
+            "seqNo": 5,
+            "txnTime": 1000,
+            "data": "{\"name\": \"flag\", \"value\": \"on\"}",
+            "state_proof": {"root_hash": "root", "proof_nodes": "nodes", "multi_signature": {}}
+        });
+        let parsed = spec.parse_reply(&reply, b"key").unwrap();
+        match &parsed[0].kvs_to_verify {
+            KeyValuesInSP::Simple(data) => assert_eq!(
+                data.kvs,
+                vec![(
+                    base64::encode("key"),
+                    Some(r#"{"lsn":5,"lut":1000,"val":"on"}"#.to_string())
+                )]
+            ),
+            _ => panic!("Unexpected state proof type"),
+        }
+
+        let spec = StateProofSpec::new()
+            .metadata(false)
+            .hashing(StateValueHashing::Sha256);
+        let parsed = spec.parse_reply(&reply, b"key").unwrap();
+        let expected = hex::encode(Sha256::digest(b"{\"name\": \"flag\", \"value\": \"on\"}"));
+        match &parsed[0].kvs_to_verify {
+            KeyValuesInSP::Simple(data) => assert_eq!(data.kvs[0].1, Some(expected)),
+            _ => panic!("Unexpected state proof type"),
+        }
+
+        let mut absent = reply;
+        absent["data"] = SJsonValue::Null;
+        let parsed = spec.parse_reply(&absent, b"key").unwrap();
+        match &parsed[0].kvs_to_verify {
+            KeyValuesInSP::Simple(data) => assert_eq!(data.kvs[0].1, None),
+            _ => panic!("Unexpected state proof type"),
+        }
+    }
+
+    #[test]
+    fn test_register_spec() {
+        register_state_proof_spec(
+            "99981",
+            StateProofSpec::new()
+                .key(|op, _| op["name"].as_str().map(|name| name.as_bytes().to_vec()))
+                .timestamp_field("timestamp"),
+        )
+        .unwrap();
+        let spec = get_state_proof_spec("99981").unwrap();
+        let operation = json!({"type": "99981", "name": "test", "timestamp": 10});
+        assert_eq!(
+            spec.derive_key(&operation, ProtocolVersion::Node1_4),
+            Some(b"test".to_vec())
+        );
+        assert_eq!(spec.timestamps(&operation), (None, Some(10)));
+        assert!(unregister_state_proof_spec("99981").is_some());
+        assert!(get_state_proof_spec("99981").is_none());
+    }
+
+    #[test]
+    fn test_register_spec_rejects_builtin_and_write_types() {
+        // built-in state proof
+        assert!(register_state_proof_spec("105", StateProofSpec::new()).is_err());
+        // NYM write
+        assert!(register_state_proof_spec("1", StateProofSpec::new()).is_err());
+        // AUCTION_CLOSE write
+        assert!(register_state_proof_spec("99992", StateProofSpec::new()).is_err());
+        assert!(get_state_proof_spec("1").is_none());
+    }
+
+    #[test]
+    fn test_get_handle_spec() {
+        let spec = get_state_proof_spec(GET_HANDLE).unwrap();
+        let operation = json!({"type": GET_HANDLE, "handle": "alice"});
+        assert_eq!(
+            spec.derive_key(&operation, ProtocolVersion::Node1_4),
+            Some(Sha256::digest(b"alice").to_vec())
+        );
+        assert_eq!(spec.timestamps(&operation), (None, None));
+
+        let reply = json!({
+            "type": GET_HANDLE,
+            "seqNo": 3,
+            "txnTime": 1000,
+            "data": {"dest": "V4SGRU86Z58d6TV7PBUe6f"},
+            "state_proof": {"root_hash": "root", "proof_nodes": "nodes", "multi_signature": {}}
+        });
+        let parsed = spec.parse_reply(&reply, b"key").unwrap();
+        match &parsed[0].kvs_to_verify {
+            KeyValuesInSP::Simple(data) => assert_eq!(
+                data.kvs[0].1,
+                Some(r#"{"lsn":3,"lut":1000,"val":{"dest":"V4SGRU86Z58d6TV7PBUe6f"}}"#.to_string())
+            ),
+            _ => panic!("Unexpected state proof type"),
+        }
+    }
+}
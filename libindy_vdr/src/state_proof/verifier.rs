@@ -0,0 +1,283 @@
+use serde_json::Value as SJsonValue;
+
+use crate::common::error::prelude::*;
+use crate::config::constants::DEFAULT_GENERATOR;
+use crate::config::PoolConfig;
+use crate::pool::handlers::{get_last_signed_time, min_consensus};
+use crate::pool::{
+    build_node_transaction_map, build_verifiers, PoolTransactions, PreparedRequest,
+    ProtocolVersion, RequestMethod, StateProofResult, VerifierKeys, Verifiers,
+};
+use crate::utils::base58;
+
+use super::{check_freshness_at, get_cur_time, parse_generic_reply_for_proof_checking};
+use super::{verify_parsed_sp, BoxedSPParser, LedgerReceipt};
+
+/// Verifies the state proofs of stored ledger replies without network access.
+///
+/// Replies are checked against the BLS keys of the verifier pool which signed them,
+/// so the verifier should be created from the pool transactions in effect at that time.
+#[derive(Clone, Debug)]
+pub struct StateProofVerifier {
+    bls_keys: VerifierKeys,
+    f: usize,
+    freshness_threshold: u64,
+    /// The base58-encoded root and size of the pool transactions, when known
+    pool: Option<(String, usize)>,
+}
+
+impl StateProofVerifier {
+    /// Create a verifier from the summarized verifier details of a pool
+    pub fn new(verifiers: &Verifiers) -> Self {
+        let bls_keys = verifiers
+            .iter()
+            .filter_map(|(alias, info)| {
+                info.bls_key
+                    .as_ref()
+                    .map(|bls_key| (alias.clone(), bls_key.clone()))
+            })
+            .collect();
+        Self {
+            bls_keys,
+            f: min_consensus(verifiers.len()),
+            freshness_threshold: PoolConfig::default_freshness_threshold(),
+            pool: None,
+        }
+    }
+
+    /// Create a verifier from a set of pool transactions
+    pub fn from_transactions(
+        txns: &PoolTransactions,
+        protocol_version: ProtocolVersion,
+    ) -> VdrResult<Self> {
+        let merkle_tree = txns.merkle_tree()?;
+        let txn_map = build_node_transaction_map(&merkle_tree, protocol_version)?;
+        let mut verifier = Self::new(&build_verifiers(txn_map)?);
+        verifier.pool = Some((base58::encode(merkle_tree.root_hash()), merkle_tree.count()));
+        Ok(verifier)
+    }
+
+    /// Set the maximum age of a fresh state proof, in seconds
+    pub fn freshness_threshold(mut self, threshold: u64) -> Self {
+        self.freshness_threshold = threshold;
+        self
+    }
+
+    /// Verify a stored reply to a request, checking freshness against the current time
+    pub fn verify(&self, request: &PreparedRequest, reply: &str) -> VdrResult<StateProofResult> {
+        self.verify_at(request, reply, get_cur_time())
+    }
+
+    /// Verify a stored reply to a request, checking freshness against the given time
+    /// in seconds since the epoch, such as the time the reply was received.
+    ///
+    /// The request only determines the expected state proof, so it may be rebuilt
+    /// with a different request ID.
+    pub fn verify_at(
+        &self,
+        request: &PreparedRequest,
+        reply: &str,
+        timestamp: u64,
+    ) -> VdrResult<StateProofResult> {
+        let (sp_key, sp_timestamps, sp_parser): (_, _, Option<&BoxedSPParser>) =
+            match &request.method {
+                RequestMethod::BuiltinStateProof {
+                    sp_key,
+                    sp_timestamps,
+                    ..
+                } => (Some(sp_key.as_slice()), *sp_timestamps, None),
+                RequestMethod::CustomStateProof {
+                    sp_parser,
+                    sp_timestamps,
+                    ..
+                } => (None, *sp_timestamps, Some(sp_parser)),
+                _ => return Err(input_err("Request does not support state proofs")),
+            };
+
+        let message: SJsonValue =
+            serde_json::from_str(reply).with_input_err("Invalid reply JSON")?;
+        if message["op"].as_str() != Some("REPLY") {
+            return Err(input_err("Expected a REPLY message"));
+        }
+        let result = &message["result"];
+
+        let parsed_sps =
+            match parse_generic_reply_for_proof_checking(result, reply, sp_key, sp_parser) {
+                Some(parsed_sps) => parsed_sps,
+                None => return Ok(StateProofResult::Missing),
+            };
+        Ok(
+            match verify_parsed_sp(parsed_sps, &self.bls_keys, self.f, &DEFAULT_GENERATOR) {
+                Ok((asserts, None)) => {
+                    let last_write_time = get_last_signed_time(result).unwrap_or(0);
+                    if check_freshness_at(
+                        result,
+                        sp_timestamps,
+                        last_write_time,
+                        self.freshness_threshold,
+                        timestamp,
+                    ) {
+                        StateProofResult::Verified(asserts)
+                    } else {
+                        StateProofResult::Expired(asserts)
+                    }
+                }
+                Ok((asserts, Some(verify_err))) => {
+                    StateProofResult::Invalid(verify_err, Some(asserts))
+                }
+                Err(err) => StateProofResult::Invalid(err, None),
+            },
+        )
+    }
+
+    /// Verify a ledger receipt, checking freshness against the current time
+    ///
+    /// The verifier must be created from the pool transactions the receipt was created with.
+    pub fn verify_receipt(&self, receipt: &LedgerReceipt) -> VdrResult<StateProofResult> {
+        self.verify_receipt_at(receipt, get_cur_time())
+    }
+
+    /// Verify a ledger receipt, checking freshness against the given time in seconds
+    /// since the epoch.
+    ///
+    /// Freshness is determined by the signed time of the state proof, as the `timestamp`
+    /// of the receipt itself is not signed.
+    pub fn verify_receipt_at(
+        &self,
+        receipt: &LedgerReceipt,
+        timestamp: u64,
+    ) -> VdrResult<StateProofResult> {
+        let request = PreparedRequest::from_request_json_ext(receipt.request.clone(), false, None)?;
+        self.verify_receipt_with(&request, receipt, timestamp)
+    }
+
+    /// Verify a ledger receipt for a request, such as one using a custom state proof parser,
+    /// checking freshness against the given time in seconds since the epoch.
+    pub fn verify_receipt_with(
+        &self,
+        request: &PreparedRequest,
+        receipt: &LedgerReceipt,
+        timestamp: u64,
+    ) -> VdrResult<StateProofResult> {
+        let (pool_root, pool_size) = self.pool.as_ref().ok_or_else(|| {
+            input_err(
+                "Receipts must be verified using the pool transactions they were created with",
+            )
+        })?;
+        if request.req_json["operation"] != receipt.request["operation"] {
+            return Err(input_err("Receipt was not created for the request"));
+        }
+        if *pool_root != receipt.pool_root || *pool_size != receipt.pool_size {
+            return Ok(StateProofResult::Invalid(
+                "Receipt was created for different pool transactions".to_string(),
+                None,
+            ));
+        }
+        let reply = receipt.to_reply().to_string();
+        self.verify_at(request, &reply, timestamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::RequestBuilder;
+    use crate::pool::VerifierInfo;
+    use crate::utils::did::DidValue;
+
+    fn verifier() -> StateProofVerifier {
+        let info = VerifierInfo {
+            client_addr: "127.0.0.1".into(),
+            node_addr: "127.0.0.1".into(),
+            public_key: "pk".into(),
+            enc_key: vec![],
+            bls_key: None,
+        };
+        let mut verifiers = Verifiers::new();
+        for alias in ["Node1", "Node2", "Node3", "Node4"] {
+            verifiers.insert(alias.to_string(), info.clone());
+        }
+        StateProofVerifier::new(&verifiers)
+    }
+
+    #[test]
+    fn test_verify_missing_state_proof() {
+        let request = RequestBuilder::default()
+            .build_get_nym_request(
+                None,
+                &DidValue("V4SGRU86Z58d6TV7PBUe6f".to_string()),
+                None,
+                None,
+            )
+            .unwrap();
+        let reply = json!({
+            "op": "REPLY",
+            "result": {"type": "105", "dest": "V4SGRU86Z58d6TV7PBUe6f", "data": null}
+        })
+        .to_string();
+        assert_eq!(
+            verifier().verify(&request, &reply).unwrap(),
+            StateProofResult::Missing
+        );
+        assert!(verifier()
+            .verify(&request, "{\"op\": \"REQNACK\"}")
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify_unsupported_request() {
+        let request = PreparedRequest::new(
+            ProtocolVersion::Node1_4,
+            "1".to_string(),
+            "1".to_string(),
+            json!({}),
+            Some(RequestMethod::Consensus),
+        );
+        assert!(verifier().verify(&request, "{}").is_err());
+    }
+}
+
+#[cfg(all(test, feature = "simulated_pool"))]
+mod simulated_tests {
+    use serde_json::Value as SJsonValue;
+
+    use super::StateProofVerifier;
+    use crate::pool::networker::fixtures::{
+        get_nym_request, pool_with_nym, read_nym, simulated_pool, VERKEY,
+    };
+    use crate::pool::{Pool, StateProofResult};
+
+    #[test]
+    fn verify_stored_reply() {
+        let sim = simulated_pool();
+        let (pool, builder) = pool_with_nym(&sim);
+        let (reply, _) = read_nym(&pool, &builder);
+        let request = get_nym_request(&builder);
+        let verifier = StateProofVerifier::from_transactions(
+            &sim.genesis().unwrap(),
+            pool.get_config().protocol_version,
+        )
+        .unwrap();
+        assert!(verifier.verify(&request, &reply).unwrap().is_verified());
+
+        // a different value is not covered by the proof
+        let tampered = reply.replace(VERKEY, "7Sqc3ne7jtTsjLJbHXzHeRz2PSVWnQQpg8LtFdjVBgBW");
+        assert_ne!(tampered, reply);
+        assert!(matches!(
+            verifier.verify(&request, &tampered).unwrap(),
+            StateProofResult::Invalid(..)
+        ));
+
+        // a modified multi-signature does not verify against the pool keys
+        let mut message: SJsonValue = serde_json::from_str(&reply).unwrap();
+        let signature = &mut message["result"]["state_proof"]["multi_signature"]["signature"];
+        let mut sig = signature.as_str().unwrap().to_string();
+        let last = if sig.ends_with('2') { "3" } else { "2" };
+        sig.replace_range(sig.len() - 1.., last);
+        *signature = sig.into();
+        assert!(matches!(
+            verifier.verify(&request, &message.to_string()).unwrap(),
+            StateProofResult::Invalid(..)
+        ));
+    }
+}
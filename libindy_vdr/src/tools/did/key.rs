@@ -2,10 +2,7 @@
     Copyright © 2023 Province of British Columbia
     https://digital.gov.bc.ca/digital-trust
 */
-use crate::{
-    utils::clierror::{CliError, CliResult},
-    tools::did::seed::Seed,
-};
+use crate::{common::error::prelude::*, tools::did::seed::Seed};
 
 use crate::tools::wallet::Wallet;
 use aries_askar::kms::{KeyAlg, LocalKey, SecretBytes};
@@ -18,7 +15,7 @@ impl Key {
         store: &Wallet,
         seed: Option<&str>,
         metadata: Option<&str>,
-    ) -> CliResult<Key> {
+    ) -> VdrResult<Key> {
         let keypair = match seed {
             Some(seed) => {
                 let seed = Seed::from_str(seed)?;
@@ -40,20 +37,20 @@ impl Key {
         &self.0
     }
 
-    pub fn verkey_bytes(&self) -> CliResult<SecretBytes> {
-        self.0.to_public_bytes().map_err(CliError::from)
+    pub fn verkey_bytes(&self) -> VdrResult<SecretBytes> {
+        self.0.to_public_bytes().map_err(VdrError::from)
     }
 
-    pub fn verkey(&self) -> CliResult<String> {
+    pub fn verkey(&self) -> VdrResult<String> {
         let public_key = self.0.to_public_bytes()?;
         Ok(base58::encode(public_key))
     }
 
-    pub async fn sign(store: &Wallet, id: &str, bytes: &[u8]) -> CliResult<Vec<u8>> {
+    pub async fn sign(store: &Wallet, id: &str, bytes: &[u8]) -> VdrResult<Vec<u8>> {
         store
             .fetch_key(id)
             .await?
             .sign_message(bytes, None)
-            .map_err(CliError::from)
+            .map_err(VdrError::from)
     }
 }
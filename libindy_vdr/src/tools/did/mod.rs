@@ -6,10 +6,7 @@ pub mod constants;
 pub mod key;
 pub mod seed;
 
-use crate::{
-    utils::clierror::{CliError, CliResult},
-    utils::futures::block_on,
-};
+use crate::{common::error::prelude::*, utils::futures::block_on};
 
 use crate::tools::wallet::Wallet;
 use aries_askar::{Entry, EntryTag};
@@ -33,182 +30,236 @@ pub struct DidInfo {
 }
 
 impl Did {
+    /// Blocking form of [`Did::create_async`]
     pub fn create(
         store: &Wallet,
         seed: Option<&str>,
         metadata: Option<&str>,
         method: Option<&str>,
-    ) -> CliResult<(String, String)> {
-        block_on(async move {
-            let key = Key::create(store, seed, metadata).await?;
-
-            let verkey = key.verkey()?;
-            let verkey_bytes = key.verkey_bytes()?;
-            let mut did = base58::encode(&verkey_bytes[0..16]);
-            let existing_did = Self::get_opt_record(store, &did, false).await?;
-            if existing_did.is_some() {
-                return Err(CliError::Duplicate(format!(
-                    "DID already exits in the wallet"
-                )));
-            }
-            if let Some(method) = method {
-                did = DidValue(did.to_string()).to_qualified(method)?.to_string();
-            }
+    ) -> VdrResult<(String, String)> {
+        block_on(Self::create_async(store, seed, metadata, method))
+    }
 
-            let did_info = DidInfo {
-                did: did.clone(),
-                verkey: verkey.clone(),
-                verkey_type: KEY_TYPE.to_string(),
-                method: method.map(String::from),
-                metadata: metadata.map(String::from),
-                next_verkey: None,
-            };
-
-            store
-                .store_record(
-                    CATEGORY_DID,
-                    &did_info.did,
-                    &did_info.to_bytes()?,
-                    Some(&did_info.tags()),
-                    true,
-                )
-                .await?;
+    /// Create a new DID and key in the wallet
+    pub async fn create_async(
+        store: &Wallet,
+        seed: Option<&str>,
+        metadata: Option<&str>,
+        method: Option<&str>,
+    ) -> VdrResult<(String, String)> {
+        let key = Key::create(store, seed, metadata).await?;
+
+        let verkey = key.verkey()?;
+        let verkey_bytes = key.verkey_bytes()?;
+        let mut did = base58::encode(&verkey_bytes[0..16]);
+        let existing_did = Self::get_opt_record(store, &did, false).await?;
+        if existing_did.is_some() {
+            return Err(err_msg(
+                VdrErrorKind::DidDuplicate,
+                format!("DID already exits in the wallet"),
+            ));
+        }
+        if let Some(method) = method {
+            did = DidValue(did.to_string()).to_qualified(method)?.to_string();
+        }
 
-            Ok((did, verkey))
-        })
+        let did_info = DidInfo {
+            did: did.clone(),
+            verkey: verkey.clone(),
+            verkey_type: KEY_TYPE.to_string(),
+            method: method.map(String::from),
+            metadata: metadata.map(String::from),
+            next_verkey: None,
+        };
+
+        store
+            .store_record(
+                CATEGORY_DID,
+                &did_info.did,
+                &did_info.to_bytes()?,
+                Some(&did_info.tags()),
+                true,
+            )
+            .await?;
+
+        Ok((did, verkey))
     }
 
-    pub fn replace_keys_start(store: &Wallet, did: &str, seed: Option<&str>) -> CliResult<String> {
-        block_on(async move {
-            let (did_entry, mut did_info) = Self::get_record(store, &did, true).await?;
+    /// Blocking form of [`Did::replace_keys_start_async`]
+    pub fn replace_keys_start(store: &Wallet, did: &str, seed: Option<&str>) -> VdrResult<String> {
+        block_on(Self::replace_keys_start_async(store, did, seed))
+    }
 
-            let key = Key::create(store, seed, None).await?;
-            let verkey = key.verkey()?;
+    /// Generate a pending key for a DID
+    pub async fn replace_keys_start_async(
+        store: &Wallet,
+        did: &str,
+        seed: Option<&str>,
+    ) -> VdrResult<String> {
+        let (did_entry, mut did_info) = Self::get_record(store, &did, true).await?;
+
+        let key = Key::create(store, seed, None).await?;
+        let verkey = key.verkey()?;
+
+        did_info.next_verkey = Some(verkey.clone());
+
+        let value = did_info.to_bytes()?;
+        store
+            .store_record(
+                CATEGORY_DID,
+                &did_info.did,
+                &value,
+                Some(&did_entry.tags),
+                false,
+            )
+            .await?;
+
+        Ok(verkey)
+    }
 
-            did_info.next_verkey = Some(verkey.clone());
+    /// Blocking form of [`Did::replace_keys_apply_async`]
+    pub fn replace_keys_apply(store: &Wallet, did: &str) -> VdrResult<()> {
+        block_on(Self::replace_keys_apply_async(store, did))
+    }
 
-            let value = serde_json::to_vec(&did_info)?;
-            store
-                .store_record(
-                    CATEGORY_DID,
-                    &did_info.did,
-                    &value,
-                    Some(&did_entry.tags),
-                    false,
-                )
-                .await?;
+    /// Replace the key of a DID with its pending key
+    pub async fn replace_keys_apply_async(store: &Wallet, did: &str) -> VdrResult<()> {
+        let (did_entry, mut did_info) = Self::get_record(store, &did, true).await?;
+
+        let next_verkey = did_info.next_verkey.ok_or_else(|| {
+            err_msg(
+                VdrErrorKind::WalletInvalidState,
+                format!("Next key is not set for the DID {}.", did),
+            )
+        })?;
+
+        did_info.verkey = next_verkey;
+        did_info.next_verkey = None;
+
+        let value = did_info.to_bytes()?;
+        store
+            .store_record(
+                CATEGORY_DID,
+                &did_info.did,
+                &value,
+                Some(&did_entry.tags),
+                false,
+            )
+            .await?;
+
+        Ok(())
+    }
 
-            Ok(verkey)
-        })
+    /// Blocking form of [`Did::set_metadata_async`]
+    pub fn set_metadata(store: &Wallet, did: &str, metadata: &str) -> VdrResult<()> {
+        block_on(Self::set_metadata_async(store, did, metadata))
     }
 
-    pub fn replace_keys_apply(store: &Wallet, did: &str) -> CliResult<()> {
-        block_on(async move {
-            let (did_entry, mut did_info) = Self::get_record(store, &did, true).await?;
+    /// Update the metadata stored for a DID
+    pub async fn set_metadata_async(store: &Wallet, did: &str, metadata: &str) -> VdrResult<()> {
+        let (did_entry, mut did_info) = Self::get_record(store, &did, true).await?;
 
-            let next_verkey = did_info.next_verkey.ok_or_else(|| {
-                CliError::InvalidEntityState(format!("Next key is not set for the DID {}.", did))
-            })?;
+        did_info.metadata = Some(metadata.to_string());
 
-            did_info.verkey = next_verkey;
-            did_info.next_verkey = None;
-
-            let value = serde_json::to_vec(&did_info)?;
-            store
-                .store_record(
-                    CATEGORY_DID,
-                    &did_info.did,
-                    &value,
-                    Some(&did_entry.tags),
-                    false,
-                )
-                .await?;
+        let value = did_info.to_bytes()?;
+        store
+            .store_record(
+                CATEGORY_DID,
+                &did_info.did,
+                &value,
+                Some(&did_entry.tags),
+                false,
+            )
+            .await?;
 
-            Ok(())
-        })
+        Ok(())
     }
 
-    pub fn set_metadata(store: &Wallet, did: &str, metadata: &str) -> CliResult<()> {
-        block_on(async move {
-            let (did_entry, mut did_info) = Self::get_record(store, &did, true).await?;
-
-            did_info.metadata = Some(metadata.to_string());
-
-            let value = serde_json::to_vec(&did_info)?;
-            store
-                .store_record(
-                    CATEGORY_DID,
-                    &did_info.did,
-                    &value,
-                    Some(&did_entry.tags),
-                    false,
-                )
-                .await?;
+    /// Blocking form of [`Did::get_async`]
+    pub fn get(store: &Wallet, did: &DidValue) -> VdrResult<DidInfo> {
+        block_on(Self::get_async(store, did))
+    }
 
-            Ok(())
-        })
+    /// Fetch the stored information for a DID
+    pub async fn get_async(store: &Wallet, did: &DidValue) -> VdrResult<DidInfo> {
+        let (_, did_info) = Self::get_record(store, &did, true).await?;
+        Ok(did_info)
     }
 
-    pub fn get(store: &Wallet, did: &DidValue) -> CliResult<DidInfo> {
-        block_on(async move {
-            let (_, did_info) = Self::get_record(store, &did, true).await?;
-            Ok(did_info)
-        })
+    /// Blocking form of [`Did::list_async`]
+    pub fn list(store: &Wallet) -> VdrResult<Vec<DidInfo>> {
+        block_on(Self::list_async(store))
     }
 
-    pub fn list(store: &Wallet) -> CliResult<Vec<DidInfo>> {
-        block_on(async move {
-            store
-                .fetch_all_records(CATEGORY_DID)
-                .await?
-                .iter()
-                .map(|did| serde_json::from_slice(&did.value).map_err(CliError::from))
-                .collect::<CliResult<Vec<DidInfo>>>()
-        })
+    /// List all DIDs stored in the wallet
+    pub async fn list_async(store: &Wallet) -> VdrResult<Vec<DidInfo>> {
+        store
+            .fetch_all_records(CATEGORY_DID)
+            .await?
+            .iter()
+            .map(|did| DidInfo::from_bytes(&did.value))
+            .collect::<VdrResult<Vec<DidInfo>>>()
     }
 
-    pub fn abbreviate_verkey(did: &str, verkey: &str) -> CliResult<String> {
+    pub fn abbreviate_verkey(did: &str, verkey: &str) -> VdrResult<String> {
         let did = DidValue(did.to_string()).to_short().to_string();
         EncodedVerKey::from_did_and_verkey(&did, verkey)?
             .abbreviated_for_did(&did)
-            .map_err(CliError::from)
+            .map_err(VdrError::from)
+    }
+
+    /// Blocking form of [`Did::qualify_async`]
+    pub fn qualify(store: &Wallet, did: &DidValue, method: &str) -> VdrResult<DidValue> {
+        block_on(Self::qualify_async(store, did, method))
     }
 
-    pub fn qualify(store: &Wallet, did: &DidValue, method: &str) -> CliResult<DidValue> {
-        block_on(async {
-            let (entry, did_info) = Self::get_opt_record(store, &did.to_string(), true)
-                .await?
-                .ok_or_else(|| {
-                    CliError::NotFound(format!("DID {} does not exits in the wallet!", did))
-                })?;
+    /// Replace a DID in the wallet with its fully-qualified form
+    pub async fn qualify_async(
+        store: &Wallet,
+        did: &DidValue,
+        method: &str,
+    ) -> VdrResult<DidValue> {
+        let (entry, did_info) = Self::get_opt_record(store, &did.to_string(), true)
+            .await?
+            .ok_or_else(|| {
+                err_msg(
+                    VdrErrorKind::DidNotFound,
+                    format!("DID {} does not exits in the wallet!", did),
+                )
+            })?;
+
+        let qualified_did = did
+            .to_qualified(method)
+            .map_err(|_| input_err(format!("Invalid DID {} provided.", did)))?;
 
-            let qualified_did = did
-                .to_qualified(method)
-                .map_err(|_| CliError::InvalidInput(format!("Invalid DID {} provided.", did)))?;
+        Self::remove(store, &did.to_string()).await?;
 
-            Self::remove(store, &did.to_string()).await?;
+        let did_info = DidInfo {
+            did: qualified_did.to_string(),
+            ..did_info
+        };
 
-            let did_info = DidInfo {
-                did: qualified_did.to_string(),
-                ..did_info
-            };
+        let value = did_info.to_bytes()?;
+        store
+            .store_record(CATEGORY_DID, &did_info.did, &value, Some(&entry.tags), true)
+            .await?;
 
-            let value = serde_json::to_vec(&did_info)?;
-            store
-                .store_record(CATEGORY_DID, &did_info.did, &value, Some(&entry.tags), true)
-                .await?;
+        Ok(qualified_did)
+    }
 
-            Ok(qualified_did)
-        })
+    /// Blocking form of [`Did::sign_async`]
+    pub fn sign(store: &Wallet, did: &str, bytes: &[u8]) -> VdrResult<Vec<u8>> {
+        block_on(Self::sign_async(store, did, bytes))
     }
 
-    pub async fn sign(store: &Wallet, did: &str, bytes: &[u8]) -> CliResult<Vec<u8>> {
+    /// Sign a message with the current key of a DID
+    pub async fn sign_async(store: &Wallet, did: &str, bytes: &[u8]) -> VdrResult<Vec<u8>> {
         let (_, did_info) = Self::get_record(store, &did, true).await?;
 
         Key::sign(store, &did_info.verkey, bytes).await
     }
 
-    async fn remove(store: &Wallet, name: &str) -> CliResult<()> {
+    async fn remove(store: &Wallet, name: &str) -> VdrResult<()> {
         store.remove_record(CATEGORY_DID, name).await
     }
 
@@ -216,11 +267,14 @@ impl Did {
         store: &Wallet,
         name: &str,
         for_update: bool,
-    ) -> CliResult<(Entry, DidInfo)> {
+    ) -> VdrResult<(Entry, DidInfo)> {
         Self::get_opt_record(store, name, for_update)
             .await?
             .ok_or_else(|| {
-                CliError::NotFound(format!("DID {} does not exits in the wallet.", name))
+                err_msg(
+                    VdrErrorKind::DidNotFound,
+                    format!("DID {} does not exits in the wallet.", name),
+                )
             })
     }
 
@@ -228,11 +282,11 @@ impl Did {
         store: &Wallet,
         name: &str,
         for_update: bool,
-    ) -> CliResult<Option<(Entry, DidInfo)>> {
+    ) -> VdrResult<Option<(Entry, DidInfo)>> {
         let entry = store.fetch_record(CATEGORY_DID, name, for_update).await?;
         match entry {
             Some(entry) => {
-                let did_info: DidInfo = serde_json::from_slice(&entry.value)?;
+                let did_info = DidInfo::from_bytes(&entry.value)?;
                 Ok(Some((entry, did_info)))
             }
             None => Ok(None),
@@ -241,13 +295,13 @@ impl Did {
 }
 
 impl DidInfo {
-    pub fn from_bytes(bytes: &[u8]) -> CliResult<Self> {
-        serde_json::from_slice(bytes)
-            .map_err(|_| CliError::InvalidInput("Unable to parse did info".to_string()))
+    pub fn from_bytes(bytes: &[u8]) -> VdrResult<Self> {
+        serde_json::from_slice(bytes).map_err(|_| input_err("Unable to parse did info".to_string()))
     }
 
-    pub fn to_bytes(&self) -> CliResult<Vec<u8>> {
-        serde_json::to_vec(self).map_err(CliError::from)
+    pub fn to_bytes(&self) -> VdrResult<Vec<u8>> {
+        serde_json::to_vec(self)
+            .with_err_msg(VdrErrorKind::Unexpected, "Unable to serialize did info")
     }
 
     pub fn tags(&self) -> Vec<EntryTag> {
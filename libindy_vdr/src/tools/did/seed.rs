@@ -2,10 +2,10 @@
     Copyright © 2023 Province of British Columbia
     https://digital.gov.bc.ca/digital-trust
 */
-use crate::utils::clierror::{CliError, CliResult};
+use crate::common::error::prelude::*;
 
-use hex::FromHex;
 use crate::utils::base64;
+use hex::FromHex;
 
 const SEED_BYTES: usize = 32;
 
@@ -16,18 +16,18 @@ impl Seed {
         self.0.as_slice()
     }
 
-    pub fn from_str(seed: &str) -> CliResult<Seed> {
+    pub fn from_str(seed: &str) -> VdrResult<Seed> {
         if seed.as_bytes().len() == SEED_BYTES {
             // is acceptable seed length
             Ok(Seed(seed.as_bytes().to_vec()))
         } else if seed.ends_with('=') {
             // is base64 string
-            let decoded = base64::decode(&seed)
-                .map_err(|_| CliError::InvalidInput(format!("Invalid seed provided.")))?;
+            let decoded =
+                base64::decode(&seed).map_err(|_| input_err(format!("Invalid seed provided.")))?;
             if decoded.len() == SEED_BYTES {
                 Ok(Seed(decoded))
             } else {
-                Err(CliError::InvalidInput(format!(
+                Err(input_err(format!(
                     "Provided invalid base64 encoded `seed`. \
                                    The number of bytes must be {} ",
                     SEED_BYTES
@@ -35,11 +35,11 @@ impl Seed {
             }
         } else if seed.as_bytes().len() == SEED_BYTES * 2 {
             // is hex string
-            let decoded = Vec::from_hex(seed)
-                .map_err(|_| CliError::InvalidInput(format!("Seed is invalid hex")))?;
+            let decoded =
+                Vec::from_hex(seed).map_err(|_| input_err(format!("Seed is invalid hex")))?;
             Ok(Seed(decoded))
         } else {
-            Err(CliError::InvalidInput(format!(
+            Err(input_err(format!(
                 "Provided invalid `seed`. It can be either \
                                {} bytes string or base64 string or {} bytes HEX string",
                 SEED_BYTES,
@@ -6,5 +6,3 @@ pub mod did;
 // pub mod ledger;
 // pub mod pool;
 pub mod wallet;
-
-
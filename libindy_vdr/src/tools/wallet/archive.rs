@@ -0,0 +1,378 @@
+/*
+    Copyright © 2023 Province of British Columbia
+    https://digital.gov.bc.ca/digital-trust
+*/
+//! Encrypted single-file backups of askar wallets.
+//!
+//! An archive file consists of a fixed prefix (magic bytes and format version),
+//! a length-prefixed MessagePack header holding the key derivation parameters,
+//! and the ChaCha20-Poly1305 encrypted record payload. The prefix and header are
+//! bound to the payload as associated data, so the trailing Poly1305 tag acts as
+//! the MAC over the whole file.
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use aries_askar::{
+    crypto::{
+        alg::chacha20::{Chacha20Key, C20P},
+        encrypt::KeyAeadInPlace,
+        kdf::{
+            argon2::{Argon2, Params, PARAMS_INTERACTIVE, PARAMS_MODERATE, SALT_LENGTH},
+            KeyDerivation,
+        },
+        repr::KeySecretBytes,
+    },
+    kms::{KeyEntry, LocalKey, SecretBytes},
+    Entry, EntryTag,
+};
+use byteorder::{ByteOrder, LittleEndian};
+use indy_utils::base58;
+use rand::RngCore;
+
+use crate::common::error::prelude::*;
+
+/// Magic bytes identifying a wallet archive
+pub const ARCHIVE_MAGIC: &[u8; 8] = b"VDRWLTAR";
+/// Current wallet archive format version
+pub const ARCHIVE_VERSION: u32 = 1;
+
+const KEY_BYTES: usize = 32;
+const NONCE_BYTES: usize = 12;
+const PREFIX_BYTES: usize = ARCHIVE_MAGIC.len() + 8;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveHeader {
+    key_derivation_method: String,
+    salt: Vec<u8>,
+    nonce: Vec<u8>,
+    time: u64,
+    records: usize,
+    keys: usize,
+}
+
+/// A tag attached to an archived record or key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveTag {
+    pub name: String,
+    pub value: String,
+    pub plaintext: bool,
+}
+
+/// An archived wallet record
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveRecord {
+    pub category: String,
+    pub name: String,
+    pub value: Vec<u8>,
+    pub tags: Vec<ArchiveTag>,
+}
+
+/// An archived wallet key, held as a secret JWK
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveKey {
+    pub name: String,
+    pub jwk: Vec<u8>,
+    pub metadata: Option<String>,
+    pub tags: Vec<ArchiveTag>,
+}
+
+/// The decrypted contents of a wallet archive
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletArchive {
+    pub records: Vec<ArchiveRecord>,
+    pub keys: Vec<ArchiveKey>,
+}
+
+impl ArchiveTag {
+    fn from_entry_tags(tags: &[EntryTag]) -> Vec<ArchiveTag> {
+        tags.iter()
+            .map(|tag| match tag {
+                EntryTag::Encrypted(name, value) => ArchiveTag {
+                    name: name.clone(),
+                    value: value.clone(),
+                    plaintext: false,
+                },
+                EntryTag::Plaintext(name, value) => ArchiveTag {
+                    name: name.clone(),
+                    value: value.clone(),
+                    plaintext: true,
+                },
+            })
+            .collect()
+    }
+
+    pub fn to_entry_tags(tags: &[ArchiveTag]) -> Vec<EntryTag> {
+        tags.iter()
+            .map(|tag| {
+                if tag.plaintext {
+                    EntryTag::Plaintext(tag.name.clone(), tag.value.clone())
+                } else {
+                    EntryTag::Encrypted(tag.name.clone(), tag.value.clone())
+                }
+            })
+            .collect()
+    }
+}
+
+impl ArchiveRecord {
+    pub fn from_entry(entry: &Entry) -> ArchiveRecord {
+        ArchiveRecord {
+            category: entry.category.clone(),
+            name: entry.name.clone(),
+            value: entry.value.to_vec(),
+            tags: ArchiveTag::from_entry_tags(&entry.tags),
+        }
+    }
+}
+
+impl ArchiveKey {
+    pub fn from_entry(entry: &KeyEntry) -> VdrResult<ArchiveKey> {
+        let jwk = entry.load_local_key()?.to_jwk_secret()?;
+        Ok(ArchiveKey {
+            name: entry.name().to_string(),
+            jwk: jwk.to_vec(),
+            metadata: entry.metadata().map(String::from),
+            tags: ArchiveTag::from_entry_tags(entry.tags_as_slice()),
+        })
+    }
+
+    pub fn local_key(&self) -> VdrResult<LocalKey> {
+        Ok(LocalKey::from_jwk_slice(&self.jwk)?)
+    }
+}
+
+impl WalletArchive {
+    /// Check whether a file starts with the wallet archive magic bytes
+    pub fn is_archive(path: &Path) -> bool {
+        let mut magic = [0u8; ARCHIVE_MAGIC.len()];
+        File::open(path)
+            .and_then(|mut file| file.read_exact(&mut magic))
+            .map(|_| &magic == ARCHIVE_MAGIC)
+            .unwrap_or(false)
+    }
+
+    /// Encrypt the archive and write it to a new file
+    pub fn write(
+        &self,
+        path: &Path,
+        key: &str,
+        key_derivation_method: Option<&str>,
+    ) -> VdrResult<()> {
+        let bytes = self.seal(key, key_derivation_method)?;
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        Ok(())
+    }
+
+    /// Read an archive file, verifying its version and MAC
+    pub fn read(
+        path: &Path,
+        key: &str,
+        key_derivation_method: Option<&str>,
+    ) -> VdrResult<WalletArchive> {
+        let bytes = fs::read(path)?;
+        Self::unseal(&bytes, key, key_derivation_method)
+    }
+
+    /// Encrypt the archive contents into the archive file format
+    pub fn seal(&self, key: &str, key_derivation_method: Option<&str>) -> VdrResult<Vec<u8>> {
+        let method = key_derivation_method.unwrap_or("argon2m");
+        let mut rng = rand::thread_rng();
+        let mut salt = vec![0u8; SALT_LENGTH];
+        rng.fill_bytes(&mut salt);
+        let mut nonce = vec![0u8; NONCE_BYTES];
+        rng.fill_bytes(&mut nonce);
+
+        let header = ArchiveHeader {
+            key_derivation_method: method.to_string(),
+            salt,
+            nonce,
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            records: self.records.len(),
+            keys: self.keys.len(),
+        };
+        let header_bytes = rmp_serde::to_vec(&header)
+            .with_err_msg(VdrErrorKind::Unexpected, "Error serializing archive header")?;
+        let payload = rmp_serde::to_vec(self)
+            .with_err_msg(VdrErrorKind::Unexpected, "Error serializing archive contents")?;
+
+        let mut output = Vec::with_capacity(PREFIX_BYTES + header_bytes.len() + payload.len());
+        output.extend_from_slice(ARCHIVE_MAGIC);
+        let mut prefix = [0u8; 8];
+        LittleEndian::write_u32(&mut prefix[..4], ARCHIVE_VERSION);
+        LittleEndian::write_u32(&mut prefix[4..], header_bytes.len() as u32);
+        output.extend_from_slice(&prefix);
+        output.extend_from_slice(&header_bytes);
+
+        let cipher = archive_key(key, &header)?;
+        let mut buffer = SecretBytes::from_slice(&payload);
+        cipher
+            .encrypt_in_place(&mut buffer, &header.nonce, &output)
+            .map_err(|_| err_msg(VdrErrorKind::Unexpected, "Error encrypting wallet archive"))?;
+        output.extend_from_slice(buffer.as_ref());
+        Ok(output)
+    }
+
+    /// Decrypt the archive file format, verifying its version and MAC
+    pub fn unseal(
+        bytes: &[u8],
+        key: &str,
+        key_derivation_method: Option<&str>,
+    ) -> VdrResult<WalletArchive> {
+        if bytes.len() < PREFIX_BYTES || &bytes[..ARCHIVE_MAGIC.len()] != ARCHIVE_MAGIC {
+            return Err(input_err("Invalid wallet archive: missing archive header"));
+        }
+        let version = LittleEndian::read_u32(&bytes[ARCHIVE_MAGIC.len()..]);
+        if version != ARCHIVE_VERSION {
+            return Err(err_msg(
+                VdrErrorKind::Incompatible,
+                format!("Unsupported wallet archive version: {}", version),
+            ));
+        }
+        let header_len = LittleEndian::read_u32(&bytes[ARCHIVE_MAGIC.len() + 4..]) as usize;
+        let header_end = PREFIX_BYTES
+            .checked_add(header_len)
+            .filter(|end| *end <= bytes.len())
+            .ok_or_else(|| input_err("Invalid wallet archive: truncated header"))?;
+        let header: ArchiveHeader = rmp_serde::from_slice(&bytes[PREFIX_BYTES..header_end])
+            .with_input_err("Invalid wallet archive: unable to parse header")?;
+
+        if let Some(method) = key_derivation_method {
+            if method != header.key_derivation_method {
+                return Err(input_err(format!(
+                    "Wallet archive key derivation method mismatch: expected {}, found {}",
+                    method, header.key_derivation_method
+                )));
+            }
+        }
+
+        let cipher = archive_key(key, &header)?;
+        let mut buffer = SecretBytes::from_slice(&bytes[header_end..]);
+        cipher
+            .decrypt_in_place(&mut buffer, &header.nonce, &bytes[..header_end])
+            .map_err(|_| {
+                err_msg(
+                    VdrErrorKind::WalletAccessDenied,
+                    "Wallet archive MAC check failed: invalid key or corrupted archive",
+                )
+            })?;
+        let archive: WalletArchive = rmp_serde::from_slice(buffer.as_ref())
+            .with_input_err("Invalid wallet archive: unable to parse contents")?;
+
+        if archive.records.len() != header.records || archive.keys.len() != header.keys {
+            return Err(input_err(
+                "Invalid wallet archive: record count does not match header",
+            ));
+        }
+        Ok(archive)
+    }
+}
+
+fn archive_key(key: &str, header: &ArchiveHeader) -> VdrResult<Chacha20Key<C20P>> {
+    let key_bytes = match header.key_derivation_method.as_str() {
+        "argon2m" => derive_key(key.as_bytes(), &header.salt, PARAMS_MODERATE)?,
+        "argon2i" => derive_key(key.as_bytes(), &header.salt, PARAMS_INTERACTIVE)?,
+        "raw" => base58::decode(key).map_err(|_| input_err("Invalid raw archive key"))?,
+        method => {
+            return Err(input_err(format!(
+                "Unsupported key derivation method \"{}\" provided for the archive",
+                method
+            )))
+        }
+    };
+    if header.nonce.len() != NONCE_BYTES {
+        return Err(input_err("Invalid wallet archive: invalid nonce"));
+    }
+    Chacha20Key::from_secret_bytes(&key_bytes).map_err(|_| input_err("Invalid archive key"))
+}
+
+fn derive_key(passphrase: &[u8], salt: &[u8], params: Params) -> VdrResult<Vec<u8>> {
+    let mut key = [0u8; KEY_BYTES];
+    Argon2::new(passphrase, salt, params)
+        .and_then(|mut kdf| kdf.derive_key_bytes(&mut key))
+        .map_err(|_| input_err("Unable to derive archive key"))?;
+    Ok(key.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAW_KEY: &str = "6gxCYBvDnVWCTUnqtYjN6aq5V5oWpHwV5x1rgdS4iNNP";
+
+    fn archive() -> WalletArchive {
+        WalletArchive {
+            records: vec![ArchiveRecord {
+                category: "did".to_string(),
+                name: "VsKV7grR1BUE29mG2Fm2kX".to_string(),
+                value: b"{\"metadata\":\"test\"}".to_vec(),
+                tags: vec![ArchiveTag {
+                    name: "verkey".to_string(),
+                    value: "GjZWsBLgZCR18aL468JAT7w9CZRiBnpxUPPgyQxh4voa".to_string(),
+                    plaintext: false,
+                }],
+            }],
+            keys: vec![],
+        }
+    }
+
+    #[test]
+    fn test_archive_round_trip_works() {
+        let archive = archive();
+        let sealed = archive.seal(RAW_KEY, Some("raw")).unwrap();
+        assert_eq!(&sealed[..ARCHIVE_MAGIC.len()], ARCHIVE_MAGIC);
+        let opened = WalletArchive::unseal(&sealed, RAW_KEY, Some("raw")).unwrap();
+        assert_eq!(archive, opened);
+    }
+
+    #[test]
+    fn test_archive_round_trip_works_for_derived_key() {
+        let archive = archive();
+        let sealed = archive.seal("passphrase", Some("argon2i")).unwrap();
+        let opened = WalletArchive::unseal(&sealed, "passphrase", None).unwrap();
+        assert_eq!(archive, opened);
+    }
+
+    #[test]
+    fn test_archive_unseal_fails_for_wrong_key() {
+        let sealed = archive().seal("passphrase", Some("argon2i")).unwrap();
+        let err = WalletArchive::unseal(&sealed, "other", None).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::WalletAccessDenied));
+    }
+
+    #[test]
+    fn test_archive_unseal_fails_for_tampered_content() {
+        let mut sealed = archive().seal(RAW_KEY, Some("raw")).unwrap();
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        WalletArchive::unseal(&sealed, RAW_KEY, None).unwrap_err();
+    }
+
+    #[test]
+    fn test_archive_unseal_fails_for_tampered_header() {
+        let mut sealed = archive().seal(RAW_KEY, Some("raw")).unwrap();
+        sealed[PREFIX_BYTES + 1] ^= 1;
+        WalletArchive::unseal(&sealed, RAW_KEY, None).unwrap_err();
+    }
+
+    #[test]
+    fn test_archive_unseal_fails_for_unknown_version() {
+        let mut sealed = archive().seal(RAW_KEY, Some("raw")).unwrap();
+        LittleEndian::write_u32(&mut sealed[ARCHIVE_MAGIC.len()..], ARCHIVE_VERSION + 1);
+        let err = WalletArchive::unseal(&sealed, RAW_KEY, None).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::Incompatible));
+    }
+}
@@ -2,7 +2,8 @@
     Copyright © 2023 Province of British Columbia
     https://digital.gov.bc.ca/digital-trust
 */
-use crate::utils::clierror::{CliError, CliResult};
+use crate::common::error::prelude::*;
+use super::archive::WalletArchive;
 use std::{ffi::OsStr, fs, path::PathBuf};
 
 pub struct WalletBackup {
@@ -11,6 +12,7 @@ pub struct WalletBackup {
 
 #[derive(Debug)]
 pub enum BackupKind {
+    Archive,
     Askar,
     Libindy,
 }
@@ -18,14 +20,14 @@ pub enum BackupKind {
 pub const DEFAULT_BACKUP_NAME: &'static str = "backup";
 
 impl WalletBackup {
-    pub fn from_file(path: &str) -> CliResult<Self> {
+    pub fn from_file(path: &str) -> VdrResult<Self> {
         let path = PathBuf::from(path);
         Ok(WalletBackup { path })
     }
 
-    pub fn init_dir(&self) -> CliResult<()> {
+    pub fn init_dir(&self) -> VdrResult<()> {
         if self.exists() {
-            return Err(CliError::Duplicate(format!(
+            return Err(input_err(format!(
                 "Wallet backup \"{}\" already exists",
                 self.path.to_string_lossy()
             )));
@@ -34,7 +36,7 @@ impl WalletBackup {
         fs::DirBuilder::new()
             .recursive(true)
             .create(&self.path)
-            .map_err(CliError::from)
+            .map_err(VdrError::from)
     }
 
     pub fn id(&self) -> String {
@@ -49,12 +51,15 @@ impl WalletBackup {
         self.path.exists()
     }
 
-    pub fn kind(&self) -> CliResult<BackupKind> {
+    pub fn kind(&self) -> VdrResult<BackupKind> {
         let metadata = fs::metadata(&self.path)?;
         // if specified path to directory consider it as Askar backup
         if metadata.is_dir() {
             return Ok(BackupKind::Askar);
         }
+        if WalletArchive::is_archive(&self.path) {
+            return Ok(BackupKind::Archive);
+        }
 
         let extension = self.path.extension().and_then(OsStr::to_str);
         match extension {
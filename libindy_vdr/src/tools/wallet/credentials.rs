@@ -2,10 +2,7 @@
     Copyright © 2023 Province of British Columbia
     https://digital.gov.bc.ca/digital-trust
 */
-use crate::{
-    utils::clierror::{CliError, CliResult},
-    tools::wallet::Credentials,
-};
+use crate::{common::error::prelude::*, tools::wallet::Credentials};
 
 use aries_askar::{Argon2Level, KdfMethod, PassKey, StoreKeyMethod};
 
@@ -17,7 +14,7 @@ pub struct WalletCredentials<'a> {
 }
 
 impl<'a> WalletCredentials<'a> {
-    pub fn build(credentials: &Credentials) -> CliResult<WalletCredentials> {
+    pub fn build(credentials: &Credentials) -> VdrResult<WalletCredentials> {
         let key_method = Self::map_key_derivation_method(
             credentials
                 .key_derivation_method
@@ -49,7 +46,7 @@ impl<'a> WalletCredentials<'a> {
         })
     }
 
-    fn map_key_derivation_method(key: Option<&str>) -> CliResult<StoreKeyMethod> {
+    fn map_key_derivation_method(key: Option<&str>) -> VdrResult<StoreKeyMethod> {
         match key {
             None | Some("argon2m") => Ok(StoreKeyMethod::DeriveKey(KdfMethod::Argon2i(
                 Argon2Level::Moderate,
@@ -58,7 +55,7 @@ impl<'a> WalletCredentials<'a> {
                 Argon2Level::Interactive,
             ))),
             Some("raw") => Ok(StoreKeyMethod::RawKey),
-            Some(value) => Err(CliError::InvalidInput(format!(
+            Some(value) => Err(input_err(format!(
                 "Unsupported key derivation method \"{}\" provided for the wallet.",
                 value
             ))),
@@ -31,10 +31,7 @@ use std::{
 
 //use crate::println_err;
 
-use crate::{
-    utils::clierror::{CliError, CliResult},
-    tools::wallet::ImportConfig,
-};
+use crate::{common::error::prelude::*, tools::wallet::ImportConfig};
 
 pub struct LibindyBackupReader {
     reader: Reader<BufReader<File>>,
@@ -45,12 +42,15 @@ const HASHBYTES: usize = 32;
 const KEYBYTES: usize = 32;
 
 impl LibindyBackupReader {
-    pub fn init(config: &ImportConfig) -> CliResult<LibindyBackupReader> {
+    pub fn init(config: &ImportConfig) -> VdrResult<LibindyBackupReader> {
         let backup_file = fs::OpenOptions::new()
             .read(true)
             .open(&config.path)
             .map_err(|_| {
-                CliError::NotFound(format!("Wallet backup \"{}\" not found", config.path))
+                err_msg(
+                    VdrErrorKind::FileSystem,
+                    format!("Wallet backup \"{}\" not found", config.path),
+                )
             })?;
 
         let mut reader = BufReader::new(backup_file);
@@ -67,11 +67,9 @@ impl LibindyBackupReader {
         Ok(backup_reader)
     }
 
-    pub fn read_record(&mut self) -> CliResult<Option<BackupRecord>> {
+    pub fn read_record(&mut self) -> VdrResult<Option<BackupRecord>> {
         let record_len = self.reader.read_u32::<LittleEndian>().map_err(|_| {
-            CliError::InvalidInput(
-                "Invalid backup content: Unable to read backup record".to_string(),
-            )
+            input_err("Invalid backup content: Unable to read backup record".to_string())
         })? as usize;
 
         if record_len == 0 {
@@ -80,23 +78,19 @@ impl LibindyBackupReader {
 
         let mut record = vec![0u8; record_len];
         self.reader.read_exact(&mut record).map_err(|err| {
-        //    println_err!("{:?}", err);
-            CliError::InvalidInput(
-                "Invalid backup content: Unable to read backup record1".to_string(),
-            )
+            //    println_err!("{:?}", err);
+            input_err("Invalid backup content: Unable to read backup record1".to_string())
         })?;
 
         let record: BackupRecord = rmp_serde::from_slice(&record).map_err(|_| {
-            CliError::InvalidInput(
-                "Invalid backup content: Unable to parse backup record".to_string(),
-            )
+            input_err("Invalid backup content: Unable to parse backup record".to_string())
         })?;
 
         Ok(Some(record))
     }
 
     #[allow(unused)]
-    pub fn read_records(&mut self) -> CliResult<Vec<BackupRecord>> {
+    pub fn read_records(&mut self) -> VdrResult<Vec<BackupRecord>> {
         let mut records: Vec<BackupRecord> = Vec::new();
         loop {
             match self.read_record()? {
@@ -112,11 +106,11 @@ impl LibindyBackupReader {
     fn read_backup_header(
         reader: &mut BufReader<File>,
         config: &ImportConfig,
-    ) -> CliResult<(Vec<u8>, Vec<u8>, usize, Vec<u8>)> {
+    ) -> VdrResult<(Vec<u8>, Vec<u8>, usize, Vec<u8>)> {
         let header_len = reader.read_u32::<LittleEndian>()? as usize;
 
         if header_len == 0 {
-            return Err(CliError::InvalidInput(
+            return Err(input_err(
                 "Invalid backup content: Unable to read backup header".to_string(),
             ));
         }
@@ -126,13 +120,11 @@ impl LibindyBackupReader {
         reader.read_exact(&mut header_bytes)?;
 
         let header: BackupHeader = rmp_serde::from_slice(&header_bytes).map_err(|_| {
-            CliError::InvalidInput(
-                "Invalid backup content: Unable to parse backup header".to_string(),
-            )
+            input_err("Invalid backup content: Unable to parse backup header".to_string())
         })?;
 
         if header.version != 0 {
-            return Err(CliError::InvalidInput(
+            return Err(input_err(
                 "Invalid backup content: Unsupported backup version".to_string(),
             ));
         }
@@ -157,9 +149,7 @@ impl LibindyBackupReader {
             }
             BackupEncryptionMethod::ChaCha20Poly1305IETFRaw { nonce, chunk_size } => {
                 let key = base58::decode(config.key.as_bytes()).map_err(|_| {
-                    CliError::InvalidInput(
-                        "Invalid backup content: Unable to decode backup key".to_string(),
-                    )
+                    input_err("Invalid backup content: Unable to decode backup key".to_string())
                 })?;
                 (key, nonce, chunk_size)
             }
@@ -168,18 +158,18 @@ impl LibindyBackupReader {
         Ok((key, nonce, chunk_size, header_bytes))
     }
 
-    fn validate_backup_header(&mut self, header_bytes: &[u8]) -> CliResult<()> {
+    fn validate_backup_header(&mut self, header_bytes: &[u8]) -> VdrResult<()> {
         let mut header_hash = vec![0u8; HASHBYTES];
         self.reader.read_exact(&mut header_hash)?;
         if SHA256::digest(header_bytes) != header_hash {
-            return Err(CliError::InvalidInput(
+            return Err(input_err(
                 "Invalid backup content: Header digest mismatch".to_string(),
             ));
         }
         Ok(())
     }
 
-    fn derive_backup_key(passphrase: &[u8], salt: &[u8], params: Params) -> CliResult<Vec<u8>> {
+    fn derive_backup_key(passphrase: &[u8], salt: &[u8], params: Params) -> VdrResult<Vec<u8>> {
         // **Libindy ISSUE**: For backup purpose Libindy generates Salt of 32 bytes length.
         // BUT in fact salt is truncated till 16 bytes for key derivation.
         let salt = &salt[0..SALT_LENGTH];
@@ -187,15 +177,11 @@ impl LibindyBackupReader {
         let mut key = [0u8; KEYBYTES];
         Argon2::new(passphrase, salt, params)
             .map_err(|_| {
-                CliError::InvalidInput(
-                    "Invalid backup content: Unable to derive backup key".to_string(),
-                )
+                input_err("Invalid backup content: Unable to derive backup key".to_string())
             })?
             .derive_key_bytes(&mut key)
             .map_err(|_| {
-                CliError::InvalidInput(
-                    "Invalid backup content: Unable to derive backup key".to_string(),
-                )
+                input_err("Invalid backup content: Unable to derive backup key".to_string())
             })?;
 
         Ok(key.to_vec())
@@ -211,11 +197,9 @@ struct Reader<R: Read> {
 }
 
 impl<R: Read> Reader<R> {
-    fn new(inner: R, key: Vec<u8>, nonce: Vec<u8>, chunk_size: usize) -> CliResult<Self> {
+    fn new(inner: R, key: Vec<u8>, nonce: Vec<u8>, chunk_size: usize) -> VdrResult<Self> {
         let key = Chacha20Key::from_secret_bytes(&key).map_err(|_| {
-            CliError::InvalidInput(
-                "Invalid backup content: Unable to derive backup key".to_string(),
-            )
+            input_err("Invalid backup content: Unable to derive backup key".to_string())
         })?;
         Ok(Reader {
             rest_buffer: Vec::new(),
@@ -276,7 +260,7 @@ impl<R: Read> Read for Reader<R> {
                     io::Error::new(io::ErrorKind::InvalidData, "Unable to decrypt data")
                 })?;
 
-          //  sodium_increment(&mut self.nonce);
+            //  sodium_increment(&mut self.nonce);
 
             let to_copy = cmp::min(chunk.len(), buf.len() - pos);
             buf[pos..pos + to_copy].copy_from_slice(&chunk[..to_copy]);
@@ -341,12 +325,12 @@ pub struct DidRecord {
 pub trait LibindyBackupRecord {
     const TYPE: &'static str;
 
-    fn from_str<'a>(json: &'a str) -> CliResult<Self>
+    fn from_str<'a>(json: &'a str) -> VdrResult<Self>
     where
         Self: Deserialize<'a>,
     {
         serde_json::from_str(json).map_err(|_| {
-            CliError::InvalidInput(format!(
+            input_err(format!(
                 "Invalid backup content: Unable to parse {} record",
                 Self::TYPE
             ))
@@ -379,12 +363,9 @@ impl LibindyBackupRecord for KeyRecord {
 }
 
 impl KeyRecord {
-    pub fn key_bytes(&self) -> CliResult<Vec<u8>> {
+    pub fn key_bytes(&self) -> VdrResult<Vec<u8>> {
         base58::decode(&self.signkey)
-            .map_err(|_| {
-                CliError::InvalidInput("Invalid backup content: Unable to decode key".to_string())
-            })
-            .map_err(CliError::from)
+            .map_err(|_| input_err("Invalid backup content: Unable to decode key".to_string()))
     }
 }
 
@@ -6,22 +6,18 @@
     Copyright © 2023 Province of British Columbia
     https://digital.gov.bc.ca/digital-trust
 */
+pub mod archive;
 pub mod backup;
 mod credentials;
 pub mod libindy_backup_reader;
 mod uri;
 pub mod wallet_config;
-pub mod wallet_binding;
-use crate::{println_warn, utils::clierror::{CliError,CliResult}};
-use crate::{
-//   error::{CliError, CliResult},
-    tools::did::constants::CATEGORY_DID,
-    utils::futures::block_on,
-};
 use self::{
+    archive::{ArchiveKey, ArchiveRecord, ArchiveTag, WalletArchive},
     credentials::WalletCredentials,
     uri::{StorageType, WalletUri},
 };
+use crate::common::error::prelude::*;
 use crate::tools::wallet::libindy_backup_reader::LibindyBackupRecord;
 use crate::tools::{
     did::{constants::KEY_TYPE, DidInfo},
@@ -32,6 +28,7 @@ use crate::tools::{
         },
     },
 };
+use crate::{tools::did::constants::CATEGORY_DID, utils::futures::block_on};
 use aries_askar::{
     any::AnyStore,
     kms::{KeyAlg, LocalKey},
@@ -39,6 +36,8 @@ use aries_askar::{
 };
 use backup::WalletBackup;
 use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::path::Path;
 use wallet_config::{WalletConfig, WalletDirectory};
 
 #[derive(Debug)]
@@ -61,6 +60,10 @@ pub struct ExportConfig {
     pub path: String,
     pub key: String,
     pub key_derivation_method: Option<String>,
+    /// Record categories to export, in addition to the wallet keys.
+    /// Defaults to every category in the wallet.
+    #[serde(default)]
+    pub categories: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -71,165 +74,447 @@ pub struct ImportConfig {
 }
 
 impl Wallet {
-    pub fn create(config: &WalletConfig, credentials: &Credentials) -> CliResult<()> {
-        block_on(async move {
-            if config.exists() {
-                return Err(CliError::Duplicate(format!(
-                    "Wallet \"{}\" already exists",
-                    config.id
-                )));
-            }
-            print!("Wallet async function executing...");
-            let wallet_uri = WalletUri::build(config, credentials, None)?;
-            let credentials = WalletCredentials::build(credentials)?;
+    /// Blocking form of [`Wallet::create_async`]
+    pub fn create(config: &WalletConfig, credentials: &Credentials) -> VdrResult<()> {
+        block_on(Self::create_async(config, credentials))
+    }
 
-            config.create_path()?;
+    /// Create and provision a new wallet
+    pub async fn create_async(config: &WalletConfig, credentials: &Credentials) -> VdrResult<()> {
+        if config.exists() {
+            return Err(err_msg(
+                VdrErrorKind::WalletDuplicate,
+                format!("Wallet \"{}\" already exists", config.id),
+            ));
+        }
+        let wallet_uri = WalletUri::build(config, credentials, None)?;
+        let credentials = WalletCredentials::build(credentials)?;
 
-            let store = wallet_uri
-                .value()
-                .provision_backend(
-                    credentials.key_method,
-                    credentials.key.as_ref(),
-                    None,
-                    false,
+        config.create_path()?;
+
+        let store = wallet_uri
+            .value()
+            .provision_backend(
+                credentials.key_method,
+                credentials.key.as_ref(),
+                None,
+                false,
+            )
+            .await?;
+
+        // Askar: If there is any opened store when delete the wallet, function returns ok and deletes wallet file successfully
+        // But next if we create wallet with the same again it will contain old records
+        // So we have to close all store handles
+        store.close().await?;
+
+        config.store()?;
+
+        Ok(())
+    }
+
+    /// Blocking form of [`Wallet::open_async`]
+    pub fn open(config: &WalletConfig, credentials: &Credentials) -> VdrResult<Wallet> {
+        block_on(Self::open_async(config, credentials))
+    }
+
+    /// Open an existing wallet
+    pub async fn open_async(config: &WalletConfig, credentials: &Credentials) -> VdrResult<Wallet> {
+        let wallet_uri = WalletUri::build(config, credentials, None)?;
+        let credentials = WalletCredentials::build(credentials)?;
+
+        let mut store: AnyStore = wallet_uri
+            .value()
+            .open_backend(Some(credentials.key_method), credentials.key.as_ref(), None)
+            .await
+            .map_err(|err: AskarError| match err.kind() {
+                AskarErrorKind::NotFound => err_msg(
+                    VdrErrorKind::WalletNotFound,
+                    format!("Wallet \"{}\" not found or unavailable.", config.id),
                 )
-                .await?;
+                .with_source(err),
+                _ => VdrError::from(err),
+            })?;
 
-            // Askar: If there is any opened store when delete the wallet, function returns ok and deletes wallet file successfully
-            // But next if we create wallet with the same again it will contain old records
-            // So we have to close all store handles
-            store.close().await?;
+        if let (Some(rekey), Some(rekey_method)) = (credentials.rekey, credentials.rekey_method) {
+            store.rekey(rekey_method, rekey).await?;
+        }
 
-            Ok(())
+        Ok(Wallet {
+            store,
+            name: config.id.to_string(),
         })
     }
 
-    pub fn open(config: &WalletConfig, credentials: &Credentials) -> CliResult<Wallet> {
-        block_on(async move {
-            let wallet_uri = WalletUri::build(config, credentials, None)?;
-            let credentials = WalletCredentials::build(credentials)?;
+    /// Blocking form of [`Wallet::close_async`]
+    pub fn close(self) -> VdrResult<()> {
+        block_on(self.close_async())
+    }
 
-            let mut store: AnyStore = wallet_uri
-                .value()
-                .open_backend(Some(credentials.key_method), credentials.key.as_ref(), None)
-                .await
-                .map_err(|err: AskarError| match err.kind() {
-                    AskarErrorKind::NotFound => CliError::NotFound(format!(
-                        "Wallet \"{}\" not found or unavailable.",
-                        config.id
-                    )),
-                    _ => CliError::from(err),
-                })?;
-
-            if let (Some(rekey), Some(rekey_method)) = (credentials.rekey, credentials.rekey_method)
-            {
-                store.rekey(rekey_method, rekey).await?;
-            }
+    /// Close the wallet store
+    pub async fn close_async(self) -> VdrResult<()> {
+        self.store.close().await.map_err(VdrError::from)
+    }
 
-            Ok(Wallet {
-                store,
-                name: config.id.to_string(),
-            })
-        })
+    /// Blocking form of [`Wallet::rekey_async`]
+    pub fn rekey(&mut self, key: &str, key_derivation_method: Option<&str>) -> VdrResult<()> {
+        block_on(self.rekey_async(key, key_derivation_method))
     }
 
-    pub fn close(self) -> CliResult<()> {
-        block_on(async move { self.store.close().await.map_err(CliError::from) })
+    /// Re-encrypt the wallet store under a new key
+    pub async fn rekey_async(
+        &mut self,
+        key: &str,
+        key_derivation_method: Option<&str>,
+    ) -> VdrResult<()> {
+        let credentials = Credentials {
+            rekey: Some(key.to_string()),
+            rekey_derivation_method: key_derivation_method.map(String::from),
+            ..Credentials::default()
+        };
+        let credentials = WalletCredentials::build(&credentials)?;
+        if let (Some(rekey), Some(rekey_method)) = (credentials.rekey, credentials.rekey_method) {
+            self.store.rekey(rekey_method, rekey).await?;
+        }
+        Ok(())
     }
 
-    pub fn delete(config: &WalletConfig, credentials: &Credentials) -> CliResult<()> {
-        block_on(async move {
-            let wallet_uri = WalletUri::build(config, credentials, None)?;
+    /// Blocking form of [`Wallet::delete_async`]
+    pub fn delete(config: &WalletConfig, credentials: &Credentials) -> VdrResult<()> {
+        block_on(Self::delete_async(config, credentials))
+    }
 
-            let removed = wallet_uri.value().remove_backend().await?;
-            if !removed {
-                return Err(CliError::InvalidEntityState(format!(
-                    "Unable to delete wallet {}",
-                    config.id
-                )));
-            }
-            WalletDirectory::from_id(&config.id).delete()?;
-            Ok(())
-        })
+    /// Delete a wallet and its storage
+    pub async fn delete_async(config: &WalletConfig, credentials: &Credentials) -> VdrResult<()> {
+        let wallet_uri = WalletUri::build(config, credentials, None)?;
+
+        let removed = wallet_uri.value().remove_backend().await?;
+        if !removed {
+            return Err(err_msg(
+                VdrErrorKind::WalletNotFound,
+                format!("Unable to delete wallet {}", config.id),
+            ));
+        }
+        WalletDirectory::from_id(&config.id).delete()?;
+        if config.exists() {
+            config.delete()?;
+        }
+        Ok(())
     }
 
     pub fn list() -> Vec<JsonValue> {
         WalletDirectory::list_wallets()
     }
 
-    pub fn export(&self, export_config: &ExportConfig) -> CliResult<()> {
-        block_on(async move {
-            let backup = WalletBackup::from_file(&export_config.path)?;
-
-            let backup_config = WalletConfig {
-                id: backup.id(),
-                storage_type: StorageType::Sqlite.to_str().to_string(),
-                ..WalletConfig::default()
-            };
-            let backup_credentials = Credentials {
-                key: export_config.key.clone(),
-                key_derivation_method: export_config.key_derivation_method.clone(),
-                ..Credentials::default()
-            };
-
-            let backup_uri = WalletUri::build(
-                &backup_config,
-                &backup_credentials,
-                Some(&export_config.path),
-            )?;
-            let backup_credentials = WalletCredentials::build(&backup_credentials)?;
-
-            backup.init_dir()?;
-
-            let backup_store = backup_uri
-                .value()
-                .provision_backend(
-                    backup_credentials.key_method,
-                    backup_credentials.key.as_ref(),
-                    None,
-                    false,
-                )
-                .await?;
+    /// Blocking form of [`Wallet::export_async`]
+    pub fn export(&self, export_config: &ExportConfig) -> VdrResult<()> {
+        block_on(self.export_async(export_config))
+    }
 
-            Self::copy_records_from_askar_store(&self.store, &backup_store).await?;
+    /// Export the wallet records and keys to an encrypted archive file
+    pub async fn export_async(&self, export_config: &ExportConfig) -> VdrResult<()> {
+        let backup = WalletBackup::from_file(&export_config.path)?;
+        if backup.exists() {
+            return Err(input_err(format!(
+                "Wallet backup \"{}\" already exists",
+                export_config.path
+            )));
+        }
 
-            backup_store.close().await?;
+        let archive = self
+            .collect_archive(export_config.categories.as_deref())
+            .await?;
+        archive.write(
+            Path::new(&export_config.path),
+            &export_config.key,
+            export_config.key_derivation_method.as_deref(),
+        )
+    }
 
-            Ok(())
-        })
+    async fn collect_archive(&self, categories: Option<&[String]>) -> VdrResult<WalletArchive> {
+        let mut session = self.store.session(None).await?;
+        let mut archive = WalletArchive::default();
+        match categories {
+            Some(categories) => {
+                for category in categories {
+                    let entries = session
+                        .fetch_all(Some(category.as_str()), None, None, false)
+                        .await?;
+                    archive
+                        .records
+                        .extend(entries.iter().map(ArchiveRecord::from_entry));
+                }
+            }
+            None => {
+                let entries = session.fetch_all(None, None, None, false).await?;
+                archive
+                    .records
+                    .extend(entries.iter().map(ArchiveRecord::from_entry));
+            }
+        }
+        for entry in session
+            .fetch_all_keys(None, None, None, None, false)
+            .await?
+        {
+            archive.keys.push(ArchiveKey::from_entry(&entry)?);
+        }
+        session.commit().await?;
+
+        Ok(archive)
     }
 
+    /// Blocking form of [`Wallet::import_async`]
     pub fn import(
         config: &WalletConfig,
         credentials: &Credentials,
         import_config: &ImportConfig,
-    ) -> CliResult<()> {
-        block_on(async move {
-            let backup = WalletBackup::from_file(&import_config.path)?;
-            if !backup.exists() {
-                return Err(CliError::NotFound(format!(
-                    "Wallet backup \"{}\" does not exist",
-                    import_config.path
-                )));
+    ) -> VdrResult<()> {
+        block_on(Self::import_async(config, credentials, import_config))
+    }
+
+    /// Create a new wallet from a backup file
+    pub async fn import_async(
+        config: &WalletConfig,
+        credentials: &Credentials,
+        import_config: &ImportConfig,
+    ) -> VdrResult<()> {
+        let backup = WalletBackup::from_file(&import_config.path)?;
+        if !backup.exists() {
+            return Err(err_msg(
+                VdrErrorKind::FileSystem,
+                format!("Wallet backup \"{}\" does not exist", import_config.path),
+            ));
+        }
+
+        if config.exists() {
+            return Err(err_msg(
+                VdrErrorKind::WalletDuplicate,
+                format!("Wallet \"{}\" already exists", config.id),
+            ));
+        }
+
+        match backup.kind()? {
+            BackupKind::Archive => {
+                Self::import_archive(config, credentials, import_config).await?
+            }
+            BackupKind::Askar => {
+                Self::import_askar_backup(&backup, config, credentials, import_config).await?
+            }
+            BackupKind::Libindy => {
+                Self::import_libindy_backup(&backup, config, credentials, import_config).await?
+            }
+        }
+
+        config.store()?;
+
+        Ok(())
+    }
+
+    /// Blocking form of [`Wallet::migrate_async`]
+    pub fn migrate(
+        from: &WalletConfig,
+        from_credentials: &Credentials,
+        to: &WalletConfig,
+        to_credentials: &Credentials,
+        categories: Option<&[String]>,
+    ) -> VdrResult<()> {
+        block_on(Self::migrate_async(
+            from,
+            from_credentials,
+            to,
+            to_credentials,
+            categories,
+        ))
+    }
+
+    /// Copy the records and keys of a wallet into a new wallet, which may use
+    /// a different storage backend, verifying the record counts after the copy
+    pub async fn migrate_async(
+        from: &WalletConfig,
+        from_credentials: &Credentials,
+        to: &WalletConfig,
+        to_credentials: &Credentials,
+        categories: Option<&[String]>,
+    ) -> VdrResult<()> {
+        if to.exists() {
+            return Err(err_msg(
+                VdrErrorKind::WalletDuplicate,
+                format!("Wallet \"{}\" already exists", to.id),
+            ));
+        }
+
+        let wallet = Self::open_async(from, from_credentials).await?;
+        let collected = async {
+            let archive = wallet.collect_archive(categories).await?;
+            let counts = Self::record_counts(&wallet.store, categories).await?;
+            VdrResult::Ok((archive, counts))
+        }
+        .await;
+        wallet.close_async().await?;
+        let (archive, source_counts) = collected?;
+
+        let new_wallet_uri = WalletUri::build(to, to_credentials, None)?;
+        let new_wallet_credentials = WalletCredentials::build(to_credentials)?;
+
+        to.create_path()?;
+
+        let new_store = new_wallet_uri
+            .value()
+            .provision_backend(
+                new_wallet_credentials.key_method,
+                new_wallet_credentials.key.as_ref(),
+                None,
+                false,
+            )
+            .await?;
+
+        let restored = async {
+            Self::restore_archive(&new_store, &archive).await?;
+            // compare against the source wallet rather than the collected archive
+            let counts = Self::record_counts(&new_store, categories).await?;
+            if counts != source_counts {
+                return Err(err_msg(
+                    VdrErrorKind::Wallet,
+                    format!(
+                        "Migrated wallet contents do not match the source wallet: \
+                        expected {:?}, found {:?}",
+                        source_counts, counts
+                    ),
+                ));
             }
+            Ok(())
+        }
+        .await;
+        new_store.close().await?;
+        if let Err(err) = restored {
+            // don't leave a partially migrated wallet behind
+            new_wallet_uri.value().remove_backend().await.ok();
+            return Err(err);
+        }
+
+        to.store()?;
+
+        Ok(())
+    }
 
-            if config.exists() {
-                return Err(CliError::Duplicate(format!(
-                    "Wallet \"{}\" already exists",
-                    config.id
-                )));
+    /// Count the records in each category of a store, along with its keys
+    async fn record_counts(
+        store: &AnyStore,
+        categories: Option<&[String]>,
+    ) -> VdrResult<(BTreeMap<String, usize>, usize)> {
+        let mut session = store.session(None).await?;
+        let mut counts = BTreeMap::new();
+        for entry in session.fetch_all(None, None, None, false).await? {
+            if categories.map_or(true, |categories| categories.contains(&entry.category)) {
+                *counts.entry(entry.category).or_insert(0) += 1;
             }
+        }
+        let keys = session
+            .fetch_all_keys(None, None, None, None, false)
+            .await?
+            .len();
+        session.commit().await?;
+        Ok((counts, keys))
+    }
 
-            match backup.kind()? {
-                BackupKind::Askar => {
-                    Self::import_askar_backup(&backup, &config, &credentials, &import_config).await
-                }
-                BackupKind::Libindy => {
-                    Self::import_libindy_backup(&backup, &config, &credentials, &import_config)
-                        .await
-                }
+    async fn import_archive(
+        config: &WalletConfig,
+        credentials: &Credentials,
+        import_config: &ImportConfig,
+    ) -> VdrResult<()> {
+        // decrypt and verify the archive before touching the new wallet
+        let archive = WalletArchive::read(
+            Path::new(&import_config.path),
+            &import_config.key,
+            import_config.key_derivation_method.as_deref(),
+        )?;
+
+        let new_wallet_uri = WalletUri::build(config, credentials, None)?;
+        let new_wallet_credentials = WalletCredentials::build(credentials)?;
+
+        config.create_path()?;
+
+        let new_store = new_wallet_uri
+            .value()
+            .provision_backend(
+                new_wallet_credentials.key_method,
+                new_wallet_credentials.key.as_ref(),
+                None,
+                false,
+            )
+            .await?;
+
+        let restored = Self::restore_archive(&new_store, &archive).await;
+        new_store.close().await?;
+        if let Err(err) = restored {
+            // don't leave a partially imported wallet behind
+            new_wallet_uri.value().remove_backend().await.ok();
+            return Err(err);
+        }
+
+        Ok(())
+    }
+
+    /// Insert the archived records and keys into a store and verify the counts
+    async fn restore_archive(store: &AnyStore, archive: &WalletArchive) -> VdrResult<()> {
+        let mut session = store.session(None).await?;
+        let mut expected_counts = BTreeMap::new();
+        for record in &archive.records {
+            let tags = ArchiveTag::to_entry_tags(&record.tags);
+            session
+                .insert(
+                    &record.category,
+                    &record.name,
+                    &record.value,
+                    Some(&tags),
+                    None,
+                )
+                .await?;
+            *expected_counts.entry(record.category.as_str()).or_insert(0) += 1;
+        }
+        for key in &archive.keys {
+            let tags = ArchiveTag::to_entry_tags(&key.tags);
+            session
+                .insert_key(
+                    &key.name,
+                    &key.local_key()?,
+                    key.metadata.as_deref(),
+                    Some(&tags),
+                    None,
+                )
+                .await?;
+        }
+        session.commit().await?;
+
+        // verify that every archived record made it into the new wallet
+        let mut session = store.session(None).await?;
+        for (category, expected) in expected_counts {
+            let found = session
+                .fetch_all(Some(category), None, None, false)
+                .await?
+                .len();
+            if found != expected {
+                return Err(err_msg(
+                    VdrErrorKind::Wallet,
+                    format!(
+                        "Restored {} of {} records in category \"{}\"",
+                        found, expected, category
+                    ),
+                ));
             }
-        })
+        }
+        let found = session
+            .fetch_all_keys(None, None, None, None, false)
+            .await?
+            .len();
+        if found != archive.keys.len() {
+            return Err(err_msg(
+                VdrErrorKind::Wallet,
+                format!("Restored {} of {} keys", found, archive.keys.len()),
+            ));
+        }
+        session.commit().await?;
+
+        Ok(())
     }
 
     async fn import_askar_backup(
@@ -237,7 +522,7 @@ impl Wallet {
         config: &WalletConfig,
         credentials: &Credentials,
         import_config: &ImportConfig,
-    ) -> CliResult<()> {
+    ) -> VdrResult<()> {
         // prepare config and credentials for backup and new wallet
         let backup_config = WalletConfig {
             id: backup.id(),
@@ -270,8 +555,11 @@ impl Wallet {
             )
             .await
             .map_err(|err: AskarError| match err.kind() {
-                AskarErrorKind::NotFound => CliError::NotFound(err.to_string()),
-                _ => CliError::from(err),
+                AskarErrorKind::NotFound => {
+                    err_msg(VdrErrorKind::WalletNotFound, "Wallet backup not found")
+                        .with_source(err)
+                }
+                _ => VdrError::from(err),
             })?;
 
         // create directory for new wallet and provision it
@@ -302,7 +590,7 @@ impl Wallet {
         config: &WalletConfig,
         credentials: &Credentials,
         import_config: &ImportConfig,
-    ) -> CliResult<()> {
+    ) -> VdrResult<()> {
         // prepare config and credentials for new wallet
         let new_wallet_uri = WalletUri::build(&config, &credentials, None)?;
         let new_wallet_credentials = WalletCredentials::build(&credentials)?;
@@ -332,12 +620,12 @@ impl Wallet {
         Ok(())
     }
 
-    async fn copy_records_from_askar_store(from: &AnyStore, to: &AnyStore) -> CliResult<()> {
+    async fn copy_records_from_askar_store(from: &AnyStore, to: &AnyStore) -> VdrResult<()> {
         let mut from_session = from.session(None).await?;
         let mut to_session = to.session(None).await?;
 
         let did_entries = from_session
-            .fetch_all(CATEGORY_DID, None, None, false)
+            .fetch_all(Some(CATEGORY_DID), None, None, false)
             .await?;
 
         for entry in did_entries {
@@ -379,7 +667,7 @@ impl Wallet {
     async fn copy_records_from_libindy_backup(
         backup_reader: &mut LibindyBackupReader,
         to: &AnyStore,
-    ) -> CliResult<()> {
+    ) -> VdrResult<()> {
         let mut to_session = to.session(None).await?;
 
         while let Some(record) = backup_reader.read_record()? {
@@ -421,10 +709,13 @@ impl Wallet {
                         .fetch(CATEGORY_DID, &temporary_did_record.did, true)
                         .await?
                         .ok_or_else(|| {
-                            CliError::NotFound(format!(
-                                "DID {} does not exits in the wallet.",
-                                temporary_did_record.did
-                            ))
+                            err_msg(
+                                VdrErrorKind::DidNotFound,
+                                format!(
+                                    "DID {} does not exits in the wallet.",
+                                    temporary_did_record.did
+                                ),
+                            )
                         })?;
 
                     let mut did_info: DidInfo = DidInfo::from_bytes(&did_entry.value)?;
@@ -448,10 +739,10 @@ impl Wallet {
                         .fetch(CATEGORY_DID, &record.id, true)
                         .await?
                         .ok_or_else(|| {
-                            CliError::NotFound(format!(
-                                "DID {} does not exits in the wallet.",
-                                record.id
-                            ))
+                            err_msg(
+                                VdrErrorKind::DidNotFound,
+                                format!("DID {} does not exits in the wallet.", record.id),
+                            )
                         })?;
 
                     let mut did_info: DidInfo = DidInfo::from_bytes(&did_entry.value)?;
@@ -469,14 +760,12 @@ impl Wallet {
                         .ok();
                 }
                 _ => {
-                    println_warn!("Unsupported record type {}", record.type_);
-                    println_warn!("Record");
-                    println_warn!("{:?}", record);
+                    warn!("Unsupported record type {}: {:?}", record.type_, record);
                 }
             }
         }
 
-        to_session.commit().await.map_err(CliError::from)
+        to_session.commit().await.map_err(VdrError::from)
     }
 
     pub async fn store_record(
@@ -486,22 +775,25 @@ impl Wallet {
         value: &[u8],
         tags: Option<&[EntryTag]>,
         new: bool,
-    ) -> CliResult<()> {
+    ) -> VdrResult<()> {
         let mut session = self.store.session(None).await?;
         if new {
             session.insert(category, id, value, tags, None).await?
         } else {
-            session.replace(category, id, value, tags, None).await?
+            session
+                .replace(category, id, value, tags, None)
+                .await
+                .map_err(|err| record_err(err, category, id))?
         }
-        session.commit().await.map_err(CliError::from)
+        session.commit().await.map_err(VdrError::from)
     }
 
-    pub async fn fetch_all_records(&self, category: &str) -> CliResult<Vec<Entry>> {
+    pub async fn fetch_all_records(&self, category: &str) -> VdrResult<Vec<Entry>> {
         let mut session = self.store.session(None).await?;
         session
-            .fetch_all(category, None, None, false)
+            .fetch_all(Some(category), None, None, false)
             .await
-            .map_err(CliError::from)
+            .map_err(VdrError::from)
     }
 
     pub async fn fetch_record(
@@ -509,18 +801,21 @@ impl Wallet {
         category: &str,
         id: &str,
         for_update: bool,
-    ) -> CliResult<Option<Entry>> {
+    ) -> VdrResult<Option<Entry>> {
         let mut session = self.store.session(None).await?;
         session
             .fetch(category, &id, for_update)
             .await
-            .map_err(CliError::from)
+            .map_err(VdrError::from)
     }
 
-    pub async fn remove_record(&self, category: &str, id: &str) -> CliResult<()> {
+    pub async fn remove_record(&self, category: &str, id: &str) -> VdrResult<()> {
         let mut session = self.store.session(None).await?;
-        session.remove(category, id).await.map_err(CliError::from)?;
-        session.commit().await.map_err(CliError::from)
+        session
+            .remove(category, id)
+            .await
+            .map_err(|err| record_err(err, category, id))?;
+        session.commit().await.map_err(VdrError::from)
     }
 
     pub async fn insert_key(
@@ -528,21 +823,184 @@ impl Wallet {
         id: &str,
         key: &LocalKey,
         metadata: Option<&str>,
-    ) -> CliResult<()> {
+    ) -> VdrResult<()> {
         let mut session = self.store.session(None).await?;
         session
             .insert_key(id, key, metadata, None, None)
             .await
-            .map_err(CliError::from)
+            .map_err(VdrError::from)
     }
 
-    pub async fn fetch_key(&self, id: &str) -> CliResult<LocalKey> {
+    pub async fn fetch_key(&self, id: &str) -> VdrResult<LocalKey> {
         let mut session = self.store.session(None).await?;
         session
             .fetch_key(id, false)
             .await?
-            .ok_or_else(|| CliError::NotFound(format!("Key {} does not exits in the wallet!", id)))?
+            .ok_or_else(|| {
+                err_msg(
+                    VdrErrorKind::KeyNotFound,
+                    format!("Key {} does not exits in the wallet!", id),
+                )
+            })?
             .load_local_key()
-            .map_err(CliError::from)
+            .map_err(VdrError::from)
+    }
+}
+
+/// Map an error from a record operation, reporting a missing record by its category
+fn record_err(err: AskarError, category: &str, id: &str) -> VdrError {
+    match err.kind() {
+        AskarErrorKind::NotFound if category == CATEGORY_DID => err_msg(
+            VdrErrorKind::DidNotFound,
+            format!("DID {} does not exist in the wallet", id),
+        )
+        .with_source(err),
+        AskarErrorKind::NotFound => err_msg(
+            VdrErrorKind::WalletItemNotFound,
+            format!(
+                "Record \"{}\" does not exist in category \"{}\"",
+                id, category
+            ),
+        )
+        .with_source(err),
+        _ => VdrError::from(err),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use super::*;
+
+    /// Configuration and credentials for a new sqlite wallet with a unique name
+    pub(crate) fn wallet_config(prefix: &str) -> (WalletConfig, Credentials) {
+        let config = WalletConfig {
+            id: format!("{}_{}", prefix, rand::random::<u32>()),
+            storage_type: StorageType::Sqlite.to_str().to_string(),
+            storage_config: None,
+        };
+        let credentials = Credentials {
+            key: "test_wallet_key".to_string(),
+            key_derivation_method: Some("argon2i".to_string()),
+            ..Credentials::default()
+        };
+        (config, credentials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::wallet_config;
+    use super::*;
+    use crate::tools::did::Did;
+    use crate::utils::did::DidValue;
+    use crate::utils::environment::EnvironmentUtils;
+
+    #[test]
+    fn test_not_found_errors() {
+        let (config, credentials) = wallet_config("not_found");
+        let err = Wallet::open(&config, &credentials).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::WalletNotFound));
+
+        Wallet::create(&config, &credentials).unwrap();
+        let wallet = Wallet::open(&config, &credentials).unwrap();
+        let err = block_on(wallet.remove_record(CATEGORY_DID, "unknown")).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::DidNotFound));
+        let err =
+            block_on(wallet.store_record("other", "unknown", b"value", None, false)).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::WalletItemNotFound));
+        let err = block_on(wallet.fetch_key("unknown")).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::KeyNotFound));
+
+        wallet.close().unwrap();
+        Wallet::delete(&config, &credentials).unwrap();
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let (config, credentials) = wallet_config("export");
+        Wallet::create(&config, &credentials).unwrap();
+        let wallet = Wallet::open(&config, &credentials).unwrap();
+        let (did, _verkey) = Did::create(&wallet, None, None, None).unwrap();
+        block_on(wallet.store_record("custom", "record", b"value", None, true)).unwrap();
+
+        let path = EnvironmentUtils::tmp_file_path(&format!("{}.archive", config.id));
+        let export_config = ExportConfig {
+            path: path.to_string_lossy().to_string(),
+            key: "export_key".to_string(),
+            key_derivation_method: Some("argon2i".to_string()),
+            categories: None,
+        };
+        wallet.export(&export_config).unwrap();
+        wallet.close().unwrap();
+
+        let (imported, imported_credentials) = wallet_config("import");
+        let import_config = ImportConfig {
+            path: export_config.path.clone(),
+            key: export_config.key.clone(),
+            key_derivation_method: export_config.key_derivation_method.clone(),
+        };
+        Wallet::import(&imported, &imported_credentials, &import_config).unwrap();
+        let wallet = Wallet::open(&imported, &imported_credentials).unwrap();
+        Did::get(&wallet, &DidValue(did.clone())).unwrap();
+        Did::sign(&wallet, &did, b"message").unwrap();
+        let record = block_on(wallet.fetch_record("custom", "record", false))
+            .unwrap()
+            .unwrap();
+        assert_eq!(&record.value[..], b"value");
+        wallet.close().unwrap();
+
+        Wallet::delete(&config, &credentials).unwrap();
+        Wallet::delete(&imported, &imported_credentials).unwrap();
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_rekey_round_trip() {
+        let (config, credentials) = wallet_config("rekey");
+        Wallet::create(&config, &credentials).unwrap();
+        let mut wallet = Wallet::open(&config, &credentials).unwrap();
+        let (did, _verkey) = Did::create(&wallet, None, None, None).unwrap();
+        wallet.rekey("new_wallet_key", Some("argon2i")).unwrap();
+        wallet.close().unwrap();
+
+        let err = Wallet::open(&config, &credentials).unwrap_err();
+        assert!(matches!(err.kind(), VdrErrorKind::WalletAccessDenied));
+        let new_credentials = Credentials {
+            key: "new_wallet_key".to_string(),
+            key_derivation_method: Some("argon2i".to_string()),
+            ..Credentials::default()
+        };
+        let wallet = Wallet::open(&config, &new_credentials).unwrap();
+        Did::get(&wallet, &DidValue(did)).unwrap();
+        wallet.close().unwrap();
+
+        Wallet::delete(&config, &new_credentials).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_round_trip() {
+        let (from, from_credentials) = wallet_config("migrate_from");
+        Wallet::create(&from, &from_credentials).unwrap();
+        let wallet = Wallet::open(&from, &from_credentials).unwrap();
+        let (did, _verkey) = Did::create(&wallet, None, None, None).unwrap();
+        block_on(wallet.store_record("custom", "record", b"value", None, true)).unwrap();
+        wallet.close().unwrap();
+
+        let (to, to_credentials) = wallet_config("migrate_to");
+        Wallet::migrate(&from, &from_credentials, &to, &to_credentials, None).unwrap();
+
+        for (config, credentials) in [(&from, &from_credentials), (&to, &to_credentials)] {
+            let wallet = Wallet::open(config, credentials).unwrap();
+            Did::get(&wallet, &DidValue(did.clone())).unwrap();
+            Did::sign(&wallet, &did, b"message").unwrap();
+            let record = block_on(wallet.fetch_record("custom", "record", false))
+                .unwrap()
+                .unwrap();
+            assert_eq!(&record.value[..], b"value");
+            wallet.close().unwrap();
+        }
+
+        Wallet::delete(&from, &from_credentials).unwrap();
+        Wallet::delete(&to, &to_credentials).unwrap();
     }
 }
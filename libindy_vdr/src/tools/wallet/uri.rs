@@ -3,9 +3,7 @@
     https://digital.gov.bc.ca/digital-trust
 */
 use crate::{
-    utils::clierror::{CliError, CliResult},
-    tools::wallet::Credentials,
-    utils::environment::EnvironmentUtils,
+    common::error::prelude::*, tools::wallet::Credentials, utils::environment::EnvironmentUtils,
 };
 
 use crate::tools::wallet::wallet_config::WalletConfig;
@@ -37,7 +35,7 @@ impl WalletUri {
         config: &WalletConfig,
         credentials: &Credentials,
         path: Option<&str>,
-    ) -> CliResult<WalletUri> {
+    ) -> VdrResult<WalletUri> {
         let storage_type = Self::map_storage_type(&config.storage_type)?;
         let uri = match storage_type {
             StorageType::Sqlite => Self::build_sqlite_uri(config, credentials, path),
@@ -50,7 +48,7 @@ impl WalletUri {
         config: &WalletConfig,
         _credentials: &Credentials,
         path: Option<&str>,
-    ) -> CliResult<String> {
+    ) -> VdrResult<String> {
         let path = match path {
             Some(path) => {
                 let mut path = PathBuf::from(path);
@@ -82,38 +80,27 @@ impl WalletUri {
         Ok(uri)
     }
 
-    fn build_postgres_uri(config: &WalletConfig, credentials: &Credentials) -> CliResult<String> {
+    fn build_postgres_uri(config: &WalletConfig, credentials: &Credentials) -> VdrResult<String> {
         let storage_config = config
             .storage_config
             .as_ref()
-            .ok_or(CliError::InvalidInput(
-                "No 'storage_config' provided for postgres store".to_string(),
-            ))?;
-        let storage_credentials =
-            credentials
-                .storage_credentials
-                .as_ref()
-                .ok_or(CliError::InvalidInput(
-                    "No 'storage_credentials' provided for postgres store".to_string(),
-                ))?;
+            .ok_or_else(|| input_err("No 'storage_config' provided for postgres store"))?;
+        let storage_credentials = credentials
+            .storage_credentials
+            .as_ref()
+            .ok_or_else(|| input_err("No 'storage_credentials' provided for postgres store"))?;
 
         let config_url = storage_config["url"]
             .as_str()
-            .ok_or(CliError::InvalidInput(
-                "No 'url' provided for postgres store".to_string(),
-            ))?;
+            .ok_or_else(|| input_err("No 'url' provided for postgres store"))?;
 
         let account = storage_credentials["account"]
             .as_str()
-            .ok_or(CliError::InvalidInput(
-                "No 'account' provided for postgres store".to_string(),
-            ))?;
+            .ok_or_else(|| input_err("No 'account' provided for postgres store"))?;
 
         let password = storage_credentials["password"]
             .as_str()
-            .ok_or(CliError::InvalidInput(
-                "No 'password' provided for postgres store".to_string(),
-            ))?;
+            .ok_or_else(|| input_err("No 'password' provided for postgres store"))?;
 
         let mut params: Vec<String> = Vec::new();
         if let Some(connection_timeout) = storage_config["connect_timeout"].as_u64() {
@@ -146,11 +133,11 @@ impl WalletUri {
         Ok(uri)
     }
 
-    fn map_storage_type(storage_type: &str) -> CliResult<StorageType> {
+    fn map_storage_type(storage_type: &str) -> VdrResult<StorageType> {
         match storage_type {
             "default" | "sqlite" | "sqlite_storage" => Ok(StorageType::Sqlite),
             "postgres" | "postgres_storage" => Ok(StorageType::Postgres),
-            value => Err(CliError::InvalidInput(format!(
+            value => Err(input_err(format!(
                 "Unsupported storage type provided: {}",
                 value
             ))),
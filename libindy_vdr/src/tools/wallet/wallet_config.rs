@@ -4,7 +4,7 @@
 */
 use crate::utils::environment::EnvironmentUtils;
 
-use crate::utils::clierror::{CliError,CliResult};
+use crate::common::error::prelude::*;
 use serde_json::Value as JsonValue;
 use std::{
     fs,
@@ -21,7 +21,7 @@ pub struct WalletConfig {
 }
 
 impl WalletConfig {
-    pub fn store(&self) -> CliResult<()> {
+    pub fn store(&self) -> VdrResult<()> {
         Self::create_wallets_directory()?;
         let mut config_file = File::create(&self.path())?;
         let config_json = json!(self).to_string();
@@ -30,25 +30,25 @@ impl WalletConfig {
         Ok(())
     }
 
-    pub fn read(id: &str) -> CliResult<Self> {
+    pub fn read(id: &str) -> VdrResult<Self> {
         let path = EnvironmentUtils::wallet_config_path(id);
 
         let mut config_json = String::new();
         let mut file = File::open(path)?;
         file.read_to_string(&mut config_json)?;
 
-        serde_json::from_str(&config_json).map_err(CliError::from)
+        serde_json::from_str(&config_json).with_input_err("Unable to parse wallet config")
     }
 
-    pub fn delete(&self) -> CliResult<()> {
-        fs::remove_file(&self.path()).map_err(CliError::from)
+    pub fn delete(&self) -> VdrResult<()> {
+        fs::remove_file(&self.path()).map_err(VdrError::from)
     }
 
     pub(crate) fn exists(&self) -> bool {
         self.path().exists()
     }
 
-    pub(crate) fn create_path(&self) -> CliResult<()> {
+    pub(crate) fn create_path(&self) -> VdrResult<()> {
         WalletDirectory::from_id(&self.id).create()
     }
 
@@ -56,11 +56,11 @@ impl WalletConfig {
         EnvironmentUtils::wallet_config_path(&self.id)
     }
 
-    pub(crate) fn create_wallets_directory() -> CliResult<()> {
+    pub(crate) fn create_wallets_directory() -> VdrResult<()> {
         fs::DirBuilder::new()
             .recursive(true)
             .create(EnvironmentUtils::wallets_path())
-            .map_err(CliError::from)
+            .map_err(VdrError::from)
     }
 }
 
@@ -78,21 +78,21 @@ impl WalletDirectory {
         }
     }
 
-    pub(crate) fn create(&self) -> CliResult<()> {
+    pub(crate) fn create(&self) -> VdrResult<()> {
         fs::DirBuilder::new()
             .recursive(true)
             .create(&self.path)
-            .map_err(CliError::from)
+            .map_err(VdrError::from)
     }
 
-    pub(crate) fn delete(&self) -> CliResult<()> {
+    pub(crate) fn delete(&self) -> VdrResult<()> {
         if !self.path.exists() {
-            return Err(CliError::NotFound(format!(
-                "Wallet \"{}\" does not exist",
-                self.id
-            )));
+            return Err(err_msg(
+                VdrErrorKind::WalletNotFound,
+                format!("Wallet \"{}\" does not exist", self.id),
+            ));
         }
-        fs::remove_dir_all(self.path.as_path()).map_err(CliError::from)
+        fs::remove_dir_all(self.path.as_path()).map_err(VdrError::from)
     }
 
     pub(crate) fn list_wallets() -> Vec<JsonValue> {
@@ -1 +1,105 @@
-pub use aries_askar::future::block_on;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use futures_channel::oneshot;
+use once_cell::sync::Lazy;
+
+pub use aries_askar::future::{block_on, spawn_ok};
+
+/// Pending timers shared by all pools, driven by a single background thread
+static TIMERS: Lazy<Arc<TimerQueue>> = Lazy::new(|| {
+    let queue = Arc::new(TimerQueue::default());
+    let worker = queue.clone();
+    thread::Builder::new()
+        .name("indy-vdr-timer".to_string())
+        .spawn(move || worker.run())
+        .expect("Error starting timer thread");
+    queue
+});
+
+type Timers = BTreeMap<(Instant, u64), oneshot::Sender<()>>;
+
+#[derive(Default)]
+struct TimerQueue {
+    timers: Mutex<Timers>,
+    changed: Condvar,
+    counter: AtomicU64,
+}
+
+impl TimerQueue {
+    fn lock(&self) -> MutexGuard<'_, Timers> {
+        // the timer map is consistent even if a holder panicked
+        self.timers.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn add(&self, deadline: Instant, sender: oneshot::Sender<()>) {
+        let seq = self.counter.fetch_add(1, Ordering::Relaxed);
+        self.lock().insert((deadline, seq), sender);
+        self.changed.notify_one();
+    }
+
+    fn run(&self) {
+        let mut timers = self.lock();
+        loop {
+            let now = Instant::now();
+            let due = timers
+                .keys()
+                .take_while(|(deadline, _)| *deadline <= now)
+                .copied()
+                .collect::<Vec<_>>();
+            for key in due {
+                if let Some(sender) = timers.remove(&key) {
+                    sender.send(()).ok();
+                }
+            }
+            // drop timers which are no longer awaited
+            timers.retain(|_, sender| !sender.is_canceled());
+            timers = match timers.keys().next() {
+                Some((deadline, _)) => {
+                    let wait = deadline.saturating_duration_since(now);
+                    self.changed
+                        .wait_timeout(timers, wait)
+                        .unwrap_or_else(|err| err.into_inner())
+                        .0
+                }
+                None => self
+                    .changed
+                    .wait(timers)
+                    .unwrap_or_else(|err| err.into_inner()),
+            };
+        }
+    }
+}
+
+/// Create a future which resolves after a delay
+///
+/// All timers are driven by one shared background thread.
+pub fn start_timer(delay: Duration) -> oneshot::Receiver<()> {
+    let (sender, receiver) = oneshot::channel();
+    TIMERS.add(Instant::now() + delay, sender);
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timers_resolve_in_order() {
+        let start = Instant::now();
+        let late = start_timer(Duration::from_millis(200));
+        let early = start_timer(Duration::from_millis(50));
+        let dropped = start_timer(Duration::from_millis(100));
+        drop(dropped);
+
+        block_on(early).unwrap();
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_millis(200));
+        block_on(late).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+}
@@ -4,7 +4,6 @@ mod macros;
 pub mod base58;
 pub mod base64;
 pub mod txn_signature;
-pub mod clierror;
 pub mod futures;
 pub mod environment;
 #[macro_use]
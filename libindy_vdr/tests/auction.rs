@@ -0,0 +1,200 @@
+#[macro_use]
+mod utils;
+
+inject_dependencies!();
+
+use indy_vdr::ledger::constants;
+use indy_vdr::utils::did::DidValue;
+
+use crate::utils::fixtures::*;
+use crate::utils::helpers;
+
+const AUCTION_ID: &str = "auction-1";
+const HANDLE: &str = "alice";
+const AMOUNT: u64 = 100;
+
+#[test]
+fn empty() {
+    // Empty test to run module
+}
+
+#[cfg(test)]
+mod builder {
+    use super::*;
+    use indy_vdr::ledger::RequestBuilder;
+    use indy_vdr::pool::{PreparedRequest, RequestMethod};
+
+    mod auction_start {
+        use super::*;
+
+        #[rstest]
+        fn test_pool_build_auction_start(
+            request_builder: RequestBuilder,
+            trustee_did: DidValue,
+            my_did: DidValue,
+        ) {
+            let request = request_builder
+                .build_auction_start_request(
+                    &trustee_did,
+                    &my_did,
+                    AUCTION_ID.to_string(),
+                    HANDLE.to_string(),
+                )
+                .unwrap();
+
+            let expected_result = json!({
+                "type": constants::AUCTION_START,
+                "dest": my_did,
+                "auctionid": AUCTION_ID,
+                "handle": HANDLE,
+            });
+
+            helpers::check_request_operation(&request, expected_result);
+        }
+
+        #[rstest]
+        fn test_pool_build_auction_start_for_invalid_handle(
+            request_builder: RequestBuilder,
+            trustee_did: DidValue,
+            my_did: DidValue,
+        ) {
+            let _err = request_builder
+                .build_auction_start_request(
+                    &trustee_did,
+                    &my_did,
+                    AUCTION_ID.to_string(),
+                    "Not a handle".to_string(),
+                )
+                .unwrap_err();
+        }
+    }
+
+    mod auction_bid {
+        use super::*;
+
+        #[rstest]
+        fn test_pool_build_auction_bid(request_builder: RequestBuilder, my_did: DidValue) {
+            let request = request_builder
+                .build_auction_bid_request(&my_did, AUCTION_ID.to_string(), AMOUNT)
+                .unwrap();
+
+            let expected_result = json!({
+                "type": constants::AUCTION_BID,
+                "auctionid": AUCTION_ID,
+                "amount": AMOUNT,
+            });
+
+            helpers::check_request_operation(&request, expected_result);
+        }
+
+        #[rstest]
+        fn test_pool_build_auction_bid_for_zero_amount(
+            request_builder: RequestBuilder,
+            my_did: DidValue,
+        ) {
+            let _err = request_builder
+                .build_auction_bid_request(&my_did, AUCTION_ID.to_string(), 0)
+                .unwrap_err();
+        }
+
+        #[rstest]
+        fn test_pool_build_auction_bid_for_invalid_auction_id(
+            request_builder: RequestBuilder,
+            my_did: DidValue,
+        ) {
+            let _err = request_builder
+                .build_auction_bid_request(&my_did, "auction:1".to_string(), AMOUNT)
+                .unwrap_err();
+        }
+    }
+
+    mod auction_close {
+        use super::*;
+
+        #[rstest]
+        fn test_pool_build_auction_close(request_builder: RequestBuilder, trustee_did: DidValue) {
+            let request = request_builder
+                .build_auction_close_request(&trustee_did, AUCTION_ID.to_string())
+                .unwrap();
+
+            let expected_result = json!({
+                "type": constants::AUCTION_CLOSE,
+                "auctionid": AUCTION_ID,
+            });
+
+            helpers::check_request_operation(&request, expected_result);
+        }
+    }
+
+    mod get_auction {
+        use super::*;
+
+        #[rstest]
+        fn test_pool_build_get_auction(request_builder: RequestBuilder) {
+            let request = request_builder
+                .build_get_auction_request(None, AUCTION_ID.to_string(), None, None)
+                .unwrap();
+
+            let expected_result = json!({
+                "type": constants::GET_AUCTION,
+                "auctionid": AUCTION_ID,
+            });
+
+            helpers::check_request_operation(&request, expected_result);
+        }
+
+        #[rstest]
+        fn test_pool_build_get_auction_uses_state_proof(request_builder: RequestBuilder) {
+            let timestamp = 123456789;
+            let request = request_builder
+                .build_get_auction_request(None, AUCTION_ID.to_string(), None, Some(timestamp))
+                .unwrap();
+            let parsed = PreparedRequest::from_request_json(request.req_json.to_string()).unwrap();
+
+            let expected = RequestMethod::BuiltinStateProof {
+                sp_key: format!("auction:{}", AUCTION_ID).into_bytes(),
+                sp_timestamps: (None, Some(timestamp)),
+                hedge_policy: None,
+            };
+            assert_eq!(request.method, expected);
+            assert_eq!(parsed.method, expected);
+        }
+
+        #[rstest]
+        fn test_pool_build_get_auction_for_invalid_auction_id(request_builder: RequestBuilder) {
+            let _err = request_builder
+                .build_get_auction_request(None, "auction:1".to_string(), None, None)
+                .unwrap_err();
+        }
+
+        #[rstest]
+        fn test_pool_build_get_auction_for_seq_no_and_timestamp(request_builder: RequestBuilder) {
+            let _err = request_builder
+                .build_get_auction_request(None, AUCTION_ID.to_string(), Some(1), Some(1))
+                .unwrap_err();
+        }
+    }
+
+    mod handle_transfer {
+        use super::*;
+
+        #[rstest]
+        fn test_pool_build_handle_transfer(
+            request_builder: RequestBuilder,
+            trustee_did: DidValue,
+            my_did: DidValue,
+        ) {
+            let request = request_builder
+                .build_handle_transfer_request(&trustee_did, &my_did, HANDLE.to_string())
+                .unwrap();
+
+            let expected_result = json!({
+                "type": constants::HANDLE_TRANSACTION,
+                "dest": my_did,
+                "handle": HANDLE,
+            });
+
+            helpers::check_request_operation(&request, expected_result);
+        }
+    }
+}
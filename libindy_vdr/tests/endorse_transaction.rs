@@ -8,6 +8,54 @@ fn empty() {
     // Empty test to run module
 }
 
+#[cfg(test)]
+mod check_signer {
+    use crate::utils::fixtures::*;
+    use indy_vdr::ledger::RequestBuilder;
+    use indy_vdr::pool::PreparedRequest;
+    use indy_vdr::utils::did::DidValue;
+
+    fn nym_request(request_builder: &RequestBuilder, identifier: &DidValue) -> PreparedRequest {
+        request_builder
+            .build_nym_request(identifier, identifier, None, None, None, None, None)
+            .unwrap()
+    }
+
+    #[rstest]
+    fn test_check_signer_works_for_identifier(
+        request_builder: RequestBuilder,
+        trustee_did: DidValue,
+        fq_trustee_did: DidValue,
+    ) {
+        let request = nym_request(&request_builder, &trustee_did);
+        request.check_signer(&trustee_did, false).unwrap();
+        request.check_signer(&fq_trustee_did, false).unwrap();
+    }
+
+    #[rstest]
+    fn test_check_signer_fails_for_other_did(
+        request_builder: RequestBuilder,
+        trustee_did: DidValue,
+        my_did: DidValue,
+    ) {
+        let request = nym_request(&request_builder, &trustee_did);
+        request.check_signer(&my_did, false).unwrap_err();
+        request.check_signer(&my_did, true).unwrap_err();
+    }
+
+    #[rstest]
+    fn test_check_signer_works_for_endorser(
+        request_builder: RequestBuilder,
+        trustee_did: DidValue,
+        my_did: DidValue,
+    ) {
+        let mut request = nym_request(&request_builder, &trustee_did);
+        request.set_endorser(&my_did).unwrap();
+        request.check_signer(&my_did, true).unwrap();
+        request.check_signer(&my_did, false).unwrap_err();
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "local_nodes_pool")]
 mod endorse_transaction {
@@ -0,0 +1,107 @@
+#[macro_use]
+mod utils;
+
+inject_dependencies!();
+
+use indy_vdr::ledger::constants;
+use indy_vdr::utils::did::DidValue;
+
+use crate::utils::fixtures::*;
+use crate::utils::helpers;
+
+const HANDLE: &str = "alice";
+
+#[test]
+fn empty() {
+    // Empty test to run module
+}
+
+#[cfg(test)]
+mod builder {
+    use super::*;
+    use indy_vdr::ledger::RequestBuilder;
+    use indy_vdr::pool::RequestMethod;
+    use sha2::{Digest, Sha256};
+
+    mod handle {
+        use super::*;
+
+        #[rstest]
+        fn test_pool_build_handle(
+            request_builder: RequestBuilder,
+            trustee_did: DidValue,
+            my_did: DidValue,
+        ) {
+            let handle_request = request_builder
+                .build_handle_request(&trustee_did, &my_did, HANDLE.to_string())
+                .unwrap();
+
+            let expected_result = json!({
+                "type": constants::HANDLE,
+                "dest": my_did,
+                "handle": HANDLE,
+            });
+
+            helpers::check_request_operation(&handle_request, expected_result);
+        }
+
+        #[rstest]
+        fn test_pool_build_handle_for_invalid_handle(
+            request_builder: RequestBuilder,
+            trustee_did: DidValue,
+            my_did: DidValue,
+        ) {
+            let _err = request_builder
+                .build_handle_request(&trustee_did, &my_did, "Alice".to_string())
+                .unwrap_err();
+        }
+    }
+
+    mod get_handle {
+        use super::*;
+
+        #[rstest]
+        fn test_pool_build_get_handle(request_builder: RequestBuilder) {
+            let get_handle_request = request_builder
+                .build_get_handle_request(None, HANDLE.to_string(), None, None)
+                .unwrap();
+
+            let expected_result = json!({
+                "type": constants::GET_HANDLE,
+                "handle": HANDLE,
+            });
+
+            helpers::check_request_operation(&get_handle_request, expected_result);
+        }
+
+        #[rstest]
+        fn test_pool_build_get_handle_for_invalid_handle(request_builder: RequestBuilder) {
+            let _err = request_builder
+                .build_get_handle_request(None, "-alice".to_string(), None, None)
+                .unwrap_err();
+        }
+
+        #[rstest]
+        fn test_pool_build_get_handle_for_seq_no_and_timestamp(request_builder: RequestBuilder) {
+            let _err = request_builder
+                .build_get_handle_request(None, HANDLE.to_string(), Some(1), Some(1))
+                .unwrap_err();
+        }
+
+        #[rstest]
+        fn test_pool_build_get_handle_uses_state_proof(request_builder: RequestBuilder) {
+            let get_handle_request = request_builder
+                .build_get_handle_request(None, HANDLE.to_string(), None, None)
+                .unwrap();
+
+            assert_eq!(
+                get_handle_request.method,
+                RequestMethod::BuiltinStateProof {
+                    sp_key: Sha256::digest(HANDLE.as_bytes()).to_vec(),
+                    sp_timestamps: (None, None),
+                    hedge_policy: None,
+                }
+            );
+        }
+    }
+}
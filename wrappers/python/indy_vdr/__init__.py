@@ -4,6 +4,7 @@ from .bindings import set_cache_directory, set_config, set_protocol_version, ver
 from .error import VdrError, VdrErrorCode
 from .ledger import LedgerType
 from .pool import Pool, open_pool
+from .registry import PoolRegistry
 from .request import Request
 from .resolver import Resolver
 
@@ -16,6 +17,7 @@ __all__ = [
     "version",
     "LedgerType",
     "Pool",
+    "PoolRegistry",
     "Request",
     "Resolver",
     "VdrError",
@@ -358,6 +358,58 @@ def pool_get_verifiers(pool_handle: PoolHandle) -> asyncio.Future:
     )
 
 
+def registry_register(namespace: str, params: Union[str, bytes, dict]):
+    """Register the pool used to resolve DIDs in a `did:indy` namespace."""
+    params_p = (
+        encode_str(params) if isinstance(params, (str, bytes)) else encode_json(params)
+    )
+    do_call("indy_vdr_registry_register", encode_str(namespace), params_p)
+
+
+def registry_deregister(namespace: str):
+    """Remove the pool registered for a `did:indy` namespace."""
+    do_call("indy_vdr_registry_deregister", encode_str(namespace))
+
+
+def registry_list() -> str:
+    """List the registered pools."""
+    pools = lib_string()
+    do_call("indy_vdr_registry_list", byref(pools))
+    return pools.value.decode("utf-8")
+
+
+def registry_refresh(namespace: str) -> asyncio.Future:
+    """Fetch the latest transactions for the pool registered for a namespace."""
+    return do_call_async("indy_vdr_registry_refresh", encode_str(namespace))
+
+
+def registry_evict_idle(max_idle: int) -> str:
+    """Disconnect the registered pools not used within `max_idle` seconds."""
+    evicted = lib_string()
+    do_call("indy_vdr_registry_evict_idle", c_int64(max_idle), byref(evicted))
+    return evicted.value.decode("utf-8")
+
+
+def registry_resolve(did: str) -> asyncio.Future:
+    """Resolve a DID using the pool registered for its namespace."""
+    return do_call_async(
+        "indy_vdr_registry_resolve",
+        encode_str(did),
+        return_type=lib_string,
+        post_process=str,
+    )
+
+
+def registry_dereference(did_url: str) -> asyncio.Future:
+    """Dereference a DID Url using the pool registered for its namespace."""
+    return do_call_async(
+        "indy_vdr_registry_dereference",
+        encode_str(did_url),
+        return_type=lib_string,
+        post_process=str,
+    )
+
+
 def request_free(handle: RequestHandle):
     """Manually free a prepared request which won't be submitted."""
     do_call("indy_vdr_request_free", handle)
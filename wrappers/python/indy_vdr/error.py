@@ -17,6 +17,15 @@ class VdrErrorCode(IntEnum):
     POOL_TIMEOUT = 32
     WRAPPER = 99
     RESOLVER = 9
+    WALLET = 40
+    WALLET_NOT_FOUND = 41
+    WALLET_DUPLICATE = 42
+    WALLET_ACCESS_DENIED = 43
+    WALLET_INVALID_STATE = 44
+    WALLET_ITEM_NOT_FOUND = 45
+    DID_NOT_FOUND = 50
+    DID_DUPLICATE = 51
+    KEY_NOT_FOUND = 52
 
 
 class VdrError(Exception):
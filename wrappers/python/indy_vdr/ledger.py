@@ -15,6 +15,7 @@ class LedgerType(IntEnum):
     POOL = 0
     DOMAIN = 1
     CONFIG = 2
+    AUDIT = 3
 
     @classmethod
     def from_value(cls, val: Union[int, str, "LedgerType"]) -> "LedgerType":
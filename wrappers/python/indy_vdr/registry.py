@@ -0,0 +1,41 @@
+"""Pools registered for resolving `did:indy` DIDs by namespace."""
+
+import json
+from typing import Sequence, Union
+
+from . import bindings
+
+
+class PoolRegistry:
+    """The library's registry of pools identified by their `did:indy` namespace.
+
+    Pools are connected when first used, and may be refreshed or evicted when idle.
+    """
+
+    def register(self, namespace: str, params: Union[str, bytes, dict]):
+        """Register a pool, given the parameters accepted by `open_pool`."""
+        bindings.registry_register(namespace, params)
+
+    def deregister(self, namespace: str):
+        """Remove the pool registered for a namespace."""
+        bindings.registry_deregister(namespace)
+
+    def list(self) -> Sequence[dict]:
+        """List the registered pools."""
+        return json.loads(bindings.registry_list())
+
+    async def refresh(self, namespace: str):
+        """Load any new pool transactions for a registered pool."""
+        await bindings.registry_refresh(namespace)
+
+    def evict_idle(self, max_idle: int) -> Sequence[str]:
+        """Disconnect the pools not used within `max_idle` seconds."""
+        return json.loads(bindings.registry_evict_idle(max_idle))
+
+    async def resolve(self, did: str) -> dict:
+        """Resolve a DID to retrieve a DID Doc."""
+        return json.loads(await bindings.registry_resolve(did))
+
+    async def dereference(self, did_url: str) -> dict:
+        """Dereference a DID Url to retrieve a ledger object."""
+        return json.loads(await bindings.registry_dereference(did_url))